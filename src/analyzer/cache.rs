@@ -0,0 +1,382 @@
+//! Per-project analysis cache for incremental re-analysis.
+//!
+//! Stores the content hash, size, modification time and parsed symbols of
+//! every analyzed file under `<cache_dir>/analysis/`. On the next run files
+//! whose size and mtime are unchanged are reused without being read, files
+//! whose contents hash the same are reused without being reparsed, and only
+//! the rest are handed to the caller's parse function.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::discovery::DiscoveredFile;
+use crate::types::{FileChanges, FileMetrics, SourceFile, Symbol};
+use crate::utils::hash_string;
+
+/// Bump when the cached symbol format changes so stale caches are discarded.
+const CACHE_VERSION: u32 = 6;
+
+/// Cached state of a single source file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedFile {
+    hash: String,
+    size_bytes: u64,
+    modified_nanos: Option<u64>,
+    parsed: ParsedFile,
+}
+
+/// What parsing a single file produces.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParsedFile {
+    pub symbols: Vec<Symbol>,
+    pub metrics: FileMetrics,
+    pub namespace: Option<String>,
+    pub imports: Vec<String>,
+}
+
+/// On-disk cache layout.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    project_path: PathBuf,
+    /// Keyed by path relative to the project root, using `/` separators
+    files: BTreeMap<String, CachedFile>,
+}
+
+/// Result of refreshing the cache against a fresh scan.
+#[derive(Debug, Default)]
+pub struct CacheRefresh {
+    /// Source files with their (cached or freshly parsed) symbols
+    pub files: Vec<SourceFile>,
+    /// Changes since the previous run, `None` when there was no previous run
+    pub changes: Option<FileChanges>,
+    /// Number of files that had to be reparsed
+    pub reparsed: usize,
+}
+
+/// Incremental analysis cache for one project.
+pub struct AnalysisCache {
+    /// Where the cache is persisted, `None` for an in-memory cache
+    location: Option<PathBuf>,
+    state: CacheFile,
+    /// Whether a previous run was loaded from disk
+    has_previous: bool,
+}
+
+impl AnalysisCache {
+    /// An empty cache that is never persisted. Every file gets parsed.
+    pub fn in_memory(project_root: &Path) -> Self {
+        Self {
+            location: None,
+            state: CacheFile {
+                version: CACHE_VERSION,
+                project_path: project_root.to_path_buf(),
+                files: BTreeMap::new(),
+            },
+            has_previous: false,
+        }
+    }
+
+    /// Load the cache for `project_root` from `cache_dir`.
+    ///
+    /// A missing, unreadable or outdated cache yields an empty one.
+    pub fn load(cache_dir: &Path, project_root: &Path) -> Self {
+        let canonical = project_root
+            .canonicalize()
+            .unwrap_or_else(|_| project_root.to_path_buf());
+        let key = hash_string(&canonical.to_string_lossy());
+        let location = cache_dir.join(format!("{}.json", &key[..16]));
+
+        let mut cache = Self::in_memory(project_root);
+        cache.location = Some(location.clone());
+
+        if !location.exists() {
+            return cache;
+        }
+
+        let loaded = fs::read_to_string(&location)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(serde_json::from_str::<CacheFile>(&content)?));
+
+        match loaded {
+            Ok(state) if state.version == CACHE_VERSION => {
+                cache.state.files = state.files;
+                cache.has_previous = true;
+            }
+            Ok(state) => {
+                tracing::debug!(
+                    version = state.version,
+                    "Discarding analysis cache with outdated version"
+                );
+            }
+            Err(e) => {
+                tracing::warn!(
+                    path = %location.display(),
+                    error = %e,
+                    "Failed to load analysis cache, starting fresh"
+                );
+            }
+        }
+
+        cache
+    }
+
+    /// Persist the cache. In-memory caches are not written.
+    pub fn save(&self) -> Result<()> {
+        let Some(ref location) = self.location else {
+            return Ok(());
+        };
+
+        if let Some(parent) = location.parent() {
+            fs::create_dir_all(parent).context("Failed to create analysis cache directory")?;
+        }
+
+        let json = serde_json::to_string(&self.state).context("Failed to serialize cache")?;
+
+        // Write to a temp file first so a crash never leaves a half-written cache
+        let tmp = location.with_extension("json.tmp");
+        fs::write(&tmp, json)
+            .with_context(|| format!("Failed to write analysis cache: {}", tmp.display()))?;
+        fs::rename(&tmp, location)
+            .with_context(|| format!("Failed to write analysis cache: {}", location.display()))?;
+
+        tracing::debug!(path = %location.display(), "Saved analysis cache");
+        Ok(())
+    }
+
+    /// Bring the cache up to date with a fresh scan, reparsing only what changed.
+    ///
+    /// When `truncated` is set the scan was partial, so files missing from it
+    /// are kept in the cache and not reported as removed.
+    /// `parse` receives the file path, its language (extension) and contents.
+    pub fn refresh(
+        &mut self,
+        root: &Path,
+        discovered: Vec<DiscoveredFile>,
+        truncated: bool,
+        mut parse: impl FnMut(&Path, &str, &str) -> ParsedFile,
+    ) -> CacheRefresh {
+        let mut changes = FileChanges::default();
+        let mut reparsed = 0;
+        let mut seen = HashSet::new();
+        let mut files = Vec::with_capacity(discovered.len());
+
+        for file in discovered {
+            let relative = Self::relative_key(root, &file.path);
+            let language = file
+                .path
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or_default()
+                .to_string();
+            let modified_nanos = Self::modified_nanos(&file.path);
+
+            let cached = self.state.files.get(&relative);
+
+            let parsed = match cached {
+                // Same size and mtime: trust the cache without reading the file
+                Some(entry)
+                    if entry.size_bytes == file.size_bytes
+                        && modified_nanos.is_some()
+                        && entry.modified_nanos == modified_nanos =>
+                {
+                    entry.parsed.clone()
+                }
+                _ => {
+                    let source = match fs::read(&file.path) {
+                        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                        Err(e) => {
+                            tracing::debug!(path = %file.path.display(), error = %e, "Skipping unreadable file");
+                            continue;
+                        }
+                    };
+                    let hash = hash_string(&source);
+
+                    let parsed = match cached {
+                        Some(entry) if entry.hash == hash => entry.parsed.clone(),
+                        _ => {
+                            if cached.is_some() {
+                                changes.modified.push(PathBuf::from(&relative));
+                            } else {
+                                changes.added.push(PathBuf::from(&relative));
+                            }
+                            reparsed += 1;
+                            parse(&file.path, &language, &source)
+                        }
+                    };
+
+                    self.state.files.insert(
+                        relative.clone(),
+                        CachedFile {
+                            hash,
+                            size_bytes: file.size_bytes,
+                            modified_nanos,
+                            parsed: parsed.clone(),
+                        },
+                    );
+                    parsed
+                }
+            };
+
+            seen.insert(relative);
+            files.push(SourceFile {
+                path: file.path,
+                language,
+                size_bytes: file.size_bytes,
+                symbols: parsed.symbols,
+                metrics: parsed.metrics,
+                namespace: parsed.namespace,
+                imports: parsed.imports,
+            });
+        }
+
+        if !truncated {
+            self.state.files.retain(|key, _| {
+                let keep = seen.contains(key);
+                if !keep {
+                    changes.removed.push(PathBuf::from(key));
+                }
+                keep
+            });
+        }
+
+        CacheRefresh {
+            files,
+            changes: self.has_previous.then_some(changes),
+            reparsed,
+        }
+    }
+
+    fn relative_key(root: &Path, path: &Path) -> String {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    fn modified_nanos(path: &Path) -> Option<u64> {
+        let modified = fs::metadata(path).ok()?.modified().ok()?;
+        let nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+        u64::try_from(nanos).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::symbols::SymbolExtractor;
+    use tempfile::tempdir;
+
+    fn parse_with(
+        extractor: &mut SymbolExtractor,
+    ) -> impl FnMut(&Path, &str, &str) -> ParsedFile + '_ {
+        |path, language, source| ParsedFile {
+            symbols: extractor.extract(path, language, source).unwrap().symbols,
+            ..Default::default()
+        }
+    }
+
+    fn discovered(path: &Path) -> DiscoveredFile {
+        DiscoveredFile {
+            path: path.to_path_buf(),
+            size_bytes: fs::metadata(path).unwrap().len(),
+        }
+    }
+
+    #[test]
+    fn test_first_run_has_no_changes() {
+        let project = tempdir().unwrap();
+        let cache_dir = tempdir().unwrap();
+        let file = project.path().join("Service.cs");
+        fs::write(&file, "public class Service { }").unwrap();
+
+        let mut cache = AnalysisCache::load(cache_dir.path(), project.path());
+        let refresh = cache.refresh(
+            project.path(),
+            vec![discovered(&file)],
+            false,
+            parse_with(&mut SymbolExtractor::new()),
+        );
+
+        assert!(refresh.changes.is_none());
+        assert_eq!(refresh.reparsed, 1);
+        assert_eq!(refresh.files[0].symbols[0].name, "Service");
+    }
+
+    #[test]
+    fn test_rerun_reports_added_modified_removed() {
+        let project = tempdir().unwrap();
+        let cache_dir = tempdir().unwrap();
+        let kept = project.path().join("Kept.cs");
+        let edited = project.path().join("Edited.cs");
+        let deleted = project.path().join("Deleted.cs");
+        fs::write(&kept, "class Kept { }").unwrap();
+        fs::write(&edited, "class Edited { }").unwrap();
+        fs::write(&deleted, "class Deleted { }").unwrap();
+
+        let mut extractor = SymbolExtractor::new();
+        let mut cache = AnalysisCache::load(cache_dir.path(), project.path());
+        cache.refresh(
+            project.path(),
+            vec![discovered(&kept), discovered(&edited), discovered(&deleted)],
+            false,
+            parse_with(&mut extractor),
+        );
+        cache.save().unwrap();
+
+        fs::write(&edited, "class Edited { void Run() { } }").unwrap();
+        fs::remove_file(&deleted).unwrap();
+        let added = project.path().join("Added.cs");
+        fs::write(&added, "class Added { }").unwrap();
+
+        let mut cache = AnalysisCache::load(cache_dir.path(), project.path());
+        let refresh = cache.refresh(
+            project.path(),
+            vec![discovered(&added), discovered(&edited), discovered(&kept)],
+            false,
+            parse_with(&mut extractor),
+        );
+
+        let changes = refresh.changes.unwrap();
+        assert_eq!(changes.added, vec![PathBuf::from("Added.cs")]);
+        assert_eq!(changes.modified, vec![PathBuf::from("Edited.cs")]);
+        assert_eq!(changes.removed, vec![PathBuf::from("Deleted.cs")]);
+        assert_eq!(refresh.reparsed, 2);
+    }
+
+    #[test]
+    fn test_truncated_scan_keeps_unseen_entries() {
+        let project = tempdir().unwrap();
+        let cache_dir = tempdir().unwrap();
+        let a = project.path().join("A.cs");
+        let b = project.path().join("B.cs");
+        fs::write(&a, "class A { }").unwrap();
+        fs::write(&b, "class B { }").unwrap();
+
+        let mut extractor = SymbolExtractor::new();
+        let mut cache = AnalysisCache::load(cache_dir.path(), project.path());
+        cache.refresh(
+            project.path(),
+            vec![discovered(&a), discovered(&b)],
+            false,
+            parse_with(&mut extractor),
+        );
+        cache.save().unwrap();
+
+        let mut cache = AnalysisCache::load(cache_dir.path(), project.path());
+        let refresh = cache.refresh(
+            project.path(),
+            vec![discovered(&a)],
+            true,
+            parse_with(&mut extractor),
+        );
+
+        let changes = refresh.changes.unwrap();
+        assert!(changes.removed.is_empty());
+        assert_eq!(refresh.reparsed, 0);
+    }
+}
@@ -2,6 +2,7 @@ use anyhow::Result;
 use std::path::Path;
 use tree_sitter::{Node, Parser};
 
+use super::grammar::node_range;
 use crate::types::{CSharpFile, ClassInfo, InterfaceInfo, MethodInfo, PropertyInfo};
 
 #[allow(dead_code)]
@@ -21,24 +22,29 @@ impl CSharpAnalyzer {
 
     pub fn analyze_file(&mut self, path: &Path) -> Result<CSharpFile> {
         let source = std::fs::read_to_string(path)?;
+        self.analyze_source(path, &source)
+    }
+
+    /// Analyze C# source that has already been read from `path`.
+    pub fn analyze_source(&mut self, path: &Path, source: &str) -> Result<CSharpFile> {
         let tree = self
             .parser
-            .parse(&source, None)
+            .parse(source, None)
             .ok_or_else(|| anyhow::anyhow!("Failed to parse file"))?;
 
         let root = tree.root_node();
 
         // Extract namespace
-        let namespace = self.extract_namespace(&root, &source);
+        let namespace = self.extract_namespace(&root, source);
 
         // Extract using directives
-        let usings = self.extract_usings(&root, &source);
+        let usings = self.extract_usings(&root, source);
 
         // Extract classes
-        let classes = self.extract_classes(&root, &source);
+        let classes = self.extract_classes(&root, source);
 
         // Extract interfaces
-        let interfaces = self.extract_interfaces(&root, &source);
+        let interfaces = self.extract_interfaces(&root, source);
 
         Ok(CSharpFile {
             path: path.to_path_buf(),
@@ -145,6 +151,7 @@ impl CSharpAnalyzer {
                 interfaces: vec![],
                 methods,
                 properties,
+                range: node_range(*node),
             })
         } else {
             None
@@ -224,6 +231,7 @@ impl CSharpAnalyzer {
                 parameters: vec![],
                 modifiers,
                 is_async,
+                range: node_range(*node),
             })
         } else {
             None
@@ -280,6 +288,7 @@ impl CSharpAnalyzer {
                 prop_type,
                 has_getter,
                 has_setter,
+                range: node_range(*node),
             })
         } else {
             None
@@ -327,7 +336,11 @@ impl CSharpAnalyzer {
         }
 
         if !name.is_empty() {
-            Some(InterfaceInfo { name, methods })
+            Some(InterfaceInfo {
+                name,
+                methods,
+                range: node_range(*node),
+            })
         } else {
             None
         }
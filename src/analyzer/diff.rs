@@ -0,0 +1,236 @@
+//! Changed-file detection for diff-scoped analysis.
+//!
+//! Changes come either from `git diff <base>` in the local clone or from a
+//! unified diff supplied by the caller. Both are reduced to `ChangedFile`s
+//! carrying the touched line ranges of the new version of each file.
+
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+
+use super::git;
+use crate::types::{ChangeStatus, ChangedFile, LineRange};
+
+/// Files changed in the working tree relative to the git ref `base`.
+///
+/// Includes untracked files as additions. Paths are relative to `root`.
+///
+/// # Errors
+/// Returns an error if `base` looks like an option, `root` is not in a git
+/// work tree, or `base` does not resolve
+pub fn changes_against_ref(root: &Path, base: &str) -> Result<Vec<ChangedFile>> {
+    // A ref starting with '-' would be parsed as a git option
+    if base.is_empty() || base.starts_with('-') {
+        bail!("Invalid git ref: '{}'", base);
+    }
+
+    let diff = git::run(
+        root,
+        &[
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-textconv",
+            "--find-renames",
+            "-U0",
+            "--relative",
+            base,
+            "--",
+            ".",
+        ],
+    )?;
+    let mut files = parse_unified_diff(&diff);
+
+    let untracked = git::run(
+        root,
+        &["ls-files", "--others", "--exclude-standard", "--", "."],
+    )?;
+    for line in untracked.lines().filter(|l| !l.is_empty()) {
+        let path = PathBuf::from(line);
+        if !files.iter().any(|f| f.path == path) {
+            files.push(ChangedFile {
+                path,
+                status: ChangeStatus::Added,
+                lines: vec![],
+            });
+        }
+    }
+
+    Ok(files)
+}
+
+/// Parse a unified diff (`git diff` or `diff -u` output).
+///
+/// Paths drop the `a/` and `b/` prefixes git adds. Line ranges refer to the
+/// new version of the file; a pure deletion marks the line it follows.
+pub fn parse_unified_diff(diff: &str) -> Vec<ChangedFile> {
+    let mut files: Vec<ChangedFile> = Vec::new();
+    let mut old_path: Option<String> = None;
+    let mut after_old_header = false;
+
+    let mut lines = diff.lines().peekable();
+    while let Some(line) = lines.next() {
+        // Removed/added lines starting with "--"/"++" look like headers, but
+        // real headers come as a "---" line directly followed by "+++"
+        let is_header = |next: Option<&&str>| next.is_some_and(|n| n.starts_with("+++ "));
+
+        if line.starts_with("diff --git ") {
+            old_path = None;
+        } else if let Some(to) = line.strip_prefix("rename to ") {
+            // Pure renames have no ---/+++ headers
+            files.push(ChangedFile {
+                path: PathBuf::from(to.trim_matches('"')),
+                status: ChangeStatus::Renamed,
+                lines: vec![],
+            });
+        } else if line.starts_with("--- ") && is_header(lines.peek()) {
+            old_path = diff_path(&line[4..], "a/");
+            after_old_header = true;
+        } else if let Some(rest) = line.strip_prefix("+++ ").filter(|_| after_old_header) {
+            after_old_header = false;
+            let new_path = diff_path(rest, "b/");
+            let (path, status) = match (old_path.take(), new_path) {
+                (None, Some(new)) => (new, ChangeStatus::Added),
+                (Some(old), None) => (old, ChangeStatus::Deleted),
+                (Some(old), Some(new)) if old != new => (new, ChangeStatus::Renamed),
+                (Some(_), Some(new)) => (new, ChangeStatus::Modified),
+                (None, None) => continue,
+            };
+            let path = PathBuf::from(path);
+            if files
+                .last()
+                .is_some_and(|f| f.path == path && f.status == ChangeStatus::Renamed)
+            {
+                continue;
+            }
+            files.push(ChangedFile {
+                path,
+                status,
+                lines: vec![],
+            });
+        } else if line.starts_with("@@") {
+            if let (Some(file), Some(range)) = (files.last_mut(), hunk_range(line)) {
+                file.lines.push(range);
+            }
+        }
+    }
+
+    // Deleted files have no lines in the new version
+    for file in &mut files {
+        if file.status == ChangeStatus::Deleted {
+            file.lines.clear();
+        }
+    }
+
+    files
+}
+
+/// Path from a `---`/`+++` header, `None` for `/dev/null`.
+fn diff_path(header: &str, prefix: &str) -> Option<String> {
+    // Drop the optional tab-separated timestamp of `diff -u`
+    let path = header.split('\t').next().unwrap_or(header).trim();
+    let path = path.trim_matches('"');
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix(prefix).unwrap_or(path).to_string())
+}
+
+/// New-file line range of a `@@ -a,b +c,d @@` hunk header.
+fn hunk_range(header: &str) -> Option<LineRange> {
+    let new = header
+        .split_whitespace()
+        .find(|part| part.starts_with('+'))?;
+    let mut parts = new[1..].splitn(2, ',');
+    let start: usize = parts.next()?.parse().ok()?;
+    let count: usize = match parts.next() {
+        Some(count) => count.parse().ok()?,
+        None => 1,
+    };
+
+    let start = start.max(1);
+    Some(LineRange {
+        start,
+        end: start + count.saturating_sub(1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -3,0 +4,2 @@ fn a() {
++    one();
++    two();
+@@ -10 +12 @@ fn b() {
+-    old();
++    new();
+diff --git a/src/new.rs b/src/new.rs
+new file mode 100644
+--- /dev/null
++++ b/src/new.rs
+@@ -0,0 +1,3 @@
++fn x() {}
++fn y() {}
++fn z() {}
+diff --git a/src/gone.rs b/src/gone.rs
+deleted file mode 100644
+--- a/src/gone.rs
++++ /dev/null
+@@ -1,2 +0,0 @@
+-fn x() {}
+-fn y() {}
+";
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let files = parse_unified_diff(DIFF);
+
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].path, PathBuf::from("src/lib.rs"));
+        assert_eq!(files[0].status, ChangeStatus::Modified);
+        assert_eq!(
+            files[0].lines,
+            vec![
+                LineRange { start: 4, end: 5 },
+                LineRange { start: 12, end: 12 }
+            ]
+        );
+        assert_eq!(files[1].status, ChangeStatus::Added);
+        assert_eq!(files[1].lines, vec![LineRange { start: 1, end: 3 }]);
+        assert_eq!(files[2].path, PathBuf::from("src/gone.rs"));
+        assert_eq!(files[2].status, ChangeStatus::Deleted);
+        assert!(files[2].lines.is_empty());
+    }
+
+    #[test]
+    fn test_pure_rename() {
+        let diff = "diff --git a/old.rs b/new.rs\nsimilarity index 100%\nrename from old.rs\nrename to new.rs\n";
+        let files = parse_unified_diff(diff);
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, PathBuf::from("new.rs"));
+        assert_eq!(files[0].status, ChangeStatus::Renamed);
+    }
+
+    #[test]
+    fn test_plain_diff_u_headers() {
+        let diff =
+            "--- app.py\t2024-01-01 00:00:00\n+++ app.py\t2024-01-02 00:00:00\n@@ -1,2 +1,3 @@\n";
+        let files = parse_unified_diff(diff);
+
+        assert_eq!(files[0].path, PathBuf::from("app.py"));
+        assert_eq!(files[0].status, ChangeStatus::Modified);
+        assert_eq!(files[0].lines, vec![LineRange { start: 1, end: 3 }]);
+    }
+
+    #[test]
+    fn test_rejects_option_like_ref() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(changes_against_ref(dir.path(), "--output=/tmp/x").is_err());
+    }
+}
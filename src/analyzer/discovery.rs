@@ -0,0 +1,467 @@
+//! Source file discovery.
+//!
+//! Walks a project tree in parallel, honoring `.gitignore`/`.ignore` files,
+//! the configured `ignore_patterns`, a per-project `.mcpignore` override file,
+//! the maximum file size and the scan caps (file count, depth and total
+//! bytes). The walk is blocking, so async callers should run it on the
+//! blocking pool.
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::AnalyzerConfig;
+use crate::types::{ScanLimit, ScanSummary};
+
+/// Per-project ignore file (gitignore syntax).
+///
+/// Rules in this file take precedence over `.gitignore` and `.ignore`, so a
+/// `!pattern` line can re-include files the other ignore files exclude.
+pub const PROJECT_IGNORE_FILE: &str = ".mcpignore";
+
+/// Walks a project directory and yields the files worth analyzing.
+#[derive(Debug, Clone)]
+pub struct FileDiscovery {
+    ignore_globs: GlobSet,
+    max_file_size: Option<u64>,
+    max_files: Option<usize>,
+    max_depth: Option<usize>,
+    max_total_bytes: Option<u64>,
+    threads: usize,
+    include_hidden: bool,
+}
+
+/// A file found during discovery.
+#[derive(Debug, Clone)]
+pub struct DiscoveredFile {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+}
+
+/// Files found by a scan plus a summary of how the scan went.
+#[derive(Debug, Clone, Default)]
+pub struct ScanOutcome {
+    /// Discovered files, sorted by path
+    pub files: Vec<DiscoveredFile>,
+    pub summary: ScanSummary,
+}
+
+/// Shared state for the parallel walker's visitors.
+#[derive(Default)]
+struct ScanState {
+    files: Vec<DiscoveredFile>,
+    total_bytes: u64,
+    truncated: Option<ScanLimit>,
+}
+
+impl FileDiscovery {
+    /// Build a discovery walker from the analyzer configuration.
+    ///
+    /// # Errors
+    /// Returns an error if one of the configured `ignore_patterns` is not a valid glob.
+    pub fn new(config: &AnalyzerConfig) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &config.ignore_patterns {
+            for glob in Self::expand_pattern(pattern) {
+                builder.add(
+                    Glob::new(&glob)
+                        .with_context(|| format!("Invalid ignore pattern '{}'", pattern))?,
+                );
+            }
+        }
+
+        let non_zero = |n: usize| (n > 0).then_some(n);
+        let megabytes = |mb: usize| non_zero(mb).map(|mb| mb as u64 * 1024 * 1024);
+
+        Ok(Self {
+            ignore_globs: builder.build()?,
+            max_file_size: megabytes(config.max_file_size_mb),
+            max_files: non_zero(config.max_files),
+            max_depth: non_zero(config.max_depth),
+            max_total_bytes: megabytes(config.max_total_size_mb),
+            threads: config.scan_threads,
+            include_hidden: false,
+        })
+    }
+
+    /// Also walk hidden files and directories (`.env`, `.github`), except `.git`.
+    #[must_use]
+    pub fn with_hidden(mut self, include: bool) -> Self {
+        self.include_hidden = include;
+        self
+    }
+
+    /// Turns a configured pattern into the globs actually matched.
+    ///
+    /// Patterns are relative to the project root. A leading `/` anchors the
+    /// pattern to the root; otherwise it matches at any depth. `dir/**` also
+    /// matches `dir` itself so the whole subtree is pruned without descending.
+    fn expand_pattern(pattern: &str) -> Vec<String> {
+        let base = match pattern.strip_prefix('/') {
+            Some(anchored) => anchored.to_string(),
+            None if pattern.starts_with("**/") => pattern.to_string(),
+            None => format!("**/{}", pattern),
+        };
+
+        let mut globs = vec![base.clone()];
+        if let Some(dir) = base.strip_suffix("/**") {
+            globs.push(dir.to_string());
+        }
+        globs
+    }
+
+    /// Returns true if `path` is excluded by the configured ignore patterns.
+    pub fn is_ignored(&self, root: &Path, path: &Path) -> bool {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        !relative.as_os_str().is_empty() && self.ignore_globs.is_match(relative)
+    }
+
+    /// Build the underlying walker for `root`.
+    fn walker(&self, root: &Path) -> WalkBuilder {
+        let mut builder = self.walker_at(root, root);
+        builder
+            .parents(false)
+            .max_depth(self.max_depth)
+            .threads(self.threads);
+        builder
+    }
+
+    /// A walker over `start`, a directory of the project at `root`, without
+    /// the scan caps.
+    fn walker_at(&self, root: &Path, start: &Path) -> WalkBuilder {
+        let mut builder = WalkBuilder::new(start);
+        builder
+            .follow_links(false)
+            .hidden(!self.include_hidden)
+            .ignore(true)
+            .git_ignore(true)
+            .git_exclude(true)
+            .require_git(false)
+            .max_filesize(self.max_file_size)
+            .add_custom_ignore_filename(PROJECT_IGNORE_FILE);
+
+        let globs = self.clone();
+        let root_path = root.to_path_buf();
+        builder.filter_entry(move |entry: &DirEntry| {
+            !globs.is_ignored(&root_path, entry.path()) && entry.file_name() != ".git"
+        });
+
+        builder
+    }
+
+    /// Whether the directory `dir` holds a file the scan would have collected.
+    ///
+    /// Ignore files of the parent directories apply, so ignored subtrees do
+    /// not count.
+    fn has_match_below(
+        &self,
+        root: &Path,
+        dir: &Path,
+        matches: &(dyn Fn(&Path) -> bool + Sync),
+    ) -> bool {
+        self.walker_at(root, dir)
+            .parents(true)
+            .build()
+            .filter_map(std::result::Result::ok)
+            .any(|e| e.file_type().is_some_and(|t| t.is_file()) && matches(e.path()))
+    }
+
+    /// Collect every non-ignored file under `root` whose extension is in `extensions`.
+    ///
+    /// Directories are walked in parallel. When a scan cap is hit the walk
+    /// stops early and `summary.truncated` records which limit was reached;
+    /// which files make it in before the cut-off is not deterministic.
+    pub fn scan(&self, root: &Path, extensions: &[&str]) -> ScanOutcome {
+        self.scan_matching(root, |path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|ext| extensions.contains(&ext))
+        })
+    }
+
+    /// Collect every non-ignored file under `root` accepted by `matches`.
+    ///
+    /// Same walk and caps as [`FileDiscovery::scan`], for callers selecting
+    /// files by name rather than extension.
+    pub fn scan_matching<F>(&self, root: &Path, matches: F) -> ScanOutcome
+    where
+        F: Fn(&Path) -> bool + Sync,
+    {
+        let state = Mutex::new(ScanState::default());
+
+        self.walker(root).build_parallel().run(|| {
+            Box::new(|entry| {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        tracing::debug!(error = %e, "Skipping unreadable entry");
+                        return WalkState::Continue;
+                    }
+                };
+                self.visit(root, &entry, &matches, &state)
+            })
+        });
+
+        let mut state = state.into_inner().unwrap_or_else(|e| e.into_inner());
+        state.files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        if let Some(limit) = state.truncated {
+            tracing::warn!(
+                root = %root.display(),
+                files = state.files.len(),
+                %limit,
+                "File discovery truncated"
+            );
+        }
+
+        ScanOutcome {
+            summary: ScanSummary {
+                files_scanned: state.files.len(),
+                total_bytes: state.total_bytes,
+                truncated: state.truncated,
+                ..Default::default()
+            },
+            files: state.files,
+        }
+    }
+
+    fn visit(
+        &self,
+        root: &Path,
+        entry: &DirEntry,
+        matches: &(dyn Fn(&Path) -> bool + Sync),
+        state: &Mutex<ScanState>,
+    ) -> WalkState {
+        let Some(file_type) = entry.file_type() else {
+            return WalkState::Continue;
+        };
+
+        if file_type.is_dir() {
+            // The walker yields directories at max_depth but never descends
+            // into them; the scan is truncated only if they hold a match
+            if let Some(max) = self.max_depth.filter(|&d| entry.depth() >= d) {
+                let reported = state
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .truncated
+                    .is_some();
+                if !reported && self.has_match_below(root, entry.path(), matches) {
+                    let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+                    state.truncated.get_or_insert(ScanLimit::Depth(max));
+                }
+            }
+            return WalkState::Continue;
+        }
+
+        if !file_type.is_file() {
+            return WalkState::Continue;
+        }
+
+        let path = entry.path();
+        if !matches(path) {
+            return WalkState::Continue;
+        }
+
+        let size_bytes = match entry.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(e) => {
+                tracing::debug!(path = %path.display(), error = %e, "Skipping file without metadata");
+                return WalkState::Continue;
+            }
+        };
+
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(max) = self.max_files {
+            if state.files.len() >= max {
+                state.truncated = Some(ScanLimit::Files(max));
+                return WalkState::Quit;
+            }
+        }
+
+        if let Some(max) = self.max_total_bytes {
+            if state.total_bytes + size_bytes > max {
+                state.truncated = Some(ScanLimit::TotalSize(max / (1024 * 1024)));
+                return WalkState::Quit;
+            }
+        }
+
+        state.total_bytes += size_bytes;
+        state.files.push(DiscoveredFile {
+            path: path.to_path_buf(),
+            size_bytes,
+        });
+
+        WalkState::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn config(patterns: &[&str], max_mb: usize) -> AnalyzerConfig {
+        AnalyzerConfig {
+            ignore_patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            max_file_size_mb: max_mb,
+            ..Default::default()
+        }
+    }
+
+    fn relative_names(root: &Path, outcome: &ScanOutcome) -> Vec<String> {
+        outcome
+            .files
+            .iter()
+            .map(|f| {
+                f.path
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_configured_patterns_match_at_any_depth() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/generated")).unwrap();
+        fs::create_dir_all(dir.path().join("app/bin")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(dir.path().join("src/generated/api.rs"), "").unwrap();
+        fs::write(dir.path().join("app/bin/out.rs"), "").unwrap();
+
+        let discovery = FileDiscovery::new(&config(&["bin/**", "generated/**"], 10)).unwrap();
+        let outcome = discovery.scan(dir.path(), &["rs"]);
+
+        assert_eq!(relative_names(dir.path(), &outcome), vec!["src/main.rs"]);
+    }
+
+    #[test]
+    fn test_gitignore_and_project_override() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("fixtures")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "fixtures/\n*.gen.ts\n").unwrap();
+        fs::write(dir.path().join(PROJECT_IGNORE_FILE), "!fixtures/\n").unwrap();
+        fs::write(dir.path().join("index.ts"), "").unwrap();
+        fs::write(dir.path().join("api.gen.ts"), "").unwrap();
+        fs::write(dir.path().join("fixtures/data.ts"), "").unwrap();
+
+        let discovery = FileDiscovery::new(&config(&[], 10)).unwrap();
+        let outcome = discovery.scan(dir.path(), &["ts"]);
+
+        assert_eq!(
+            relative_names(dir.path(), &outcome),
+            vec!["fixtures/data.ts", "index.ts"]
+        );
+    }
+
+    #[test]
+    fn test_max_file_size() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("small.py"), "x = 1").unwrap();
+        fs::write(dir.path().join("big.py"), vec![b'#'; 2 * 1024 * 1024]).unwrap();
+
+        let limited = FileDiscovery::new(&config(&[], 1)).unwrap();
+        let outcome = limited.scan(dir.path(), &["py"]);
+        assert_eq!(relative_names(dir.path(), &outcome), vec!["small.py"]);
+
+        let unlimited = FileDiscovery::new(&config(&[], 0)).unwrap();
+        assert_eq!(unlimited.scan(dir.path(), &["py"]).files.len(), 2);
+    }
+
+    #[test]
+    fn test_max_files_truncates_scan() {
+        let dir = tempdir().unwrap();
+        for i in 0..10 {
+            fs::write(dir.path().join(format!("f{i}.go")), "package main").unwrap();
+        }
+
+        let discovery = FileDiscovery::new(&AnalyzerConfig {
+            max_files: 4,
+            ..Default::default()
+        })
+        .unwrap();
+        let outcome = discovery.scan(dir.path(), &["go"]);
+
+        assert_eq!(outcome.files.len(), 4);
+        assert_eq!(outcome.summary.files_scanned, 4);
+        assert_eq!(outcome.summary.truncated, Some(ScanLimit::Files(4)));
+    }
+
+    #[test]
+    fn test_max_depth_reports_truncation() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b/c")).unwrap();
+        fs::create_dir_all(dir.path().join("a/b/generated")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "generated/\n").unwrap();
+        fs::write(dir.path().join("a/top.java"), "").unwrap();
+        fs::write(dir.path().join("a/b/c/notes.txt"), "").unwrap();
+        fs::write(dir.path().join("a/b/generated/Gen.java"), "").unwrap();
+
+        let discovery = FileDiscovery::new(&AnalyzerConfig {
+            max_depth: 2,
+            ..Default::default()
+        })
+        .unwrap();
+
+        // Only non-matching and ignored files lie deeper: nothing was skipped
+        let outcome = discovery.scan(dir.path(), &["java"]);
+        assert_eq!(relative_names(dir.path(), &outcome), vec!["a/top.java"]);
+        assert!(outcome.summary.truncated.is_none());
+
+        fs::write(dir.path().join("a/b/c/deep.java"), "").unwrap();
+        let outcome = discovery.scan(dir.path(), &["java"]);
+        assert_eq!(relative_names(dir.path(), &outcome), vec!["a/top.java"]);
+        assert_eq!(outcome.summary.truncated, Some(ScanLimit::Depth(2)));
+    }
+
+    #[test]
+    fn test_untruncated_scan_summary() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+        let discovery = FileDiscovery::new(&AnalyzerConfig::default()).unwrap();
+        let outcome = discovery.scan(dir.path(), &["rs"]);
+
+        assert_eq!(outcome.summary.total_bytes, 12);
+        assert!(outcome.summary.truncated.is_none());
+    }
+
+    #[test]
+    fn test_hidden_files_are_opt_in() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::create_dir_all(dir.path().join(".github/workflows")).unwrap();
+        fs::write(dir.path().join(".git/config"), "").unwrap();
+        fs::write(dir.path().join(".github/workflows/ci.yml"), "").unwrap();
+        fs::write(dir.path().join(".env"), "").unwrap();
+        fs::write(dir.path().join("app.yml"), "").unwrap();
+
+        let is_config = |path: &Path| {
+            path.file_name()
+                .is_some_and(|n| n == ".env" || n == "config")
+                || path.extension().is_some_and(|e| e == "yml")
+        };
+        let discovery = FileDiscovery::new(&config(&[], 10)).unwrap();
+        let outcome = discovery.scan_matching(dir.path(), is_config);
+        assert_eq!(relative_names(dir.path(), &outcome), vec!["app.yml"]);
+
+        let outcome = discovery
+            .with_hidden(true)
+            .scan_matching(dir.path(), is_config);
+        assert_eq!(
+            relative_names(dir.path(), &outcome),
+            vec![".env", ".github/workflows/ci.yml", "app.yml"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_rejected() {
+        assert!(FileDiscovery::new(&config(&["src/[bad"], 10)).is_err());
+    }
+}
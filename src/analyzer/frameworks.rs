@@ -0,0 +1,319 @@
+//! Framework detection from a signature catalog.
+//!
+//! Each signature names a canonical framework (the key pattern `framework`
+//! fields use) and the signals that identify it: dependency names, a version
+//! requirement, files and directories. Every matching signature becomes a
+//! ranked candidate whose confidence comes from the kinds of signals that
+//! matched. The built-in catalog ships as `data/frameworks.toml`.
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobMatcher};
+use semver::VersionReq;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::path::Path;
+
+use crate::health::version::{Ecosystem, VersionRange};
+use crate::types::{Dependency, FrameworkCandidate, ProjectMetadata, ProjectType};
+
+/// Catalog compiled into the binary.
+const BUILTIN_CATALOG: &str = include_str!("../../data/frameworks.toml");
+
+/// Confidence contributed by each kind of signal.
+const DEPENDENCY_WEIGHT: f32 = 0.6;
+const FILE_WEIGHT: f32 = 0.25;
+const DIRECTORY_WEIGHT: f32 = 0.15;
+
+/// A catalog file: `[[frameworks]]` entries.
+#[derive(Debug, Deserialize)]
+struct CatalogFile {
+    frameworks: Vec<Signature>,
+}
+
+/// How to recognize one framework.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Signature {
+    name: String,
+    #[serde(default)]
+    project_type: Vec<String>,
+    /// Dependency names; `*` matches any characters
+    #[serde(default)]
+    dependencies: Vec<String>,
+    /// Requirement for the lowest version the matched dependency allows
+    version: Option<String>,
+    #[serde(default)]
+    files: Vec<String>,
+    #[serde(default)]
+    directories: Vec<String>,
+    /// Less specific frameworks this one builds on
+    #[serde(default)]
+    extends: Vec<String>,
+    entry_point: Option<String>,
+    build_command: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledSignature {
+    signature: Signature,
+    dependencies: Vec<GlobMatcher>,
+    version: Option<VersionReq>,
+}
+
+/// Ranks the frameworks a project uses.
+#[derive(Debug, Clone)]
+pub struct FrameworkCatalog {
+    signatures: Vec<CompiledSignature>,
+}
+
+impl Default for FrameworkCatalog {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl FrameworkCatalog {
+    /// The catalog compiled into the binary (`data/frameworks.toml`).
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN_CATALOG).expect("built-in framework catalog is valid")
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let file: CatalogFile = toml::from_str(content)?;
+        let signatures = file
+            .frameworks
+            .into_iter()
+            .map(|signature| {
+                let dependencies = signature
+                    .dependencies
+                    .iter()
+                    .map(|name| Ok(Glob::new(name)?.compile_matcher()))
+                    .collect::<Result<_>>()
+                    .with_context(|| format!("Invalid dependency in '{}'", signature.name))?;
+                let version = signature
+                    .version
+                    .as_deref()
+                    .map(VersionReq::parse)
+                    .transpose()
+                    .with_context(|| format!("Invalid version in '{}'", signature.name))?;
+                Ok(CompiledSignature {
+                    signature,
+                    dependencies,
+                    version,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { signatures })
+    }
+
+    /// Frameworks matching the project, most likely first.
+    ///
+    /// A framework ranks above the ones it `extends` when both match;
+    /// otherwise candidates are ordered by confidence, then catalog order.
+    pub fn detect(
+        &self,
+        project_type: ProjectType,
+        dependencies: &[Dependency],
+        root: &Path,
+    ) -> Vec<FrameworkCandidate> {
+        let ecosystem = Ecosystem::for_project(project_type);
+        let mut matched: Vec<(&Signature, FrameworkCandidate)> = self
+            .signatures
+            .iter()
+            .filter(|s| {
+                s.signature
+                    .project_type
+                    .iter()
+                    .any(|t| t.eq_ignore_ascii_case(project_type.as_str()))
+            })
+            .filter_map(|s| Some((&s.signature, s.evaluate(ecosystem, dependencies, root)?)))
+            .collect();
+
+        let extended: Vec<String> = matched
+            .iter()
+            .flat_map(|(s, _)| s.extends.iter().cloned())
+            .collect();
+        matched.sort_by_key(|(s, c)| {
+            (
+                extended.contains(&s.name),
+                Reverse((c.confidence * 100.0).round() as u32),
+            )
+        });
+        matched.into_iter().map(|(_, c)| c).collect()
+    }
+
+    /// Record the detected frameworks in `metadata`.
+    ///
+    /// The first candidate becomes the `framework` metadata value, and its
+    /// entry point and build command replace the project defaults.
+    pub fn apply(&self, metadata: &mut ProjectMetadata, candidates: Vec<FrameworkCandidate>) {
+        if let Some(primary) = candidates.first() {
+            metadata
+                .extra
+                .insert("framework".to_string(), primary.name.clone());
+            if let Some(signature) = self
+                .signatures
+                .iter()
+                .map(|s| &s.signature)
+                .find(|s| s.name == primary.name)
+            {
+                if let Some(ref entry) = signature.entry_point {
+                    metadata.entry_point = Some(entry.clone());
+                }
+                if let Some(ref command) = signature.build_command {
+                    metadata.build_command = Some(command.clone());
+                }
+            }
+        }
+        metadata.frameworks = candidates;
+    }
+}
+
+impl CompiledSignature {
+    fn evaluate(
+        &self,
+        ecosystem: Option<Ecosystem>,
+        dependencies: &[Dependency],
+        root: &Path,
+    ) -> Option<FrameworkCandidate> {
+        let mut confidence = 0.0;
+        let mut evidence = Vec::new();
+
+        let dependency = dependencies.iter().find(|d| {
+            self.dependencies.iter().any(|m| m.is_match(&d.name))
+                && self.version.as_ref().is_none_or(|req| {
+                    ecosystem
+                        .and_then(|eco| VersionRange::parse(eco, &d.version))
+                        .and_then(|range| range.lowest().cloned())
+                        .is_some_and(|v| req.matches(&v))
+                })
+        });
+        if let Some(dependency) = dependency {
+            confidence += DEPENDENCY_WEIGHT;
+            evidence.push(format!(
+                "dependency {} {}",
+                dependency.name, dependency.version
+            ));
+        }
+        if let Some(file) = self.signature.files.iter().find(|f| root.join(f).is_file()) {
+            confidence += FILE_WEIGHT;
+            evidence.push(format!("file {}", file));
+        }
+        if let Some(dir) = self
+            .signature
+            .directories
+            .iter()
+            .find(|d| root.join(d).is_dir())
+        {
+            confidence += DIRECTORY_WEIGHT;
+            evidence.push(format!("directory {}", dir));
+        }
+
+        (!evidence.is_empty()).then(|| FrameworkCandidate {
+            name: self.signature.name.clone(),
+            confidence: f32::min(confidence, 1.0),
+            evidence,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn dep(name: &str, version: &str) -> Dependency {
+        Dependency {
+            name: name.to_string(),
+            version: version.to_string(),
+            dev_only: false,
+        }
+    }
+
+    fn names(candidates: &[FrameworkCandidate]) -> Vec<&str> {
+        candidates.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_builtin_catalog_parses() {
+        let catalog = FrameworkCatalog::builtin();
+        assert!(catalog.signatures.len() > 20);
+        for s in &catalog.signatures {
+            assert_eq!(s.signature.name, s.signature.name.to_lowercase());
+            assert!(!s.signature.project_type.is_empty(), "{}", s.signature.name);
+        }
+    }
+
+    #[test]
+    fn test_specific_framework_ranks_above_base() {
+        let dir = tempdir().unwrap();
+        let candidates = FrameworkCatalog::builtin().detect(
+            ProjectType::Node,
+            &[
+                dep("react", "^18.2.0"),
+                dep("react-dom", "^18.2.0"),
+                dep("next", "14.1.0"),
+            ],
+            dir.path(),
+        );
+        assert_eq!(names(&candidates), vec!["nextjs", "react"]);
+        assert_eq!(candidates[0].evidence, vec!["dependency next 14.1.0"]);
+
+        // The config file alone still puts Next.js above React
+        fs::write(dir.path().join("next.config.mjs"), "export default {}").unwrap();
+        let candidates = FrameworkCatalog::builtin().detect(
+            ProjectType::Node,
+            &[dep("react", "^18.2.0")],
+            dir.path(),
+        );
+        assert_eq!(names(&candidates), vec!["nextjs", "react"]);
+        assert!(candidates[0].confidence < candidates[1].confidence);
+    }
+
+    #[test]
+    fn test_files_and_confidence() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("artisan"), "#!/usr/bin/env php").unwrap();
+        let catalog = FrameworkCatalog::builtin();
+
+        let candidates = catalog.detect(ProjectType::Php, &[], dir.path());
+        assert_eq!(names(&candidates), vec!["laravel"]);
+        assert!((candidates[0].confidence - 0.25).abs() < f32::EPSILON);
+
+        let candidates = catalog.detect(
+            ProjectType::Php,
+            &[dep("laravel/framework", "^10.0")],
+            dir.path(),
+        );
+        assert!((candidates[0].confidence - 0.85).abs() < 1e-6);
+
+        let mut metadata = ProjectMetadata::default();
+        catalog.apply(&mut metadata, candidates);
+        assert_eq!(metadata.extra["framework"], "laravel");
+        assert_eq!(metadata.entry_point.as_deref(), Some("public/index.php"));
+        assert_eq!(metadata.frameworks.len(), 1);
+    }
+
+    #[test]
+    fn test_version_requirement_and_wildcards() {
+        let dir = tempdir().unwrap();
+        let catalog = FrameworkCatalog::builtin();
+
+        let legacy = catalog.detect(ProjectType::Node, &[dep("angular", "~1.8.2")], dir.path());
+        assert_eq!(names(&legacy), vec!["angularjs"]);
+        let modern = catalog.detect(ProjectType::Node, &[dep("angular", "^2.0.0")], dir.path());
+        assert!(modern.is_empty());
+
+        let go = catalog.detect(
+            ProjectType::Go,
+            &[dep("github.com/gofiber/fiber/v2", "v2.52.0")],
+            dir.path(),
+        );
+        assert_eq!(names(&go), vec!["fiber"]);
+
+        // Signatures only apply to their project types
+        let rust = catalog.detect(ProjectType::Rust, &[dep("react", "1")], dir.path());
+        assert!(rust.is_empty());
+    }
+}
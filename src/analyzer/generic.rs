@@ -1,22 +1,52 @@
 use anyhow::{Context, Result};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
 
+use super::cache::{AnalysisCache, ParsedFile};
 use super::detector::ProjectDetector;
+use super::discovery::FileDiscovery;
+use super::frameworks::FrameworkCatalog;
+use super::git::GitAnalyzer;
+use super::infra::InfraAnalyzer;
+use super::metrics::MetricsAnalyzer;
+use super::symbols::{ExtractedFile, SymbolExtractor};
+use crate::config::AnalyzerConfig;
 #[allow(unused_imports)]
 use crate::types::{
-    Dependency, Project, ProjectMetadata, ProjectType, SourceFile, Symbol, SymbolKind,
+    ChangeStatus, ChangedFile, Dependency, GitInfo, InfraSummary, Project, ProjectMetadata,
+    ProjectType, ScanSummary, SourceFile, Symbol, SymbolKind,
 };
 
 /// Generic project analyzer that works with any project type.
 ///
 /// Supports multiple project types including Rust, Node.js, Python, .NET,
 /// Go, Java, and PHP projects.
-pub struct GenericAnalyzer;
+#[derive(Default)]
+pub struct GenericAnalyzer {
+    config: AnalyzerConfig,
+    cache_dir: Option<PathBuf>,
+    frameworks: FrameworkCatalog,
+}
 
 impl GenericAnalyzer {
-    /// Analyze a project directory and return a generic Project struct.
+    /// Create an analyzer using the given analyzer configuration.
+    pub fn new(config: AnalyzerConfig) -> Self {
+        Self {
+            config,
+            cache_dir: None,
+            frameworks: FrameworkCatalog::builtin(),
+        }
+    }
+
+    /// Enable incremental re-analysis, persisting per-project caches in `dir`.
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Analyze a project directory with `config`, keeping the incremental
+    /// cache in `cache_dir`.
     ///
     /// # Arguments
     /// * `path` - Path to the project root directory
@@ -26,14 +56,60 @@ impl GenericAnalyzer {
     ///
     /// # Errors
     /// Returns an error if the project cannot be analyzed or required files are missing
-    pub async fn analyze(path: &Path) -> Result<Project> {
+    pub async fn analyze(
+        path: &Path,
+        config: AnalyzerConfig,
+        cache_dir: impl Into<PathBuf>,
+    ) -> Result<Project> {
+        Self::new(config)
+            .with_cache_dir(cache_dir)
+            .analyze_project(path)
+            .await
+    }
+
+    /// Analyze a project directory.
+    ///
+    /// File discovery honors `.gitignore`/`.ignore`, the project's `.mcpignore`,
+    /// the configured `ignore_patterns` and scan limits. With a cache directory
+    /// set, only files changed since the previous run are reparsed and
+    /// `Project.scan.changes` lists what was added, modified or removed.
+    ///
+    /// # Errors
+    /// Returns an error if the project cannot be analyzed, required files are
+    /// missing, or an ignore pattern is invalid
+    pub async fn analyze_project(&self, path: &Path) -> Result<Project> {
+        self.analyze_scoped(path, None).await
+    }
+
+    /// Analyze only the given changed files of a project.
+    ///
+    /// Deleted files are skipped. The incremental cache and git history are
+    /// not used, so the result reflects just the diff.
+    ///
+    /// # Errors
+    /// Returns an error if the project cannot be analyzed or required files are missing
+    pub async fn analyze_changed_files(
+        &self,
+        path: &Path,
+        changed: &[ChangedFile],
+    ) -> Result<Project> {
+        let only = changed
+            .iter()
+            .filter(|f| f.status != ChangeStatus::Deleted)
+            .map(|f| path.join(&f.path))
+            .collect();
+        self.analyze_scoped(path, Some(only)).await
+    }
+
+    /// Analyze a project, restricted to the files in `only` when given.
+    async fn analyze_scoped(&self, path: &Path, only: Option<HashSet<PathBuf>>) -> Result<Project> {
         // Detect project type
         let project_type = ProjectDetector::detect(path);
 
         tracing::debug!(project_type = ?project_type, "Detected project type");
 
         // Get project info based on type
-        let (name, version, dependencies, metadata) = match project_type {
+        let (name, version, dependencies, mut metadata) = match project_type {
             ProjectType::DotNet => Self::parse_dotnet_project(path)?,
             ProjectType::Rust => Self::parse_rust_project(path)?,
             ProjectType::Node => Self::parse_node_project(path)?,
@@ -43,10 +119,19 @@ impl GenericAnalyzer {
             ProjectType::Php => Self::parse_php_project(path)?,
             ProjectType::Unknown => Self::parse_unknown_project(path)?,
         };
+        let frameworks = self.frameworks.detect(project_type, &dependencies, path);
+        self.frameworks.apply(&mut metadata, frameworks);
 
         // Find and analyze source files
         let extensions = ProjectDetector::get_source_extensions(&project_type);
-        let files = Self::find_and_analyze_files(path, &extensions)?;
+        let scoped = only.is_some();
+        let (files, scan) = self.find_and_analyze_files(path, &extensions, only).await?;
+        let git = if scoped {
+            None
+        } else {
+            metadata.infra = self.read_infra(path).await?;
+            self.read_git_history(path).await
+        };
 
         Ok(Project {
             path: path.to_path_buf(),
@@ -56,9 +141,37 @@ impl GenericAnalyzer {
             dependencies,
             files,
             metadata,
+            scan,
+            git,
         })
     }
 
+    /// Read git history when enabled. Failures only cost the history.
+    async fn read_git_history(&self, path: &Path) -> Option<GitInfo> {
+        if !self.config.git_history {
+            return None;
+        }
+
+        let analyzer =
+            GitAnalyzer::new(self.config.git_history_days, self.config.git_recent_commits);
+        let root = path.to_path_buf();
+        tokio::task::spawn_blocking(move || analyzer.analyze(&root))
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Git history task failed");
+                None
+            })
+    }
+
+    /// Summarize Docker, Kubernetes, Terraform and CI configuration.
+    async fn read_infra(&self, path: &Path) -> Result<InfraSummary> {
+        let analyzer = InfraAnalyzer::new(&self.config)?;
+        let root = path.to_path_buf();
+        tokio::task::spawn_blocking(move || analyzer.analyze(&root))
+            .await
+            .context("Infrastructure scan task failed")
+    }
+
     // ========================================================================
     // Project-specific parsers
     // ========================================================================
@@ -151,26 +264,11 @@ impl GenericAnalyzer {
             }
         }
 
-        let mut metadata = ProjectMetadata {
+        let metadata = ProjectMetadata {
             entry_point: json["main"].as_str().map(|s| s.to_string()),
             ..Default::default()
         };
 
-        // Detect framework
-        if json["dependencies"].get("react").is_some() {
-            metadata
-                .extra
-                .insert("framework".to_string(), "react".to_string());
-        } else if json["dependencies"].get("vue").is_some() {
-            metadata
-                .extra
-                .insert("framework".to_string(), "vue".to_string());
-        } else if json["dependencies"].get("next").is_some() {
-            metadata
-                .extra
-                .insert("framework".to_string(), "next".to_string());
-        }
-
         Ok((name, version, dependencies, metadata))
     }
 
@@ -210,13 +308,18 @@ impl GenericAnalyzer {
                     continue;
                 }
 
-                // Parse "package==version" or "package>=version" etc.
-                let (pkg_name, pkg_version) = if let Some(pos) = line.find("==") {
-                    (line[..pos].to_string(), line[pos + 2..].to_string())
-                } else if let Some(pos) = line.find(">=") {
-                    (line[..pos].to_string(), line[pos + 2..].to_string())
-                } else {
-                    (line.to_string(), "*".to_string())
+                // "package==version" keeps the version; other specifiers
+                // (">=1.0,<2", "~=1.4") are kept whole. Environment markers are dropped.
+                let line = line.split(';').next().unwrap_or(line).trim();
+                let (pkg_name, pkg_version) = match line.find(['=', '<', '>', '!', '~']) {
+                    Some(pos) => {
+                        let spec = line[pos..].trim();
+                        (
+                            line[..pos].trim().to_string(),
+                            spec.strip_prefix("==").unwrap_or(spec).to_string(),
+                        )
+                    }
+                    None => (line.to_string(), "*".to_string()),
                 };
 
                 dependencies.push(Dependency {
@@ -376,12 +479,6 @@ impl GenericAnalyzer {
             }
         }
 
-        // Detect framework
-        let framework = Self::detect_php_framework(&dependencies, path);
-        if let Some(fw) = framework {
-            metadata.extra.insert("framework".to_string(), fw);
-        }
-
         // Check for frontend (Vue, etc.)
         let package_json = path.join("package.json");
         if package_json.exists() {
@@ -419,88 +516,12 @@ impl GenericAnalyzer {
             }
         }
 
-        // Set entry point based on framework
-        if metadata
-            .extra
-            .get("framework")
-            .map(|f| f == "laravel")
-            .unwrap_or(false)
-        {
-            metadata.entry_point = Some("public/index.php".to_string());
-            metadata.build_command = Some("php artisan serve".to_string());
-        } else if metadata
-            .extra
-            .get("framework")
-            .map(|f| f == "symfony")
-            .unwrap_or(false)
-        {
-            metadata.entry_point = Some("public/index.php".to_string());
-            metadata.build_command = Some("symfony server:start".to_string());
-        } else {
-            metadata.entry_point = Some("index.php".to_string());
-            metadata.build_command = Some("php -S localhost:8000".to_string());
-        }
+        metadata.entry_point = Some("index.php".to_string());
+        metadata.build_command = Some("php -S localhost:8000".to_string());
 
         Ok((name, version, dependencies, metadata))
     }
 
-    /// Detect PHP framework from dependencies and directory structure
-    fn detect_php_framework(dependencies: &[Dependency], path: &Path) -> Option<String> {
-        // Laravel detection
-        if dependencies.iter().any(|d| d.name == "laravel/framework") {
-            return Some("laravel".to_string());
-        }
-        if path.join("artisan").exists() {
-            return Some("laravel".to_string());
-        }
-
-        // Symfony detection
-        if dependencies.iter().any(|d| d.name.starts_with("symfony/")) {
-            // Check if it's a full Symfony app or just uses components
-            if dependencies
-                .iter()
-                .any(|d| d.name == "symfony/framework-bundle")
-            {
-                return Some("symfony".to_string());
-            }
-        }
-
-        // WordPress detection
-        if path.join("wp-config.php").exists() || path.join("wp-content").exists() {
-            return Some("wordpress".to_string());
-        }
-
-        // CodeIgniter detection
-        if dependencies
-            .iter()
-            .any(|d| d.name == "codeigniter4/framework")
-        {
-            return Some("codeigniter".to_string());
-        }
-
-        // Yii detection
-        if dependencies.iter().any(|d| d.name.starts_with("yiisoft/")) {
-            return Some("yii".to_string());
-        }
-
-        // CakePHP detection
-        if dependencies.iter().any(|d| d.name == "cakephp/cakephp") {
-            return Some("cakephp".to_string());
-        }
-
-        // Slim detection
-        if dependencies.iter().any(|d| d.name == "slim/slim") {
-            return Some("slim".to_string());
-        }
-
-        // Drupal detection
-        if dependencies.iter().any(|d| d.name == "drupal/core") {
-            return Some("drupal".to_string());
-        }
-
-        None
-    }
-
     fn parse_unknown_project(
         path: &Path,
     ) -> Result<(String, Option<String>, Vec<Dependency>, ProjectMetadata)> {
@@ -649,41 +670,65 @@ impl GenericAnalyzer {
         None
     }
 
-    fn find_and_analyze_files(path: &Path, extensions: &[&str]) -> Result<Vec<SourceFile>> {
-        let mut files = Vec::new();
-
-        for entry in WalkDir::new(path)
-            .follow_links(false)
-            .into_iter()
-            .filter_entry(|e| {
-                let name = e.file_name().to_str().unwrap_or("");
-                !name.starts_with('.')
-                    && name != "node_modules"
-                    && name != "target"
-                    && name != "bin"
-                    && name != "obj"
-                    && name != "__pycache__"
-                    && name != ".git"
-                    && name != "vendor"
-            })
-        {
-            let entry = entry?;
-            let file_path = entry.path();
-
-            if let Some(ext) = file_path.extension().and_then(|e| e.to_str()) {
-                if extensions.contains(&ext) {
-                    let metadata = fs::metadata(file_path)?;
+    /// Discover and parse source files on the blocking pool so large trees
+    /// don't stall the runtime.
+    async fn find_and_analyze_files(
+        &self,
+        path: &Path,
+        extensions: &[&'static str],
+        only: Option<HashSet<PathBuf>>,
+    ) -> Result<(Vec<SourceFile>, ScanSummary)> {
+        let discovery = FileDiscovery::new(&self.config)?;
+        let root = path.to_path_buf();
+        let extensions = extensions.to_vec();
+        // A restricted scan would make every other file look removed
+        let cache_dir = self.cache_dir.clone().filter(|_| only.is_none());
+
+        tokio::task::spawn_blocking(move || {
+            let mut outcome = discovery.scan(&root, &extensions);
+            if let Some(ref only) = only {
+                outcome.files.retain(|f| only.contains(&f.path));
+                outcome.summary.files_scanned = outcome.files.len();
+                outcome.summary.total_bytes = outcome.files.iter().map(|f| f.size_bytes).sum();
+            }
 
-                    files.push(SourceFile {
-                        path: file_path.to_path_buf(),
-                        language: ext.to_string(),
-                        size_bytes: metadata.len(),
-                        symbols: Vec::new(), // TODO: Parse symbols with tree-sitter
-                    });
-                }
+            let mut cache = match cache_dir {
+                Some(ref dir) => AnalysisCache::load(dir, &root),
+                None => AnalysisCache::in_memory(&root),
+            };
+            let mut extractor = SymbolExtractor::new();
+            let mut metrics = MetricsAnalyzer::new();
+            let refresh = cache.refresh(
+                &root,
+                outcome.files,
+                outcome.summary.truncated.is_some(),
+                |path, language, source| {
+                    let extracted = extractor
+                        .extract(path, language, source)
+                        .unwrap_or_else(|e| {
+                            tracing::warn!(path = %path.display(), error = %e, "Failed to extract symbols");
+                            ExtractedFile::default()
+                        });
+                    ParsedFile {
+                        symbols: extracted.symbols,
+                        metrics: metrics.analyze(language, source),
+                        namespace: extracted.imports.namespace,
+                        imports: extracted.imports.imports,
+                    }
+                },
+            );
+            if let Err(e) = cache.save() {
+                tracing::warn!(error = %e, "Failed to save analysis cache (non-fatal)");
             }
-        }
 
-        Ok(files)
+            let scan = ScanSummary {
+                files_reparsed: refresh.reparsed,
+                changes: refresh.changes,
+                ..outcome.summary
+            };
+            (refresh.files, scan)
+        })
+        .await
+        .context("File discovery task failed")
     }
 }
@@ -0,0 +1,384 @@
+//! Git history for a project, read through the local `git` binary.
+//!
+//! Everything comes from the local clone (`status`, `log`, `rev-parse`), so
+//! it works offline. Projects outside a repository, or machines without
+//! `git`, simply get no history.
+
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::types::{AuthorCommits, DirectoryAuthors, FileChurn, GitInfo, UncommittedChange};
+
+/// Number of entries kept in each ranked list.
+const TOP_N: usize = 10;
+/// Authors listed per directory.
+const AUTHORS_PER_DIR: usize = 3;
+
+/// Run `git` in `dir`, returning stdout on success.
+///
+/// The project may be untrusted, so repository config that runs commands
+/// (`core.fsmonitor`, hooks) is overridden on every invocation.
+///
+/// # Errors
+/// Returns an error if `git` cannot be started or exits unsuccessfully
+pub fn run(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args([
+            "-c",
+            "core.fsmonitor=false",
+            "-c",
+            "core.hooksPath=/dev/null",
+        ])
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        // Never block on a credential or editor prompt
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Collects `GitInfo` for projects inside a git work tree.
+pub struct GitAnalyzer {
+    history_days: u32,
+    recent_commits: usize,
+}
+
+impl GitAnalyzer {
+    /// `history_days` bounds the churn window, `recent_commits` how many of the
+    /// latest commits contribute to the recently touched files.
+    pub fn new(history_days: u32, recent_commits: usize) -> Self {
+        Self {
+            history_days,
+            recent_commits,
+        }
+    }
+
+    /// Read the history of the repository containing `root`.
+    ///
+    /// Returns `None` when `root` is not inside a git work tree or `git`
+    /// cannot be run. Paths are relative to `root`.
+    pub fn analyze(&self, root: &Path) -> Option<GitInfo> {
+        let inside = self.git(root, &["rev-parse", "--is-inside-work-tree"])?;
+        if inside.trim() != "true" {
+            return None;
+        }
+
+        // Status paths are relative to the repository top level
+        let prefix = self
+            .git(root, &["rev-parse", "--show-prefix"])
+            .map(|p| p.trim().to_string())
+            .unwrap_or_default();
+
+        // Fails on a repository without commits
+        let branch = self
+            .git(root, &["rev-parse", "--abbrev-ref", "HEAD"])
+            .map(|b| b.trim().to_string())
+            .filter(|b| b != "HEAD");
+        let head = self
+            .git(root, &["rev-parse", "--short", "HEAD"])
+            .map(|h| h.trim().to_string());
+
+        let uncommitted = self
+            .git(
+                root,
+                &[
+                    "status",
+                    "--porcelain=v1",
+                    "--untracked-files=all",
+                    "--",
+                    ".",
+                ],
+            )
+            .map(|out| Self::parse_status(&out, &prefix))
+            .unwrap_or_default();
+
+        let since = format!("--since={}.days.ago", self.history_days);
+        let (hot_files, directory_authors) = self
+            .git(
+                root,
+                &[
+                    "log",
+                    &since,
+                    "--no-merges",
+                    "--no-ext-diff",
+                    "--no-textconv",
+                    "--format=%x00%aN",
+                    "--name-only",
+                    "--relative",
+                    "--",
+                    ".",
+                ],
+            )
+            .map(|out| Self::parse_churn(&out))
+            .unwrap_or_default();
+
+        let limit = format!("-n{}", self.recent_commits);
+        let recent_files = self
+            .git(
+                root,
+                &[
+                    "log",
+                    &limit,
+                    "--no-ext-diff",
+                    "--no-textconv",
+                    "--format=",
+                    "--name-only",
+                    "--relative",
+                    "--",
+                    ".",
+                ],
+            )
+            .map(|out| Self::parse_recent(&out))
+            .unwrap_or_default();
+
+        Some(GitInfo {
+            branch,
+            head,
+            uncommitted,
+            history_days: self.history_days,
+            hot_files,
+            directory_authors,
+            recent_files,
+        })
+    }
+
+    /// Run `git` in `dir`, logging failures instead of returning them.
+    fn git(&self, dir: &Path, args: &[&str]) -> Option<String> {
+        run(dir, args)
+            .map_err(|e| tracing::debug!(args = ?args, error = %e, "git command failed"))
+            .ok()
+    }
+
+    /// Parse `git status --porcelain=v1`, keeping paths below `prefix`.
+    fn parse_status(output: &str, prefix: &str) -> Vec<UncommittedChange> {
+        output
+            .lines()
+            .filter(|line| line.len() > 3)
+            .filter_map(|line| {
+                let (code, path) = line.split_at(3);
+                // Renames are reported as "old -> new"
+                let path = path.rsplit(" -> ").next().unwrap_or(path).trim_matches('"');
+                let relative = path.strip_prefix(prefix)?;
+                Some(UncommittedChange {
+                    path: PathBuf::from(relative),
+                    status: code.trim().to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Parse `git log --format=%x00%aN --name-only` into the most changed files
+    /// and the primary authors per top-level directory.
+    fn parse_churn(output: &str) -> (Vec<FileChurn>, Vec<DirectoryAuthors>) {
+        let mut file_commits: HashMap<&str, usize> = HashMap::new();
+        let mut dir_authors: BTreeMap<String, HashMap<&str, usize>> = BTreeMap::new();
+        let mut author = "";
+
+        for line in output.lines() {
+            if let Some(name) = line.strip_prefix('\0') {
+                author = name;
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+
+            *file_commits.entry(line).or_default() += 1;
+
+            let directory = match line.split_once('/') {
+                Some((dir, _)) => dir.to_string(),
+                None => ".".to_string(),
+            };
+            *dir_authors
+                .entry(directory)
+                .or_default()
+                .entry(author)
+                .or_default() += 1;
+        }
+
+        let mut hot_files: Vec<FileChurn> = file_commits
+            .into_iter()
+            .map(|(path, commits)| FileChurn {
+                path: PathBuf::from(path),
+                commits,
+            })
+            .collect();
+        hot_files.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.path.cmp(&b.path)));
+        hot_files.truncate(TOP_N);
+
+        let mut directory_authors: Vec<DirectoryAuthors> = dir_authors
+            .into_iter()
+            .map(|(directory, authors)| {
+                let mut authors: Vec<AuthorCommits> = authors
+                    .into_iter()
+                    .map(|(name, commits)| AuthorCommits {
+                        name: name.to_string(),
+                        commits,
+                    })
+                    .collect();
+                authors.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name)));
+                authors.truncate(AUTHORS_PER_DIR);
+                DirectoryAuthors { directory, authors }
+            })
+            .collect();
+        directory_authors
+            .sort_by_key(|d| std::cmp::Reverse(d.authors.iter().map(|a| a.commits).sum::<usize>()));
+        directory_authors.truncate(TOP_N);
+
+        (hot_files, directory_authors)
+    }
+
+    /// Unique files from `git log --format= --name-only`, newest first.
+    fn parse_recent(output: &str) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = Vec::new();
+        for line in output.lines().filter(|l| !l.is_empty()) {
+            let path = PathBuf::from(line);
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+        files.truncate(TOP_N * 2);
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn run(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args([
+                "-c",
+                "user.email=dev@example.com",
+                "-c",
+                "commit.gpgsign=false",
+            ])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn commit(dir: &Path, author: &str, file: &str, content: &str) {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+        run(dir, &["add", "-A"]);
+        run(
+            dir,
+            &[
+                "-c",
+                &format!("user.name={author}"),
+                "commit",
+                "-q",
+                "-m",
+                file,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_not_a_repository() {
+        let dir = tempdir().unwrap();
+        assert!(GitAnalyzer::new(90, 10).analyze(dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_history_of_local_repository() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        run(root, &["init", "-q", "-b", "main"]);
+        commit(root, "Ana", "src/lib.rs", "1");
+        commit(root, "Ana", "src/lib.rs", "2");
+        commit(root, "Ben", "src/main.rs", "1");
+        commit(root, "Ben", "README.md", "1");
+        fs::write(root.join("src/lib.rs"), "dirty").unwrap();
+        fs::write(root.join("notes.txt"), "new").unwrap();
+
+        let info = GitAnalyzer::new(30, 2).analyze(root).unwrap();
+
+        assert_eq!(info.branch.as_deref(), Some("main"));
+        assert!(info.head.is_some());
+        assert_eq!(info.hot_files[0].path, PathBuf::from("src/lib.rs"));
+        assert_eq!(info.hot_files[0].commits, 2);
+
+        let src = &info.directory_authors[0];
+        assert_eq!(src.directory, "src");
+        assert_eq!(src.authors[0].name, "Ana");
+
+        assert_eq!(
+            info.recent_files,
+            vec![PathBuf::from("README.md"), PathBuf::from("src/main.rs")]
+        );
+
+        let mut uncommitted: Vec<_> = info
+            .uncommitted
+            .iter()
+            .map(|c| (c.path.to_string_lossy().into_owned(), c.status.as_str()))
+            .collect();
+        uncommitted.sort();
+        assert_eq!(
+            uncommitted,
+            vec![
+                ("notes.txt".to_string(), "??"),
+                ("src/lib.rs".to_string(), "M")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_subdirectory_paths_are_project_relative() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        run(root, &["init", "-q"]);
+        commit(root, "Ana", "app/src/a.rs", "1");
+        commit(root, "Ana", "other/b.rs", "1");
+        fs::write(root.join("app/src/a.rs"), "dirty").unwrap();
+        fs::write(root.join("other/b.rs"), "dirty").unwrap();
+
+        let info = GitAnalyzer::new(30, 10).analyze(&root.join("app")).unwrap();
+
+        assert_eq!(info.hot_files.len(), 1);
+        assert_eq!(info.hot_files[0].path, PathBuf::from("src/a.rs"));
+        assert_eq!(info.uncommitted.len(), 1);
+        assert_eq!(info.uncommitted[0].path, PathBuf::from("src/a.rs"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_repository_config_cannot_run_commands() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        run(root, &["init", "-q"]);
+        commit(root, "Ana", "src/a.rs", "1");
+
+        let marker = root.join("pwned");
+        let hook = format!("touch {}", marker.display());
+        run(root, &["config", "core.fsmonitor", &hook]);
+        fs::write(root.join("src/a.rs"), "dirty").unwrap();
+
+        let info = GitAnalyzer::new(30, 10).analyze(root).unwrap();
+
+        assert_eq!(info.uncommitted.len(), 1);
+        assert!(!marker.exists());
+    }
+}
@@ -0,0 +1,86 @@
+//! Tree-sitter grammars available for source analysis.
+
+use tree_sitter::{Language, Node, Point};
+
+use crate::types::{SourcePosition, SourceRange};
+
+/// A source language with a bundled tree-sitter grammar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Grammar {
+    CSharp,
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+    Java,
+}
+
+impl Grammar {
+    /// Look up the grammar for a file extension (as stored in `SourceFile.language`).
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "cs" => Some(Self::CSharp),
+            "rs" => Some(Self::Rust),
+            "py" | "pyi" => Some(Self::Python),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            "go" => Some(Self::Go),
+            "java" => Some(Self::Java),
+            _ => None,
+        }
+    }
+
+    /// The tree-sitter language for this grammar.
+    pub fn language(self) -> Language {
+        match self {
+            Self::CSharp => tree_sitter_c_sharp::language(),
+            Self::Rust => tree_sitter_rust::language(),
+            Self::Python => tree_sitter_python::language(),
+            Self::JavaScript => tree_sitter_javascript::language(),
+            Self::TypeScript => tree_sitter_typescript::language_typescript(),
+            Self::Tsx => tree_sitter_typescript::language_tsx(),
+            Self::Go => tree_sitter_go::language(),
+            Self::Java => tree_sitter_java::language(),
+        }
+    }
+}
+
+/// Source range of a syntax node, with 1-based lines and columns.
+pub fn node_range(node: Node) -> SourceRange {
+    let position = |point: Point, byte: usize| SourcePosition {
+        line: point.row + 1,
+        column: point.column + 1,
+        byte,
+    };
+    SourceRange {
+        start: position(node.start_position(), node.start_byte()),
+        end: position(node.end_position(), node.end_byte()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree_sitter::Parser;
+
+    #[test]
+    fn test_every_grammar_loads() {
+        for ext in ["cs", "rs", "py", "js", "ts", "tsx", "go", "java"] {
+            let grammar = Grammar::from_extension(ext).unwrap();
+            let mut parser = Parser::new();
+            assert!(
+                parser.set_language(grammar.language()).is_ok(),
+                "{:?} grammar failed to load",
+                grammar
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_extension() {
+        assert_eq!(Grammar::from_extension("php"), None);
+    }
+}
@@ -0,0 +1,537 @@
+//! Recurring structural idioms.
+//!
+//! Mines the syntax trees of a project for functions and statements that
+//! share one structure: repeated DI registrations, identical error-handling
+//! wrappers, components with the same lifecycle method. Each candidate node
+//! is reduced to its shape (node kinds and tokens, with identifiers and
+//! literals as slots) and nodes are grouped by shape. Within a group, slots
+//! whose text is the same everywhere are kept and the others become `$1`,
+//! `$2`, ...; groups where too little is shared are split on their least
+//! varied slot until enough is, or until they are too small to count.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use tree_sitter::{Node, Parser};
+
+use super::grammar::Grammar;
+use super::test_suite::is_test_path;
+use crate::types::Project;
+
+/// Node kinds that can form an idiom: functions, methods and statements.
+const CANDIDATE_KINDS: &[&str] = &[
+    "method_declaration",
+    "constructor_declaration",
+    "method_definition",
+    "function_item",
+    "function_declaration",
+    "function_definition",
+    "expression_statement",
+    "local_declaration_statement",
+    "let_declaration",
+    "try_statement",
+    "using_statement",
+    "if_statement",
+    "match_expression",
+    "with_statement",
+];
+
+/// Smaller nodes (in named nodes) are too trivial to be worth a pattern.
+const MIN_NODES: usize = 10;
+/// Larger nodes are skipped: they practically never repeat as a whole.
+const MAX_NODES: usize = 300;
+/// Share of identifier slots that must be the same in every occurrence.
+const MIN_FIXED_SHARE: f32 = 0.5;
+/// Idioms returned by one mining pass, most significant first.
+const MAX_IDIOMS: usize = 20;
+
+/// One place an idiom occurs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdiomOccurrence {
+    /// File path relative to the project root
+    pub path: PathBuf,
+    /// 1-based line of the first character
+    pub line: usize,
+}
+
+/// A structure recurring in a project.
+#[derive(Debug, Clone)]
+pub struct Idiom {
+    /// Syntax node kind of the occurrences (`try_statement`)
+    pub kind: String,
+    /// File extension of the occurrences
+    pub language: String,
+    /// The first occurrence with the parts that vary replaced by `$1`, `$2`, ...
+    pub template: String,
+    /// Source of the first occurrence
+    pub example: String,
+    /// Sorted by path and line
+    pub occurrences: Vec<IdiomOccurrence>,
+}
+
+/// An identifier or literal of an occurrence.
+struct Slot {
+    kind: &'static str,
+    text: String,
+    /// Byte range within the occurrence's text
+    range: (usize, usize),
+}
+
+/// A candidate node with its shape reduced to slots.
+struct Occurrence {
+    path: PathBuf,
+    line: usize,
+    column: usize,
+    start: usize,
+    end: usize,
+    text: String,
+    slots: Vec<Slot>,
+}
+
+/// Where an occurrence is: path and byte range.
+type Span = (PathBuf, usize, usize);
+
+/// Occurrences sharing one shape.
+struct Group {
+    kind: &'static str,
+    language: String,
+    size: usize,
+    occurrences: Vec<Occurrence>,
+}
+
+/// Mines idioms, reusing one parser across files.
+pub struct IdiomMiner {
+    parser: Parser,
+}
+
+impl Default for IdiomMiner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdiomMiner {
+    pub fn new() -> Self {
+        Self {
+            parser: Parser::new(),
+        }
+    }
+
+    /// Find the idioms occurring at least `min_occurrences` times (and at
+    /// least twice) in the source files of `project`.
+    ///
+    /// Idioms nested in the occurrences of a more significant one are left
+    /// out. Test files and Rust `#[cfg(test)]` modules are skipped, as are
+    /// files that cannot be read or parsed.
+    pub fn mine(&mut self, project: &Project, min_occurrences: usize) -> Vec<Idiom> {
+        let min = min_occurrences.max(2);
+        let mut groups: HashMap<String, Group> = HashMap::new();
+
+        for file in &project.files {
+            let Some(grammar) = Grammar::from_extension(&file.language) else {
+                continue;
+            };
+            let Ok(source) = fs::read_to_string(&file.path) else {
+                continue;
+            };
+            if self.parser.set_language(grammar.language()).is_err() {
+                continue;
+            }
+            let Some(tree) = self.parser.parse(&source, None) else {
+                continue;
+            };
+            let relative = file.path.strip_prefix(&project.path).unwrap_or(&file.path);
+            // Test code repeats fixtures and assertions, not project idioms
+            if is_test_path(relative) {
+                continue;
+            }
+
+            let mut stack = vec![tree.root_node()];
+            while let Some(node) = stack.pop() {
+                if is_test_module(node, &source) {
+                    continue;
+                }
+                let mut cursor = node.walk();
+                stack.extend(node.children(&mut cursor));
+                if !CANDIDATE_KINDS.contains(&node.kind()) || node.has_error() {
+                    continue;
+                }
+                let Some((shape, slots, size)) = reduce(node, &source) else {
+                    continue;
+                };
+                let start = node.start_byte();
+                let group = groups
+                    .entry(format!("{}\n{}", file.language, shape))
+                    .or_insert_with(|| Group {
+                        kind: node.kind(),
+                        language: file.language.clone(),
+                        size,
+                        occurrences: Vec::new(),
+                    });
+                group.occurrences.push(Occurrence {
+                    path: relative.to_path_buf(),
+                    line: node.start_position().row + 1,
+                    column: node.start_position().column,
+                    start,
+                    end: node.end_byte(),
+                    text: source[start..node.end_byte()].to_string(),
+                    slots,
+                });
+            }
+        }
+
+        // (weight, idiom, occurrence spans)
+        let mut found: Vec<(usize, Idiom, Vec<Span>)> = Vec::new();
+        for group in groups.values_mut() {
+            if group.occurrences.len() < min {
+                continue;
+            }
+            group
+                .occurrences
+                .sort_by(|a, b| a.path.cmp(&b.path).then(a.start.cmp(&b.start)));
+            let mut clusters = Vec::new();
+            refine(
+                (0..group.occurrences.len()).collect(),
+                &group.occurrences,
+                min,
+                &mut clusters,
+            );
+            for members in clusters {
+                let idiom = to_idiom(group, &members);
+                let ranges = members
+                    .iter()
+                    .map(|&m| {
+                        let o = &group.occurrences[m];
+                        (o.path.clone(), o.start, o.end)
+                    })
+                    .collect();
+                found.push((members.len() * group.size, idiom, ranges));
+            }
+        }
+
+        found.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.template.cmp(&b.1.template)));
+        let mut covered: Vec<Span> = Vec::new();
+        let mut idioms = Vec::new();
+        for (_, idiom, ranges) in found {
+            let nested = ranges.iter().all(|(path, start, end)| {
+                covered
+                    .iter()
+                    .any(|(p, s, e)| p == path && s <= start && end <= e)
+            });
+            if nested {
+                continue;
+            }
+            covered.extend(ranges);
+            idioms.push(idiom);
+            if idioms.len() == MAX_IDIOMS {
+                break;
+            }
+        }
+        idioms
+    }
+}
+
+/// A Rust module marked `#[cfg(test)]`.
+fn is_test_module(node: Node, source: &str) -> bool {
+    node.kind() == "mod_item"
+        && node.prev_named_sibling().is_some_and(|attribute| {
+            attribute.kind() == "attribute_item"
+                && source[attribute.start_byte()..attribute.end_byte()].contains("cfg(test)")
+        })
+}
+
+/// Shape of the subtree at `node`, its slots and its size in named nodes;
+/// `None` when it is too small or too large.
+fn reduce(node: Node, source: &str) -> Option<(String, Vec<Slot>, usize)> {
+    let origin = node.start_byte();
+    let mut shape = String::new();
+    let mut slots = Vec::new();
+    let mut size = 0;
+
+    // Nodes to visit, and `None` to close the node opened last
+    let mut stack = vec![Some(node)];
+    while let Some(entry) = stack.pop() {
+        let Some(node) = entry else {
+            shape.push(')');
+            continue;
+        };
+        if node.kind().contains("comment") {
+            continue;
+        }
+        if !node.is_named() {
+            shape.push_str(node.kind());
+            shape.push(' ');
+            continue;
+        }
+        size += 1;
+        if size > MAX_NODES {
+            return None;
+        }
+        shape.push('(');
+        shape.push_str(node.kind());
+        if node.child_count() == 0 {
+            slots.push(Slot {
+                kind: node.kind(),
+                text: source[node.start_byte()..node.end_byte()].to_string(),
+                range: (node.start_byte() - origin, node.end_byte() - origin),
+            });
+            shape.push(')');
+            continue;
+        }
+        shape.push(' ');
+        stack.push(None);
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev().map(Some));
+    }
+
+    (size >= MIN_NODES).then_some((shape, slots, size))
+}
+
+fn is_identifier(kind: &str) -> bool {
+    kind.contains("identifier")
+}
+
+/// Split `members` (same shape) into clusters sharing enough identifiers.
+fn refine(members: Vec<usize>, occurrences: &[Occurrence], min: usize, out: &mut Vec<Vec<usize>>) {
+    if members.len() < min {
+        return;
+    }
+    let first = &occurrences[members[0]];
+    let fixed = |k: usize| {
+        members
+            .iter()
+            .all(|&m| occurrences[m].slots[k].text == first.slots[k].text)
+    };
+    let identifiers: Vec<usize> = (0..first.slots.len())
+        .filter(|&k| is_identifier(first.slots[k].kind))
+        .collect();
+    let fixed_count = identifiers.iter().filter(|&&k| fixed(k)).count();
+    #[allow(clippy::cast_precision_loss)]
+    let enough = fixed_count as f32 >= identifiers.len() as f32 * MIN_FIXED_SHARE;
+    if fixed_count > 0 && enough {
+        out.push(members);
+        return;
+    }
+
+    // Split on the varying identifier with the fewest distinct values that
+    // still leaves a large enough bucket
+    let mut best: Option<BTreeMap<&str, Vec<usize>>> = None;
+    for &k in &identifiers {
+        if fixed(k) {
+            continue;
+        }
+        let mut buckets: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for &m in &members {
+            buckets
+                .entry(occurrences[m].slots[k].text.as_str())
+                .or_default()
+                .push(m);
+        }
+        let useful = buckets.values().any(|b| b.len() >= min);
+        let fewer = match best {
+            Some(ref current) => buckets.len() < current.len(),
+            None => true,
+        };
+        if useful && fewer {
+            best = Some(buckets);
+        }
+    }
+    for bucket in best.into_iter().flat_map(BTreeMap::into_values) {
+        refine(bucket, occurrences, min, out);
+    }
+}
+
+/// The idiom formed by `members` of `group`, shown through its first occurrence.
+fn to_idiom(group: &Group, members: &[usize]) -> Idiom {
+    let example = &group.occurrences[members[0]];
+
+    // Varying slots get a placeholder; slots varying together share one
+    let mut placeholders: Vec<Vec<&str>> = Vec::new();
+    let mut replacements: Vec<((usize, usize), usize)> = Vec::new();
+    for (k, slot) in example.slots.iter().enumerate() {
+        let values: Vec<&str> = members
+            .iter()
+            .map(|&m| group.occurrences[m].slots[k].text.as_str())
+            .collect();
+        if values.iter().all(|v| *v == slot.text) {
+            continue;
+        }
+        let number = if let Some(i) = placeholders.iter().position(|p| *p == values) {
+            i + 1
+        } else {
+            placeholders.push(values);
+            placeholders.len()
+        };
+        replacements.push((slot.range, number));
+    }
+
+    let mut template = example.text.clone();
+    for ((start, end), number) in replacements.into_iter().rev() {
+        template.replace_range(start..end, &format!("${}", number));
+    }
+
+    Idiom {
+        kind: group.kind.to_string(),
+        language: group.language.clone(),
+        template: dedent(&template, example.column),
+        example: dedent(&example.text, example.column),
+        occurrences: members
+            .iter()
+            .map(|&m| IdiomOccurrence {
+                path: group.occurrences[m].path.clone(),
+                line: group.occurrences[m].line,
+            })
+            .collect(),
+    }
+}
+
+/// Remove the indentation of the first line (`column` characters) from the others.
+fn dedent(text: &str, column: usize) -> String {
+    let mut lines = text.lines();
+    let mut out = lines.next().unwrap_or_default().to_string();
+    for line in lines {
+        let indent = line
+            .chars()
+            .take(column)
+            .take_while(|c| c.is_whitespace())
+            .map(char::len_utf8)
+            .sum::<usize>();
+        out.push('\n');
+        out.push_str(&line[indent..]);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::source_file;
+    use crate::types::{ProjectType, SourceFile};
+
+    fn project(dir: &std::path::Path, files: &[(&str, &str)]) -> Project {
+        let files = files
+            .iter()
+            .map(|(name, source)| {
+                let path = dir.join(name);
+                fs::write(&path, source).unwrap();
+                SourceFile {
+                    size_bytes: source.len() as u64,
+                    ..source_file(path)
+                }
+            })
+            .collect();
+        Project {
+            name: "app".to_string(),
+            project_type: ProjectType::DotNet,
+            files,
+            ..crate::test_support::project(dir)
+        }
+    }
+
+    const PROGRAM: &str = r#"
+var builder = WebApplication.CreateBuilder(args);
+builder.Services.AddScoped<IOrderService, OrderService>();
+builder.Services.AddScoped<ICartService, CartService>();
+builder.Services.AddScoped<IUserService, UserService>();
+builder.Services.AddSingleton<IClock, SystemClock>();
+Console.WriteLine("ready");
+"#;
+
+    #[test]
+    fn test_repeated_registrations() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project(dir.path(), &[("Program.cs", PROGRAM)]);
+
+        let idioms = IdiomMiner::new().mine(&project, 3);
+        assert_eq!(idioms.len(), 1);
+        let idiom = &idioms[0];
+        assert_eq!(idiom.kind, "expression_statement");
+        assert_eq!(idiom.template, "builder.Services.AddScoped<$1, $2>();");
+        assert_eq!(
+            idiom.example,
+            "builder.Services.AddScoped<IOrderService, OrderService>();"
+        );
+        let lines: Vec<usize> = idiom.occurrences.iter().map(|o| o.line).collect();
+        assert_eq!(lines, vec![3, 4, 5]);
+
+        // Sharing only `builder.Services` is not enough to group all four
+        let idioms = IdiomMiner::new().mine(&project, 5);
+        assert!(idioms.is_empty());
+    }
+
+    #[test]
+    fn test_error_wrappers_across_files_and_nesting() {
+        let component = |name: &str, service: &str| {
+            format!(
+                r#"public class {name}
+{{
+    protected override async Task OnInitializedAsync()
+    {{
+        try
+        {{
+            items = await {service}.GetAllAsync();
+        }}
+        catch (HttpRequestException ex)
+        {{
+            Logger.LogError(ex, "Failed to load");
+        }}
+    }}
+}}
+"#
+            )
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let orders = component("Orders", "OrderService");
+        let users = component("Users", "UserService");
+        let carts = component("Carts", "CartService");
+        let project = project(
+            dir.path(),
+            &[
+                ("Orders.cs", &orders),
+                ("Users.cs", &users),
+                ("Carts.cs", &carts),
+            ],
+        );
+
+        let idioms = IdiomMiner::new().mine(&project, 3);
+        // The try statement and the assignment are nested in the method
+        assert_eq!(idioms.len(), 1);
+        let idiom = &idioms[0];
+        assert_eq!(idiom.kind, "method_declaration");
+        assert_eq!(idiom.occurrences.len(), 3);
+        assert_eq!(idiom.occurrences[0].path, PathBuf::from("Carts.cs"));
+        assert!(idiom
+            .template
+            .starts_with("protected override async Task OnInitializedAsync()\n{\n    try"));
+        assert!(idiom.template.contains("items = await $1.GetAllAsync();"));
+        assert!(idiom.template.contains("catch (HttpRequestException ex)"));
+    }
+
+    #[test]
+    fn test_test_code_is_skipped() {
+        let writes = "fn setup(dir: &Path) {\n    fs::write(dir.join(\"a.json\"), \"{}\").unwrap();\n    fs::write(dir.join(\"b.json\"), \"{}\").unwrap();\n    fs::write(dir.join(\"c.json\"), \"{}\").unwrap();\n}\n";
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("tests")).unwrap();
+        let inline = format!("pub fn run() {{}}\n\n#[cfg(test)]\nmod tests {{\n{writes}}}\n");
+        let tests = project(
+            dir.path(),
+            &[("lib.rs", &inline), ("tests/setup.rs", writes)],
+        );
+        assert!(IdiomMiner::new().mine(&tests, 3).is_empty());
+
+        // The same code outside tests is an idiom
+        let source = project(dir.path(), &[("setup.rs", writes)]);
+        assert_eq!(IdiomMiner::new().mine(&source, 3).len(), 1);
+    }
+
+    #[test]
+    fn test_dedent() {
+        assert_eq!(
+            dedent("if x {\n        y();\n    }", 4),
+            "if x {\n    y();\n}"
+        );
+        assert_eq!(dedent("a\nb", 4), "a\nb");
+    }
+}
@@ -0,0 +1,414 @@
+//! Import extraction for the module dependency graph.
+//!
+//! Collects the raw import specifiers of a file (`use` paths, module
+//! strings, dotted names) plus the namespace or package it declares.
+//! Resolving them to project modules is left to `module_graph`.
+//! C# is handled by `CSharpAnalyzer`, which already collects usings.
+
+use std::path::Path;
+use tree_sitter::{Node, Parser};
+
+use super::grammar::Grammar;
+
+/// Imports and the declared namespace of one file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileImports {
+    /// Declared namespace or package (Java `package`, PHP `namespace`)
+    pub namespace: Option<String>,
+    /// Raw import specifiers, in source order
+    pub imports: Vec<String>,
+}
+
+/// Extracts imports, reusing one parser across files.
+pub struct ImportExtractor {
+    parser: Parser,
+}
+
+impl Default for ImportExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImportExtractor {
+    pub fn new() -> Self {
+        Self {
+            parser: Parser::new(),
+        }
+    }
+
+    /// Extract the imports of `source`, written in `language` (the file extension).
+    pub fn extract(&mut self, language: &str, source: &str) -> FileImports {
+        if language == "php" {
+            return Self::php(source);
+        }
+
+        let Some(grammar) = Grammar::from_extension(language) else {
+            return FileImports::default();
+        };
+        if self.parser.set_language(grammar.language()).is_err() {
+            return FileImports::default();
+        }
+        let Some(tree) = self.parser.parse(source, None) else {
+            return FileImports::default();
+        };
+
+        let mut result = FileImports::default();
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            let descend = Self::visit(grammar, node, source.as_bytes(), &mut result);
+            if descend {
+                let mut cursor = node.walk();
+                // Reverse so the stack pops children in source order
+                let children: Vec<Node> = node.children(&mut cursor).collect();
+                stack.extend(children.into_iter().rev());
+            }
+        }
+        result
+    }
+
+    /// Record imports found at `node`; returns whether to descend into it.
+    fn visit(grammar: Grammar, node: Node, source: &[u8], out: &mut FileImports) -> bool {
+        let text = |n: Node| n.utf8_text(source).unwrap_or_default().to_string();
+
+        match (grammar, node.kind()) {
+            (Grammar::Rust, "use_declaration") => {
+                if let Some(argument) = node.child_by_field_name("argument") {
+                    out.imports.extend(expand_use_tree(&text(argument)));
+                }
+                false
+            }
+            // `mod foo;` declares a child rather than depending on it; recording
+            // it would turn every child's `use super::…` into a cycle
+            (Grammar::Rust, "mod_item") => node.child_by_field_name("body").is_some(),
+            (Grammar::Python, "import_statement") => {
+                let mut cursor = node.walk();
+                for name in node.children_by_field_name("name", &mut cursor) {
+                    out.imports.push(text(Self::python_name(name)));
+                }
+                false
+            }
+            (Grammar::Python, "import_from_statement") => {
+                let Some(module) = node.child_by_field_name("module_name") else {
+                    return false;
+                };
+                let module = text(module);
+                let mut cursor = node.walk();
+                let names: Vec<String> = node
+                    .children_by_field_name("name", &mut cursor)
+                    .map(|n| text(Self::python_name(n)))
+                    .collect();
+                if names.is_empty() {
+                    out.imports.push(module.clone());
+                }
+                // `from pkg import mod` may name a submodule; resolution falls back to `pkg`
+                for name in names {
+                    if module.ends_with('.') {
+                        out.imports.push(format!("{}{}", module, name));
+                    } else {
+                        out.imports.push(format!("{}.{}", module, name));
+                    }
+                }
+                false
+            }
+            (
+                Grammar::JavaScript | Grammar::TypeScript | Grammar::Tsx,
+                "import_statement" | "export_statement",
+            ) => {
+                if let Some(source_node) = node.child_by_field_name("source") {
+                    out.imports.push(unquote(&text(source_node)));
+                    false
+                } else {
+                    // `export function ...` may contain require() calls
+                    true
+                }
+            }
+            (Grammar::JavaScript | Grammar::TypeScript | Grammar::Tsx, "call_expression") => {
+                let callee = node.child_by_field_name("function");
+                let is_import = callee
+                    .map(|f| f.kind() == "import" || text(f) == "require")
+                    .unwrap_or(false);
+                if is_import {
+                    let first_arg = node
+                        .child_by_field_name("arguments")
+                        .and_then(|args| args.named_child(0))
+                        .filter(|arg| arg.kind() == "string");
+                    if let Some(arg) = first_arg {
+                        out.imports.push(unquote(&text(arg)));
+                    }
+                }
+                true
+            }
+            (Grammar::Go, "import_spec") => {
+                if let Some(path) = node.child_by_field_name("path") {
+                    out.imports.push(unquote(&text(path)));
+                }
+                false
+            }
+            (Grammar::Java, "package_declaration") => {
+                out.namespace = Some(Self::strip_statement(&text(node), "package"));
+                false
+            }
+            (Grammar::Java, "import_declaration") => {
+                let import = Self::strip_statement(&text(node), "import");
+                let import = import.strip_prefix("static ").unwrap_or(&import).trim();
+                out.imports.push(import.replace(' ', ""));
+                false
+            }
+            // Imports only appear in statement position; skip expression-heavy leaves
+            (_, _) => node.child_count() > 0,
+        }
+    }
+
+    /// The dotted name of a Python import, ignoring `as` aliases.
+    fn python_name(node: Node) -> Node {
+        if node.kind() == "aliased_import" {
+            node.child_by_field_name("name").unwrap_or(node)
+        } else {
+            node
+        }
+    }
+
+    /// `import a.b.C;` -> `a.b.C`
+    fn strip_statement(statement: &str, keyword: &str) -> String {
+        statement
+            .trim()
+            .trim_start_matches(keyword)
+            .trim_end_matches(';')
+            .trim()
+            .to_string()
+    }
+
+    /// PHP has no bundled grammar: read namespace, `use` and `require` lines.
+    fn php(source: &str) -> FileImports {
+        let mut result = FileImports::default();
+
+        for line in source.lines() {
+            let trimmed = line.trim();
+
+            if let Some(ns) = trimmed.strip_prefix("namespace ") {
+                let ns = ns.trim_end_matches(['{', ';', ' ']).trim();
+                if !ns.is_empty() {
+                    result.namespace = Some(ns.to_string());
+                }
+            } else if let Some(rest) = line.strip_prefix("use ") {
+                // Only unindented `use`: indented ones are trait uses inside classes
+                if rest.starts_with("function ") || rest.starts_with("const ") {
+                    continue;
+                }
+                let rest = rest.trim().trim_end_matches(';');
+                for import in expand_php_use(rest) {
+                    result.imports.push(import);
+                }
+            } else if let Some(path) = Self::php_include(trimmed) {
+                result.imports.push(path);
+            }
+        }
+
+        result
+    }
+
+    /// Path of a `require`/`include` with a literal, relative to the file.
+    fn php_include(line: &str) -> Option<String> {
+        const KEYWORDS: [&str; 4] = ["require_once", "require", "include_once", "include"];
+        let rest = KEYWORDS.iter().find_map(|k| line.strip_prefix(k))?;
+        let start = rest.find(['\'', '"'])?;
+        let quote = rest[start..].chars().next()?;
+        let literal = &rest[start + 1..];
+        let path = &literal[..literal.find(quote)?];
+        if !Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("php"))
+        {
+            return None;
+        }
+        // `__DIR__ . '/x.php'` is relative to the file's directory
+        Some(match path.strip_prefix('/') {
+            Some(relative) => format!("./{}", relative),
+            None if path.starts_with('.') => path.to_string(),
+            None => format!("./{}", path),
+        })
+    }
+}
+
+/// Strip the quotes of a string literal.
+fn unquote(literal: &str) -> String {
+    literal.trim_matches(['"', '\'', '`']).to_string()
+}
+
+/// Expand a Rust use tree into flat paths.
+///
+/// `crate::a::{b, c::{D, E as F}, self}` -> `crate::a::b`, `crate::a::c::D`,
+/// `crate::a::c::E`, `crate::a`. Glob imports keep their prefix.
+pub fn expand_use_tree(tree: &str) -> Vec<String> {
+    let normalized: String = tree.split_whitespace().collect::<Vec<_>>().join(" ");
+    let normalized = normalized
+        .replace(" ::", "::")
+        .replace(":: ", "::")
+        .replace("{ ", "{")
+        .replace(" }", "}")
+        .replace(" ,", ",")
+        .replace(", ", ",");
+
+    let mut out = Vec::new();
+    expand_group(&normalized, "::", &mut out);
+    out
+}
+
+/// Expand a PHP use clause, including group uses (`A\{B, C\D}`).
+fn expand_php_use(clause: &str) -> Vec<String> {
+    let normalized: String = clause.split_whitespace().collect::<Vec<_>>().join(" ");
+    let normalized = normalized
+        .replace("{ ", "{")
+        .replace(" }", "}")
+        .replace(", ", ",");
+
+    let mut out = Vec::new();
+    // Plain `use A, B;` lists several imports without braces
+    for part in split_top_level(&normalized) {
+        expand_group(part, "\\", &mut out);
+    }
+    out.into_iter()
+        .map(|path| path.trim_start_matches('\\').to_string())
+        .collect()
+}
+
+fn expand_group(tree: &str, separator: &str, out: &mut Vec<String>) {
+    let Some(open) = tree.find('{') else {
+        let path = tree.split(" as ").next().unwrap_or(tree).trim();
+        let glob = format!("{}*", separator);
+        let path = path.strip_suffix(&glob).unwrap_or(path);
+        if !path.is_empty() {
+            out.push(path.to_string());
+        }
+        return;
+    };
+
+    let prefix = &tree[..open];
+    let close = tree.rfind('}').unwrap_or(tree.len());
+    let inner = &tree[open + 1..close.max(open + 1)];
+
+    for part in split_top_level(inner) {
+        if part == "self" {
+            let parent = prefix.strip_suffix(separator).unwrap_or(prefix);
+            if !parent.is_empty() {
+                out.push(parent.to_string());
+            }
+        } else {
+            expand_group(&format!("{}{}", prefix, part), separator, out);
+        }
+    }
+}
+
+/// Split on commas that are not nested inside braces.
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(s[start..].trim());
+    parts.into_iter().filter(|p| !p.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_use_tree() {
+        assert_eq!(
+            expand_use_tree("crate::a::{b, c::{D, E as F}, self}"),
+            vec![
+                "crate::a::b",
+                "crate::a::c::D",
+                "crate::a::c::E",
+                "crate::a"
+            ]
+        );
+        assert_eq!(expand_use_tree("super::*"), vec!["super"]);
+    }
+
+    #[test]
+    fn test_rust_imports() {
+        let source = "use crate::types::{\n    Project,\n    Symbol,\n};\nmod cache;\nmod inline { use super::x; }\n";
+        let imports = ImportExtractor::new().extract("rs", source);
+
+        assert_eq!(
+            imports.imports,
+            vec!["crate::types::Project", "crate::types::Symbol", "super::x"]
+        );
+    }
+
+    #[test]
+    fn test_python_imports() {
+        let source = "import os, pkg.util as u\nfrom . import sibling\nfrom ..core import models\nfrom app.services import *\n";
+        let imports = ImportExtractor::new().extract("py", source);
+
+        assert_eq!(
+            imports.imports,
+            vec![
+                "os",
+                "pkg.util",
+                ".sibling",
+                "..core.models",
+                "app.services"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_javascript_imports() {
+        let source = "import React from 'react';\nimport { a } from \"./a\";\nexport * from './b';\nconst c = require('../c');\nconst d = await import('./d');\n";
+        let imports = ImportExtractor::new().extract("js", source);
+
+        assert_eq!(imports.imports, vec!["react", "./a", "./b", "../c", "./d"]);
+    }
+
+    #[test]
+    fn test_go_and_java_imports() {
+        let go = "package main\n\nimport (\n\t\"fmt\"\n\tsvc \"example.com/app/services\"\n)\n";
+        assert_eq!(
+            ImportExtractor::new().extract("go", go).imports,
+            vec!["fmt", "example.com/app/services"]
+        );
+
+        let java = "package com.app.web;\n\nimport com.app.domain.User;\nimport static com.app.util.Strings.trim;\nimport java.util.*;\n";
+        let imports = ImportExtractor::new().extract("java", java);
+        assert_eq!(imports.namespace.as_deref(), Some("com.app.web"));
+        assert_eq!(
+            imports.imports,
+            vec![
+                "com.app.domain.User",
+                "com.app.util.Strings.trim",
+                "java.util.*"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_php_imports() {
+        let source = "<?php\nnamespace App\\Http\\Controllers;\n\nuse App\\Models\\User;\nuse App\\Services\\{Billing, Mail\\Mailer as M};\nuse function strlen;\nrequire_once __DIR__ . '/helpers.php';\n\nclass A {\n    use SomeTrait;\n}\n";
+        let imports = ImportExtractor::new().extract("php", source);
+
+        assert_eq!(imports.namespace.as_deref(), Some("App\\Http\\Controllers"));
+        assert_eq!(
+            imports.imports,
+            vec![
+                "App\\Models\\User",
+                "App\\Services\\Billing",
+                "App\\Services\\Mail\\Mailer",
+                "./helpers.php"
+            ]
+        );
+    }
+}
@@ -0,0 +1,659 @@
+//! Infrastructure and CI detection.
+//!
+//! Summarizes Dockerfiles (base images, stages, exposed ports), Compose
+//! services, Kubernetes manifests, Helm charts, Terraform configuration and
+//! CI pipelines (GitHub Actions, GitLab CI, Azure Pipelines). Discovery
+//! includes hidden directories so `.github/workflows` is found. YAML files
+//! are read line by line for the few keys needed, without a YAML parser;
+//! anchors, flow mappings and multi-line scalars are not resolved.
+
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::discovery::FileDiscovery;
+use crate::config::AnalyzerConfig;
+use crate::types::{
+    CiPipeline, ComposeService, Dockerfile, HelmChart, InfraSummary, KubernetesResource,
+    ResourceCount, TerraformSummary,
+};
+
+/// Top-level GitLab CI keys that configure the pipeline rather than name a job.
+const GITLAB_RESERVED: &[&str] = &[
+    "stages",
+    "variables",
+    "image",
+    "services",
+    "before_script",
+    "after_script",
+    "cache",
+    "include",
+    "default",
+    "workflow",
+];
+
+/// Detects the infrastructure and CI files of a project.
+pub struct InfraAnalyzer {
+    discovery: FileDiscovery,
+}
+
+impl InfraAnalyzer {
+    /// Build an analyzer honoring the configured ignore patterns and scan caps.
+    ///
+    /// # Errors
+    /// Returns an error if one of the configured `ignore_patterns` is not a valid glob.
+    pub fn new(config: &AnalyzerConfig) -> Result<Self> {
+        Ok(Self {
+            discovery: FileDiscovery::new(config)?.with_hidden(true),
+        })
+    }
+
+    /// Walk `root` and summarize its infrastructure files. Blocking.
+    pub fn analyze(&self, root: &Path) -> InfraSummary {
+        let outcome = self.discovery.scan_matching(root, is_infra_file);
+        let mut summary = InfraSummary::default();
+        let mut terraform = Terraform::default();
+
+        for file in outcome.files {
+            let Ok(content) = fs::read_to_string(&file.path) else {
+                continue;
+            };
+            let relative = file
+                .path
+                .strip_prefix(root)
+                .unwrap_or(&file.path)
+                .to_path_buf();
+            let name = file_name(&file.path);
+
+            if is_dockerfile(name) {
+                summary
+                    .dockerfiles
+                    .push(parse_dockerfile(relative, &content));
+            } else if file.path.extension().is_some_and(|e| e == "tf") {
+                terraform.add(&content);
+            } else if is_compose_file(name) {
+                summary
+                    .compose_services
+                    .extend(parse_compose(&relative, &content));
+            } else if relative.starts_with(".github/workflows") {
+                summary.ci.push(parse_github_workflow(relative, &content));
+            } else if name == ".gitlab-ci.yml" {
+                summary.ci.push(parse_gitlab_ci(relative, &content));
+            } else if name.starts_with("azure-pipelines") {
+                summary.ci.push(parse_azure_pipeline(relative, &content));
+            } else if name == "Chart.yaml" {
+                summary
+                    .helm_charts
+                    .extend(parse_helm_chart(relative, &content));
+            } else if !is_helm_template(&file.path) {
+                summary
+                    .kubernetes
+                    .extend(parse_kubernetes(&relative, &content));
+            }
+        }
+
+        summary.terraform = terraform.finish();
+        summary
+    }
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+}
+
+fn is_dockerfile(name: &str) -> bool {
+    name == "Dockerfile"
+        || name == "Containerfile"
+        || name.starts_with("Dockerfile.")
+        || name.ends_with(".Dockerfile")
+        || name.ends_with(".dockerfile")
+}
+
+fn is_compose_file(name: &str) -> bool {
+    (name.starts_with("docker-compose") || name.starts_with("compose"))
+        && Path::new(name)
+            .extension()
+            .is_some_and(|e| e == "yml" || e == "yaml")
+}
+
+fn is_infra_file(path: &Path) -> bool {
+    is_dockerfile(file_name(path))
+        || path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e, "yml" | "yaml" | "tf"))
+}
+
+/// Files under a chart's `templates/`, which are Go templates rather than YAML.
+fn is_helm_template(path: &Path) -> bool {
+    path.ancestors().any(|dir| {
+        file_name(dir) == "templates"
+            && dir
+                .parent()
+                .is_some_and(|chart| chart.join("Chart.yaml").is_file())
+    })
+}
+
+fn parse_dockerfile(path: PathBuf, content: &str) -> Dockerfile {
+    let mut dockerfile = Dockerfile {
+        path,
+        base_images: Vec::new(),
+        stages: Vec::new(),
+        exposed_ports: Vec::new(),
+    };
+    for line in content.lines().map(str::trim) {
+        let mut words = line.split_whitespace();
+        let Some(instruction) = words.next() else {
+            continue;
+        };
+        if instruction.eq_ignore_ascii_case("FROM") {
+            let args: Vec<&str> = words.filter(|w| !w.starts_with("--")).collect();
+            let Some(&image) = args.first() else {
+                continue;
+            };
+            // `FROM build` starts from an earlier stage, not an image
+            if !dockerfile
+                .stages
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(image))
+            {
+                dockerfile.base_images.push(image.to_string());
+            }
+            if let [_, as_keyword, stage, ..] = args.as_slice() {
+                if as_keyword.eq_ignore_ascii_case("as") {
+                    dockerfile.stages.push((*stage).to_string());
+                }
+            }
+        } else if instruction.eq_ignore_ascii_case("EXPOSE") {
+            dockerfile.exposed_ports.extend(words.map(str::to_string));
+        }
+    }
+    dockerfile
+}
+
+// ----------------------------------------------------------------------------
+// Minimal YAML reading
+// ----------------------------------------------------------------------------
+
+/// A YAML line: indentation and text without a trailing comment.
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    indent: usize,
+    text: &'a str,
+}
+
+/// The documents of a YAML file, each as its non-blank, non-comment lines.
+fn yaml_documents(content: &str) -> Vec<Vec<Line<'_>>> {
+    let mut documents = vec![Vec::new()];
+    for raw in content.lines() {
+        if raw.trim_end() == "---" {
+            documents.push(Vec::new());
+            continue;
+        }
+        let text = match raw.find(" #") {
+            Some(i) => &raw[..i],
+            None => raw,
+        };
+        let trimmed = text.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = text.len() - text.trim_start().len();
+        if let Some(document) = documents.last_mut() {
+            document.push(Line {
+                indent,
+                text: trimmed,
+            });
+        }
+    }
+    documents.retain(|d| !d.is_empty());
+    documents
+}
+
+/// `key: value` (or `key:`) of a line, without a list marker and quotes.
+fn key_value(text: &str) -> Option<(&str, &str)> {
+    let text = text.strip_prefix("- ").unwrap_or(text);
+    let (key, value) = match text.find(": ") {
+        Some(i) => (&text[..i], text[i + 2..].trim()),
+        None => (text.strip_suffix(':')?, ""),
+    };
+    Some((unquote(key), value))
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches(['"', '\''])
+}
+
+/// The lines nested under `lines[index]`.
+fn nested<'a, 'b>(lines: &'b [Line<'a>], index: usize) -> &'b [Line<'a>] {
+    let indent = lines[index].indent;
+    let end = lines[index + 1..]
+        .iter()
+        .position(|l| l.indent <= indent)
+        .map_or(lines.len(), |p| index + 1 + p);
+    &lines[index + 1..end]
+}
+
+/// Indices of the lines at the shallowest indentation of `block`.
+fn direct_children<'b>(block: &'b [Line<'_>]) -> impl Iterator<Item = usize> + 'b {
+    let indent = block.iter().map(|l| l.indent).min().unwrap_or(0);
+    block
+        .iter()
+        .enumerate()
+        .filter(move |(_, l)| l.indent == indent)
+        .map(|(i, _)| i)
+}
+
+fn find_key(block: &[Line<'_>], key: &str) -> Option<usize> {
+    direct_children(block).find(|&i| key_value(block[i].text).is_some_and(|(k, _)| k == key))
+}
+
+/// Scalar value of `key` among the direct children of `block`.
+fn scalar(block: &[Line<'_>], key: &str) -> Option<String> {
+    let index = find_key(block, key)?;
+    let (_, value) = key_value(block[index].text)?;
+    let value = unquote(value);
+    (!value.is_empty() && !value.starts_with(['[', '{', '|', '>'])).then(|| value.to_string())
+}
+
+/// Items of `key`: a scalar, a flow list (`[a, b]`), a block list or the keys of a mapping.
+fn list(block: &[Line<'_>], key: &str) -> Vec<String> {
+    let Some(index) = find_key(block, key) else {
+        return Vec::new();
+    };
+    let value = key_value(block[index].text).map_or("", |(_, v)| v);
+    if let Some(items) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        return items
+            .split(',')
+            .map(unquote)
+            .filter(|i| !i.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    if !value.is_empty() {
+        return vec![unquote(value).to_string()];
+    }
+    let children = nested(block, index);
+    direct_children(children)
+        .filter_map(|i| {
+            let text = children[i].text;
+            match text.strip_prefix("- ") {
+                Some(item) => Some(unquote(item).to_string()),
+                None => key_value(text).map(|(k, _)| k.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Keys of the mapping under `key`.
+fn keys(block: &[Line<'_>], key: &str) -> Vec<String> {
+    let Some(index) = find_key(block, key) else {
+        return Vec::new();
+    };
+    let children = nested(block, index);
+    direct_children(children)
+        .filter_map(|i| key_value(children[i].text).map(|(k, _)| k.to_string()))
+        .collect()
+}
+
+fn parse_compose(path: &Path, content: &str) -> Vec<ComposeService> {
+    let mut services = Vec::new();
+    for document in yaml_documents(content) {
+        let Some(index) = find_key(&document, "services") else {
+            continue;
+        };
+        let block = nested(&document, index);
+        for i in direct_children(block).collect::<Vec<_>>() {
+            let Some((name, _)) = key_value(block[i].text) else {
+                continue;
+            };
+            let service = nested(block, i);
+            let build = scalar(service, "build").or_else(|| {
+                let index = find_key(service, "build")?;
+                scalar(nested(service, index), "context")
+            });
+            services.push(ComposeService {
+                file: path.to_path_buf(),
+                name: name.to_string(),
+                image: scalar(service, "image"),
+                build,
+                ports: list(service, "ports"),
+                depends_on: list(service, "depends_on"),
+            });
+        }
+    }
+    services
+}
+
+fn parse_kubernetes(path: &Path, content: &str) -> Vec<KubernetesResource> {
+    yaml_documents(content)
+        .iter()
+        .filter_map(|document| {
+            scalar(document, "apiVersion")?;
+            let kind = scalar(document, "kind")?;
+            let name =
+                find_key(document, "metadata").and_then(|i| scalar(nested(document, i), "name"));
+            let images: BTreeSet<String> = document
+                .iter()
+                .filter_map(|l| key_value(l.text))
+                .filter(|(k, v)| *k == "image" && !v.is_empty())
+                .map(|(_, v)| unquote(v).to_string())
+                .collect();
+            Some(KubernetesResource {
+                path: path.to_path_buf(),
+                kind,
+                name,
+                images: images.into_iter().collect(),
+            })
+        })
+        .collect()
+}
+
+fn parse_helm_chart(path: PathBuf, content: &str) -> Option<HelmChart> {
+    let document = yaml_documents(content).into_iter().next()?;
+    Some(HelmChart {
+        path,
+        name: scalar(&document, "name")?,
+        version: scalar(&document, "version"),
+    })
+}
+
+fn parse_github_workflow(path: PathBuf, content: &str) -> CiPipeline {
+    let document = yaml_documents(content)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    CiPipeline {
+        provider: "GitHub Actions".to_string(),
+        path,
+        name: scalar(&document, "name"),
+        triggers: list(&document, "on"),
+        stages: Vec::new(),
+        jobs: keys(&document, "jobs"),
+    }
+}
+
+fn parse_gitlab_ci(path: PathBuf, content: &str) -> CiPipeline {
+    let document = yaml_documents(content)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let jobs = direct_children(&document)
+        .filter_map(|i| key_value(document[i].text).map(|(k, _)| k))
+        .filter(|k| !k.starts_with('.') && !GITLAB_RESERVED.contains(k))
+        .map(str::to_string)
+        .collect();
+    CiPipeline {
+        provider: "GitLab CI".to_string(),
+        path,
+        name: None,
+        triggers: Vec::new(),
+        stages: list(&document, "stages"),
+        jobs,
+    }
+}
+
+fn parse_azure_pipeline(path: PathBuf, content: &str) -> CiPipeline {
+    let document = yaml_documents(content)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let items = |key: &str| -> Vec<String> {
+        document
+            .iter()
+            .filter(|l| l.text.starts_with("- "))
+            .filter_map(|l| key_value(l.text))
+            .filter(|(k, v)| *k == key && !v.is_empty())
+            .map(|(_, v)| unquote(v).to_string())
+            .collect()
+    };
+    let mut jobs = items("job");
+    jobs.extend(items("deployment"));
+    CiPipeline {
+        provider: "Azure Pipelines".to_string(),
+        path,
+        name: None,
+        triggers: list(&document, "trigger"),
+        stages: items("stage"),
+        jobs,
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Terraform
+// ----------------------------------------------------------------------------
+
+/// Terraform blocks collected across files.
+#[derive(Default)]
+struct Terraform {
+    files: usize,
+    providers: BTreeSet<String>,
+    resources: BTreeMap<String, usize>,
+    modules: BTreeSet<String>,
+}
+
+impl Terraform {
+    fn add(&mut self, content: &str) {
+        self.files += 1;
+        for line in content.lines().map(str::trim_start) {
+            let mut words = line.split_whitespace();
+            let (Some(block), Some(label)) = (words.next(), words.next()) else {
+                continue;
+            };
+            let label = label.trim_matches('"');
+            match block {
+                "provider" => {
+                    self.providers.insert(label.to_string());
+                }
+                "resource" | "data" => {
+                    if let Some((provider, _)) = label.split_once('_') {
+                        self.providers.insert(provider.to_string());
+                    }
+                    if block == "resource" {
+                        *self.resources.entry(label.to_string()).or_default() += 1;
+                    }
+                }
+                "module" => {
+                    self.modules.insert(label.to_string());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn finish(self) -> Option<TerraformSummary> {
+        if self.files == 0 {
+            return None;
+        }
+        let mut resources: Vec<ResourceCount> = self
+            .resources
+            .into_iter()
+            .map(|(resource_type, count)| ResourceCount {
+                resource_type,
+                count,
+            })
+            .collect();
+        resources.sort_by_key(|r| std::cmp::Reverse(r.count));
+        Some(TerraformSummary {
+            files: self.files,
+            providers: self.providers.into_iter().collect(),
+            resources,
+            modules: self.modules.into_iter().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn analyze(root: &Path) -> InfraSummary {
+        InfraAnalyzer::new(&AnalyzerConfig::default())
+            .unwrap()
+            .analyze(root)
+    }
+
+    #[test]
+    fn test_dockerfile_stages_and_ports() {
+        let dockerfile = parse_dockerfile(
+            PathBuf::from("Dockerfile"),
+            "FROM --platform=$BUILDPLATFORM node:20-alpine AS build\n\
+             RUN npm ci\n\
+             FROM nginx:1.27-alpine\n\
+             COPY --from=build /app/dist /usr/share/nginx/html\n\
+             EXPOSE 80 443/tcp\n",
+        );
+        assert_eq!(
+            dockerfile.base_images,
+            vec!["node:20-alpine", "nginx:1.27-alpine"]
+        );
+        assert_eq!(dockerfile.stages, vec!["build"]);
+        assert_eq!(dockerfile.exposed_ports, vec!["80", "443/tcp"]);
+
+        let dockerfile = parse_dockerfile(
+            PathBuf::from("Dockerfile"),
+            "FROM rust:1.80 AS builder\nFROM builder AS test\n",
+        );
+        assert_eq!(dockerfile.base_images, vec!["rust:1.80"]);
+        assert_eq!(dockerfile.stages, vec!["builder", "test"]);
+    }
+
+    #[test]
+    fn test_compose_services() {
+        let services = parse_compose(
+            Path::new("docker-compose.yml"),
+            "services:\n\
+             \x20 web:\n\
+             \x20   build:\n\
+             \x20     context: ./web\n\
+             \x20   ports:\n\
+             \x20     - \"8080:80\"\n\
+             \x20   depends_on:\n\
+             \x20     db:\n\
+             \x20       condition: service_healthy\n\
+             \x20 db:\n\
+             \x20   image: postgres:16 # pinned\n\
+             \x20   ports: [\"5432:5432\"]\n\
+             volumes:\n\
+             \x20 data: {}\n",
+        );
+        assert_eq!(services.len(), 2);
+        assert_eq!(services[0].name, "web");
+        assert_eq!(services[0].build.as_deref(), Some("./web"));
+        assert_eq!(services[0].ports, vec!["8080:80"]);
+        assert_eq!(services[0].depends_on, vec!["db"]);
+        assert_eq!(services[1].image.as_deref(), Some("postgres:16"));
+        assert_eq!(services[1].ports, vec!["5432:5432"]);
+    }
+
+    #[test]
+    fn test_kubernetes_and_helm() {
+        let dir = tempdir().unwrap();
+        write(
+            dir.path(),
+            "k8s/api.yaml",
+            "apiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: api\nspec:\n  template:\n    spec:\n      containers:\n        - name: api\n          image: ghcr.io/acme/api:1.4\n---\napiVersion: v1\nkind: Service\nmetadata:\n  name: api\n",
+        );
+        write(dir.path(), "k8s/values.yaml", "replicas: 2\n");
+        write(
+            dir.path(),
+            "charts/api/Chart.yaml",
+            "apiVersion: v2\nname: api\nversion: 0.3.1\n",
+        );
+        write(
+            dir.path(),
+            "charts/api/templates/deployment.yaml",
+            "apiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: {{ .Release.Name }}\n",
+        );
+
+        let summary = analyze(dir.path());
+        let kinds: Vec<(&str, Option<&str>)> = summary
+            .kubernetes
+            .iter()
+            .map(|r| (r.kind.as_str(), r.name.as_deref()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![("Deployment", Some("api")), ("Service", Some("api"))]
+        );
+        assert_eq!(summary.kubernetes[0].images, vec!["ghcr.io/acme/api:1.4"]);
+        assert_eq!(summary.helm_charts.len(), 1);
+        assert_eq!(summary.helm_charts[0].version.as_deref(), Some("0.3.1"));
+    }
+
+    #[test]
+    fn test_ci_pipelines() {
+        let dir = tempdir().unwrap();
+        write(
+            dir.path(),
+            ".github/workflows/ci.yml",
+            "name: CI\non:\n  push:\n    branches: [main]\n  pull_request:\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - uses: actions/checkout@v4\n  test:\n    needs: build\n",
+        );
+        write(
+            dir.path(),
+            ".gitlab-ci.yml",
+            "stages: [build, deploy]\nvariables:\n  A: b\n.template:\n  image: alpine\ncompile:\n  stage: build\nrelease:\n  stage: deploy\n",
+        );
+        write(
+            dir.path(),
+            "azure-pipelines.yml",
+            "trigger:\n  - main\nstages:\n  - stage: Build\n    jobs:\n      - job: Compile\n  - stage: Deploy\n    jobs:\n      - deployment: Production\n",
+        );
+
+        let summary = analyze(dir.path());
+        assert_eq!(summary.ci.len(), 3);
+        let github = summary
+            .ci
+            .iter()
+            .find(|c| c.provider == "GitHub Actions")
+            .unwrap();
+        assert_eq!(github.name.as_deref(), Some("CI"));
+        assert_eq!(github.triggers, vec!["push", "pull_request"]);
+        assert_eq!(github.jobs, vec!["build", "test"]);
+
+        let gitlab = summary
+            .ci
+            .iter()
+            .find(|c| c.provider == "GitLab CI")
+            .unwrap();
+        assert_eq!(gitlab.stages, vec!["build", "deploy"]);
+        assert_eq!(gitlab.jobs, vec!["compile", "release"]);
+
+        let azure = summary
+            .ci
+            .iter()
+            .find(|c| c.provider == "Azure Pipelines")
+            .unwrap();
+        assert_eq!(azure.triggers, vec!["main"]);
+        assert_eq!(azure.stages, vec!["Build", "Deploy"]);
+        assert_eq!(azure.jobs, vec!["Compile", "Production"]);
+    }
+
+    #[test]
+    fn test_terraform_summary() {
+        let dir = tempdir().unwrap();
+        write(
+            dir.path(),
+            "infra/main.tf",
+            "provider \"aws\" {\n  region = \"eu-west-1\"\n}\n\nresource \"aws_s3_bucket\" \"assets\" {}\nresource \"aws_s3_bucket\" \"logs\" {}\nresource \"cloudflare_record\" \"www\" {}\nmodule \"vpc\" {\n  source = \"terraform-aws-modules/vpc/aws\"\n}\n",
+        );
+        write(dir.path(), "infra/variables.tf", "variable \"env\" {}\n");
+
+        let terraform = analyze(dir.path()).terraform.unwrap();
+        assert_eq!(terraform.files, 2);
+        assert_eq!(terraform.providers, vec!["aws", "cloudflare"]);
+        assert_eq!(terraform.resources[0].resource_type, "aws_s3_bucket");
+        assert_eq!(terraform.resources[0].count, 2);
+        assert_eq!(terraform.modules, vec!["vpc"]);
+    }
+}
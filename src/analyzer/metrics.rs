@@ -0,0 +1,513 @@
+//! Line counts and complexity metrics for source files.
+//!
+//! Files with a bundled grammar are measured from their tree-sitter AST:
+//! comment lines come from comment nodes, and cyclomatic complexity and
+//! nesting depth from control-flow nodes. Other languages fall back to a
+//! lexical line classifier and report no function metrics.
+
+use tree_sitter::{Node, Parser};
+
+use super::grammar::Grammar;
+use crate::types::FileMetrics;
+
+/// Node kinds that drive the metrics for one grammar.
+struct Rules {
+    /// Nodes that open a new function scope
+    functions: &'static [&'static str],
+    /// Nodes that add one branch to the enclosing function's complexity
+    decisions: &'static [&'static str],
+    /// Control-flow nodes that increase nesting depth
+    nesting: &'static [&'static str],
+    /// Short-circuit operators, each adding one branch
+    logical_operators: &'static [&'static str],
+    /// Comment nodes
+    comments: &'static [&'static str],
+}
+
+const RUST: Rules = Rules {
+    functions: &["function_item", "closure_expression"],
+    decisions: &[
+        "if_expression",
+        "while_expression",
+        "for_expression",
+        "match_arm",
+    ],
+    nesting: &[
+        "if_expression",
+        "while_expression",
+        "for_expression",
+        "loop_expression",
+        "match_expression",
+    ],
+    logical_operators: &["&&", "||"],
+    comments: &["line_comment", "block_comment"],
+};
+
+const PYTHON: Rules = Rules {
+    functions: &["function_definition", "lambda"],
+    decisions: &[
+        "if_statement",
+        "elif_clause",
+        "for_statement",
+        "while_statement",
+        "except_clause",
+        "conditional_expression",
+        "for_in_clause",
+        "if_clause",
+        "case_clause",
+    ],
+    nesting: &[
+        "if_statement",
+        "for_statement",
+        "while_statement",
+        "try_statement",
+        "with_statement",
+        "match_statement",
+    ],
+    logical_operators: &["and", "or"],
+    comments: &["comment"],
+};
+
+const JAVASCRIPT: Rules = Rules {
+    functions: &[
+        "function_declaration",
+        "function",
+        "function_expression",
+        "generator_function",
+        "generator_function_declaration",
+        "arrow_function",
+        "method_definition",
+    ],
+    decisions: &[
+        "if_statement",
+        "for_statement",
+        "for_in_statement",
+        "while_statement",
+        "do_statement",
+        "switch_case",
+        "catch_clause",
+        "ternary_expression",
+    ],
+    nesting: &[
+        "if_statement",
+        "for_statement",
+        "for_in_statement",
+        "while_statement",
+        "do_statement",
+        "switch_statement",
+        "try_statement",
+    ],
+    logical_operators: &["&&", "||", "??"],
+    comments: &["comment"],
+};
+
+const GO: Rules = Rules {
+    functions: &["function_declaration", "method_declaration", "func_literal"],
+    decisions: &[
+        "if_statement",
+        "for_statement",
+        "expression_case",
+        "type_case",
+        "communication_case",
+    ],
+    nesting: &[
+        "if_statement",
+        "for_statement",
+        "expression_switch_statement",
+        "type_switch_statement",
+        "select_statement",
+    ],
+    logical_operators: &["&&", "||"],
+    comments: &["comment"],
+};
+
+const JAVA: Rules = Rules {
+    functions: &[
+        "method_declaration",
+        "constructor_declaration",
+        "lambda_expression",
+    ],
+    decisions: &[
+        "if_statement",
+        "for_statement",
+        "enhanced_for_statement",
+        "while_statement",
+        "do_statement",
+        "switch_label",
+        "catch_clause",
+        "ternary_expression",
+    ],
+    nesting: &[
+        "if_statement",
+        "for_statement",
+        "enhanced_for_statement",
+        "while_statement",
+        "do_statement",
+        "switch_expression",
+        "try_statement",
+        "try_with_resources_statement",
+    ],
+    logical_operators: &["&&", "||"],
+    comments: &["line_comment", "block_comment", "comment"],
+};
+
+const CSHARP: Rules = Rules {
+    functions: &[
+        "method_declaration",
+        "constructor_declaration",
+        "local_function_statement",
+        "lambda_expression",
+        "anonymous_method_expression",
+    ],
+    decisions: &[
+        "if_statement",
+        "for_statement",
+        "for_each_statement",
+        "while_statement",
+        "do_statement",
+        "switch_section",
+        "switch_expression_arm",
+        "catch_clause",
+        "conditional_expression",
+    ],
+    nesting: &[
+        "if_statement",
+        "for_statement",
+        "for_each_statement",
+        "while_statement",
+        "do_statement",
+        "switch_statement",
+        "try_statement",
+    ],
+    logical_operators: &["&&", "||", "??"],
+    comments: &["comment"],
+};
+
+const fn rules_for(grammar: Grammar) -> &'static Rules {
+    match grammar {
+        Grammar::CSharp => &CSHARP,
+        Grammar::Rust => &RUST,
+        Grammar::Python => &PYTHON,
+        Grammar::JavaScript | Grammar::TypeScript | Grammar::Tsx => &JAVASCRIPT,
+        Grammar::Go => &GO,
+        Grammar::Java => &JAVA,
+    }
+}
+
+/// Comment delimiters used by the lexical fallback.
+struct CommentSyntax {
+    line: &'static [&'static str],
+    block: &'static [(&'static str, &'static str)],
+}
+
+fn comment_syntax(language: &str) -> CommentSyntax {
+    match language {
+        "php" => CommentSyntax {
+            line: &["//", "#"],
+            block: &[("/*", "*/")],
+        },
+        "vue" | "svelte" => CommentSyntax {
+            line: &["//"],
+            block: &[("<!--", "-->"), ("/*", "*/")],
+        },
+        "razor" | "cshtml" => CommentSyntax {
+            line: &["//"],
+            block: &[("@*", "*@"), ("<!--", "-->"), ("/*", "*/")],
+        },
+        "twig" => CommentSyntax {
+            line: &[],
+            block: &[("{#", "#}")],
+        },
+        "fs" => CommentSyntax {
+            line: &["//"],
+            block: &[("(*", "*)")],
+        },
+        "vb" => CommentSyntax {
+            line: &["'"],
+            block: &[],
+        },
+        "py" | "pyi" => CommentSyntax {
+            line: &["#"],
+            block: &[],
+        },
+        _ => CommentSyntax {
+            line: &["//"],
+            block: &[("/*", "*/")],
+        },
+    }
+}
+
+/// Computes `FileMetrics`, reusing one parser across files.
+pub struct MetricsAnalyzer {
+    parser: Parser,
+}
+
+impl Default for MetricsAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            parser: Parser::new(),
+        }
+    }
+
+    /// Measure `source`, written in `language` (the file extension).
+    pub fn analyze(&mut self, language: &str, source: &str) -> FileMetrics {
+        if let Some(grammar) = Grammar::from_extension(language) {
+            if self.parser.set_language(grammar.language()).is_ok() {
+                if let Some(tree) = self.parser.parse(source, None) {
+                    return Self::from_ast(tree.root_node(), source, rules_for(grammar));
+                }
+            }
+            tracing::debug!(language, "Falling back to lexical metrics");
+        }
+
+        Self::lexical(source, &comment_syntax(language))
+    }
+
+    fn from_ast(root: Node, source: &str, rules: &Rules) -> FileMetrics {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut has_code = vec![false; lines.len()];
+        let mut has_comment = vec![false; lines.len()];
+
+        // Complexity of each function found, starting at 1 per function
+        let mut functions: Vec<usize> = Vec::new();
+        let mut top_level_branches = 0;
+        let mut max_nesting = 0;
+
+        // (node, nesting depth, enclosing function)
+        let mut stack: Vec<(Node, usize, Option<usize>)> = vec![(root, 0, None)];
+
+        while let Some((node, depth, function)) = stack.pop() {
+            let kind = node.kind();
+            let rows = node.start_position().row..=node.end_position().row;
+
+            if rules.comments.contains(&kind) {
+                for row in rows {
+                    if let Some(flag) = has_comment.get_mut(row) {
+                        *flag = true;
+                    }
+                }
+                continue;
+            }
+
+            if node.child_count() == 0 {
+                if !node.is_missing() {
+                    for row in rows {
+                        if let Some(flag) = has_code.get_mut(row) {
+                            *flag = true;
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let mut function = function;
+            if rules.functions.contains(&kind) {
+                functions.push(1);
+                function = Some(functions.len() - 1);
+            }
+
+            let mut branches = usize::from(rules.decisions.contains(&kind));
+            let mut cursor = node.walk();
+            branches += node
+                .children(&mut cursor)
+                .filter(|c| !c.is_named() && rules.logical_operators.contains(&c.kind()))
+                .count();
+            if branches > 0 {
+                match function {
+                    Some(idx) => functions[idx] += branches,
+                    None => top_level_branches += branches,
+                }
+            }
+
+            let mut child_depth = depth;
+            if rules.nesting.contains(&kind) && !Self::is_else_if(node) {
+                child_depth += 1;
+                max_nesting = max_nesting.max(child_depth);
+            }
+
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                stack.push((child, child_depth, function));
+            }
+        }
+
+        let mut metrics = FileMetrics {
+            functions: functions.len(),
+            cyclomatic_complexity: functions.iter().sum::<usize>() + top_level_branches,
+            max_function_complexity: functions.iter().copied().max().unwrap_or(0),
+            max_nesting_depth: max_nesting,
+            ..Default::default()
+        };
+
+        for (idx, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                metrics.blank_lines += 1;
+            } else if has_comment[idx] && !has_code[idx] {
+                metrics.comment_lines += 1;
+            } else {
+                metrics.code_lines += 1;
+            }
+        }
+
+        metrics
+    }
+
+    /// `else if` chains are siblings, not nested branches.
+    fn is_else_if(node: Node) -> bool {
+        node.parent()
+            .is_some_and(|p| p.kind() == "else_clause" || p.kind() == node.kind())
+    }
+
+    fn lexical(source: &str, syntax: &CommentSyntax) -> FileMetrics {
+        let mut metrics = FileMetrics::default();
+        let mut block_end: Option<&str> = None;
+
+        for line in source.lines() {
+            let mut rest = line.trim();
+            if rest.is_empty() && block_end.is_none() {
+                metrics.blank_lines += 1;
+                continue;
+            }
+
+            let mut code = false;
+            let mut comment = false;
+
+            while !rest.is_empty() {
+                if let Some(end) = block_end {
+                    comment = true;
+                    match rest.find(end) {
+                        Some(pos) => {
+                            rest = rest[pos + end.len()..].trim_start();
+                            block_end = None;
+                        }
+                        None => break,
+                    }
+                } else if syntax.line.iter().any(|m| rest.starts_with(m)) {
+                    comment = true;
+                    break;
+                } else if let Some((start, end)) = syntax
+                    .block
+                    .iter()
+                    .find(|(start, _)| rest.starts_with(start))
+                {
+                    rest = &rest[start.len()..];
+                    block_end = Some(end);
+                } else {
+                    code = true;
+                    // A block comment opened after code keeps the following lines in comment state
+                    let opened = syntax
+                        .block
+                        .iter()
+                        .filter_map(|(start, end)| rest.find(start).map(|pos| (pos, start, end)))
+                        .min_by_key(|(pos, _, _)| *pos);
+                    match opened {
+                        Some((pos, start, end)) => {
+                            rest = &rest[pos + start.len()..];
+                            block_end = Some(end);
+                        }
+                        None => break,
+                    }
+                }
+            }
+
+            if code {
+                metrics.code_lines += 1;
+            } else if comment {
+                metrics.comment_lines += 1;
+            } else {
+                metrics.blank_lines += 1;
+            }
+        }
+
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_lines_and_complexity() {
+        let source = r"// Entry point
+fn main() {
+    let x = 3;
+
+    /* block
+       comment */
+    if x > 1 && x < 5 {
+        for i in 0..x {
+            println!({i}); // trailing comment counts as code
+        }
+    } else if x == 0 {
+        return;
+    }
+}
+
+fn helper(v: Option<u8>) -> u8 {
+    match v {
+        Some(n) => n,
+        None => 0,
+    }
+}
+";
+        let metrics = MetricsAnalyzer::new().analyze("rs", source);
+
+        assert_eq!(metrics.comment_lines, 3);
+        assert_eq!(metrics.blank_lines, 2);
+        assert_eq!(metrics.code_lines, 16);
+        assert_eq!(metrics.functions, 2);
+        // main: 1 + if + && + for + else-if = 5; helper: 1 + 2 arms = 3
+        assert_eq!(metrics.max_function_complexity, 5);
+        assert_eq!(metrics.cyclomatic_complexity, 8);
+        // if -> for (the else-if is not an extra level)
+        assert_eq!(metrics.max_nesting_depth, 2);
+    }
+
+    #[test]
+    fn test_python_boolean_operators() {
+        let source = "def check(a, b):\n    # guard\n    if a and b or not a:\n        return 1\n    return 0\n";
+        let metrics = MetricsAnalyzer::new().analyze("py", source);
+
+        assert_eq!(metrics.functions, 1);
+        assert_eq!(metrics.cyclomatic_complexity, 4);
+        assert_eq!(metrics.comment_lines, 1);
+        assert_eq!(metrics.code_lines, 4);
+    }
+
+    #[test]
+    fn test_csharp_nesting() {
+        let source = r"
+class A {
+    void Run(int[] xs) {
+        foreach (var x in xs) {
+            try {
+                if (x > 0) { Console.WriteLine(x); }
+            } catch (Exception) { }
+        }
+    }
+}";
+        let metrics = MetricsAnalyzer::new().analyze("cs", source);
+
+        assert_eq!(metrics.functions, 1);
+        assert_eq!(metrics.max_nesting_depth, 3);
+        assert_eq!(metrics.max_function_complexity, 4);
+    }
+
+    #[test]
+    fn test_lexical_fallback() {
+        let source = "<?php\n// comment\n# another\n/* block\n still block */\n$x = 1; /* opens\n closes */\n\necho $x;\n";
+        let metrics = MetricsAnalyzer::new().analyze("php", source);
+
+        assert_eq!(metrics.code_lines, 3);
+        assert_eq!(metrics.comment_lines, 5);
+        assert_eq!(metrics.blank_lines, 1);
+        assert_eq!(metrics.functions, 0);
+    }
+}
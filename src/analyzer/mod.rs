@@ -1,12 +1,29 @@
+pub mod cache;
 pub mod csharp;
 pub mod detector;
+pub mod diff;
+pub mod discovery;
+pub mod frameworks;
 pub mod generic;
+pub mod git;
+pub mod grammar;
+pub mod idioms;
+pub mod imports;
+pub mod infra;
+pub mod metrics;
+pub mod module_graph;
+pub mod outline;
 pub mod project;
+pub mod symbol_index;
+pub mod symbols;
+pub mod test_suite;
 
 #[allow(unused_imports)]
 pub use csharp::CSharpAnalyzer;
 #[allow(unused_imports)]
 pub use detector::ProjectDetector;
+#[allow(unused_imports)]
+pub use discovery::FileDiscovery;
 pub use generic::GenericAnalyzer;
 #[allow(unused_imports)]
 pub use project::ProjectAnalyzer;
@@ -0,0 +1,754 @@
+//! Intra-project module dependency graph.
+//!
+//! Resolves the raw imports collected per file (`SourceFile.imports`) to
+//! modules of the same project. External packages are dropped. A module is
+//! a source file, except for C# (a namespace) and Go (a package directory),
+//! where imports name those rather than files.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use crate::types::{
+    LayerViolation, ModuleDependents, ModuleGraphSummary, Project, ProjectType, SourceFile,
+};
+
+/// Conventional architecture layers, lowest first. A module belongs to the
+/// first layer one of its path segments names; lower layers must not import
+/// higher ones.
+const LAYERS: &[(&str, &[&str])] = &[
+    (
+        "domain",
+        &["domain", "models", "model", "entities", "entity", "core"],
+    ),
+    (
+        "data",
+        &[
+            "data",
+            "repositories",
+            "repository",
+            "infrastructure",
+            "persistence",
+            "db",
+            "database",
+        ],
+    ),
+    (
+        "application",
+        &[
+            "services",
+            "service",
+            "application",
+            "usecases",
+            "use_cases",
+        ],
+    ),
+    (
+        "presentation",
+        &[
+            "controllers",
+            "controller",
+            "api",
+            "handlers",
+            "routes",
+            "views",
+            "pages",
+            "components",
+            "ui",
+            "web",
+            "http",
+        ],
+    ),
+];
+
+/// Extensions tried when resolving extension-less JS/TS imports.
+const JS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs", "vue", "svelte"];
+
+/// Entries kept in each ranked list of the summary.
+const TOP_N: usize = 10;
+
+/// Directed graph of module imports within a project.
+#[derive(Debug, Default)]
+pub struct ModuleGraph {
+    /// Module -> modules it imports
+    edges: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ModuleGraph {
+    /// Resolve the imports of every project file into a module graph.
+    pub fn build(project: &Project) -> Self {
+        let index = ModuleIndex::new(project);
+        let mut graph = Self::default();
+
+        for file in &project.files {
+            let Some(from) = index.module_of(file) else {
+                continue;
+            };
+            let targets = graph.edges.entry(from.clone()).or_default();
+            for import in &file.imports {
+                for to in index.resolve(file, import) {
+                    if to != from {
+                        targets.insert(to);
+                    }
+                }
+            }
+        }
+
+        // Imported modules are nodes too, even without imports of their own
+        let imported: Vec<String> = graph.edges.values().flatten().cloned().collect();
+        for module in imported {
+            graph.edges.entry(module).or_default();
+        }
+
+        graph
+    }
+
+    /// Number of modules.
+    pub fn module_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Number of import edges.
+    pub fn edge_count(&self) -> usize {
+        self.edges.values().map(BTreeSet::len).sum()
+    }
+
+    /// Whether `module` is a node of the graph.
+    pub fn contains(&self, module: &str) -> bool {
+        self.edges.contains_key(module)
+    }
+
+    /// Modules imported by `module`.
+    pub fn dependencies_of(&self, module: &str) -> Vec<&str> {
+        self.edges
+            .get(module)
+            .map(|targets| targets.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Modules importing `module`.
+    pub fn dependents_of(&self, module: &str) -> Vec<&str> {
+        self.edges
+            .iter()
+            .filter(|(_, targets)| targets.contains(module))
+            .map(|(from, _)| from.as_str())
+            .collect()
+    }
+
+    /// Cycles, layering violations and the most depended-on modules.
+    pub fn summary(&self) -> ModuleGraphSummary {
+        let mut most_depended: Vec<ModuleDependents> = self
+            .in_degrees()
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(module, dependents)| ModuleDependents {
+                module: module.to_string(),
+                dependents,
+            })
+            .collect();
+        most_depended.sort_by(|a, b| {
+            b.dependents
+                .cmp(&a.dependents)
+                .then_with(|| a.module.cmp(&b.module))
+        });
+        most_depended.truncate(TOP_N);
+
+        let mut cycles = self.cycles();
+        cycles.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        let cycle_count = cycles.len();
+        cycles.truncate(TOP_N);
+
+        let violations = self.layer_violations();
+        let violation_count = violations.len();
+
+        ModuleGraphSummary {
+            modules: self.module_count(),
+            edges: self.edge_count(),
+            cycle_count,
+            cycles,
+            violation_count,
+            layer_violations: violations.into_iter().take(TOP_N * 2).collect(),
+            most_depended,
+        }
+    }
+
+    fn in_degrees(&self) -> HashMap<&str, usize> {
+        let mut degrees: HashMap<&str, usize> =
+            self.edges.keys().map(|m| (m.as_str(), 0)).collect();
+        for to in self.edges.values().flatten() {
+            *degrees.entry(to.as_str()).or_default() += 1;
+        }
+        degrees
+    }
+
+    /// Strongly connected components with more than one module (Tarjan,
+    /// iterative so deep import chains cannot overflow the stack).
+    fn cycles(&self) -> Vec<Vec<String>> {
+        let nodes: Vec<&str> = self.edges.keys().map(String::as_str).collect();
+        let position: HashMap<&str, usize> =
+            nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let successors: Vec<Vec<usize>> = nodes
+            .iter()
+            .map(|n| {
+                self.edges[*n]
+                    .iter()
+                    .map(|t| position[t.as_str()])
+                    .collect()
+            })
+            .collect();
+
+        let mut index = vec![usize::MAX; nodes.len()];
+        let mut lowlink = vec![0; nodes.len()];
+        let mut on_stack = vec![false; nodes.len()];
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+        let mut components = Vec::new();
+
+        for root in 0..nodes.len() {
+            if index[root] != usize::MAX {
+                continue;
+            }
+            // (node, next successor to visit)
+            let mut work = vec![(root, 0)];
+            while let Some(&mut (node, ref mut next)) = work.last_mut() {
+                if *next == 0 {
+                    index[node] = next_index;
+                    lowlink[node] = next_index;
+                    next_index += 1;
+                    stack.push(node);
+                    on_stack[node] = true;
+                }
+
+                if let Some(&succ) = successors[node].get(*next) {
+                    *next += 1;
+                    if index[succ] == usize::MAX {
+                        work.push((succ, 0));
+                    } else if on_stack[succ] {
+                        lowlink[node] = lowlink[node].min(index[succ]);
+                    }
+                    continue;
+                }
+
+                work.pop();
+                if let Some(&(parent, _)) = work.last() {
+                    lowlink[parent] = lowlink[parent].min(lowlink[node]);
+                }
+                if lowlink[node] == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(nodes[member].to_string());
+                        if member == node {
+                            break;
+                        }
+                    }
+                    if component.len() > 1 {
+                        component.sort();
+                        components.push(component);
+                    }
+                }
+            }
+        }
+
+        components
+    }
+
+    /// Imports from a lower architecture layer into a higher one.
+    fn layer_violations(&self) -> Vec<LayerViolation> {
+        let mut violations = Vec::new();
+        for (from, targets) in &self.edges {
+            let Some((from_rank, from_layer)) = layer_of(from) else {
+                continue;
+            };
+            for to in targets {
+                if let Some((to_rank, to_layer)) = layer_of(to) {
+                    if from_rank < to_rank {
+                        violations.push(LayerViolation {
+                            from: from.clone(),
+                            to: to.clone(),
+                            from_layer: from_layer.to_string(),
+                            to_layer: to_layer.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Layer rank and name for a module, from its path or namespace segments.
+fn layer_of(module: &str) -> Option<(usize, &'static str)> {
+    module
+        .split(['/', '.', '\\', ':'])
+        .map(str::to_lowercase)
+        .find_map(|segment| {
+            LAYERS
+                .iter()
+                .enumerate()
+                .find(|(_, (_, names))| names.contains(&segment.as_str()))
+                .map(|(rank, (name, _))| (rank, *name))
+        })
+}
+
+/// Lookup tables from import names to module keys.
+struct ModuleIndex<'a> {
+    project: &'a Project,
+    /// Relative file paths (with `/` separators) of every source file
+    files: BTreeSet<String>,
+    /// Rust module path (`a::b`) -> file
+    rust_modules: HashMap<String, String>,
+    /// Crate name as written in `use` paths
+    crate_name: String,
+    /// Python dotted module -> file
+    python_modules: HashMap<String, String>,
+    /// Java/PHP fully qualified class name -> file
+    classes: HashMap<String, String>,
+    /// Java package / PHP namespace -> files
+    packages: HashMap<String, Vec<String>>,
+    /// C# namespaces declared in the project
+    namespaces: BTreeSet<String>,
+}
+
+impl<'a> ModuleIndex<'a> {
+    fn new(project: &'a Project) -> Self {
+        let mut index = Self {
+            project,
+            files: BTreeSet::new(),
+            rust_modules: HashMap::new(),
+            crate_name: project.name.replace('-', "_"),
+            python_modules: HashMap::new(),
+            classes: HashMap::new(),
+            packages: HashMap::new(),
+            namespaces: BTreeSet::new(),
+        };
+
+        for file in &project.files {
+            let relative = index.relative(&file.path);
+            index.files.insert(relative.clone());
+
+            match file.language.as_str() {
+                "rs" => {
+                    if let Some(module) = rust_module_path(&relative) {
+                        index.rust_modules.insert(module, relative.clone());
+                    }
+                }
+                "py" | "pyi" => {
+                    for module in python_module_names(&relative) {
+                        index.python_modules.insert(module, relative.clone());
+                    }
+                }
+                "java" | "php" => {
+                    let separator = if file.language == "php" { "\\" } else { "." };
+                    let stem = Path::new(&relative)
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or_default();
+                    let qualified = match file.namespace {
+                        Some(ref ns) => format!("{}{}{}", ns, separator, stem),
+                        None => stem.to_string(),
+                    };
+                    index.classes.insert(qualified, relative.clone());
+                    if let Some(ref ns) = file.namespace {
+                        index
+                            .packages
+                            .entry(ns.clone())
+                            .or_default()
+                            .push(relative.clone());
+                    }
+                }
+                "cs" => {
+                    if let Some(ref ns) = file.namespace {
+                        index.namespaces.insert(ns.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        index
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.project.path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// The module a file belongs to, `None` for C# files without a namespace.
+    fn module_of(&self, file: &SourceFile) -> Option<String> {
+        let relative = self.relative(&file.path);
+        match file.language.as_str() {
+            "cs" => file.namespace.clone(),
+            "go" => Some(go_package_dir(&relative)),
+            _ => Some(relative),
+        }
+    }
+
+    /// Project modules an import refers to (empty for external imports).
+    fn resolve(&self, file: &SourceFile, import: &str) -> Vec<String> {
+        let relative = self.relative(&file.path);
+        let resolved = match file.language.as_str() {
+            "rs" => self.resolve_rust(&relative, import),
+            "py" | "pyi" => self.resolve_python(&relative, import),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "vue" | "svelte" => {
+                self.resolve_path(&relative, import)
+            }
+            "go" => self.resolve_go(import),
+            "java" => return self.resolve_qualified(import, "."),
+            "php" if import.starts_with('.') => self.resolve_path(&relative, import),
+            "php" => return self.resolve_qualified(import, "\\"),
+            "cs" => self.resolve_namespace(import),
+            _ => None,
+        };
+        resolved.into_iter().collect()
+    }
+
+    fn resolve_rust(&self, file: &str, import: &str) -> Option<String> {
+        let current = rust_module_path(file)?;
+        let current: Vec<&str> = current.split("::").filter(|s| !s.is_empty()).collect();
+        let segments: Vec<&str> = import.split("::").collect();
+
+        let mut path: Vec<&str> = match segments.first().copied()? {
+            "crate" => vec![],
+            name if name == self.crate_name => vec![],
+            "self" => current.clone(),
+            "super" => {
+                let ups = segments.iter().take_while(|s| **s == "super").count();
+                let keep = current.len().checked_sub(ups)?;
+                let mut path = current[..keep].to_vec();
+                path.extend(&segments[ups..]);
+                return self.longest_rust_prefix(&path, 0);
+            }
+            // 2018 paths resolve relative to the current module; anything
+            // that is not a child module there is an external crate
+            _ => {
+                let mut path = current.clone();
+                path.extend(&segments);
+                return self.longest_rust_prefix(&path, current.len() + 1);
+            }
+        };
+        path.extend(&segments[1..]);
+        self.longest_rust_prefix(&path, 0)
+    }
+
+    /// The deepest known module along `path`, at least `min_len` segments deep.
+    fn longest_rust_prefix(&self, path: &[&str], min_len: usize) -> Option<String> {
+        (min_len..=path.len())
+            .rev()
+            .find_map(|len| self.rust_modules.get(&path[..len].join("::")))
+            .cloned()
+    }
+
+    fn resolve_python(&self, file: &str, import: &str) -> Option<String> {
+        let dots = import.chars().take_while(|c| *c == '.').count();
+        let rest = &import[dots..];
+
+        let absolute = if dots == 0 {
+            rest.to_string()
+        } else {
+            // The package of `a/b/c.py` is `a.b`; of `a/b/__init__.py` also `a.b`
+            let module = python_module_names(file).into_iter().next()?;
+            let mut package: Vec<&str> = module.split('.').collect();
+            if !file.ends_with("__init__.py") {
+                package.pop();
+            }
+            let keep = package.len().checked_sub(dots - 1)?;
+            let mut parts = package[..keep].to_vec();
+            parts.extend(rest.split('.').filter(|s| !s.is_empty()));
+            parts.join(".")
+        };
+
+        let parts: Vec<&str> = absolute.split('.').collect();
+        (1..=parts.len())
+            .rev()
+            .find_map(|len| self.python_modules.get(&parts[..len].join(".")))
+            .cloned()
+    }
+
+    /// Resolve a relative file import (`./x`, `../y`, `@/z`).
+    fn resolve_path(&self, file: &str, import: &str) -> Option<String> {
+        let base = if let Some(aliased) = import.strip_prefix("@/") {
+            PathBuf::from("src").join(aliased)
+        } else if import.starts_with("./") || import.starts_with("../") {
+            Path::new(file).parent()?.join(import)
+        } else {
+            return None;
+        };
+        let base = normalize(&base)?;
+
+        let mut candidates = vec![base.clone()];
+        for ext in JS_EXTENSIONS {
+            candidates.push(format!("{}.{}", base, ext));
+        }
+        for ext in JS_EXTENSIONS {
+            candidates.push(format!("{}/index.{}", base, ext));
+        }
+        candidates.into_iter().find(|c| self.files.contains(c))
+    }
+
+    fn resolve_go(&self, import: &str) -> Option<String> {
+        if self.project.project_type != ProjectType::Go {
+            return None;
+        }
+        let dir = if import == self.project.name {
+            ".".to_string()
+        } else {
+            import
+                .strip_prefix(&self.project.name)?
+                .strip_prefix('/')?
+                .to_string()
+        };
+        self.files
+            .iter()
+            .any(|f| {
+                Path::new(f).extension().is_some_and(|ext| ext == "go") && go_package_dir(f) == dir
+            })
+            .then_some(dir)
+    }
+
+    /// Java/PHP: a class, a package wildcard or a static member import.
+    fn resolve_qualified(&self, import: &str, separator: &str) -> Vec<String> {
+        let wildcard = format!("{}*", separator);
+        if let Some(package) = import.strip_suffix(&wildcard) {
+            return self.packages.get(package).cloned().unwrap_or_default();
+        }
+        if let Some(file) = self.classes.get(import) {
+            return vec![file.clone()];
+        }
+        // `import static a.b.C.member` or a nested class
+        import
+            .rsplit_once(separator)
+            .and_then(|(owner, _)| self.classes.get(owner))
+            .cloned()
+            .into_iter()
+            .collect()
+    }
+
+    fn resolve_namespace(&self, import: &str) -> Option<String> {
+        let mut name = import;
+        loop {
+            if self.namespaces.contains(name) {
+                return Some(name.to_string());
+            }
+            // `using static A.B.Type` names a type inside namespace `A.B`
+            name = name.rsplit_once('.')?.0;
+        }
+    }
+}
+
+/// `src/a/b.rs` -> `a::b`, `src/a/mod.rs` -> `a`, `src/lib.rs` -> `` (crate root).
+fn rust_module_path(relative: &str) -> Option<String> {
+    let inner = relative.strip_prefix("src/")?.strip_suffix(".rs")?;
+    let mut segments: Vec<&str> = inner.split('/').collect();
+    match segments.last().copied() {
+        Some("mod") => {
+            segments.pop();
+        }
+        Some("lib" | "main") if segments.len() == 1 => segments.clear(),
+        _ => {}
+    }
+    Some(segments.join("::"))
+}
+
+/// Dotted module names for a Python file, also without a leading `src.`.
+fn python_module_names(relative: &str) -> Vec<String> {
+    let Some(inner) = relative
+        .strip_suffix(".py")
+        .or_else(|| relative.strip_suffix(".pyi"))
+    else {
+        return vec![];
+    };
+    let inner = inner.strip_suffix("/__init__").unwrap_or(inner);
+    let dotted = inner.replace('/', ".");
+
+    let mut names = vec![dotted.clone()];
+    if let Some(stripped) = dotted.strip_prefix("src.") {
+        names.push(stripped.to_string());
+    }
+    names
+}
+
+/// Directory of a Go file, `.` for the module root.
+fn go_package_dir(relative: &str) -> String {
+    match relative.rsplit_once('/') {
+        Some((dir, _)) => dir.to_string(),
+        None => ".".to_string(),
+    }
+}
+
+/// Resolve `.` and `..` lexically, `None` if the path escapes the project.
+fn normalize(path: &Path) -> Option<String> {
+    let mut parts: Vec<String> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            _ => return None,
+        }
+    }
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::imports::ImportExtractor;
+    use crate::test_support::source_file;
+
+    fn file(path: &str, namespace: Option<&str>, imports: &[&str]) -> SourceFile {
+        SourceFile {
+            namespace: namespace.map(String::from),
+            imports: imports.iter().map(|s| s.to_string()).collect(),
+            ..source_file(PathBuf::from("/p").join(path))
+        }
+    }
+
+    fn project(name: &str, project_type: ProjectType, files: Vec<SourceFile>) -> Project {
+        Project {
+            name: name.to_string(),
+            project_type,
+            files,
+            ..crate::test_support::project("/p")
+        }
+    }
+
+    #[test]
+    fn test_rust_resolution_and_cycles() {
+        let graph = ModuleGraph::build(&project(
+            "my-app",
+            ProjectType::Rust,
+            vec![
+                file(
+                    "src/lib.rs",
+                    None,
+                    &["self::models", "self::services", "std::fs"],
+                ),
+                file("src/models/mod.rs", None, &["crate::services::Billing"]),
+                file(
+                    "src/services.rs",
+                    None,
+                    &["super::models::User", "my_app::models"],
+                ),
+            ],
+        ));
+
+        assert_eq!(
+            graph.dependencies_of("src/lib.rs"),
+            vec!["src/models/mod.rs", "src/services.rs"]
+        );
+        assert_eq!(
+            graph.dependencies_of("src/services.rs"),
+            vec!["src/models/mod.rs"]
+        );
+
+        let summary = graph.summary();
+        assert_eq!(summary.cycle_count, 1);
+        assert_eq!(
+            summary.cycles[0],
+            vec![
+                "src/models/mod.rs".to_string(),
+                "src/services.rs".to_string()
+            ]
+        );
+        // models (domain) -> services (application)
+        assert_eq!(summary.violation_count, 1);
+        assert_eq!(summary.layer_violations[0].from, "src/models/mod.rs");
+        assert_eq!(summary.most_depended[0].module, "src/models/mod.rs");
+        assert_eq!(summary.most_depended[0].dependents, 2);
+    }
+
+    #[test]
+    fn test_child_using_super_is_not_a_cycle() {
+        let mut extractor = ImportExtractor::new();
+        let mut source = |path: &str, code: &str| {
+            let imports = extractor.extract("rs", code).imports;
+            let imports: Vec<&str> = imports.iter().map(String::as_str).collect();
+            file(path, None, &imports)
+        };
+        let files = vec![
+            source("src/main.rs", "mod a;\nfn run() {}\n"),
+            source("src/a/mod.rs", "mod b;\n"),
+            source("src/a/b.rs", "use super::run;\n"),
+        ];
+        let graph = ModuleGraph::build(&project("app", ProjectType::Rust, files));
+
+        assert_eq!(graph.dependencies_of("src/a/b.rs"), vec!["src/a/mod.rs"]);
+        assert!(graph.dependencies_of("src/a/mod.rs").is_empty());
+        assert_eq!(graph.summary().cycle_count, 0);
+    }
+
+    #[test]
+    fn test_python_and_javascript_resolution() {
+        let graph = ModuleGraph::build(&project(
+            "app",
+            ProjectType::Python,
+            vec![
+                file("app/__init__.py", None, &[]),
+                file("app/views.py", None, &[".models.User", "django.db"]),
+                file("app/models.py", None, &[]),
+                file("web/index.ts", None, &["./util", "../lib", "react"]),
+                file("web/util.ts", None, &[]),
+                file("lib/index.js", None, &[]),
+            ],
+        ));
+
+        assert_eq!(graph.dependencies_of("app/views.py"), vec!["app/models.py"]);
+        assert_eq!(
+            graph.dependencies_of("web/index.ts"),
+            vec!["lib/index.js", "web/util.ts"]
+        );
+    }
+
+    #[test]
+    fn test_namespace_based_languages() {
+        let graph = ModuleGraph::build(&project(
+            "App",
+            ProjectType::DotNet,
+            vec![
+                file(
+                    "Controllers/Home.cs",
+                    Some("App.Controllers"),
+                    &["App.Services", "System"],
+                ),
+                file("Services/Mail.cs", Some("App.Services"), &[]),
+                file(
+                    "src/Http/UserController.php",
+                    Some("App\\Http"),
+                    &["App\\Models\\User"],
+                ),
+                file("src/Models/User.php", Some("App\\Models"), &[]),
+                file("Web.java", Some("com.app.web"), &["com.app.domain.*"]),
+                file("User.java", Some("com.app.domain"), &[]),
+            ],
+        ));
+
+        assert_eq!(
+            graph.dependencies_of("App.Controllers"),
+            vec!["App.Services"]
+        );
+        assert_eq!(
+            graph.dependencies_of("src/Http/UserController.php"),
+            vec!["src/Models/User.php"]
+        );
+        assert_eq!(graph.dependencies_of("Web.java"), vec!["User.java"]);
+        assert_eq!(graph.dependents_of("App.Services"), vec!["App.Controllers"]);
+    }
+
+    #[test]
+    fn test_go_packages() {
+        let graph = ModuleGraph::build(&project(
+            "example.com/app",
+            ProjectType::Go,
+            vec![
+                file("main.go", None, &["example.com/app/internal/store", "fmt"]),
+                file("internal/store/store.go", None, &[]),
+            ],
+        ));
+
+        assert_eq!(graph.dependencies_of("."), vec!["internal/store"]);
+    }
+}
@@ -0,0 +1,540 @@
+//! Symbol outlines for tree-sitter languages.
+//!
+//! Walks the syntax tree of a file and maps declarations (types, functions,
+//! members) onto the generic `Symbol` tree, with the source range and the
+//! declaration header of each symbol. Function bodies are not descended
+//! into, so locals and closures never show up in the outline.
+
+use tree_sitter::{Node, Parser};
+
+use super::grammar::{node_range, Grammar};
+use crate::types::{Symbol, SymbolKind};
+
+/// Longest signature kept, in characters.
+const MAX_SIGNATURE_LEN: usize = 200;
+
+/// Node kinds holding the body of a declaration, for grammars without a `body` field.
+const BODY_KINDS: &[&str] = &[
+    "block",
+    "declaration_list",
+    "field_declaration_list",
+    "enum_variant_list",
+    "accessor_list",
+    "arrow_expression_clause",
+    "class_body",
+    "interface_body",
+    "enum_body",
+    "statement_block",
+    "enum_member_declaration_list",
+];
+
+/// Node kinds carrying modifiers, per grammar.
+const MODIFIER_KINDS: &[&str] = &[
+    "modifier",
+    "modifiers",
+    "visibility_modifier",
+    "accessibility_modifier",
+    "function_modifiers",
+];
+
+/// Extracts symbol outlines, reusing one parser across files.
+pub struct OutlineExtractor {
+    parser: Parser,
+}
+
+impl Default for OutlineExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutlineExtractor {
+    pub fn new() -> Self {
+        Self {
+            parser: Parser::new(),
+        }
+    }
+
+    /// Extract the top-level symbols of `source`, written in `language` (the file extension).
+    ///
+    /// Languages without a grammar yield no symbols.
+    pub fn extract(&mut self, language: &str, source: &str) -> Vec<Symbol> {
+        let Some(grammar) = Grammar::from_extension(language) else {
+            return vec![];
+        };
+        if self.parser.set_language(grammar.language()).is_err() {
+            return vec![];
+        }
+        let Some(tree) = self.parser.parse(source, None) else {
+            return vec![];
+        };
+
+        let mut symbols = Vec::new();
+        Self::collect(
+            grammar,
+            tree.root_node(),
+            source.as_bytes(),
+            false,
+            &mut symbols,
+        );
+        symbols
+    }
+
+    /// Collect the symbols below `node`. `in_type` turns functions into methods.
+    fn collect(grammar: Grammar, node: Node, source: &[u8], in_type: bool, out: &mut Vec<Symbol>) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match Self::symbol_kind(grammar, child, in_type) {
+                Some(kind) => out.extend(Self::symbol(grammar, child, kind, source)),
+                None if Self::is_transparent(grammar, child) => {
+                    Self::collect(grammar, child, source, in_type, out);
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Kind of the symbol declared by `node`, if it declares one.
+    fn symbol_kind(grammar: Grammar, node: Node, in_type: bool) -> Option<SymbolKind> {
+        let function = || {
+            if in_type {
+                SymbolKind::Method
+            } else {
+                SymbolKind::Function
+            }
+        };
+
+        let kind = match (grammar, node.kind()) {
+            (Grammar::Rust, "function_item" | "function_signature_item") => function(),
+            (Grammar::Rust, "struct_item" | "union_item") => SymbolKind::Struct,
+            (Grammar::Rust, "enum_item") => SymbolKind::Enum,
+            (Grammar::Rust, "trait_item") => SymbolKind::Trait,
+            (Grammar::Rust, "impl_item") => SymbolKind::Impl,
+            (Grammar::Rust, "mod_item") => SymbolKind::Module,
+            (Grammar::Rust, "type_item") => SymbolKind::Other("type".to_string()),
+            (Grammar::Rust, "const_item" | "static_item") => SymbolKind::Other("const".to_string()),
+            (Grammar::Rust, "macro_definition") => SymbolKind::Other("macro".to_string()),
+            (Grammar::Rust, "field_declaration") if in_type => SymbolKind::Field,
+
+            (Grammar::Python, "class_definition") => SymbolKind::Class,
+            (Grammar::Python, "function_definition") => function(),
+
+            (
+                Grammar::JavaScript | Grammar::TypeScript | Grammar::Tsx,
+                "class_declaration" | "abstract_class_declaration" | "class",
+            ) => SymbolKind::Class,
+            (
+                Grammar::JavaScript | Grammar::TypeScript | Grammar::Tsx,
+                "function_declaration" | "generator_function_declaration" | "function_signature",
+            ) => function(),
+            (
+                Grammar::JavaScript | Grammar::TypeScript | Grammar::Tsx,
+                "method_definition" | "method_signature" | "abstract_method_signature",
+            ) => SymbolKind::Method,
+            (
+                Grammar::JavaScript | Grammar::TypeScript | Grammar::Tsx,
+                "public_field_definition" | "field_definition" | "property_signature",
+            ) => SymbolKind::Field,
+            (Grammar::TypeScript | Grammar::Tsx, "interface_declaration") => SymbolKind::Interface,
+            (Grammar::TypeScript | Grammar::Tsx, "enum_declaration") => SymbolKind::Enum,
+            (Grammar::TypeScript | Grammar::Tsx, "type_alias_declaration") => {
+                SymbolKind::Other("type".to_string())
+            }
+            (Grammar::TypeScript | Grammar::Tsx, "module" | "internal_module") => {
+                SymbolKind::Module
+            }
+            // `const handler = () => {}` at module level
+            (
+                Grammar::JavaScript | Grammar::TypeScript | Grammar::Tsx,
+                "lexical_declaration" | "variable_declaration",
+            ) if !in_type && Self::declared_function(node).is_some() => function(),
+
+            (Grammar::Go, "function_declaration") => SymbolKind::Function,
+            (Grammar::Go, "method_declaration") => SymbolKind::Method,
+            (Grammar::Go, "type_spec") => match node.child_by_field_name("type")?.kind() {
+                "struct_type" => SymbolKind::Struct,
+                "interface_type" => SymbolKind::Interface,
+                _ => SymbolKind::Other("type".to_string()),
+            },
+
+            (Grammar::Java, "class_declaration" | "record_declaration") => SymbolKind::Class,
+            (Grammar::Java, "interface_declaration" | "annotation_type_declaration") => {
+                SymbolKind::Interface
+            }
+            (Grammar::Java, "enum_declaration") => SymbolKind::Enum,
+            (Grammar::Java, "method_declaration" | "constructor_declaration") => SymbolKind::Method,
+            (Grammar::Java, "field_declaration") => SymbolKind::Field,
+
+            (Grammar::CSharp, "class_declaration" | "record_declaration") => SymbolKind::Class,
+            (Grammar::CSharp, "struct_declaration" | "record_struct_declaration") => {
+                SymbolKind::Struct
+            }
+            (Grammar::CSharp, "interface_declaration") => SymbolKind::Interface,
+            (Grammar::CSharp, "enum_declaration") => SymbolKind::Enum,
+            (
+                Grammar::CSharp,
+                "method_declaration" | "constructor_declaration" | "operator_declaration",
+            ) => SymbolKind::Method,
+            (Grammar::CSharp, "property_declaration") => SymbolKind::Property,
+            (Grammar::CSharp, "field_declaration") => SymbolKind::Field,
+            (Grammar::CSharp, "delegate_declaration") => SymbolKind::Other("delegate".to_string()),
+
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Whether declarations nested in a non-symbol `node` belong to its parent.
+    fn is_transparent(grammar: Grammar, node: Node) -> bool {
+        match (grammar, node.kind()) {
+            // Namespaces group types without being part of their names
+            (
+                Grammar::CSharp,
+                "namespace_declaration" | "file_scoped_namespace_declaration" | "declaration_list",
+            ) => true,
+            (Grammar::Python, "decorated_definition" | "block") => true,
+            (
+                Grammar::JavaScript | Grammar::TypeScript | Grammar::Tsx,
+                "export_statement" | "ambient_declaration" | "statement_block",
+            ) => true,
+            (Grammar::Go, "type_declaration") => true,
+            (Grammar::Rust, "declaration_list" | "field_declaration_list") => true,
+            _ => false,
+        }
+    }
+
+    /// Build the symbol declared by `node`; `None` when it has no name.
+    fn symbol(grammar: Grammar, node: Node, kind: SymbolKind, source: &[u8]) -> Option<Symbol> {
+        let name = Self::name(grammar, node, source)?;
+        let body = Self::body(node);
+
+        let mut children = Vec::new();
+        let is_container = matches!(
+            kind,
+            SymbolKind::Class
+                | SymbolKind::Struct
+                | SymbolKind::Interface
+                | SymbolKind::Trait
+                | SymbolKind::Impl
+                | SymbolKind::Module
+        );
+        if is_container {
+            if let Some(body) = body {
+                let in_type = kind != SymbolKind::Module;
+                Self::collect(grammar, body, source, in_type, &mut children);
+            }
+        }
+
+        Some(Symbol {
+            name,
+            kind,
+            modifiers: Self::modifiers(node, source),
+            children,
+            range: Some(node_range(node)),
+            signature: Self::signature(node, body, source),
+        })
+    }
+
+    fn name(grammar: Grammar, node: Node, source: &[u8]) -> Option<String> {
+        let text = |n: Node| n.utf8_text(source).ok().map(str::to_string);
+
+        match (grammar, node.kind()) {
+            (Grammar::Rust, "impl_item") => {
+                let ty = text(node.child_by_field_name("type")?)?;
+                match node.child_by_field_name("trait") {
+                    Some(t) => Some(format!("{} for {}", text(t)?, ty)),
+                    None => Some(ty),
+                }
+            }
+            (_, "lexical_declaration" | "variable_declaration") => {
+                text(Self::declared_function(node)?.child_by_field_name("name")?)
+            }
+            (Grammar::Java | Grammar::CSharp, "field_declaration") => {
+                // C# nests the declarators in a `variable_declaration`
+                let declarator = Self::find_kind(node, "variable_declarator").or_else(|| {
+                    Self::find_kind(
+                        Self::find_kind(node, "variable_declaration")?,
+                        "variable_declarator",
+                    )
+                })?;
+                match declarator.child_by_field_name("name") {
+                    Some(name) => text(name),
+                    None => text(Self::find_kind(declarator, "identifier")?),
+                }
+            }
+            (Grammar::CSharp, "operator_declaration") => {
+                let operator = node.child_by_field_name("operator")?;
+                Some(format!("operator {}", text(operator)?))
+            }
+            _ => match node.child_by_field_name("name") {
+                Some(name) => text(name),
+                None => text(Self::find_kind(node, "identifier")?),
+            },
+        }
+    }
+
+    /// The `variable_declarator` of a declaration whose value is a function.
+    fn declared_function(node: Node) -> Option<Node> {
+        let mut cursor = node.walk();
+        let declarator = node
+            .named_children(&mut cursor)
+            .find(|c| c.kind() == "variable_declarator")?;
+        let value = declarator.child_by_field_name("value")?;
+        matches!(
+            value.kind(),
+            "arrow_function" | "function" | "function_expression" | "generator_function"
+        )
+        .then_some(declarator)
+    }
+
+    /// First direct child of `node` with the given kind.
+    fn find_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let found = node.named_children(&mut cursor).find(|c| c.kind() == kind);
+        found
+    }
+
+    fn body(node: Node) -> Option<Node> {
+        if let Some(body) = node.child_by_field_name("body") {
+            return Some(body);
+        }
+        let mut cursor = node.walk();
+        let body = node
+            .named_children(&mut cursor)
+            .find(|c| BODY_KINDS.contains(&c.kind()));
+        body
+    }
+
+    fn modifiers(node: Node, source: &[u8]) -> Vec<String> {
+        let mut modifiers = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if !MODIFIER_KINDS.contains(&child.kind()) {
+                continue;
+            }
+            let Ok(text) = child.utf8_text(source) else {
+                continue;
+            };
+            // Java groups annotations with the modifiers
+            modifiers.extend(
+                text.split_whitespace()
+                    .filter(|m| !m.starts_with('@'))
+                    .map(str::to_string),
+            );
+        }
+        modifiers
+    }
+
+    /// Declaration header: the text up to the body, on one line.
+    fn signature(node: Node, body: Option<Node>, source: &[u8]) -> Option<String> {
+        let end = body.map_or(node.end_byte(), |b| b.start_byte());
+        let header = std::str::from_utf8(source.get(node.start_byte()..end)?).ok()?;
+        let header = header
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            // Parameters wrapped one per line
+            .replace("( ", "(")
+            .replace(", )", ")")
+            .replace(" )", ")");
+        // Drop the body opener (`{`, `:`, `=>`) and statement terminator
+        let header = header
+            .trim_end_matches(['{', ':', ';', ' '])
+            .trim_end_matches("=>")
+            .trim_end();
+        if header.is_empty() {
+            return None;
+        }
+
+        if header.chars().count() > MAX_SIGNATURE_LEN {
+            let truncated: String = header.chars().take(MAX_SIGNATURE_LEN).collect();
+            Some(format!("{}...", truncated))
+        } else {
+            Some(header.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::LineRange;
+
+    fn outline(language: &str, source: &str) -> Vec<Symbol> {
+        OutlineExtractor::new().extract(language, source)
+    }
+
+    fn names(symbols: &[Symbol]) -> Vec<(String, String)> {
+        symbols
+            .iter()
+            .map(|s| (s.kind.to_string(), s.name.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_outline() {
+        let source = r"
+pub struct Config {
+    pub name: String,
+}
+
+impl Config {
+    /// Load it
+    pub async fn load(
+        path: &Path,
+    ) -> Result<Self> {
+        let inner = |x: u32| x;
+        todo!()
+    }
+}
+
+impl Display for Config {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result { Ok(()) }
+}
+
+pub trait Store {
+    fn get(&self, key: &str) -> Option<String>;
+}
+
+mod helpers {
+    fn help() {}
+}
+";
+        let symbols = outline("rs", source);
+
+        assert_eq!(
+            names(&symbols),
+            vec![
+                ("struct".to_string(), "Config".to_string()),
+                ("impl".to_string(), "Config".to_string()),
+                ("impl".to_string(), "Display for Config".to_string()),
+                ("trait".to_string(), "Store".to_string()),
+                ("module".to_string(), "helpers".to_string()),
+            ]
+        );
+        assert_eq!(
+            names(&symbols[0].children),
+            vec![("field".to_string(), "name".to_string())]
+        );
+
+        let load = &symbols[1].children[0];
+        assert_eq!(load.kind, SymbolKind::Method);
+        let range = load.range.unwrap();
+        assert_eq!(range.lines(), LineRange { start: 8, end: 13 });
+        assert_eq!(range.start.column, 5);
+        assert_eq!(&source[range.start.byte..range.start.byte + 3], "pub");
+        assert_eq!(
+            load.signature.as_deref(),
+            Some("pub async fn load(path: &Path) -> Result<Self>")
+        );
+        assert_eq!(load.modifiers, vec!["pub".to_string(), "async".to_string()]);
+        assert!(load.children.is_empty());
+
+        assert_eq!(symbols[3].children[0].name, "get");
+        assert_eq!(symbols[4].children[0].kind, SymbolKind::Function);
+    }
+
+    #[test]
+    fn test_python_outline() {
+        let source = "class Repo(Base):\n    @property\n    def name(self) -> str:\n        return 'x'\n\ndef main():\n    pass\n";
+        let symbols = outline("py", source);
+
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].signature.as_deref(), Some("class Repo(Base)"));
+        assert_eq!(symbols[0].children[0].kind, SymbolKind::Method);
+        assert_eq!(
+            symbols[0].children[0].signature.as_deref(),
+            Some("def name(self) -> str")
+        );
+        assert_eq!(symbols[1].kind, SymbolKind::Function);
+    }
+
+    #[test]
+    fn test_typescript_outline() {
+        let source = "export interface User { id: number; }\nexport class UserService {\n  private cache = new Map();\n  async find(id: number): Promise<User> { return this.cache.get(id); }\n}\nexport const handler = async (req: Request) => { return 1; };\nconst limit = 10;\n";
+        let symbols = outline("ts", source);
+
+        assert_eq!(
+            names(&symbols),
+            vec![
+                ("interface".to_string(), "User".to_string()),
+                ("class".to_string(), "UserService".to_string()),
+                ("function".to_string(), "handler".to_string()),
+            ]
+        );
+        assert_eq!(
+            names(&symbols[1].children),
+            vec![
+                ("field".to_string(), "cache".to_string()),
+                ("method".to_string(), "find".to_string()),
+            ]
+        );
+        assert_eq!(
+            symbols[1].children[1].signature.as_deref(),
+            Some("async find(id: number): Promise<User>")
+        );
+    }
+
+    #[test]
+    fn test_go_and_java_outline() {
+        let go = "package store\n\ntype Store struct {\n\tdb *sql.DB\n}\n\nfunc (s *Store) Get(id int) (*User, error) {\n\treturn nil, nil\n}\n";
+        let symbols = outline("go", go);
+        assert_eq!(
+            names(&symbols),
+            vec![
+                ("struct".to_string(), "Store".to_string()),
+                ("method".to_string(), "Get".to_string()),
+            ]
+        );
+        assert_eq!(
+            symbols[1].signature.as_deref(),
+            Some("func (s *Store) Get(id int) (*User, error)")
+        );
+
+        let java = "package app;\n\n@Service\npublic class UserService {\n    private final Repo repo;\n    public User find(long id) { return null; }\n}\n";
+        let symbols = outline("java", java);
+        assert_eq!(symbols[0].modifiers, vec!["public".to_string()]);
+        assert_eq!(
+            names(&symbols[0].children),
+            vec![
+                ("field".to_string(), "repo".to_string()),
+                ("method".to_string(), "find".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_csharp_outline() {
+        let source = "namespace App.Services;\n\npublic class OrderService : IOrderService\n{\n    private readonly ILogger _logger;\n    public string Name { get; set; }\n    public async Task<Order> GetAsync(int id)\n    {\n        return null;\n    }\n}\n";
+        let symbols = outline("cs", source);
+
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "OrderService");
+        assert_eq!(
+            symbols[0].signature.as_deref(),
+            Some("public class OrderService : IOrderService")
+        );
+        assert_eq!(
+            names(&symbols[0].children),
+            vec![
+                ("field".to_string(), "_logger".to_string()),
+                ("property".to_string(), "Name".to_string()),
+                ("method".to_string(), "GetAsync".to_string()),
+            ]
+        );
+        let method = &symbols[0].children[2];
+        assert_eq!(
+            method.range.map(|r| r.lines()),
+            Some(LineRange { start: 7, end: 10 })
+        );
+        assert_eq!(
+            method.signature.as_deref(),
+            Some("public async Task<Order> GetAsync(int id)")
+        );
+    }
+
+    #[test]
+    fn test_unknown_language() {
+        assert!(outline("php", "<?php class A {}").is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+//! Project-wide symbol lookup.
+//!
+//! Flattens the `Symbol` trees of every file into qualified names
+//! (`OrderService.GetAsync`) so a symbol can be located by a partial or
+//! misspelled name without reading any source.
+
+use std::path::{Path, PathBuf};
+
+use crate::types::{Project, Symbol, SymbolMatch};
+
+/// One symbol of the project, with its qualified name.
+struct Entry {
+    path: PathBuf,
+    qualified_name: String,
+    symbol_lower: String,
+    qualified_lower: String,
+    /// The symbol without its children
+    symbol: Symbol,
+}
+
+/// Searchable index of all symbols in a project.
+pub struct SymbolIndex {
+    entries: Vec<Entry>,
+}
+
+impl SymbolIndex {
+    /// Index the symbols of `project`. Paths are relative to the project root.
+    pub fn build(project: &Project) -> Self {
+        let mut entries = Vec::new();
+        for file in &project.files {
+            let path = file
+                .path
+                .strip_prefix(&project.path)
+                .unwrap_or(&file.path)
+                .to_path_buf();
+            for symbol in &file.symbols {
+                Self::add(&path, symbol, None, &mut entries);
+            }
+        }
+        Self { entries }
+    }
+
+    fn add(path: &Path, symbol: &Symbol, parent: Option<&str>, entries: &mut Vec<Entry>) {
+        let qualified_name = match parent {
+            Some(parent) => format!("{}.{}", parent, symbol.name),
+            None => symbol.name.clone(),
+        };
+        for child in &symbol.children {
+            Self::add(path, child, Some(&qualified_name), entries);
+        }
+        entries.push(Entry {
+            path: path.to_path_buf(),
+            symbol_lower: symbol.name.to_lowercase(),
+            qualified_lower: qualified_name.to_lowercase(),
+            qualified_name,
+            symbol: Symbol {
+                children: vec![],
+                ..symbol.clone()
+            },
+        });
+    }
+
+    /// Number of indexed symbols.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find symbols matching `query` and/or of the given `kind`, best first.
+    ///
+    /// `query` matches case-insensitively against the symbol name or its
+    /// qualified name (`Class.Method`, `::` also accepted): exact matches
+    /// rank first, then prefixes, substrings and finally in-order
+    /// subsequences (`ordsvc` finds `OrderService`). Without a query every
+    /// symbol of `kind` is returned.
+    pub fn search(
+        &self,
+        query: Option<&str>,
+        kind: Option<&str>,
+        max_results: usize,
+    ) -> Vec<SymbolMatch> {
+        let query = query
+            .map(|q| q.trim().replace("::", ".").to_lowercase())
+            .filter(|q| !q.is_empty());
+
+        let mut matches: Vec<(f32, &Entry)> = self
+            .entries
+            .iter()
+            .filter(|e| kind.is_none_or(|k| e.symbol.kind.to_string().eq_ignore_ascii_case(k)))
+            .filter_map(|e| {
+                let score = match query {
+                    Some(ref q) => Self::score(q, e)?,
+                    None => 1.0,
+                };
+                Some((score, e))
+            })
+            .collect();
+
+        matches.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .total_cmp(a_score)
+                .then_with(|| a.qualified_name.len().cmp(&b.qualified_name.len()))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        matches.truncate(max_results);
+
+        matches
+            .into_iter()
+            .map(|(score, e)| SymbolMatch {
+                path: e.path.clone(),
+                qualified_name: e.qualified_name.clone(),
+                kind: e.symbol.kind.clone(),
+                range: e.symbol.range,
+                signature: e.symbol.signature.clone(),
+                score,
+            })
+            .collect()
+    }
+
+    /// Match score in (0, 1], `None` when the entry does not match.
+    fn score(query: &str, entry: &Entry) -> Option<f32> {
+        let name = entry.symbol_lower.as_str();
+        let qualified = entry.qualified_lower.as_str();
+
+        if name == query || qualified == query {
+            return Some(1.0);
+        }
+        if name.starts_with(query) {
+            return Some(0.9);
+        }
+        if qualified.ends_with(&format!(".{}", query)) {
+            return Some(0.85);
+        }
+        if name.contains(query) {
+            return Some(0.75);
+        }
+        // Members of a matching type only match when asked for (`order.`)
+        if query.contains('.') && qualified.contains(query) {
+            return Some(0.65);
+        }
+        if is_subsequence(query, name) {
+            // Favor names that are mostly made of the query
+            let coverage = query.chars().count() as f32 / name.chars().count() as f32;
+            return Some(0.3 + 0.3 * coverage);
+        }
+        None
+    }
+}
+
+/// Whether the characters of `needle` appear in `haystack` in order.
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|c| chars.any(|h| h == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::source_file;
+    use crate::types::{
+        LineRange, ProjectType, SourceFile, SourcePosition, SourceRange, SymbolKind,
+    };
+
+    fn symbol(
+        name: &str,
+        kind: SymbolKind,
+        lines: (usize, usize),
+        children: Vec<Symbol>,
+    ) -> Symbol {
+        Symbol {
+            name: name.to_string(),
+            kind,
+            modifiers: vec![],
+            children,
+            range: Some(SourceRange {
+                start: SourcePosition {
+                    line: lines.0,
+                    column: 1,
+                    byte: 0,
+                },
+                end: SourcePosition {
+                    line: lines.1,
+                    column: 2,
+                    byte: 0,
+                },
+            }),
+            signature: None,
+        }
+    }
+
+    fn project() -> Project {
+        let file = |path: &str, symbols: Vec<Symbol>| SourceFile {
+            symbols,
+            ..source_file(PathBuf::from("/repo").join(path))
+        };
+        Project {
+            name: "repo".to_string(),
+            project_type: ProjectType::DotNet,
+            files: vec![
+                file(
+                    "Services/OrderService.cs",
+                    vec![symbol(
+                        "OrderService",
+                        SymbolKind::Class,
+                        (3, 40),
+                        vec![
+                            symbol("GetOrder", SymbolKind::Method, (5, 10), vec![]),
+                            symbol("Save", SymbolKind::Method, (12, 20), vec![]),
+                        ],
+                    )],
+                ),
+                file(
+                    "Models/Order.cs",
+                    vec![symbol("Order", SymbolKind::Class, (1, 9), vec![])],
+                ),
+            ],
+            ..crate::test_support::project("/repo")
+        }
+    }
+
+    #[test]
+    fn test_exact_and_prefix_ranking() {
+        let index = SymbolIndex::build(&project());
+        assert_eq!(index.len(), 4);
+
+        let results = index.search(Some("order"), None, 10);
+        let names: Vec<&str> = results.iter().map(|m| m.qualified_name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Order", "OrderService", "OrderService.GetOrder"]
+        );
+        assert_eq!(results[0].path, PathBuf::from("Models/Order.cs"));
+    }
+
+    #[test]
+    fn test_qualified_and_fuzzy_queries() {
+        let index = SymbolIndex::build(&project());
+
+        let results = index.search(Some("OrderService::save"), None, 10);
+        assert_eq!(results[0].qualified_name, "OrderService.Save");
+        assert_eq!(
+            results[0].range.map(|r| r.lines()),
+            Some(LineRange { start: 12, end: 20 })
+        );
+
+        let results = index.search(Some("ordsvc"), None, 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].qualified_name, "OrderService");
+    }
+
+    #[test]
+    fn test_kind_filter() {
+        let index = SymbolIndex::build(&project());
+
+        let methods = index.search(None, Some("Method"), 10);
+        assert_eq!(methods.len(), 2);
+        assert!(index
+            .search(Some("order"), Some("interface"), 10)
+            .is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+//! Symbol and import extraction for discovered source files.
+//!
+//! Builds the generic `Symbol` tree stored in `SourceFile.symbols` with
+//! `OutlineExtractor`, and collects each file's imports.
+
+use anyhow::Result;
+use std::path::Path;
+
+use super::csharp::CSharpAnalyzer;
+use super::imports::{FileImports, ImportExtractor};
+use super::outline::OutlineExtractor;
+use crate::types::Symbol;
+
+/// Everything extracted from one file.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedFile {
+    pub symbols: Vec<Symbol>,
+    pub imports: FileImports,
+}
+
+/// Extracts symbols and imports from source files, reusing parsers across files.
+#[derive(Default)]
+pub struct SymbolExtractor {
+    csharp: Option<CSharpAnalyzer>,
+    imports: ImportExtractor,
+    outline: OutlineExtractor,
+}
+
+impl SymbolExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Extract the symbols and imports of a file whose contents have already been read.
+    ///
+    /// Languages without a tree-sitter grammar yield no symbols.
+    pub fn extract(&mut self, path: &Path, language: &str, source: &str) -> Result<ExtractedFile> {
+        match language {
+            "cs" => {
+                let analyzer = match self.csharp.as_mut() {
+                    Some(analyzer) => analyzer,
+                    None => self.csharp.insert(CSharpAnalyzer::new()?),
+                };
+                let file = analyzer.analyze_source(path, source)?;
+                Ok(ExtractedFile {
+                    symbols: self.outline.extract(language, source),
+                    imports: FileImports {
+                        namespace: file.namespace,
+                        imports: file.usings,
+                    },
+                })
+            }
+            _ => Ok(ExtractedFile {
+                symbols: self.outline.extract(language, source),
+                imports: self.imports.extract(language, source),
+            }),
+        }
+    }
+}
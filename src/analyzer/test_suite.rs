@@ -0,0 +1,726 @@
+//! Test suite detection and coverage gaps.
+//!
+//! Classifies the analyzed files as tests or production code by path
+//! conventions, detects the test frameworks from dependencies, imports in
+//! test files and config files at the project root, and maps each test file
+//! back to the module (directory) it covers, matching file names first
+//! (`test_orders.py` covers `orders.py`) and the test's directory second.
+//! Modules left without tests are the coverage gaps.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path};
+
+use crate::types::{
+    ModuleTests, Project, ProjectType, SeverityLevel, SourceFile, Suggestion, SymbolKind,
+    TestFramework, TestSummary,
+};
+
+/// Directory names that hold tests.
+const TEST_DIRS: &[&str] = &[
+    "test",
+    "tests",
+    "__tests__",
+    "spec",
+    "specs",
+    "testing",
+    "e2e",
+    "cypress",
+];
+/// Suffixes of .NET test project directories (`Orders.Tests`).
+const TEST_PROJECT_SUFFIXES: &[&str] = &[".Tests", ".Test", ".UnitTests", ".IntegrationTests"];
+/// File stems standing for their directory (`mod.rs`, `index.ts`, `__init__.py`).
+const DIRECTORY_STEMS: &[&str] = &["mod", "index", "__init__", "lib", "main"];
+/// Test locations listed in the summary.
+const MAX_LOCATIONS: usize = 10;
+/// Projects smaller than this are not told they lack tests.
+const MIN_SOURCE_FILES: usize = 5;
+/// Untested modules smaller than this are not reported.
+const MIN_MODULE_FILES: usize = 2;
+/// Untested modules reported as suggestions.
+const MAX_UNTESTED_SUGGESTIONS: usize = 5;
+
+/// How a test framework shows up in a project.
+struct Signature {
+    name: &'static str,
+    command: &'static str,
+    /// Project type whose toolchain has the framework built in
+    builtin: Option<ProjectType>,
+    /// Package names, compared case-insensitively
+    dependencies: &'static [&'static str],
+    /// Import prefixes in test files
+    imports: &'static [&'static str],
+    /// Config files at the project root
+    files: &'static [&'static str],
+}
+
+const SIGNATURES: &[Signature] = &[
+    Signature {
+        name: "cargo test",
+        command: "cargo test",
+        builtin: Some(ProjectType::Rust),
+        dependencies: &[],
+        imports: &[],
+        files: &[],
+    },
+    Signature {
+        name: "go test",
+        command: "go test ./...",
+        builtin: Some(ProjectType::Go),
+        dependencies: &[],
+        imports: &[],
+        files: &[],
+    },
+    Signature {
+        name: "Jest",
+        command: "npx jest",
+        builtin: None,
+        dependencies: &["jest"],
+        imports: &["@jest/globals"],
+        files: &[
+            "jest.config.js",
+            "jest.config.ts",
+            "jest.config.mjs",
+            "jest.config.cjs",
+        ],
+    },
+    Signature {
+        name: "Vitest",
+        command: "npx vitest run",
+        builtin: None,
+        dependencies: &["vitest"],
+        imports: &["vitest"],
+        files: &["vitest.config.ts", "vitest.config.js", "vitest.config.mts"],
+    },
+    Signature {
+        name: "Mocha",
+        command: "npx mocha",
+        builtin: None,
+        dependencies: &["mocha"],
+        imports: &[],
+        files: &[".mocharc.json", ".mocharc.yml", ".mocharc.js"],
+    },
+    Signature {
+        name: "Playwright",
+        command: "npx playwright test",
+        builtin: None,
+        dependencies: &["@playwright/test"],
+        imports: &["@playwright/test"],
+        files: &["playwright.config.ts", "playwright.config.js"],
+    },
+    Signature {
+        name: "pytest",
+        command: "pytest",
+        builtin: None,
+        dependencies: &["pytest"],
+        imports: &["pytest"],
+        files: &["pytest.ini", "conftest.py"],
+    },
+    Signature {
+        name: "unittest",
+        command: "python -m unittest",
+        builtin: None,
+        dependencies: &[],
+        imports: &["unittest"],
+        files: &[],
+    },
+    Signature {
+        name: "xUnit",
+        command: "dotnet test",
+        builtin: None,
+        dependencies: &["xunit"],
+        imports: &["Xunit"],
+        files: &[],
+    },
+    Signature {
+        name: "NUnit",
+        command: "dotnet test",
+        builtin: None,
+        dependencies: &["nunit"],
+        imports: &["NUnit.Framework"],
+        files: &[],
+    },
+    Signature {
+        name: "MSTest",
+        command: "dotnet test",
+        builtin: None,
+        dependencies: &["mstest.testframework"],
+        imports: &["Microsoft.VisualStudio.TestTools.UnitTesting"],
+        files: &[],
+    },
+    Signature {
+        name: "JUnit",
+        command: "mvn test",
+        builtin: None,
+        dependencies: &["junit", "junit-jupiter"],
+        imports: &["org.junit"],
+        files: &[],
+    },
+    Signature {
+        name: "TestNG",
+        command: "mvn test",
+        builtin: None,
+        dependencies: &["testng"],
+        imports: &["org.testng"],
+        files: &[],
+    },
+    Signature {
+        name: "PHPUnit",
+        command: "vendor/bin/phpunit",
+        builtin: None,
+        dependencies: &["phpunit/phpunit"],
+        imports: &["PHPUnit"],
+        files: &["phpunit.xml", "phpunit.xml.dist"],
+    },
+    Signature {
+        name: "Pest",
+        command: "vendor/bin/pest",
+        builtin: None,
+        dependencies: &["pestphp/pest"],
+        imports: &[],
+        files: &["tests/Pest.php"],
+    },
+];
+
+/// Whether a path (relative to the project root) looks like a test file.
+pub fn is_test_path(path: &Path) -> bool {
+    let in_test_dir = path.parent().is_some_and(|dir| {
+        dir.components().any(|c| {
+            c.as_os_str()
+                .to_str()
+                .is_some_and(|name| TEST_DIRS.contains(&name) || is_test_project_dir(name))
+        })
+    });
+    let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+        return in_test_dir;
+    };
+    in_test_dir
+        // pytest's `test_*.py`; elsewhere the prefix names ordinary modules
+        || (stem.starts_with("test_") && path.extension().is_some_and(|e| e == "py"))
+        || stem.ends_with("_test")
+        || stem.ends_with("_tests")
+        || stem == "conftest"
+        // `app.test.ts`, `app.spec.js`
+        || matches!(
+            Path::new(stem).extension().and_then(|e| e.to_str()),
+            Some("test" | "spec")
+        )
+        || stem.ends_with("Test")
+        || stem.ends_with("Tests")
+}
+
+fn is_test_project_dir(name: &str) -> bool {
+    TEST_PROJECT_SUFFIXES.iter().any(|s| name.ends_with(s))
+}
+
+/// Detect the test frameworks of `project` and its per-module test coverage.
+///
+/// Reads `package.json` for the `test` script; everything else comes from
+/// the analyzed files.
+pub fn analyze(project: &Project) -> TestSummary {
+    let relative = |file: &SourceFile| {
+        file.path
+            .strip_prefix(&project.path)
+            .unwrap_or(&file.path)
+            .to_path_buf()
+    };
+    let (tests, sources): (Vec<&SourceFile>, Vec<&SourceFile>) = project
+        .files
+        .iter()
+        .partition(|f| is_test_path(&relative(f)));
+
+    // Modules by source file stem, so tests can be matched by name
+    let mut modules: BTreeMap<String, ModuleTests> = BTreeMap::new();
+    let mut by_stem: HashMap<String, Vec<String>> = HashMap::new();
+    let mut inline_test_files = 0;
+    for file in &sources {
+        let path = relative(file);
+        let module = module_of(&path);
+        let entry = modules
+            .entry(module.clone())
+            .or_insert_with(|| ModuleTests {
+                module: module.clone(),
+                source_files: 0,
+                test_files: 0,
+            });
+        entry.source_files += 1;
+        if has_inline_tests(file) {
+            entry.test_files += 1;
+            inline_test_files += 1;
+        }
+        for stem in subject_stems(&path) {
+            by_stem.entry(stem).or_default().push(module.clone());
+        }
+    }
+
+    let mut locations: BTreeMap<String, usize> = BTreeMap::new();
+    for file in &tests {
+        let path = relative(file);
+        *locations.entry(test_location(&path)).or_default() += 1;
+        if let Some(module) = covered_module(&path, &by_stem, &modules) {
+            if let Some(entry) = modules.get_mut(&module) {
+                entry.test_files += 1;
+            }
+        }
+    }
+    let mut locations: Vec<(String, usize)> = locations.into_iter().collect();
+    locations.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+
+    let mut modules: Vec<ModuleTests> = modules.into_values().collect();
+    modules.sort_by_key(|m| std::cmp::Reverse(m.source_files));
+
+    let frameworks = detect_frameworks(project, &tests);
+    let command = suite_command(project, &frameworks);
+    TestSummary {
+        frameworks,
+        command,
+        test_files: tests.len(),
+        source_files: sources.len(),
+        inline_test_files,
+        locations: locations
+            .into_iter()
+            .take(MAX_LOCATIONS)
+            .map(|(dir, _)| dir)
+            .collect(),
+        modules,
+    }
+}
+
+/// Suggestions for a project without tests, or for its largest untested modules.
+pub fn suggestions(summary: &TestSummary) -> Vec<Suggestion> {
+    let suggestion = |severity, message| Suggestion {
+        severity,
+        category: "testing".to_string(),
+        message,
+        file: None,
+        line: None,
+        range: None,
+        rule_id: None,
+        pattern_id: None,
+    };
+
+    if summary.test_files + summary.inline_test_files == 0 {
+        if summary.source_files < MIN_SOURCE_FILES {
+            return Vec::new();
+        }
+        let hint = summary
+            .command
+            .as_ref()
+            .map(|c| format!("; `{}` has nothing to run", c))
+            .unwrap_or_default();
+        return vec![suggestion(
+            SeverityLevel::Warning,
+            format!(
+                "No tests found among {} source files{}",
+                summary.source_files, hint
+            ),
+        )];
+    }
+
+    summary
+        .untested()
+        .filter(|m| m.source_files >= MIN_MODULE_FILES)
+        .take(MAX_UNTESTED_SUGGESTIONS)
+        .map(|m| {
+            suggestion(
+                SeverityLevel::Info,
+                format!(
+                    "Module `{}` has {} source files and no tests",
+                    m.module, m.source_files
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Directory of a file, `.` for the project root.
+fn module_of(path: &Path) -> String {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => slash_path(dir),
+        _ => ".".to_string(),
+    }
+}
+
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Names a test could use for a source file: its stem, or its directory's
+/// name for `mod.rs`-like files.
+fn subject_stems(path: &Path) -> Vec<String> {
+    let mut stems = Vec::new();
+    if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+        if DIRECTORY_STEMS.contains(&stem) {
+            if let Some(dir) = path
+                .parent()
+                .and_then(|d| d.file_name())
+                .and_then(|d| d.to_str())
+            {
+                stems.push(dir.to_lowercase());
+            }
+        }
+        stems.push(stem.to_lowercase());
+    }
+    stems
+}
+
+/// The name a test file is about: `test_orders` -> `orders`, `OrderServiceTests` -> `orderservice`.
+fn test_subject(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let stem = stem
+        .strip_suffix(".test")
+        .or_else(|| stem.strip_suffix(".spec"))
+        .unwrap_or(stem)
+        .to_lowercase();
+    let stem = stem.strip_prefix("test_").unwrap_or(&stem);
+    let stem = ["_tests", "_test", "tests", "test"]
+        .iter()
+        .find_map(|suffix| stem.strip_suffix(suffix))
+        .unwrap_or(stem);
+    let stem = stem.trim_matches(['_', '-', '.']);
+    (!stem.is_empty()).then(|| stem.to_string())
+}
+
+/// The test's directory without its test components (`tests/api` -> `api`,
+/// `Orders.Tests/Services` -> `Orders/Services`).
+fn subject_dir(path: &Path) -> String {
+    let parts: Vec<&str> = path
+        .parent()
+        .map(|dir| {
+            dir.components()
+                .filter_map(|c| c.as_os_str().to_str())
+                .filter(|name| !TEST_DIRS.contains(name))
+                .map(|name| {
+                    TEST_PROJECT_SUFFIXES
+                        .iter()
+                        .find_map(|s| name.strip_suffix(s))
+                        .unwrap_or(name)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+/// The module a test file covers, if any.
+///
+/// Among source files named like the test, the one whose directory shares
+/// the longest suffix with the test's directory wins.
+fn covered_module(
+    path: &Path,
+    by_stem: &HashMap<String, Vec<String>>,
+    modules: &BTreeMap<String, ModuleTests>,
+) -> Option<String> {
+    let dir = subject_dir(path);
+    if let Some(candidates) = test_subject(path).and_then(|s| by_stem.get(&s)) {
+        let shared = |module: &str| {
+            module
+                .rsplit('/')
+                .zip(dir.rsplit('/'))
+                .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+                .count()
+        };
+        return candidates.iter().max_by_key(|m| shared(m)).cloned();
+    }
+    modules.contains_key(&dir).then_some(dir)
+}
+
+/// Where a test lives: its directory up to the test directory (`tests`,
+/// `src/components/__tests__`), or its own directory for tests next to sources.
+fn test_location(path: &Path) -> String {
+    let mut parts = Vec::new();
+    for name in path
+        .parent()
+        .into_iter()
+        .flat_map(|dir| dir.components())
+        .filter_map(|c| c.as_os_str().to_str())
+    {
+        parts.push(name);
+        if TEST_DIRS.contains(&name) || is_test_project_dir(name) {
+            break;
+        }
+    }
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+/// Rust files with a `mod tests` (or `mod test`) module.
+fn has_inline_tests(file: &SourceFile) -> bool {
+    file.language == "rs"
+        && file
+            .symbols
+            .iter()
+            .any(|s| s.kind == SymbolKind::Module && matches!(s.name.as_str(), "tests" | "test"))
+}
+
+fn detect_frameworks(project: &Project, tests: &[&SourceFile]) -> Vec<TestFramework> {
+    let mut frameworks: Vec<TestFramework> = Vec::new();
+    for signature in SIGNATURES {
+        let builtin = signature.builtin == Some(project.project_type);
+        let by_dependency = project.dependencies.iter().any(|d| {
+            signature
+                .dependencies
+                .iter()
+                .any(|name| d.name.eq_ignore_ascii_case(name))
+        });
+        let by_import = tests.iter().flat_map(|f| &f.imports).any(|import| {
+            signature
+                .imports
+                .iter()
+                .any(|prefix| import.trim_start_matches('\\').starts_with(prefix))
+        });
+        let by_file = signature
+            .files
+            .iter()
+            .any(|f| project.path.join(f).is_file());
+        if !(builtin || by_dependency || by_import || by_file) {
+            continue;
+        }
+        // pytest runs unittest suites too
+        if signature.name == "unittest" && frameworks.iter().any(|f| f.name == "pytest") {
+            continue;
+        }
+        let command = match signature.command {
+            "mvn test" => java_test_command(&project.path),
+            command => command.to_string(),
+        };
+        frameworks.push(TestFramework {
+            name: signature.name.to_string(),
+            command,
+        });
+    }
+    frameworks
+}
+
+/// `mvn test`, or the Gradle equivalent for Gradle builds.
+fn java_test_command(root: &Path) -> String {
+    let gradle = ["build.gradle", "build.gradle.kts"]
+        .iter()
+        .any(|f| root.join(f).is_file());
+    if !gradle || root.join("pom.xml").is_file() {
+        "mvn test".to_string()
+    } else if root.join("gradlew").is_file() {
+        "./gradlew test".to_string()
+    } else {
+        "gradle test".to_string()
+    }
+}
+
+/// The command running the whole suite: the project's own script when it
+/// has one, the first framework's command otherwise.
+fn suite_command(project: &Project, frameworks: &[TestFramework]) -> Option<String> {
+    let first = frameworks.first()?;
+    if project.project_type == ProjectType::Node && has_npm_test_script(&project.path) {
+        return Some("npm test".to_string());
+    }
+    if project.project_type == ProjectType::Php && project.path.join("artisan").is_file() {
+        return Some("php artisan test".to_string());
+    }
+    Some(first.command.clone())
+}
+
+/// Whether `package.json` defines a `test` script other than npm's placeholder.
+fn has_npm_test_script(root: &Path) -> bool {
+    let Ok(content) = fs::read_to_string(root.join("package.json")) else {
+        return false;
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return false;
+    };
+    json["scripts"]["test"]
+        .as_str()
+        .is_some_and(|script| !script.contains("no test specified"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{dependency, source_file};
+    use crate::types::{Dependency, Symbol};
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    fn file(root: &Path, path: &str, imports: &[&str], symbols: Vec<Symbol>) -> SourceFile {
+        SourceFile {
+            symbols,
+            imports: imports.iter().map(|i| i.to_string()).collect(),
+            ..source_file(root.join(path))
+        }
+    }
+
+    fn project(
+        root: &Path,
+        project_type: ProjectType,
+        dependencies: &[&str],
+        files: Vec<SourceFile>,
+    ) -> Project {
+        Project {
+            project_type,
+            dependencies: dependencies
+                .iter()
+                .map(|name| Dependency {
+                    dev_only: true,
+                    ..dependency(name, "1.0.0")
+                })
+                .collect(),
+            files,
+            ..crate::test_support::project(root)
+        }
+    }
+
+    fn module<'a>(summary: &'a TestSummary, name: &str) -> &'a ModuleTests {
+        summary.modules.iter().find(|m| m.module == name).unwrap()
+    }
+
+    #[test]
+    fn test_is_test_path() {
+        assert!(is_test_path(Path::new("tests/api.rs")));
+        assert!(is_test_path(Path::new("src/app.spec.ts")));
+        assert!(is_test_path(Path::new("pkg/server_test.go")));
+        assert!(is_test_path(Path::new("Orders.Tests/OrderServiceTests.cs")));
+        assert!(is_test_path(Path::new(
+            "Orders.UnitTests/Services/Pricing.cs"
+        )));
+        assert!(is_test_path(Path::new("conftest.py")));
+        assert!(!is_test_path(Path::new("src/testing_utils.rs")));
+        assert!(!is_test_path(Path::new("src/contest.rs")));
+        assert!(is_test_path(Path::new("app/test_orders.py")));
+        assert!(!is_test_path(Path::new("src/test_suite.rs")));
+    }
+
+    #[test]
+    fn test_python_tests_map_to_modules() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let files = vec![
+            file(root, "app/orders.py", &[], vec![]),
+            file(root, "app/users.py", &[], vec![]),
+            file(root, "app/billing/invoices.py", &[], vec![]),
+            file(root, "app/billing/taxes.py", &[], vec![]),
+            file(
+                root,
+                "tests/test_orders.py",
+                &["pytest", "app.orders"],
+                vec![],
+            ),
+        ];
+        let summary = analyze(&project(root, ProjectType::Python, &[], files));
+
+        assert_eq!(summary.test_files, 1);
+        assert_eq!(summary.source_files, 4);
+        assert_eq!(summary.locations, vec!["tests"]);
+        assert_eq!(
+            summary.frameworks,
+            vec![TestFramework {
+                name: "pytest".to_string(),
+                command: "pytest".to_string()
+            }]
+        );
+        assert_eq!(summary.command.as_deref(), Some("pytest"));
+        assert_eq!(module(&summary, "app").test_files, 1);
+        assert_eq!(module(&summary, "app/billing").test_files, 0);
+
+        let suggestions = suggestions(&summary);
+        assert_eq!(suggestions.len(), 1);
+        assert!(suggestions[0].message.contains("`app/billing`"));
+    }
+
+    #[test]
+    fn test_rust_inline_tests_and_integration_tests() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let tests_module = Symbol {
+            name: "tests".to_string(),
+            kind: SymbolKind::Module,
+            modifiers: Vec::new(),
+            children: Vec::new(),
+            range: None,
+            signature: None,
+        };
+        let files = vec![
+            file(root, "src/lib.rs", &[], vec![]),
+            file(root, "src/parser/mod.rs", &[], vec![tests_module]),
+            file(root, "src/cache/mod.rs", &[], vec![]),
+            file(root, "tests/test_cache.rs", &[], vec![]),
+        ];
+        let summary = analyze(&project(root, ProjectType::Rust, &[], files));
+
+        assert_eq!(summary.inline_test_files, 1);
+        assert_eq!(summary.command.as_deref(), Some("cargo test"));
+        assert_eq!(module(&summary, "src/parser").test_files, 1);
+        assert_eq!(module(&summary, "src/cache").test_files, 1);
+        assert_eq!(module(&summary, "src").test_files, 0);
+        assert!((summary.ratio() - 2.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_framework_detection_and_commands() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{"scripts": {"test": "vitest run"}}"#,
+        )
+        .unwrap();
+        let files = vec![
+            file(root, "src/cart.ts", &[], vec![]),
+            file(
+                root,
+                "src/__tests__/cart.test.ts",
+                &["vitest", "../cart"],
+                vec![],
+            ),
+        ];
+        let summary = analyze(&project(root, ProjectType::Node, &["vitest"], files));
+        assert_eq!(summary.frameworks.len(), 1);
+        assert_eq!(summary.frameworks[0].name, "Vitest");
+        assert_eq!(summary.command.as_deref(), Some("npm test"));
+        assert_eq!(summary.locations, vec!["src/__tests__"]);
+        assert_eq!(module(&summary, "src").test_files, 1);
+
+        // .NET test projects are found through the usings of their files
+        let files = vec![
+            file(root, "Orders/OrderService.cs", &[], vec![]),
+            file(
+                root,
+                "Orders.Tests/OrderServiceTests.cs",
+                &["Xunit", "Orders"],
+                vec![],
+            ),
+        ];
+        let summary = analyze(&project(root, ProjectType::DotNet, &[], files));
+        assert_eq!(summary.frameworks[0].name, "xUnit");
+        assert_eq!(summary.command.as_deref(), Some("dotnet test"));
+        assert_eq!(module(&summary, "Orders").test_files, 1);
+    }
+
+    #[test]
+    fn test_project_without_tests() {
+        let root = PathBuf::from("/tmp/untested");
+        let files = (0..6)
+            .map(|i| file(&root, &format!("pkg/f{i}.go"), &[], vec![]))
+            .collect();
+        let summary = analyze(&project(&root, ProjectType::Go, &[], files));
+        let suggestions = suggestions(&summary);
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].severity, SeverityLevel::Warning);
+        assert_eq!(
+            suggestions[0].message,
+            "No tests found among 6 source files; `go test ./...` has nothing to run"
+        );
+    }
+}
@@ -20,9 +20,87 @@ pub struct ServerConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalyzerConfig {
     pub target_frameworks: Vec<String>,
+    /// Glob patterns (relative to the project root) excluded from file discovery.
+    /// Patterns without a leading `/` match at any depth.
     pub ignore_patterns: Vec<String>,
+    /// Files larger than this are skipped during discovery (0 = no limit).
     pub max_file_size_mb: usize,
     pub analyze_dependencies: bool,
+    /// Stop discovery after this many matching files (0 = no limit).
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+    /// Maximum directory depth below the project root (0 = no limit).
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+    /// Stop discovery once matching files add up to this many megabytes (0 = no limit).
+    #[serde(default = "default_max_total_size_mb")]
+    pub max_total_size_mb: usize,
+    /// Worker threads for the parallel walker (0 = pick automatically).
+    #[serde(default)]
+    pub scan_threads: usize,
+    /// Read branch, uncommitted changes and churn from the local git clone.
+    #[serde(default = "default_true")]
+    pub git_history: bool,
+    /// Churn window in days for the most frequently changed files.
+    #[serde(default = "default_git_history_days")]
+    pub git_history_days: u32,
+    /// How many of the latest commits count as "recently touched".
+    #[serde(default = "default_git_recent_commits")]
+    pub git_recent_commits: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_git_history_days() -> u32 {
+    90
+}
+
+fn default_git_recent_commits() -> usize {
+    10
+}
+
+fn default_max_files() -> usize {
+    20_000
+}
+
+fn default_max_depth() -> usize {
+    32
+}
+
+fn default_max_total_size_mb() -> usize {
+    512
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        Self {
+            target_frameworks: vec![
+                "net10.0".to_string(),
+                "net9.0".to_string(),
+                "net8.0".to_string(),
+            ],
+            ignore_patterns: vec![
+                "bin/**".to_string(),
+                "obj/**".to_string(),
+                "node_modules/**".to_string(),
+                "target/**".to_string(),
+                "vendor/**".to_string(),
+                "__pycache__/**".to_string(),
+                ".git/**".to_string(),
+            ],
+            max_file_size_mb: 10,
+            analyze_dependencies: true,
+            max_files: default_max_files(),
+            max_depth: default_max_depth(),
+            max_total_size_mb: default_max_total_size_mb(),
+            scan_threads: 0,
+            git_history: true,
+            git_history_days: default_git_history_days(),
+            git_recent_commits: default_git_recent_commits(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +109,10 @@ pub struct TrainingConfig {
     pub auto_extract_patterns: bool,
     pub min_pattern_occurrences: usize,
     pub categories: Vec<String>,
+    /// Build the offline vector index behind semantic and hybrid pattern
+    /// search, cached under `<cache_dir>/semantic/`.
+    #[serde(default = "default_true")]
+    pub semantic_search: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +120,20 @@ pub struct StorageConfig {
     pub base_path: PathBuf,
     pub patterns_file: String,
     pub cache_dir: String,
+    /// Directory (under `base_path`) with the suggestion rule files.
+    #[serde(default = "default_rules_dir")]
+    pub rules_dir: String,
+    /// Directory (under `base_path`) with the offline dependency health dataset.
+    #[serde(default = "default_health_dir")]
+    pub health_dir: String,
+}
+
+fn default_rules_dir() -> String {
+    "rules".to_string()
+}
+
+fn default_health_dir() -> String {
+    "health".to_string()
 }
 
 impl Default for Config {
@@ -62,21 +158,7 @@ impl Default for Config {
                 version: "0.1.0".to_string(),
                 transport: "stdio".to_string(),
             },
-            analyzer: AnalyzerConfig {
-                target_frameworks: vec![
-                    "net10.0".to_string(),
-                    "net9.0".to_string(),
-                    "net8.0".to_string(),
-                ],
-                ignore_patterns: vec![
-                    "bin/**".to_string(),
-                    "obj/**".to_string(),
-                    "node_modules/**".to_string(),
-                    ".git/**".to_string(),
-                ],
-                max_file_size_mb: 10,
-                analyze_dependencies: true,
-            },
+            analyzer: AnalyzerConfig::default(),
             training: TrainingConfig {
                 enabled: true,
                 auto_extract_patterns: true,
@@ -88,11 +170,14 @@ impl Default for Config {
                     "security".to_string(),
                     "blazor-server".to_string(),
                 ],
+                semantic_search: true,
             },
             storage: StorageConfig {
                 base_path,
                 patterns_file: "patterns".to_string(), // Directory name, not file
                 cache_dir: "cache".to_string(),
+                rules_dir: default_rules_dir(),
+                health_dir: default_health_dir(),
             },
         }
     }
@@ -0,0 +1,309 @@
+//! Review context restricted to the files of a diff.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::ContextBuilder;
+use crate::training::{SearchCriteria, SearchMode};
+use crate::types::{
+    ChangeStatus, ChangedFile, CodePattern, DiffAnalysis, LineRange, Project, SeverityLevel,
+    SourceFile, Symbol, TouchedSymbols,
+};
+use anyhow::Result;
+
+/// Files whose change affects the whole project (dependencies, environment).
+const MANIFEST_FILES: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "composer.json",
+    "pyproject.toml",
+    "requirements.txt",
+    "go.mod",
+    "pom.xml",
+    "build.gradle",
+    ".env",
+];
+
+/// Symbols queried for matching patterns.
+const MAX_QUERY_SYMBOLS: usize = 20;
+/// Touched symbols listed per file.
+const MAX_SYMBOLS_PER_FILE: usize = 20;
+
+impl ContextBuilder {
+    /// Build a review analysis for a project restricted to `changed_files`.
+    ///
+    /// `project` is expected to come from `GenericAnalyzer::analyze_changed_files`.
+    /// Patterns are looked up by the touched symbols, and project-wide
+    /// suggestions are only kept when a manifest file changed.
+    pub async fn build_diff_analysis(
+        &self,
+        project: Project,
+        base: &str,
+        changed_files: Vec<ChangedFile>,
+    ) -> Result<DiffAnalysis> {
+        let touched_symbols: Vec<TouchedSymbols> = project
+            .files
+            .iter()
+            .filter_map(|file| {
+                let relative = file.path.strip_prefix(&project.path).unwrap_or(&file.path);
+                let changed = changed_files.iter().find(|c| c.path == relative)?;
+                let symbols = Self::touched_symbol_names(file, &changed.lines);
+                (!symbols.is_empty()).then(|| TouchedSymbols {
+                    path: relative.to_path_buf(),
+                    symbols,
+                })
+            })
+            .collect();
+
+        let framework = self.detect_framework_from_project(&project);
+        let mut analysis = self.build_generic_analysis(project).await?;
+        analysis.patterns = self.patterns_for_symbols(&framework, &touched_symbols);
+        analysis.pattern_matches.clear();
+
+        let manifest_changed = changed_files.iter().any(|c| Self::is_manifest(&c.path));
+        let changed: HashSet<_> = changed_files
+            .iter()
+            .map(|c| analysis.project.path.join(&c.path))
+            .collect();
+        analysis.suggestions.retain(|s| match s.file {
+            Some(ref file) => changed.contains(file),
+            None => manifest_changed,
+        });
+
+        Ok(DiffAnalysis {
+            base: base.to_string(),
+            changed_files,
+            touched_symbols,
+            analysis,
+        })
+    }
+
+    /// Qualified names (`Class.Method`) of the symbols overlapping the changed `hunks`.
+    ///
+    /// Without hunks (added, untracked or renamed files) every symbol counts as
+    /// touched. A type whose members were touched is listed through them only.
+    fn touched_symbol_names(file: &SourceFile, hunks: &[LineRange]) -> Vec<String> {
+        fn collect(
+            symbol: &Symbol,
+            parent: Option<&str>,
+            hunks: &[LineRange],
+            out: &mut Vec<String>,
+        ) {
+            let touched = hunks.is_empty()
+                || symbol
+                    .range
+                    .is_none_or(|r| hunks.iter().any(|h| h.overlaps(&r.lines())));
+            if !touched {
+                return;
+            }
+
+            let name = match parent {
+                Some(parent) => format!("{}.{}", parent, symbol.name),
+                None => symbol.name.clone(),
+            };
+            let before = out.len();
+            for child in &symbol.children {
+                collect(child, Some(&name), hunks, out);
+            }
+            if hunks.is_empty() || out.len() == before {
+                out.push(name);
+            }
+        }
+
+        let mut names = Vec::new();
+        for symbol in &file.symbols {
+            collect(symbol, None, hunks, &mut names);
+        }
+        names.truncate(MAX_SYMBOLS_PER_FILE);
+        names
+    }
+
+    /// Framework patterns mentioning the touched symbols, falling back to the
+    /// top framework patterns when nothing matches.
+    fn patterns_for_symbols(
+        &self,
+        framework: &str,
+        touched: &[TouchedSymbols],
+    ) -> Vec<CodePattern> {
+        let Some(ref manager) = self.training_manager else {
+            return vec![];
+        };
+
+        let mut patterns: Vec<CodePattern> = Vec::new();
+        let mut names: Vec<&str> = Vec::new();
+        for name in touched.iter().flat_map(|t| &t.symbols) {
+            // `Class.Method` -> `Method`: patterns talk about members, not our classes
+            let name = name.rsplit('.').next().unwrap_or(name);
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        for name in names.into_iter().take(MAX_QUERY_SYMBOLS) {
+            let criteria = SearchCriteria {
+                query: Some(name.to_string()),
+                category: None,
+                framework: Some(framework.to_string()),
+                tags: vec![],
+                // Non-matching patterns are penalized well below this
+                min_score: 0.6,
+                max_results: Some(3),
+                mode: SearchMode::Keyword,
+            };
+            for (pattern, _) in manager.search_patterns(&criteria) {
+                if !patterns.iter().any(|p| p.id == pattern.id) {
+                    patterns.push(pattern.clone());
+                }
+            }
+        }
+
+        if patterns.is_empty() {
+            let criteria = SearchCriteria {
+                query: None,
+                category: None,
+                framework: Some(framework.to_string()),
+                tags: vec![],
+                min_score: 0.7,
+                max_results: Some(3),
+                mode: SearchMode::Keyword,
+            };
+            patterns = manager
+                .search_patterns(&criteria)
+                .into_iter()
+                .map(|(p, _)| p.clone())
+                .collect();
+        }
+
+        patterns.truncate(5);
+        patterns
+    }
+
+    fn is_manifest(path: &Path) -> bool {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        MANIFEST_FILES.contains(&name) || name.ends_with(".csproj")
+    }
+
+    /// Build markdown review context for a diff analysis.
+    pub fn build_diff_context_string(&self, diff: &DiffAnalysis) -> String {
+        let analysis = &diff.analysis;
+        let project = &analysis.project;
+        let mut context = String::new();
+
+        context.push_str(&format!(
+            "# Diff Review: {} (vs {})\n\n",
+            project.name, diff.base
+        ));
+
+        if diff.changed_files.is_empty() {
+            context.push_str("No changes found.\n");
+            return context;
+        }
+
+        context.push_str(&format!(
+            "## Changed Files ({})\n\n",
+            diff.changed_files.len()
+        ));
+        for file in &diff.changed_files {
+            let lines: usize = file.lines.iter().map(|r| r.end - r.start + 1).sum();
+            if lines > 0 {
+                context.push_str(&format!(
+                    "- [{}] {} ({} lines in {} hunks)\n",
+                    file.status.as_code(),
+                    file.path.display(),
+                    lines,
+                    file.lines.len()
+                ));
+            } else {
+                context.push_str(&format!(
+                    "- [{}] {}\n",
+                    file.status.as_code(),
+                    file.path.display()
+                ));
+            }
+        }
+        context.push('\n');
+
+        if !diff.touched_symbols.is_empty() {
+            context.push_str("## Touched Symbols\n\n");
+            for touched in &diff.touched_symbols {
+                context.push_str(&format!("### {}\n", touched.path.display()));
+                for symbol in &touched.symbols {
+                    context.push_str(&format!("- {}\n", symbol));
+                }
+            }
+            context.push('\n');
+        }
+
+        if !analysis.patterns.is_empty() {
+            context.push_str("## Relevant Patterns\n\n");
+            for pattern in &analysis.patterns {
+                context.push_str(&format!("### {}\n", pattern.title));
+                context.push_str(&format!("{}\n\n", pattern.description));
+                context.push_str("```\n");
+                context.push_str(&pattern.code);
+                context.push_str("\n```\n\n");
+            }
+        }
+
+        if !analysis.suggestions.is_empty() {
+            context.push_str("## Suggestions\n\n");
+            for suggestion in &analysis.suggestions {
+                let icon = match suggestion.severity {
+                    SeverityLevel::Error => "❌",
+                    SeverityLevel::Warning => "⚠️",
+                    SeverityLevel::Info => "ℹ️",
+                };
+                let location = suggestion
+                    .location(&project.path)
+                    .map(|l| format!(" (`{}`)", l))
+                    .unwrap_or_default();
+                context.push_str(&format!(
+                    "{} **{}**: {}{}\n",
+                    icon, suggestion.category, suggestion.message, location
+                ));
+            }
+        }
+
+        context
+    }
+
+    /// Compact single-line summary of a diff analysis for Endless Mode.
+    pub fn build_compact_diff_string(&self, diff: &DiffAnalysis) -> String {
+        let count = |status: ChangeStatus| {
+            diff.changed_files
+                .iter()
+                .filter(|f| f.status == status)
+                .count()
+        };
+        let symbols: usize = diff.touched_symbols.iter().map(|t| t.symbols.len()).sum();
+        let patterns = diff
+            .analysis
+            .patterns
+            .iter()
+            .take(3)
+            .map(|p| p.title.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            "[DIFF:{} vs {}] files:{}(+{}~{}-{}r{}) symbols:{} | patterns:[{}] suggestions:{}",
+            diff.analysis.project.name,
+            diff.base,
+            diff.changed_files.len(),
+            count(ChangeStatus::Added),
+            count(ChangeStatus::Modified),
+            count(ChangeStatus::Deleted),
+            count(ChangeStatus::Renamed),
+            symbols,
+            if patterns.is_empty() {
+                "none"
+            } else {
+                &patterns
+            },
+            diff.analysis.suggestions.len()
+        )
+    }
+}
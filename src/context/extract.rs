@@ -0,0 +1,246 @@
+//! Pattern candidates from the idioms recurring in a project.
+
+use chrono::Utc;
+
+use super::ContextBuilder;
+use crate::analyzer::idioms::{Idiom, IdiomMiner};
+use crate::types::{CodePattern, PatternCandidate, Project};
+use crate::utils::{hash_string, truncate_string};
+
+/// Locations kept per candidate.
+const MAX_LOCATIONS: usize = 5;
+
+/// Characters of the template shown in a candidate's title.
+const TITLE_LENGTH: usize = 80;
+
+/// Categories guessed from the text of an idiom, first match wins.
+const CATEGORY_KEYWORDS: &[(&str, &[&str])] = &[
+    (
+        "dependency-injection",
+        &[
+            "addscoped",
+            "addsingleton",
+            "addtransient",
+            "services.add",
+            "[inject]",
+            "provide(",
+        ],
+    ),
+    (
+        "lifecycle",
+        &[
+            "oninitialized",
+            "onparametersset",
+            "onafterrender",
+            "componentdidmount",
+            "useeffect",
+            "onmounted",
+            "ngoninit",
+            "dispose",
+        ],
+    ),
+    (
+        "error-handling",
+        &["catch", "except", "map_err", "err != nil", "unwrap_or"],
+    ),
+    (
+        "testing",
+        &[
+            "assert",
+            "#[test]",
+            "[fact]",
+            "[test]",
+            "expect(",
+            "describe(",
+        ],
+    ),
+    ("async", &["await", "async "]),
+];
+
+impl ContextBuilder {
+    /// Propose the idioms occurring at least `min_occurrences` times in
+    /// `project` as patterns for its framework.
+    ///
+    /// Candidate IDs derive from the framework and template, so the same
+    /// idiom found again keeps its ID.
+    pub fn extract_pattern_candidates(
+        &self,
+        project: &Project,
+        min_occurrences: usize,
+    ) -> Vec<PatternCandidate> {
+        let framework = self.detect_framework_from_project(project);
+        let version = project
+            .metadata
+            .target_framework
+            .clone()
+            .unwrap_or_default();
+        IdiomMiner::new()
+            .mine(project, min_occurrences)
+            .into_iter()
+            .map(|idiom| to_candidate(idiom, &framework, &version, &project.name))
+            .collect()
+    }
+}
+
+fn to_candidate(idiom: Idiom, framework: &str, version: &str, project: &str) -> PatternCandidate {
+    let category = guess_category(&idiom);
+    let locations: Vec<String> = idiom
+        .occurrences
+        .iter()
+        .take(MAX_LOCATIONS)
+        .map(|o| format!("{}:{}", o.path.display(), o.line))
+        .collect();
+    // The whole template, since a first line alone can stop mid-call
+    let summary = one_line(&idiom.template);
+    let now = Utc::now();
+
+    let pattern = CodePattern {
+        id: format!(
+            "extracted-{}",
+            &hash_string(&format!("{}\n{}", framework, idiom.template))[..12]
+        ),
+        category: category.to_string(),
+        framework: framework.to_string(),
+        version: version.to_string(),
+        title: format!(
+            "Recurring {}: {}",
+            kind_label(&idiom.kind),
+            truncate_string(&summary, TITLE_LENGTH)
+        ),
+        description: format!(
+            "Found {} times in {} (.{} files). Parts marked $1, $2, ... in the template differ between occurrences.",
+            idiom.occurrences.len(),
+            project,
+            idiom.language
+        ),
+        code: idiom.example,
+        tags: vec![
+            "extracted".to_string(),
+            category.to_string(),
+            idiom.language.clone(),
+        ],
+        usage_count: 0,
+        relevance_score: 0.5,
+        created_at: now,
+        updated_at: now,
+        deprecated: None,
+    };
+
+    PatternCandidate {
+        pattern,
+        template: idiom.template,
+        occurrences: idiom.occurrences.len(),
+        locations,
+        project: project.to_string(),
+        extracted_at: now,
+    }
+}
+
+/// `code` on one line; method chain continuations are joined without a space.
+fn one_line(code: &str) -> String {
+    let mut out = String::new();
+    for line in code.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if !out.is_empty() && !line.starts_with('.') {
+            out.push(' ');
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+/// What a syntax node kind is called in a title.
+fn kind_label(kind: &str) -> &str {
+    match kind {
+        "method_declaration" | "method_definition" => "method",
+        "constructor_declaration" => "constructor",
+        "function_item" | "function_declaration" | "function_definition" => "function",
+        "expression_statement" => "statement",
+        "local_declaration_statement" | "let_declaration" => "declaration",
+        "try_statement" => "try/catch block",
+        "using_statement" => "using block",
+        "if_statement" => "conditional",
+        "match_expression" => "match",
+        "with_statement" => "with block",
+        other => other,
+    }
+}
+
+fn guess_category(idiom: &Idiom) -> &'static str {
+    let text = idiom.template.to_lowercase();
+    CATEGORY_KEYWORDS
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|k| text.contains(k)))
+        .map_or("idioms", |(category, _)| category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::source_file;
+    use crate::types::{ProjectMetadata, ProjectType, SourceFile};
+    use std::fs;
+
+    #[test]
+    fn test_candidates_from_registrations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Program.cs");
+        let source = "var builder = WebApplication.CreateBuilder(args);\n\
+            builder.Services.AddScoped<IOrderService, OrderService>();\n\
+            builder.Services.AddScoped<ICartService, CartService>();\n\
+            builder.Services.AddScoped<IUserService, UserService>();\n";
+        fs::write(&path, source).unwrap();
+        let project = Project {
+            name: "Shop".to_string(),
+            project_type: ProjectType::DotNet,
+            files: vec![SourceFile {
+                size_bytes: source.len() as u64,
+                ..source_file(path)
+            }],
+            metadata: ProjectMetadata {
+                target_framework: Some("net8.0".to_string()),
+                ..Default::default()
+            },
+            ..crate::test_support::project(dir.path())
+        };
+
+        let builder = ContextBuilder::new();
+        let candidates = builder.extract_pattern_candidates(&project, 3);
+        assert_eq!(candidates.len(), 1);
+        let candidate = &candidates[0];
+        assert!(candidate.pattern.id.starts_with("extracted-"));
+        assert_eq!(candidate.pattern.category, "dependency-injection");
+        assert_eq!(candidate.pattern.version, "net8.0");
+        assert_eq!(
+            candidate.pattern.title,
+            "Recurring statement: builder.Services.AddScoped<$1, $2>();"
+        );
+        assert_eq!(candidate.occurrences, 3);
+        assert_eq!(
+            candidate.locations,
+            vec!["Program.cs:2", "Program.cs:3", "Program.cs:4"]
+        );
+
+        // The same idiom found again keeps its ID
+        let again = builder.extract_pattern_candidates(&project, 3);
+        assert_eq!(again[0].pattern.id, candidate.pattern.id);
+        assert!(builder.extract_pattern_candidates(&project, 4).is_empty());
+    }
+
+    #[test]
+    fn test_title_covers_the_whole_template() {
+        let idiom = Idiom {
+            kind: "let_declaration".to_string(),
+            language: "rs".to_string(),
+            template:
+                "let $1: String = self\n    .observations\n    .save($2, &output)\n    .await?;"
+                    .to_string(),
+            example: String::new(),
+            occurrences: vec![],
+        };
+        let candidate = to_candidate(idiom, "rust", "", "app");
+        assert_eq!(
+            candidate.pattern.title,
+            "Recurring declaration: let $1: String = self.observations.save($2, &output).await?;"
+        );
+    }
+}
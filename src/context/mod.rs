@@ -1,7 +1,16 @@
-use crate::training::{SearchCriteria, TrainingManager};
+mod diff;
+mod extract;
+mod ranking;
+
+use crate::analyzer::frameworks::FrameworkCatalog;
+use crate::analyzer::module_graph::ModuleGraph;
+use crate::analyzer::test_suite;
+use crate::health::DependencyHealth;
+use crate::rules::RuleEngine;
+use crate::training::{SearchCriteria, SearchMode, TrainingManager};
 use crate::types::{
-    AnalysisResult, CodePattern, DotNetProject, Project, ProjectType, SeverityLevel, Statistics,
-    Suggestion,
+    AnalysisResult, CodePattern, DotNetProject, FileHotspot, PatternMatch, Project, ProjectType,
+    SeverityLevel, Statistics, Suggestion, Symbol,
 };
 use anyhow::Result;
 
@@ -9,6 +18,9 @@ use anyhow::Result;
 #[derive(Default)]
 pub struct ContextBuilder {
     training_manager: Option<TrainingManager>,
+    rules: RuleEngine,
+    health: DependencyHealth,
+    frameworks: FrameworkCatalog,
 }
 
 impl ContextBuilder {
@@ -22,27 +34,46 @@ impl ContextBuilder {
         self
     }
 
+    /// Set the rules used for project suggestions (built-in rules by default)
+    pub fn with_rules(mut self, rules: RuleEngine) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Set the dataset for dependency health checks (built-in dataset by default)
+    pub fn with_health(mut self, health: DependencyHealth) -> Self {
+        self.health = health;
+        self
+    }
+
     /// Build complete analysis with patterns and suggestions (generic version)
     pub async fn build_generic_analysis(&self, project: Project) -> Result<AnalysisResult> {
         // Detect framework type
         let framework_type = self.detect_framework_from_project(&project);
 
-        // Get relevant patterns
-        let patterns = if let Some(ref manager) = self.training_manager {
-            self.get_patterns_for_project(manager, &framework_type, &project)?
-        } else {
-            vec![]
-        };
-
         // Generate suggestions
-        let suggestions = self.generate_project_suggestions(&project, &framework_type);
+        let tests = test_suite::analyze(&project);
+        let mut suggestions = self.generate_project_suggestions(&project, &framework_type);
+        suggestions.extend(test_suite::suggestions(&tests));
+
+        // Get relevant patterns, ranked by what the project uses
+        let (patterns, pattern_matches) = match self.training_manager {
+            Some(ref manager) => {
+                Self::get_patterns_for_project(manager, &framework_type, &project, &suggestions)
+            }
+            None => (vec![], vec![]),
+        };
 
         // Collect statistics
-        let statistics = Statistics {
+        let mut statistics = Statistics {
             total_files: project.files.len(),
-            total_classes: project.files.iter().map(|f| f.symbols.len()).sum(),
-            total_methods: 0, // Would need symbol parsing
-            total_lines: 0,
+            total_classes: project
+                .files
+                .iter()
+                .map(|f| Self::count_types(&f.symbols))
+                .sum(),
+            total_methods: project.files.iter().map(|f| f.metrics.functions).sum(),
+            total_lines: project.files.iter().map(|f| f.metrics.total_lines()).sum(),
             framework_version: project
                 .metadata
                 .target_framework
@@ -51,266 +82,156 @@ impl ContextBuilder {
                 .or(project.metadata.node_version.clone())
                 .unwrap_or_else(|| "unknown".to_string()),
             package_count: project.dependencies.len(),
+            ..Default::default()
         };
+        Self::collect_code_metrics(&project, &mut statistics);
+
+        let module_graph = ModuleGraph::build(&project).summary();
 
         Ok(AnalysisResult {
             project,
             patterns,
+            pattern_matches,
             suggestions,
             statistics,
+            module_graph,
+            tests,
         })
     }
 
+    /// Type declarations in a symbol tree, nested types included
+    fn count_types(symbols: &[Symbol]) -> usize {
+        symbols
+            .iter()
+            .map(|s| usize::from(s.kind.is_type()) + Self::count_types(&s.children))
+            .sum()
+    }
+
+    /// Aggregate per-file metrics into line totals, per-language stats and hotspots
+    fn collect_code_metrics(project: &Project, statistics: &mut Statistics) {
+        const HOTSPOT_LIMIT: usize = 5;
+
+        for file in &project.files {
+            let m = &file.metrics;
+            statistics.code_lines += m.code_lines;
+            statistics.comment_lines += m.comment_lines;
+            statistics.blank_lines += m.blank_lines;
+
+            let lang = statistics
+                .by_language
+                .entry(file.language.clone())
+                .or_default();
+            lang.files += 1;
+            lang.code_lines += m.code_lines;
+            lang.comment_lines += m.comment_lines;
+            lang.blank_lines += m.blank_lines;
+            lang.functions += m.functions;
+            lang.cyclomatic_complexity += m.cyclomatic_complexity;
+        }
+
+        let hotspot = |file: &crate::types::SourceFile| FileHotspot {
+            path: file
+                .path
+                .strip_prefix(&project.path)
+                .unwrap_or(&file.path)
+                .to_path_buf(),
+            lines: file.metrics.total_lines(),
+            cyclomatic_complexity: file.metrics.cyclomatic_complexity,
+            max_function_complexity: file.metrics.max_function_complexity,
+            max_nesting_depth: file.metrics.max_nesting_depth,
+        };
+
+        let mut by_size: Vec<_> = project
+            .files
+            .iter()
+            .filter(|f| f.metrics.total_lines() > 0)
+            .collect();
+        by_size.sort_by(|a, b| {
+            b.metrics
+                .total_lines()
+                .cmp(&a.metrics.total_lines())
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        statistics.largest_files = by_size
+            .into_iter()
+            .take(HOTSPOT_LIMIT)
+            .map(hotspot)
+            .collect();
+
+        let mut by_complexity: Vec<_> = project
+            .files
+            .iter()
+            .filter(|f| f.metrics.cyclomatic_complexity > 0)
+            .collect();
+        by_complexity.sort_by(|a, b| {
+            b.metrics
+                .cyclomatic_complexity
+                .cmp(&a.metrics.cyclomatic_complexity)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        statistics.most_complex_files = by_complexity
+            .into_iter()
+            .take(HOTSPOT_LIMIT)
+            .map(hotspot)
+            .collect();
+    }
+
     /// Detect framework from generic project
-    fn detect_framework_from_project(&self, project: &Project) -> String {
-        match project.project_type {
-            ProjectType::DotNet => {
-                // Check for Blazor, ASP.NET, etc.
-                if project
-                    .dependencies
-                    .iter()
-                    .any(|d| d.name.contains("AspNetCore.Components"))
-                {
-                    "blazor-server".to_string()
-                } else if project
-                    .dependencies
-                    .iter()
-                    .any(|d| d.name.contains("AspNetCore"))
-                {
-                    "aspnet-core".to_string()
-                } else {
-                    "dotnet".to_string()
-                }
-            }
-            ProjectType::Rust => {
-                if project.dependencies.iter().any(|d| d.name == "actix-web") {
-                    "actix-web".to_string()
-                } else if project.dependencies.iter().any(|d| d.name == "axum") {
-                    "axum".to_string()
-                } else if project.dependencies.iter().any(|d| d.name == "tokio") {
-                    "tokio".to_string()
-                } else {
-                    "rust".to_string()
-                }
-            }
-            ProjectType::Node => {
-                if let Some(fw) = project.metadata.extra.get("framework") {
-                    fw.clone()
-                } else if project.dependencies.iter().any(|d| d.name == "express") {
-                    "express".to_string()
-                } else if project.dependencies.iter().any(|d| d.name == "react") {
-                    "react".to_string()
-                } else if project.dependencies.iter().any(|d| d.name == "vue") {
-                    "vue".to_string()
-                } else if project.dependencies.iter().any(|d| d.name == "next") {
-                    "nextjs".to_string()
-                } else {
-                    "node".to_string()
-                }
-            }
-            ProjectType::Python => {
-                if project.dependencies.iter().any(|d| d.name == "django") {
-                    "django".to_string()
-                } else if project.dependencies.iter().any(|d| d.name == "flask") {
-                    "flask".to_string()
-                } else if project.dependencies.iter().any(|d| d.name == "fastapi") {
-                    "fastapi".to_string()
-                } else {
-                    "python".to_string()
-                }
-            }
-            ProjectType::Go => {
-                if project
-                    .dependencies
-                    .iter()
-                    .any(|d| d.name.contains("gin-gonic"))
-                {
-                    "gin".to_string()
-                } else if project
-                    .dependencies
-                    .iter()
-                    .any(|d| d.name.contains("fiber"))
-                {
-                    "fiber".to_string()
-                } else {
-                    "go".to_string()
-                }
-            }
-            ProjectType::Java => {
-                if project
-                    .dependencies
-                    .iter()
-                    .any(|d| d.name.contains("spring"))
-                {
-                    "spring".to_string()
-                } else {
-                    "java".to_string()
-                }
-            }
-            ProjectType::Php => {
-                // Use pre-detected framework from metadata
-                if let Some(fw) = project.metadata.extra.get("framework") {
-                    let frontend = project.metadata.extra.get("frontend");
-                    match (fw.as_str(), frontend) {
-                        ("laravel", Some(fe)) => format!("laravel-{}", fe),
-                        _ => fw.clone(),
-                    }
-                } else {
-                    "php".to_string()
-                }
-            }
-            ProjectType::Unknown => "generic".to_string(),
+    ///
+    /// Uses the candidates ranked during analysis, or ranks them now for
+    /// projects built without them. A Laravel project with a JavaScript
+    /// frontend maps to `laravel-vue` / `laravel-react`.
+    pub fn detect_framework_from_project(&self, project: &Project) -> String {
+        let primary = match project.metadata.frameworks.first() {
+            Some(candidate) => Some(candidate.name.clone()),
+            None => self
+                .frameworks
+                .detect(project.project_type, &project.dependencies, &project.path)
+                .into_iter()
+                .next()
+                .map(|c| c.name),
+        };
+        match (primary, project.metadata.extra.get("frontend")) {
+            (Some(fw), Some(frontend)) if fw == "laravel" => format!("laravel-{}", frontend),
+            (Some(fw), _) => fw,
+            (None, _) => match project.project_type {
+                ProjectType::Unknown => "generic".to_string(),
+                other => other.as_str().to_string(),
+            },
         }
     }
 
-    /// Get patterns relevant to the project
+    /// Get patterns relevant to the project, with why each was picked
+    ///
+    /// Ranks the framework's patterns, language-level patterns for the
+    /// project's file types and patterns the suggestions point to by the
+    /// dependencies, symbols, file types and suggestion categories they match.
     fn get_patterns_for_project(
-        &self,
         manager: &TrainingManager,
         framework: &str,
-        _project: &Project,
-    ) -> Result<Vec<CodePattern>> {
-        let criteria = SearchCriteria {
-            query: None,
-            category: None,
-            framework: Some(framework.to_string()),
-            tags: vec![],
-            min_score: 0.7,
-            max_results: Some(10),
-        };
-
-        let results = manager.search_patterns(&criteria);
-        Ok(results.into_iter().map(|(p, _)| p.clone()).collect())
+        project: &Project,
+        suggestions: &[Suggestion],
+    ) -> (Vec<CodePattern>, Vec<PatternMatch>) {
+        ranking::rank_patterns(
+            manager.get_all_patterns(),
+            framework,
+            project,
+            suggestions,
+            10,
+        )
+        .into_iter()
+        .map(|(pattern, why)| (pattern.clone(), why))
+        .unzip()
     }
 
     /// Generate suggestions for generic project
     fn generate_project_suggestions(&self, project: &Project, framework: &str) -> Vec<Suggestion> {
-        let mut suggestions = Vec::new();
-
-        // Project type specific suggestions
-        match project.project_type {
-            ProjectType::Node
-                if project
-                    .dependencies
-                    .iter()
-                    .any(|d| d.name == "express" && d.version.starts_with("3.")) =>
-            {
-                suggestions.push(Suggestion {
-                    severity: SeverityLevel::Warning,
-                    category: "security".to_string(),
-                    message: "Express 3.x is outdated. Consider upgrading to Express 4.x or 5.x"
-                        .to_string(),
-                    file: None,
-                    line: None,
-                });
-            }
-            ProjectType::Python
-                if !project.path.join("requirements.txt").exists()
-                    && !project.path.join("pyproject.toml").exists() =>
-            {
-                suggestions.push(Suggestion {
-                    severity: SeverityLevel::Info,
-                    category: "best-practices".to_string(),
-                    message: "Consider adding a requirements.txt or pyproject.toml for dependency management".to_string(),
-                    file: None,
-                    line: None,
-                });
-            }
-            ProjectType::Rust if project.dependencies.iter().any(|d| d.name == "unwrap") => {
-                suggestions.push(Suggestion {
-                    severity: SeverityLevel::Warning,
-                    category: "error-handling".to_string(),
-                    message: "Avoid using .unwrap() in production code. Use proper error handling with Result".to_string(),
-                    file: None,
-                    line: None,
-                });
-            }
-            ProjectType::Php => {
-                // Laravel specific suggestions
-                if let Some(fw) = project.metadata.extra.get("framework") {
-                    if fw == "laravel" {
-                        // Check for .env file
-                        if !project.path.join(".env").exists() {
-                            suggestions.push(Suggestion {
-                                severity: SeverityLevel::Warning,
-                                category: "configuration".to_string(),
-                                message: "Missing .env file. Copy .env.example to .env and configure your environment".to_string(),
-                                file: None,
-                                line: None,
-                            });
-                        }
-
-                        // Check for outdated Laravel
-                        if project
-                            .dependencies
-                            .iter()
-                            .any(|d| d.name == "laravel/framework" && d.version.starts_with("^8"))
-                        {
-                            suggestions.push(Suggestion {
-                                severity: SeverityLevel::Info,
-                                category: "upgrade".to_string(),
-                                message: "Laravel 8.x is in maintenance mode. Consider upgrading to Laravel 10 or 11".to_string(),
-                                file: None,
-                                line: None,
-                            });
-                        }
-                    }
-                }
-
-                // Vue.js with PHP suggestions
-                if let Some(frontend) = project.metadata.extra.get("frontend") {
-                    if frontend == "vue" {
-                        // Check for Inertia.js (common in Laravel + Vue)
-                        if project
-                            .dependencies
-                            .iter()
-                            .any(|d| d.name == "inertiajs/inertia-laravel")
-                        {
-                            suggestions.push(Suggestion {
-                                severity: SeverityLevel::Info,
-                                category: "architecture".to_string(),
-                                message: "Inertia.js detected. Consider using shared data for common props across pages".to_string(),
-                                file: None,
-                                line: None,
-                            });
-                        }
-                    }
-                }
-
-                // Security: Check for common security packages
-                let has_security_package = project.dependencies.iter().any(|d| {
-                    d.name == "paragonie/random_compat" || d.name == "defuse/php-encryption"
-                });
-                if !has_security_package && project.dependencies.len() > 5 {
-                    suggestions.push(Suggestion {
-                        severity: SeverityLevel::Info,
-                        category: "security".to_string(),
-                        message: "Consider adding security packages like paragonie/random_compat for cryptographic operations".to_string(),
-                        file: None,
-                        line: None,
-                    });
-                }
-            }
-            _ => {}
-        }
-
-        // Generic suggestion based on file count
-        if project.files.len() > 100 {
-            suggestions.push(Suggestion {
-                severity: SeverityLevel::Info,
-                category: "architecture".to_string(),
-                message: format!(
-                    "Large project with {} files. Consider modular organization.",
-                    project.files.len()
-                ),
-                file: None,
-                line: None,
-            });
-        }
+        let mut suggestions = self.rules.evaluate(project, framework);
+        suggestions.extend(self.health.check(project));
 
         // Framework-specific pattern availability
         if let Some(ref manager) = self.training_manager {
-            let patterns = manager.search_by_framework_and_category(framework, "");
+            let patterns = manager.search_by_framework(framework);
             if patterns.is_empty() {
                 suggestions.push(Suggestion {
                     severity: SeverityLevel::Info,
@@ -321,6 +242,9 @@ impl ContextBuilder {
                     ),
                     file: None,
                     line: None,
+                    range: None,
+                    rule_id: None,
+                    pattern_id: None,
                 });
             }
         }
@@ -342,6 +266,22 @@ impl ContextBuilder {
             context.push_str(&format!("**Version:** {}\n", version));
         }
         context.push_str(&format!("**Type:** {}\n", project.project_type.as_str()));
+        if !project.metadata.frameworks.is_empty() {
+            let frameworks: Vec<String> = project
+                .metadata
+                .frameworks
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{} ({:.2}: {})",
+                        c.name,
+                        c.confidence,
+                        c.evidence.join(", ")
+                    )
+                })
+                .collect();
+            context.push_str(&format!("**Frameworks:** {}\n", frameworks.join("; ")));
+        }
 
         // Metadata
         if let Some(ref tf) = project.metadata.target_framework {
@@ -399,31 +339,396 @@ impl ContextBuilder {
             "- Dependencies: {}\n",
             analysis.statistics.package_count
         ));
+        if analysis.statistics.total_lines > 0 {
+            context.push_str(&format!(
+                "- Lines: {} ({} code, {} comment, {} blank)\n",
+                analysis.statistics.total_lines,
+                analysis.statistics.code_lines,
+                analysis.statistics.comment_lines,
+                analysis.statistics.blank_lines
+            ));
+            context.push_str(&format!(
+                "- Functions: {}\n",
+                analysis.statistics.total_methods
+            ));
+        }
+        if let Some(limit) = project.scan.truncated {
+            context.push_str(&format!(
+                "- ⚠️ Analysis truncated: file discovery stopped at the {} after {} files. Results are partial.\n",
+                limit, project.scan.files_scanned
+            ));
+        }
         context.push('\n');
 
         // File breakdown by extension
-        let mut ext_counts: std::collections::HashMap<&str, usize> =
-            std::collections::HashMap::new();
-        for file in &project.files {
-            *ext_counts.entry(&file.language).or_insert(0) += 1;
-        }
-        if !ext_counts.is_empty() {
+        if !analysis.statistics.by_language.is_empty() {
             context.push_str("### Files by Type\n");
-            for (ext, count) in ext_counts {
-                context.push_str(&format!("- .{}: {} files\n", ext, count));
+            for (ext, lang) in &analysis.statistics.by_language {
+                context.push_str(&format!(
+                    "- .{}: {} files, {} code lines, {} comment lines, {} functions\n",
+                    ext, lang.files, lang.code_lines, lang.comment_lines, lang.functions
+                ));
             }
             context.push('\n');
         }
 
+        // Largest and most complex files
+        let stats = &analysis.statistics;
+        if !stats.largest_files.is_empty() || !stats.most_complex_files.is_empty() {
+            context.push_str("## Hotspots\n\n");
+            if !stats.largest_files.is_empty() {
+                context.push_str("### Largest Files\n");
+                for file in &stats.largest_files {
+                    context.push_str(&format!(
+                        "- {}: {} lines\n",
+                        file.path.display(),
+                        file.lines
+                    ));
+                }
+            }
+            if !stats.most_complex_files.is_empty() {
+                context.push_str("### Most Complex Files\n");
+                for file in &stats.most_complex_files {
+                    context.push_str(&format!(
+                        "- {}: complexity {} (max per function {}, nesting {})\n",
+                        file.path.display(),
+                        file.cyclomatic_complexity,
+                        file.max_function_complexity,
+                        file.max_nesting_depth
+                    ));
+                }
+            }
+            context.push('\n');
+        }
+
+        // Internal architecture: which modules import which
+        let graph = &analysis.module_graph;
+        if graph.edges > 0 {
+            context.push_str("## Module Dependencies\n\n");
+            context.push_str(&format!(
+                "- Modules: {} ({} internal imports)\n",
+                graph.modules, graph.edges
+            ));
+            context.push_str(&format!("- Import cycles: {}\n", graph.cycle_count));
+            context.push_str(&format!(
+                "- Layering violations: {}\n\n",
+                graph.violation_count
+            ));
+
+            if !graph.most_depended.is_empty() {
+                context.push_str("### Most Depended-On\n");
+                for module in graph.most_depended.iter().take(5) {
+                    context.push_str(&format!(
+                        "- {}: {} dependents\n",
+                        module.module, module.dependents
+                    ));
+                }
+            }
+            if !graph.cycles.is_empty() {
+                context.push_str("### Cycles\n");
+                for cycle in graph.cycles.iter().take(5) {
+                    context.push_str(&format!("- {}\n", cycle.join(" ↔ ")));
+                }
+            }
+            if !graph.layer_violations.is_empty() {
+                context.push_str("### Layering Violations\n");
+                for violation in graph.layer_violations.iter().take(5) {
+                    context.push_str(&format!(
+                        "- {} ({}) → {} ({})\n",
+                        violation.from, violation.from_layer, violation.to, violation.to_layer
+                    ));
+                }
+            }
+            context.push('\n');
+        }
+
+        // Test frameworks, where tests live and untested modules
+        let tests = &analysis.tests;
+        if !tests.frameworks.is_empty() || tests.test_files + tests.inline_test_files > 0 {
+            context.push_str("## Tests\n\n");
+            if !tests.frameworks.is_empty() {
+                let frameworks: Vec<String> = tests
+                    .frameworks
+                    .iter()
+                    .map(|f| format!("{} (`{}`)", f.name, f.command))
+                    .collect();
+                context.push_str(&format!("- Frameworks: {}\n", frameworks.join(", ")));
+            }
+            if let Some(ref command) = tests.command {
+                context.push_str(&format!("- Run: `{}`\n", command));
+            }
+            context.push_str(&format!(
+                "- Test files: {}{} for {} source files (ratio {:.2})\n",
+                tests.test_files,
+                if tests.inline_test_files > 0 {
+                    format!(" + {} with inline tests", tests.inline_test_files)
+                } else {
+                    String::new()
+                },
+                tests.source_files,
+                tests.ratio()
+            ));
+            if !tests.locations.is_empty() {
+                context.push_str(&format!("- Locations: {}\n", tests.locations.join(", ")));
+            }
+            context.push('\n');
+
+            if tests.modules.len() > 1 {
+                context.push_str("### By Module\n");
+                for module in tests.modules.iter().take(10) {
+                    context.push_str(&format!(
+                        "- {}: {} source, {} test files (ratio {:.2})\n",
+                        module.module,
+                        module.source_files,
+                        module.test_files,
+                        module.ratio()
+                    ));
+                }
+                context.push('\n');
+            }
+
+            let untested: Vec<_> = tests.untested().collect();
+            if !untested.is_empty() {
+                context.push_str("### Untested Modules\n");
+                for module in untested.iter().take(10) {
+                    context.push_str(&format!(
+                        "- {}: {} source files\n",
+                        module.module, module.source_files
+                    ));
+                }
+                if untested.len() > 10 {
+                    context.push_str(&format!("- ... and {} more\n", untested.len() - 10));
+                }
+                context.push('\n');
+            }
+        }
+
+        // Containers, deployment manifests and CI
+        let infra = &project.metadata.infra;
+        if !infra.is_empty() {
+            context.push_str("## Infrastructure\n\n");
+            // Blank line between sub-sections, not before the first one
+            let body_start = context.len();
+            let separate = |context: &mut String| {
+                if context.len() > body_start {
+                    context.push('\n');
+                }
+            };
+            for dockerfile in &infra.dockerfiles {
+                context.push_str(&format!(
+                    "- Dockerfile `{}`: {}",
+                    dockerfile.path.display(),
+                    dockerfile.base_images.join(" → ")
+                ));
+                if !dockerfile.exposed_ports.is_empty() {
+                    context.push_str(&format!(
+                        " (exposes {})",
+                        dockerfile.exposed_ports.join(", ")
+                    ));
+                }
+                context.push('\n');
+            }
+            if !infra.compose_services.is_empty() {
+                separate(&mut context);
+                context.push_str("### Compose Services\n");
+                for service in &infra.compose_services {
+                    let source = match (&service.image, &service.build) {
+                        (Some(image), _) => image.clone(),
+                        (None, Some(build)) => format!("build {}", build),
+                        (None, None) => "?".to_string(),
+                    };
+                    context.push_str(&format!("- {}: {}", service.name, source));
+                    if !service.ports.is_empty() {
+                        context.push_str(&format!(" ports {}", service.ports.join(", ")));
+                    }
+                    if !service.depends_on.is_empty() {
+                        context.push_str(&format!(" depends on {}", service.depends_on.join(", ")));
+                    }
+                    context.push('\n');
+                }
+            }
+            if !infra.kubernetes.is_empty() || !infra.helm_charts.is_empty() {
+                separate(&mut context);
+                context.push_str("### Kubernetes\n");
+                for chart in &infra.helm_charts {
+                    context.push_str(&format!(
+                        "- Helm chart {} {} (`{}`)\n",
+                        chart.name,
+                        chart.version.as_deref().unwrap_or(""),
+                        chart.path.display()
+                    ));
+                }
+                for resource in infra.kubernetes.iter().take(15) {
+                    context.push_str(&format!(
+                        "- {} {}",
+                        resource.kind,
+                        resource.name.as_deref().unwrap_or("?")
+                    ));
+                    if !resource.images.is_empty() {
+                        context.push_str(&format!(" [{}]", resource.images.join(", ")));
+                    }
+                    context.push_str(&format!(" (`{}`)\n", resource.path.display()));
+                }
+                if infra.kubernetes.len() > 15 {
+                    context.push_str(&format!(
+                        "- ... and {} more resources\n",
+                        infra.kubernetes.len() - 15
+                    ));
+                }
+            }
+            if let Some(ref terraform) = infra.terraform {
+                separate(&mut context);
+                context.push_str(&format!(
+                    "### Terraform\n- {} files, providers: {}\n",
+                    terraform.files,
+                    terraform.providers.join(", ")
+                ));
+                let resources: Vec<String> = terraform
+                    .resources
+                    .iter()
+                    .take(10)
+                    .map(|r| format!("{} ×{}", r.resource_type, r.count))
+                    .collect();
+                if !resources.is_empty() {
+                    context.push_str(&format!("- Resources: {}\n", resources.join(", ")));
+                }
+                if !terraform.modules.is_empty() {
+                    context.push_str(&format!("- Modules: {}\n", terraform.modules.join(", ")));
+                }
+            }
+            if !infra.ci.is_empty() {
+                separate(&mut context);
+                context.push_str("### CI\n");
+                for pipeline in &infra.ci {
+                    context.push_str(&format!(
+                        "- {} `{}`",
+                        pipeline.provider,
+                        pipeline.path.display()
+                    ));
+                    if let Some(ref name) = pipeline.name {
+                        context.push_str(&format!(" \"{}\"", name));
+                    }
+                    if !pipeline.triggers.is_empty() {
+                        context.push_str(&format!(" on {}", pipeline.triggers.join(", ")));
+                    }
+                    if !pipeline.stages.is_empty() {
+                        context.push_str(&format!("; stages: {}", pipeline.stages.join(" → ")));
+                    }
+                    if !pipeline.jobs.is_empty() {
+                        context.push_str(&format!("; jobs: {}", pipeline.jobs.join(", ")));
+                    }
+                    context.push('\n');
+                }
+            }
+            context.push('\n');
+        }
+
+        // Git history: where work is happening right now
+        if let Some(ref git) = project.git {
+            context.push_str("## Hot Files\n\n");
+            match (&git.branch, &git.head) {
+                (Some(branch), Some(head)) => {
+                    context.push_str(&format!("- Branch: {} ({})\n", branch, head));
+                }
+                (Some(branch), None) => context.push_str(&format!("- Branch: {}\n", branch)),
+                (None, Some(head)) => {
+                    context.push_str(&format!("- Branch: detached at {}\n", head));
+                }
+                (None, None) => {}
+            }
+            context.push_str(&format!(
+                "- Uncommitted changes: {}\n\n",
+                git.uncommitted.len()
+            ));
+
+            if !git.uncommitted.is_empty() {
+                context.push_str("### Uncommitted\n");
+                for change in git.uncommitted.iter().take(10) {
+                    context.push_str(&format!(
+                        "- [{}] {}\n",
+                        change.status,
+                        change.path.display()
+                    ));
+                }
+                if git.uncommitted.len() > 10 {
+                    context.push_str(&format!("- ... and {} more\n", git.uncommitted.len() - 10));
+                }
+            }
+            if !git.hot_files.is_empty() {
+                context.push_str(&format!(
+                    "### Most Changed (last {} days)\n",
+                    git.history_days
+                ));
+                for file in &git.hot_files {
+                    context.push_str(&format!(
+                        "- {}: {} commits\n",
+                        file.path.display(),
+                        file.commits
+                    ));
+                }
+            }
+            if !git.recent_files.is_empty() {
+                context.push_str("### Recently Touched\n");
+                for path in &git.recent_files {
+                    context.push_str(&format!("- {}\n", path.display()));
+                }
+            }
+            if !git.directory_authors.is_empty() {
+                context.push_str("### Primary Authors\n");
+                for dir in &git.directory_authors {
+                    let authors: Vec<String> = dir
+                        .authors
+                        .iter()
+                        .map(|a| format!("{} ({})", a.name, a.commits))
+                        .collect();
+                    context.push_str(&format!("- {}: {}\n", dir.directory, authors.join(", ")));
+                }
+            }
+            context.push('\n');
+        }
+
+        // Changes since the previous analysis (incremental cache)
+        if let Some(ref changes) = project.scan.changes {
+            context.push_str("## Changes Since Last Analysis\n\n");
+            if changes.is_empty() {
+                context.push_str("No source files changed.\n\n");
+            } else {
+                for (label, paths) in [
+                    ("Added", &changes.added),
+                    ("Modified", &changes.modified),
+                    ("Removed", &changes.removed),
+                ] {
+                    if paths.is_empty() {
+                        continue;
+                    }
+                    context.push_str(&format!("### {} ({})\n", label, paths.len()));
+                    for path in paths.iter().take(10) {
+                        context.push_str(&format!("- {}\n", path.display()));
+                    }
+                    if paths.len() > 10 {
+                        context.push_str(&format!("- ... and {} more\n", paths.len() - 10));
+                    }
+                }
+                context.push('\n');
+            }
+        }
+
         // Relevant Patterns
         if !analysis.patterns.is_empty() {
             context.push_str("## Relevant Patterns\n\n");
-            for pattern in analysis.patterns.iter().take(5) {
+            for (i, pattern) in analysis.patterns.iter().take(5).enumerate() {
                 context.push_str(&format!("### {}\n", pattern.title));
                 context.push_str(&format!(
                     "**Category:** {} | **Framework:** {}\n",
                     pattern.category, pattern.framework
                 ));
+                if let Some(why) = analysis.pattern_matches.get(i) {
+                    context.push_str(&format!(
+                        "**Why:** {} (score {:.2})\n",
+                        why.reasons.join("; "),
+                        why.score
+                    ));
+                }
                 context.push_str(&format!("{}\n\n", pattern.description));
                 context.push_str("```\n");
                 context.push_str(&pattern.code);
@@ -440,9 +745,13 @@ impl ContextBuilder {
                     SeverityLevel::Warning => "⚠️",
                     SeverityLevel::Info => "ℹ️",
                 };
+                let location = suggestion
+                    .location(&analysis.project.path)
+                    .map(|l| format!(" (`{}`)", l))
+                    .unwrap_or_default();
                 context.push_str(&format!(
-                    "{} **{}**: {}\n",
-                    icon, suggestion.category, suggestion.message
+                    "{} **{}**: {}{}\n",
+                    icon, suggestion.category, suggestion.message, location
                 ));
             }
         }
@@ -501,10 +810,83 @@ impl ContextBuilder {
             .filter(|s| matches!(s.severity, SeverityLevel::Warning))
             .count();
 
+        let truncated = if project.scan.truncated.is_some() {
+            "(truncated)"
+        } else {
+            ""
+        };
+
+        let git = match project.git {
+            Some(ref g) => format!(
+                " git:{}{}",
+                g.branch.as_deref().unwrap_or("detached"),
+                if g.uncommitted.is_empty() {
+                    String::new()
+                } else {
+                    format!("*{}", g.uncommitted.len())
+                }
+            ),
+            None => String::new(),
+        };
+
+        let tests = &analysis.tests;
+        let tests = if tests.frameworks.is_empty() && tests.test_files == 0 {
+            String::new()
+        } else {
+            format!(
+                " tests:{}+{}inline({})",
+                tests.test_files,
+                tests.inline_test_files,
+                tests.command.as_deref().unwrap_or("?")
+            )
+        };
+
+        let infra = &project.metadata.infra;
+        let infra = if infra.is_empty() {
+            String::new()
+        } else {
+            let mut parts = Vec::new();
+            if !infra.dockerfiles.is_empty() {
+                parts.push(format!("docker:{}", infra.dockerfiles.len()));
+            }
+            if !infra.compose_services.is_empty() {
+                parts.push(format!("compose:{}", infra.compose_services.len()));
+            }
+            if !infra.kubernetes.is_empty() {
+                parts.push(format!("k8s:{}", infra.kubernetes.len()));
+            }
+            if !infra.helm_charts.is_empty() {
+                parts.push(format!("helm:{}", infra.helm_charts.len()));
+            }
+            if let Some(ref terraform) = infra.terraform {
+                parts.push(format!("tf:{}", terraform.resources.len()));
+            }
+            for pipeline in &infra.ci {
+                parts.push(format!("ci:{}", pipeline.provider));
+            }
+            parts.dedup();
+            format!(" infra:[{}]", parts.join(","))
+        };
+
+        let changed = match project.scan.changes {
+            Some(ref c) if !c.is_empty() => format!(
+                " changed:+{}~{}-{}",
+                c.added.len(),
+                c.modified.len(),
+                c.removed.len()
+            ),
+            _ => String::new(),
+        };
+
         format!(
-            "{} files:{} deps:{}({}dev) edition:{} entry:{} | patterns:[{}] suggestions:{}({}err,{}warn)",
+            "{} files:{}{}{}{}{}{} deps:{}({}dev) edition:{} entry:{} | patterns:[{}] suggestions:{}({}err,{}warn)",
             ident,
             analysis.statistics.total_files,
+            truncated,
+            tests,
+            infra,
+            changed,
+            git,
             prod_deps,
             dev_deps,
             edition,
@@ -552,6 +934,7 @@ impl ContextBuilder {
             total_lines: 0,
             framework_version: project.target_framework.clone(),
             package_count: project.packages.len(),
+            ..Default::default()
         };
 
         Ok(crate::types::DotNetAnalysisResult {
@@ -614,6 +997,7 @@ impl ContextBuilder {
             tags: vec![],
             min_score: 0.7, // Only high-quality patterns
             max_results: None,
+            mode: SearchMode::Keyword,
         };
 
         let scored_patterns = manager.search_patterns(&criteria);
@@ -692,12 +1076,12 @@ impl ContextBuilder {
 
                 if is_component {
                     // Check for synchronous OnInitialized
-                    let has_sync_init = class
+                    let sync_init = class
                         .methods
                         .iter()
-                        .any(|m| m.name == "OnInitialized" && !m.is_async);
+                        .find(|m| m.name == "OnInitialized" && !m.is_async);
 
-                    if has_sync_init {
+                    if let Some(method) = sync_init {
                         suggestions.push(Suggestion {
                             severity: SeverityLevel::Warning,
                             category: "blazor-lifecycle".to_string(),
@@ -706,7 +1090,10 @@ impl ContextBuilder {
                                 class.name
                             ),
                             file: Some(file.path.clone()),
-                            line: None,
+                            line: Some(method.range.start.line),
+                            range: Some(method.range),
+                            rule_id: None,
+                            pattern_id: None,
                         });
                     }
                 }
@@ -733,7 +1120,10 @@ impl ContextBuilder {
                                 method.name, class.name
                             ),
                             file: Some(file.path.clone()),
-                            line: None,
+                            line: Some(method.range.start.line),
+                            range: Some(method.range),
+                            rule_id: None,
+                            pattern_id: None,
                         });
                     }
                 }
@@ -758,6 +1148,9 @@ impl ContextBuilder {
                 .to_string(),
             file: None,
             line: None,
+            range: None,
+            rule_id: None,
+            pattern_id: None,
         }]
     }
 
@@ -822,9 +1215,13 @@ impl ContextBuilder {
                     SeverityLevel::Warning => "⚠️",
                     SeverityLevel::Info => "ℹ️",
                 };
+                let location = suggestion
+                    .location(&analysis.project.path)
+                    .map(|l| format!(" (`{}`)", l))
+                    .unwrap_or_default();
                 context.push_str(&format!(
-                    "{} **{}**: {}\n",
-                    icon, suggestion.category, suggestion.message
+                    "{} **{}**: {}{}\n",
+                    icon, suggestion.category, suggestion.message, location
                 ));
             }
         }
@@ -0,0 +1,467 @@
+//! Project-aware pattern ranking.
+//!
+//! Patterns are scored by what the analyzed project actually uses rather
+//! than by their static relevance alone: its dependencies, the symbols it
+//! declares (a component overriding `OnInitializedAsync` pulls lifecycle
+//! patterns up), its file types and the suggestions raised against it. Each
+//! signal that contributes is recorded as a reason, so the analysis can show
+//! why a pattern was picked.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::types::{CodePattern, PatternMatch, Project, Suggestion, Symbol};
+use crate::utils::truncate_string;
+
+/// Weight of the pattern's own relevance score.
+const RELEVANCE_WEIGHT: f32 = 0.3;
+/// Bonus for a pattern written for the detected framework.
+const FRAMEWORK_WEIGHT: f32 = 0.2;
+/// Bonus per project dependency the pattern uses, up to `MAX_DEPENDENCIES`.
+const DEPENDENCY_WEIGHT: f32 = 0.15;
+const MAX_DEPENDENCIES: usize = 2;
+/// Bonus per project symbol the pattern's code mentions, up to `MAX_SYMBOLS`.
+const SYMBOL_WEIGHT: f32 = 0.1;
+const MAX_SYMBOLS: usize = 3;
+/// Bonus for a pattern about a language the project has files in.
+const FILE_TYPE_WEIGHT: f32 = 0.1;
+/// Bonus per suggestion in the pattern's category, up to `MAX_SUGGESTIONS`.
+const SUGGESTION_WEIGHT: f32 = 0.1;
+const MAX_SUGGESTIONS: usize = 2;
+/// Bonus for a pattern a suggestion explicitly points to.
+const LINKED_WEIGHT: f32 = 0.4;
+
+/// Shorter symbol names (`Get`, `Main`) are too common to say anything.
+const MIN_SYMBOL_LEN: usize = 5;
+/// Suggestion messages are cut to this many characters in reasons.
+const MAX_MESSAGE_LEN: usize = 60;
+
+/// What the project uses, gathered once and matched against every pattern.
+struct ProjectSignals<'a> {
+    /// Dependency names, lowercased, with their original spelling
+    dependencies: Vec<(String, &'a str)>,
+    /// Declared symbol names and the number of files declaring them
+    symbols: HashMap<&'a str, usize>,
+    /// Languages (as pattern frameworks and tags name them) and their file counts
+    languages: BTreeMap<&'static str, (usize, &'a str)>,
+    /// Number of suggestions per category
+    categories: HashMap<&'a str, usize>,
+    /// Suggestions pointing at a pattern, by pattern ID
+    linked: HashMap<&'a str, &'a Suggestion>,
+}
+
+impl<'a> ProjectSignals<'a> {
+    fn collect(project: &'a Project, suggestions: &'a [Suggestion]) -> Self {
+        let dependencies = project
+            .dependencies
+            .iter()
+            .map(|d| (d.name.to_lowercase(), d.name.as_str()))
+            .collect();
+
+        let mut symbols = HashMap::new();
+        let mut languages = BTreeMap::new();
+        for file in &project.files {
+            let mut names = HashSet::new();
+            collect_symbol_names(&file.symbols, &mut names);
+            for name in names {
+                *symbols.entry(name).or_insert(0) += 1;
+            }
+            for &language in language_names(&file.language) {
+                languages
+                    .entry(language)
+                    .or_insert((0, file.language.as_str()))
+                    .0 += 1;
+            }
+        }
+
+        let mut categories = HashMap::new();
+        let mut linked = HashMap::new();
+        for suggestion in suggestions {
+            *categories.entry(suggestion.category.as_str()).or_insert(0) += 1;
+            if let Some(ref id) = suggestion.pattern_id {
+                linked.entry(id.as_str()).or_insert(suggestion);
+            }
+        }
+
+        Self {
+            dependencies,
+            symbols,
+            languages,
+            categories,
+            linked,
+        }
+    }
+}
+
+/// Names of `symbols` and their children.
+fn collect_symbol_names<'a>(symbols: &'a [Symbol], names: &mut HashSet<&'a str>) {
+    for symbol in symbols {
+        if symbol.name.len() >= MIN_SYMBOL_LEN {
+            names.insert(symbol.name.as_str());
+        }
+        collect_symbol_names(&symbol.children, names);
+    }
+}
+
+/// Language names a file extension stands for, as used by pattern frameworks and tags.
+fn language_names(extension: &str) -> &'static [&'static str] {
+    match extension {
+        "cs" => &["csharp", "dotnet"],
+        "razor" | "cshtml" => &["razor", "csharp", "dotnet"],
+        "rs" => &["rust"],
+        "js" | "jsx" | "mjs" | "cjs" => &["javascript"],
+        "ts" | "tsx" => &["typescript", "javascript"],
+        "py" => &["python"],
+        "go" => &["go"],
+        "java" => &["java"],
+        "php" => &["php"],
+        "rb" => &["ruby"],
+        _ => &[],
+    }
+}
+
+/// Rank the patterns for a project whose framework is `framework`.
+///
+/// Candidates are the framework's patterns, language-level patterns for the
+/// project's file types and patterns suggestions point to; deprecated
+/// patterns are skipped. Returns at most `limit` patterns, best first, each
+/// with the reasons it was picked.
+pub(super) fn rank_patterns<'p>(
+    patterns: &'p [CodePattern],
+    framework: &str,
+    project: &Project,
+    suggestions: &[Suggestion],
+    limit: usize,
+) -> Vec<(&'p CodePattern, PatternMatch)> {
+    let signals = ProjectSignals::collect(project, suggestions);
+    let mut ranked: Vec<_> = patterns
+        .iter()
+        .filter(|p| p.deprecated.is_none())
+        .filter_map(|p| score_pattern(p, framework, &signals).map(|m| (p, m)))
+        .collect();
+
+    ranked.sort_by(|(a, a_match), (b, b_match)| {
+        b_match
+            .score
+            .partial_cmp(&a_match.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Score one pattern against the project, or `None` when it is not a candidate.
+fn score_pattern(
+    pattern: &CodePattern,
+    framework: &str,
+    signals: &ProjectSignals,
+) -> Option<PatternMatch> {
+    let in_framework = pattern.framework == framework;
+    let linked = signals.linked.get(pattern.id.as_str());
+    let language_pattern = signals.languages.contains_key(pattern.framework.as_str());
+    if !in_framework && linked.is_none() && !language_pattern {
+        return None;
+    }
+
+    let mut score = pattern.relevance_score * RELEVANCE_WEIGHT;
+    let mut reasons = Vec::new();
+
+    if in_framework {
+        score += FRAMEWORK_WEIGHT;
+        reasons.push(format!("written for {}", framework));
+    }
+
+    if let Some(suggestion) = linked {
+        score += LINKED_WEIGHT;
+        reasons.push(format!(
+            "fixes suggestion \"{}\"",
+            truncate_string(&suggestion.message, MAX_MESSAGE_LEN)
+        ));
+    }
+
+    let code = pattern.code.to_lowercase();
+    let tags: Vec<String> = pattern.tags.iter().map(|t| t.to_lowercase()).collect();
+    let dependencies: Vec<&str> = signals
+        .dependencies
+        .iter()
+        .filter(|(name, _)| uses_dependency(name, &code, &tags))
+        .map(|(_, original)| *original)
+        .collect();
+    if !dependencies.is_empty() {
+        score += DEPENDENCY_WEIGHT * dependencies.len().min(MAX_DEPENDENCIES) as f32;
+        reasons.push(format!("uses dependency {}", dependencies.join(", ")));
+    }
+
+    let identifiers: HashSet<&str> = pattern
+        .code
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| word.len() >= MIN_SYMBOL_LEN)
+        .collect();
+    let mut symbols: Vec<(&str, usize)> = identifiers
+        .into_iter()
+        .filter_map(|word| signals.symbols.get(word).map(|&files| (word, files)))
+        .collect();
+    if !symbols.is_empty() {
+        symbols.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        score += SYMBOL_WEIGHT * symbols.len().min(MAX_SYMBOLS) as f32;
+        let listed: Vec<String> = symbols
+            .iter()
+            .take(MAX_SYMBOLS)
+            .map(|(name, files)| format!("{} ({} files)", name, files))
+            .collect();
+        reasons.push(format!("project declares {}", listed.join(", ")));
+    }
+
+    let file_type = signals.languages.iter().find(|(language, _)| {
+        pattern.framework == **language || tags.iter().any(|t| t == *language)
+    });
+    if let Some((_, (files, extension))) = file_type {
+        score += FILE_TYPE_WEIGHT;
+        reasons.push(format!("project has {} .{} files", files, extension));
+    }
+
+    let flagged = signals
+        .categories
+        .iter()
+        .filter(|(category, _)| {
+            pattern.category == **category || tags.iter().any(|t| t == *category)
+        })
+        .map(|(_, &count)| count)
+        .sum::<usize>();
+    if flagged > 0 {
+        score += SUGGESTION_WEIGHT * flagged.min(MAX_SUGGESTIONS) as f32;
+        reasons.push(format!(
+            "{} {} suggestion(s) raised",
+            flagged, pattern.category
+        ));
+    }
+
+    Some(PatternMatch {
+        pattern_id: pattern.id.clone(),
+        score,
+        reasons,
+    })
+}
+
+/// Whether a pattern uses dependency `name` (lowercased): its code names the
+/// package, or a tag is the package or its last segment (`MediatR`,
+/// `Microsoft.Extensions.Logging` → `logging`).
+fn uses_dependency(name: &str, code: &str, tags: &[String]) -> bool {
+    let last = name.rsplit(['.', '/', ':']).next().unwrap_or(name);
+    if tags
+        .iter()
+        .any(|t| t == name || (last.len() >= 4 && t == last))
+    {
+        return true;
+    }
+    name.len() >= 4 && contains_word(code, name)
+}
+
+/// Whether `needle` occurs in `haystack` without letters or digits on either side.
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    haystack.match_indices(needle).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + needle.len()..].chars().next();
+        !before.is_some_and(|c| c.is_alphanumeric() || c == '_')
+            && !after.is_some_and(|c| c.is_alphanumeric() || c == '_')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{dependency, source_file, tags};
+    use crate::types::{ProjectType, SeverityLevel, SourceFile, SymbolKind};
+
+    fn pattern(
+        id: &str,
+        category: &str,
+        framework: &str,
+        code: &str,
+        pattern_tags: &[&str],
+    ) -> CodePattern {
+        CodePattern {
+            category: category.to_string(),
+            framework: framework.to_string(),
+            version: "1.0".to_string(),
+            code: code.to_string(),
+            tags: tags(pattern_tags),
+            relevance_score: 0.9,
+            ..crate::test_support::pattern(id)
+        }
+    }
+
+    fn file(path: &str, language: &str, symbols: &[&str]) -> SourceFile {
+        let symbol = |name: &&str| Symbol {
+            name: name.to_string(),
+            kind: SymbolKind::Method,
+            modifiers: vec![],
+            children: vec![],
+            range: None,
+            signature: None,
+        };
+        SourceFile {
+            language: language.to_string(),
+            symbols: vec![Symbol {
+                name: "Component".to_string(),
+                kind: SymbolKind::Class,
+                modifiers: vec![],
+                children: symbols.iter().map(symbol).collect(),
+                range: None,
+                signature: None,
+            }],
+            ..source_file(path)
+        }
+    }
+
+    fn project(dependencies: &[&str], files: Vec<SourceFile>) -> Project {
+        Project {
+            name: "app".to_string(),
+            project_type: ProjectType::DotNet,
+            dependencies: dependencies
+                .iter()
+                .map(|name| dependency(name, "1.0"))
+                .collect(),
+            files,
+            ..crate::test_support::project("/tmp/app")
+        }
+    }
+
+    fn suggestion(category: &str, pattern_id: Option<&str>) -> Suggestion {
+        Suggestion {
+            severity: SeverityLevel::Warning,
+            category: category.to_string(),
+            message: format!("{} issue", category),
+            file: None,
+            line: None,
+            range: None,
+            rule_id: None,
+            pattern_id: pattern_id.map(str::to_string),
+        }
+    }
+
+    fn patterns() -> Vec<CodePattern> {
+        vec![
+            pattern(
+                "lifecycle-init",
+                "lifecycle",
+                "blazor-server",
+                "protected override async Task OnInitializedAsync() { }",
+                &["lifecycle"],
+            ),
+            pattern(
+                "di-logging",
+                "dependency-injection",
+                "blazor-server",
+                "@inject ILogger<Counter> Logger",
+                &["dependency-injection", "logging"],
+            ),
+            pattern(
+                "jsinterop",
+                "javascript-interop",
+                "blazor-server",
+                "await JS.InvokeVoidAsync(\"focus\");",
+                &["jsinterop", "javascript"],
+            ),
+            pattern(
+                "async-void",
+                "async",
+                "dotnet",
+                "async Task SaveAsync() { }",
+                &["async"],
+            ),
+            pattern("rust-errors", "error-handling", "rust", "fn main() {}", &[]),
+            pattern("react-hooks", "state", "react", "useEffect(() => {})", &[]),
+        ]
+    }
+
+    fn ids(ranked: &[(&CodePattern, PatternMatch)]) -> Vec<String> {
+        ranked.iter().map(|(p, _)| p.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_project_signals_reorder_framework_patterns() {
+        let patterns = patterns();
+        let bare = project(&[], vec![]);
+        let ranked = rank_patterns(&patterns, "blazor-server", &bare, &[], 10);
+        // Only framework patterns qualify, all on equal footing
+        assert_eq!(
+            ids(&ranked),
+            vec!["di-logging", "jsinterop", "lifecycle-init"]
+        );
+
+        let used = project(
+            &["Microsoft.Extensions.Logging"],
+            vec![
+                file("Pages/Index.razor.cs", "cs", &["OnInitializedAsync"]),
+                file("Pages/Edit.razor.cs", "cs", &["OnInitializedAsync"]),
+            ],
+        );
+        let ranked = rank_patterns(&patterns, "blazor-server", &used, &[], 10);
+        assert_eq!(
+            ids(&ranked),
+            vec!["di-logging", "lifecycle-init", "jsinterop", "async-void"]
+        );
+
+        let (_, why) = &ranked[0];
+        assert!(why
+            .reasons
+            .contains(&"uses dependency Microsoft.Extensions.Logging".to_string()));
+        let (_, why) = &ranked[1];
+        assert!(why
+            .reasons
+            .contains(&"project declares OnInitializedAsync (2 files)".to_string()));
+        // Language-level patterns come in through the file types only
+        let (_, why) = &ranked[3];
+        assert_eq!(why.reasons, vec!["project has 2 .cs files"]);
+    }
+
+    #[test]
+    fn test_suggestions_pull_patterns_up() {
+        let patterns = patterns();
+        let bare = project(&[], vec![file("app.js", "js", &[])]);
+        let suggestions = vec![
+            suggestion("javascript-interop", None),
+            suggestion("error-handling", Some("rust-errors")),
+        ];
+        let ranked = rank_patterns(&patterns, "blazor-server", &bare, &suggestions, 2);
+
+        assert_eq!(ids(&ranked), vec!["rust-errors", "jsinterop"]);
+        assert_eq!(
+            ranked[0].1.reasons,
+            vec![
+                "fixes suggestion \"error-handling issue\"",
+                "1 error-handling suggestion(s) raised"
+            ]
+        );
+        assert_eq!(
+            ranked[1].1.reasons,
+            vec![
+                "written for blazor-server",
+                "project has 1 .js files",
+                "1 javascript-interop suggestion(s) raised"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dependency_matching() {
+        let tags = vec!["mediatr".to_string(), "logging".to_string()];
+        assert!(uses_dependency("mediatr", "", &tags));
+        assert!(uses_dependency("microsoft.extensions.logging", "", &tags));
+        assert!(uses_dependency("anyhow", "use anyhow::result;", &[]));
+        assert!(!uses_dependency("serde", "use serde_json;", &[]));
+        // Short last segments such as `Web` are too vague to match a tag
+        assert!(!uses_dependency(
+            "microsoft.aspnetcore.components.web",
+            "",
+            &["web".to_string()]
+        ));
+        assert!(contains_word(
+            "using microsoft.jsinterop;",
+            "microsoft.jsinterop"
+        ));
+        assert!(!contains_word("ijsruntime", "js"));
+    }
+}
@@ -2,11 +2,14 @@
 //!
 //! This module provides typed errors using `thiserror` for better
 //! error handling and propagation throughout the application.
+//! The binary only uses `TrainingError`; items it leaves unused are
+//! library API and allow `dead_code` individually.
 
 use std::path::PathBuf;
 use thiserror::Error;
 
 /// Main error type for the MCP Context Server.
+#[allow(dead_code)]
 #[derive(Error, Debug)]
 pub enum McpError {
     /// Error during project analysis
@@ -35,6 +38,7 @@ pub enum McpError {
 }
 
 /// Errors that can occur during project analysis.
+#[allow(dead_code)]
 #[derive(Error, Debug)]
 pub enum AnalysisError {
     /// Project path does not exist
@@ -85,24 +89,48 @@ pub enum TrainingError {
     #[error("Pattern not found: {0}")]
     PatternNotFound(String),
 
+    /// No pending pattern candidate with this ID
+    #[error("Pattern candidate not found: {0}")]
+    CandidateNotFound(String),
+
+    /// Pattern bundle failed verification
+    #[error("Invalid pattern bundle: {0}")]
+    InvalidBundle(String),
+
     /// Duplicate pattern ID
     #[error("Pattern with ID '{0}' already exists")]
     DuplicatePattern(String),
 
+    /// No such revision in a pattern's history
+    #[error("Pattern '{id}' has no revision {revision}")]
+    RevisionNotFound { id: String, revision: u32 },
+
+    /// Pattern changed since the caller read it
+    #[error("Pattern '{id}' was updated at {actual}, not {expected}; read it again and retry")]
+    Conflict {
+        id: String,
+        expected: String,
+        actual: String,
+    },
+
     /// Failed to save patterns
+    #[allow(dead_code)]
     #[error("Failed to save patterns to {path}: {reason}")]
     SaveError { path: PathBuf, reason: String },
 
     /// Failed to load patterns
+    #[allow(dead_code)]
     #[error("Failed to load patterns from {path}: {reason}")]
     LoadError { path: PathBuf, reason: String },
 
     /// Path traversal attempt detected
+    #[allow(dead_code)]
     #[error("Security error: Path traversal attempt detected for '{0}'")]
     PathTraversal(String),
 }
 
 /// Configuration errors.
+#[allow(dead_code)]
 #[derive(Error, Debug)]
 pub enum ConfigError {
     /// Config file not found
@@ -123,9 +151,11 @@ pub enum ConfigError {
 }
 
 /// Result type alias using our custom error.
+#[allow(dead_code)]
 pub type Result<T> = std::result::Result<T, McpError>;
 
 /// Result type alias for analysis operations.
+#[allow(dead_code)]
 pub type AnalysisResult<T> = std::result::Result<T, AnalysisError>;
 
 /// Result type alias for training operations.
@@ -0,0 +1,605 @@
+//! Dependency health: outdated, end-of-life and vulnerable dependencies.
+//!
+//! Declared dependencies are checked against an offline dataset, so no
+//! network access is needed: `*.toml` files list the latest release of common
+//! packages and end-of-life framework versions, and `advisories/*.json` hold
+//! security advisories in OSV format. The bundled dataset ships in
+//! `data/health/` and is compiled in as a fallback for when no health
+//! directory exists; an OSV export can be dropped into `advisories/` as is.
+
+pub mod osv;
+pub mod version;
+
+use anyhow::{bail, Context, Result};
+use chrono::{NaiveDate, Utc};
+use semver::Version;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+
+use crate::types::{Project, SeverityLevel, Suggestion};
+use osv::Advisory;
+use version::{parse_version, Ecosystem, VersionRange};
+
+/// Dataset compiled into the binary.
+const BUILTIN_VERSIONS: &str = include_str!("../../data/health/versions.toml");
+const BUILTIN_ADVISORIES: &[&str] = &[
+    include_str!("../../data/health/advisories/GHSA-35jh-r3h4-6jhm.json"),
+    include_str!("../../data/health/advisories/GHSA-rv95-896h-c2vc.json"),
+    include_str!("../../data/health/advisories/GHSA-xvch-5gv4-984h.json"),
+];
+
+/// End-of-life dates are reported this many days ahead.
+const EOL_NOTICE_DAYS: i64 = 180;
+
+/// A dataset file: `[[packages]]` and `[[eol]]` tables.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VersionsFile {
+    #[serde(default)]
+    packages: Vec<LatestRelease>,
+    #[serde(default)]
+    eol: Vec<EndOfLife>,
+}
+
+/// Latest release of a package.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LatestRelease {
+    /// OSV ecosystem name (`npm`, `crates.io`, `PyPI`, `Packagist`, `NuGet`, `Go`, `Maven`)
+    pub ecosystem: String,
+    pub name: String,
+    pub latest: String,
+}
+
+/// An end-of-life framework version: a .NET target framework or package versions.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndOfLife {
+    /// Name used in messages (`.NET 6`, `Laravel 8`)
+    pub product: String,
+    /// End of support (`YYYY-MM-DD`)
+    pub eol: NaiveDate,
+    /// Recommended upgrade target
+    pub upgrade: String,
+    /// Target framework monikers (`net6.0`); platform suffixes are ignored
+    #[serde(default)]
+    pub target_framework: Vec<String>,
+    /// OSV ecosystem of `package`
+    pub ecosystem: Option<String>,
+    pub package: Option<String>,
+    /// End-of-life versions of `package`, in the ecosystem's syntax (`>=8.0, <9.0`)
+    pub versions: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Latest {
+    ecosystem: Ecosystem,
+    name: String,
+    version: Version,
+}
+
+#[derive(Debug, Clone)]
+struct Eol {
+    entry: EndOfLife,
+    /// Ecosystem, normalized name and versions of a package entry
+    package: Option<(Ecosystem, String, VersionRange)>,
+}
+
+/// Checks declared dependencies against the offline dataset.
+#[derive(Debug, Clone)]
+pub struct DependencyHealth {
+    latest: Vec<Latest>,
+    eol: Vec<Eol>,
+    advisories: Vec<Advisory>,
+}
+
+impl Default for DependencyHealth {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl DependencyHealth {
+    /// The dataset compiled into the binary (`data/health/`).
+    pub fn builtin() -> Self {
+        let versions: VersionsFile =
+            toml::from_str(BUILTIN_VERSIONS).expect("built-in dataset is valid");
+        let advisories = BUILTIN_ADVISORIES
+            .iter()
+            .flat_map(|content| Advisory::parse_osv(content).expect("built-in dataset is valid"))
+            .collect();
+        Self::from_parts(vec![versions], advisories).expect("built-in dataset is valid")
+    }
+
+    /// Load every `*.toml` file in `dir` and every OSV `*.json` file in
+    /// `dir/advisories`, in file-name order.
+    ///
+    /// Falls back to the built-in dataset when `dir` does not exist.
+    ///
+    /// # Errors
+    /// Returns an error if a file cannot be read or parsed
+    pub fn load(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            tracing::debug!(path = %dir.display(), "No health directory, using built-in dataset");
+            return Ok(Self::builtin());
+        }
+
+        let mut versions = Vec::new();
+        for path in files_with_extension(dir, "toml")? {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            versions.push(
+                toml::from_str(&content)
+                    .with_context(|| format!("Failed to parse {}", path.display()))?,
+            );
+        }
+
+        let mut advisories = Vec::new();
+        let advisories_dir = dir.join("advisories");
+        if advisories_dir.is_dir() {
+            for path in files_with_extension(&advisories_dir, "json")? {
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                advisories.extend(
+                    Advisory::parse_osv(&content)
+                        .with_context(|| format!("Failed to parse {}", path.display()))?,
+                );
+            }
+        }
+
+        let health = Self::from_parts(versions, advisories)?;
+        tracing::info!(
+            packages = health.latest.len(),
+            eol = health.eol.len(),
+            advisories = health.advisories.len(),
+            path = %dir.display(),
+            "Loaded dependency health dataset"
+        );
+        Ok(health)
+    }
+
+    fn from_parts(files: Vec<VersionsFile>, advisories: Vec<Advisory>) -> Result<Self> {
+        let ecosystem = |name: &str| {
+            Ecosystem::from_osv_name(name).with_context(|| format!("Unknown ecosystem '{}'", name))
+        };
+
+        let mut latest: Vec<Latest> = Vec::new();
+        let mut eol = Vec::new();
+        for file in files {
+            for package in file.packages {
+                let ecosystem = ecosystem(&package.ecosystem)?;
+                let Some(version) = parse_version(&package.latest) else {
+                    bail!(
+                        "Invalid latest version '{}' for {}",
+                        package.latest,
+                        package.name
+                    );
+                };
+                let name = ecosystem.normalize_name(&package.name);
+                // Later files update the latest release
+                latest.retain(|l| !(l.ecosystem == ecosystem && l.name == name));
+                latest.push(Latest {
+                    ecosystem,
+                    name,
+                    version,
+                });
+            }
+
+            for entry in file.eol {
+                let package = match (&entry.ecosystem, &entry.package, &entry.versions) {
+                    (Some(eco), Some(package), Some(versions)) => {
+                        let eco = ecosystem(eco)?;
+                        let Some(range) = VersionRange::parse(eco, versions) else {
+                            bail!("{}: invalid versions '{}'", entry.product, versions);
+                        };
+                        Some((eco, eco.normalize_name(package), range))
+                    }
+                    (None, None, None) if !entry.target_framework.is_empty() => None,
+                    _ => bail!(
+                        "{}: an end-of-life entry needs 'target_framework' or 'ecosystem', 'package' and 'versions'",
+                        entry.product
+                    ),
+                };
+                eol.push(Eol { entry, package });
+            }
+        }
+
+        Ok(Self {
+            latest,
+            eol,
+            advisories,
+        })
+    }
+
+    /// Graded suggestions for the dependencies and target framework of `project`.
+    pub fn check(&self, project: &Project) -> Vec<Suggestion> {
+        self.check_on(project, Utc::now().date_naive())
+    }
+
+    fn check_on(&self, project: &Project, today: NaiveDate) -> Vec<Suggestion> {
+        let mut vulnerable = Vec::new();
+        let mut end_of_life = self.check_target_frameworks(project, today);
+        let mut outdated = Vec::new();
+
+        let Some(ecosystem) = Ecosystem::for_project(project.project_type) else {
+            return end_of_life;
+        };
+
+        let mut seen = HashSet::new();
+        for dependency in &project.dependencies {
+            if !seen.insert((&dependency.name, &dependency.version)) {
+                continue;
+            }
+            let Some(range) = VersionRange::parse(ecosystem, &dependency.version) else {
+                continue;
+            };
+            let name = ecosystem.normalize_name(&dependency.name);
+            let label = format!("{} {}", dependency.name, dependency.version);
+
+            vulnerable.extend(self.check_advisories(ecosystem, &name, &range, &label));
+
+            for eol in &self.eol {
+                let Some((eco, ref package, ref versions)) = eol.package else {
+                    continue;
+                };
+                if eco == ecosystem && *package == name && range.is_subset_of(versions) {
+                    end_of_life.extend(eol_suggestion(&eol.entry, today));
+                }
+            }
+
+            if let Some(latest) = self
+                .latest
+                .iter()
+                .find(|l| l.ecosystem == ecosystem && l.name == name)
+            {
+                outdated.extend(outdated_suggestion(&range, &latest.version, &label));
+            }
+        }
+
+        vulnerable.extend(end_of_life);
+        vulnerable.extend(outdated);
+        vulnerable
+    }
+
+    fn check_target_frameworks(&self, project: &Project, today: NaiveDate) -> Vec<Suggestion> {
+        let Some(ref declared) = project.metadata.target_framework else {
+            return vec![];
+        };
+        // `net6.0;net8.0`, `net6.0-windows`
+        let monikers: Vec<String> = declared
+            .split(';')
+            .map(|tfm| tfm.split('-').next().unwrap_or("").trim().to_lowercase())
+            .filter(|tfm| !tfm.is_empty())
+            .collect();
+
+        self.eol
+            .iter()
+            .filter(|eol| {
+                eol.entry
+                    .target_framework
+                    .iter()
+                    .any(|tfm| monikers.contains(&tfm.to_lowercase()))
+            })
+            .filter_map(|eol| eol_suggestion(&eol.entry, today))
+            .collect()
+    }
+
+    fn check_advisories(
+        &self,
+        ecosystem: Ecosystem,
+        name: &str,
+        range: &VersionRange,
+        label: &str,
+    ) -> Vec<Suggestion> {
+        let mut suggestions = Vec::new();
+        for advisory in &self.advisories {
+            for affected in &advisory.affected {
+                if affected.ecosystem != ecosystem
+                    || affected.name != name
+                    || !range.intersects(&affected.versions)
+                {
+                    continue;
+                }
+
+                let id = match advisory.aliases.iter().find(|a| a.starts_with("CVE-")) {
+                    Some(cve) => format!("{} ({})", advisory.id, cve),
+                    None => advisory.id.clone(),
+                };
+                // The first fix at or above what the range allows
+                let fix = affected
+                    .fixed
+                    .iter()
+                    .find(|f| range.lowest().is_none_or(|lowest| *f > lowest))
+                    .map_or_else(
+                        || "No fixed version is known".to_string(),
+                        |f| format!("Upgrade to {} or later", f),
+                    );
+
+                let (severity, message) = if range.is_subset_of(&affected.versions) {
+                    (
+                        advisory.severity,
+                        format!(
+                            "{} is affected by {}: {}. {}",
+                            label, id, advisory.summary, fix
+                        ),
+                    )
+                } else {
+                    // Some allowed versions are safe: depends on what the lockfile resolves
+                    (
+                        downgrade(advisory.severity),
+                        format!(
+                            "{} allows versions affected by {}: {}. {} and make sure the lockfile resolves to a fixed version",
+                            label, id, advisory.summary, fix
+                        ),
+                    )
+                };
+                suggestions.push(suggestion(severity, "security", message));
+            }
+        }
+        suggestions
+    }
+}
+
+fn files_with_extension(dir: &Path, extension: &str) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().and_then(|e| e.to_str()) == Some(extension))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Warning once support has ended, info while the date is near.
+fn eol_suggestion(entry: &EndOfLife, today: NaiveDate) -> Option<Suggestion> {
+    let days_left = (entry.eol - today).num_days();
+    if days_left < 0 {
+        Some(suggestion(
+            SeverityLevel::Warning,
+            "upgrade",
+            format!(
+                "{} reached end of support on {} and no longer receives security fixes. Upgrade to {}",
+                entry.product, entry.eol, entry.upgrade
+            ),
+        ))
+    } else if days_left <= EOL_NOTICE_DAYS {
+        Some(suggestion(
+            SeverityLevel::Info,
+            "upgrade",
+            format!(
+                "{} reaches end of support on {}. Plan the upgrade to {}",
+                entry.product, entry.eol, entry.upgrade
+            ),
+        ))
+    } else {
+        None
+    }
+}
+
+/// Warning when the range excludes a newer major (or 0.x minor) release,
+/// info when it only excludes newer minor releases.
+fn outdated_suggestion(range: &VersionRange, latest: &Version, label: &str) -> Option<Suggestion> {
+    if range.contains(latest) || range.lowest().is_some_and(|lowest| lowest > latest) {
+        return None;
+    }
+    // Highest major and minor the range allows
+    let (major, minor) = match range.upper() {
+        Bound::Unbounded => return None,
+        Bound::Included(v) => (v.major, v.minor),
+        Bound::Excluded(v) if v.pre.is_empty() && v.minor == 0 && v.patch == 0 => {
+            (v.major.saturating_sub(1), u64::MAX)
+        }
+        Bound::Excluded(v) if v.pre.is_empty() && v.patch == 0 => (v.major, v.minor - 1),
+        Bound::Excluded(v) => (v.major, v.minor),
+    };
+
+    if latest.major > major || (latest.major == 0 && latest.minor > minor) {
+        Some(suggestion(
+            SeverityLevel::Warning,
+            "dependencies",
+            format!(
+                "{} is outdated: the latest release is {} (breaking upgrade)",
+                label, latest
+            ),
+        ))
+    } else if latest.minor > minor || latest.major > major {
+        Some(suggestion(
+            SeverityLevel::Info,
+            "dependencies",
+            format!("{} does not allow the latest release {}", label, latest),
+        ))
+    } else {
+        None
+    }
+}
+
+fn downgrade(severity: SeverityLevel) -> SeverityLevel {
+    match severity {
+        SeverityLevel::Error => SeverityLevel::Warning,
+        SeverityLevel::Warning | SeverityLevel::Info => SeverityLevel::Info,
+    }
+}
+
+fn suggestion(severity: SeverityLevel, category: &str, message: String) -> Suggestion {
+    Suggestion {
+        severity,
+        category: category.to_string(),
+        message,
+        file: None,
+        line: None,
+        range: None,
+        rule_id: None,
+        pattern_id: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::dependency;
+    use crate::types::ProjectType;
+
+    fn project(project_type: ProjectType, dependencies: &[(&str, &str)]) -> Project {
+        Project {
+            project_type,
+            dependencies: dependencies
+                .iter()
+                .map(|(name, version)| dependency(name, version))
+                .collect(),
+            ..crate::test_support::project("/nonexistent/project")
+        }
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_advisories_are_graded_by_range() {
+        let health = DependencyHealth::builtin();
+        let today = date("2026-10-18");
+
+        // Pinned to an affected version
+        let pinned = health.check_on(&project(ProjectType::Node, &[("lodash", "4.17.15")]), today);
+        assert_eq!(pinned[0].severity, SeverityLevel::Error);
+        assert_eq!(pinned[0].category, "security");
+        assert!(pinned[0]
+            .message
+            .contains("GHSA-35jh-r3h4-6jhm (CVE-2021-23337)"));
+        assert!(pinned[0].message.contains("Upgrade to 4.17.21 or later"));
+
+        // A range that also allows the fix is one grade lower
+        let caret = health.check_on(
+            &project(ProjectType::Node, &[("lodash", "^4.17.15")]),
+            today,
+        );
+        assert_eq!(caret[0].severity, SeverityLevel::Warning);
+        assert!(caret[0].message.contains("allows versions affected"));
+
+        let fixed = health.check_on(
+            &project(ProjectType::Node, &[("lodash", "^4.17.21")]),
+            today,
+        );
+        assert!(fixed.iter().all(|s| s.category != "security"));
+
+        // Same package name in another ecosystem
+        let other = health.check_on(
+            &project(ProjectType::Python, &[("lodash", "4.17.15")]),
+            today,
+        );
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn test_end_of_life() {
+        let health = DependencyHealth::builtin();
+
+        let laravel = project(ProjectType::Php, &[("laravel/framework", "^8.75")]);
+        let suggestions = health.check_on(&laravel, date("2026-10-18"));
+        let eol: Vec<&Suggestion> = suggestions
+            .iter()
+            .filter(|s| s.category == "upgrade")
+            .collect();
+        assert_eq!(eol.len(), 1);
+        assert_eq!(eol[0].severity, SeverityLevel::Warning);
+        assert!(eol[0]
+            .message
+            .starts_with("Laravel 8 reached end of support"));
+
+        let mut dotnet = project(ProjectType::DotNet, &[]);
+        dotnet.metadata.target_framework = Some("net6.0-windows".to_string());
+        let suggestions = health.check_on(&dotnet, date("2026-10-18"));
+        assert_eq!(suggestions.len(), 1);
+        assert!(suggestions[0]
+            .message
+            .starts_with(".NET 6 reached end of support"));
+
+        // Reported ahead of time as info
+        dotnet.metadata.target_framework = Some("net8.0".to_string());
+        let suggestions = health.check_on(&dotnet, date("2026-10-18"));
+        assert_eq!(suggestions[0].severity, SeverityLevel::Info);
+        assert!(health.check_on(&dotnet, date("2025-01-01")).is_empty());
+    }
+
+    #[test]
+    fn test_outdated_grades() {
+        let versions = r#"
+[[packages]]
+ecosystem = "npm"
+name = "express"
+latest = "5.1.0"
+
+[[packages]]
+ecosystem = "crates.io"
+name = "axum"
+latest = "0.8.4"
+
+[[packages]]
+ecosystem = "crates.io"
+name = "tokio"
+latest = "1.45.0"
+"#;
+        let health =
+            DependencyHealth::from_parts(vec![toml::from_str(versions).unwrap()], vec![]).unwrap();
+        let today = date("2026-10-18");
+
+        let express = health.check_on(
+            &project(ProjectType::Node, &[("express", "^3.21.2")]),
+            today,
+        );
+        assert_eq!(express.len(), 1);
+        assert_eq!(express[0].severity, SeverityLevel::Warning);
+        assert_eq!(
+            express[0].message,
+            "express ^3.21.2 is outdated: the latest release is 5.1.0 (breaking upgrade)"
+        );
+        let pinned = health.check_on(&project(ProjectType::Node, &[("express", "~5.0.1")]), today);
+        assert_eq!(pinned[0].severity, SeverityLevel::Info);
+        assert!(health
+            .check_on(&project(ProjectType::Node, &[("express", "^5.0.0")]), today)
+            .is_empty());
+
+        // In Cargo, `0.7` is `^0.7`: 0.8 is a breaking release; `1.28` allows 1.45
+        let rust = health.check_on(
+            &project(ProjectType::Rust, &[("axum", "0.7"), ("tokio", "1.28")]),
+            today,
+        );
+        assert_eq!(rust.len(), 1);
+        assert!(rust[0].message.starts_with("axum 0.7 is outdated"));
+    }
+
+    #[test]
+    fn test_load_from_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("versions.toml"),
+            "[[eol]]\nproduct = \"Vue 2\"\neol = \"2023-12-31\"\nupgrade = \"Vue 3\"\necosystem = \"npm\"\npackage = \"vue\"\nversions = \"<3\"\n",
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("advisories")).unwrap();
+        fs::write(
+            dir.path().join("advisories/export.json"),
+            r#"[{"id": "OSV-1", "summary": "Bad", "affected": [{"package": {"ecosystem": "npm", "name": "vue"}, "versions": ["2.6.14"]}]}]"#,
+        )
+        .unwrap();
+
+        let health = DependencyHealth::load(dir.path()).unwrap();
+        let suggestions = health.check_on(
+            &project(ProjectType::Node, &[("vue", "2.6.14")]),
+            date("2026-10-18"),
+        );
+        let categories: Vec<&str> = suggestions.iter().map(|s| s.category.as_str()).collect();
+        assert_eq!(categories, vec!["security", "upgrade"]);
+        assert_eq!(suggestions[0].severity, SeverityLevel::Warning);
+
+        fs::write(
+            dir.path().join("versions.toml"),
+            "[[eol]]\nproduct = \"Broken\"\neol = \"2023-12-31\"\nupgrade = \"x\"\n",
+        )
+        .unwrap();
+        assert!(DependencyHealth::load(dir.path()).is_err());
+    }
+}
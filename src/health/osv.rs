@@ -0,0 +1,220 @@
+//! Security advisories in the OSV format (https://ossf.github.io/osv-schema/).
+//!
+//! Only the fields needed to match declared dependencies are read: the
+//! affected packages with their version ranges, the severity assigned by the
+//! database and the fixed versions for the message.
+
+use anyhow::{Context, Result};
+use semver::Version;
+use serde::Deserialize;
+use std::ops::Bound;
+
+use super::version::{parse_version, Ecosystem, Interval, VersionRange};
+use crate::types::SeverityLevel;
+
+/// An OSV record, as found in OSV JSON exports.
+#[derive(Debug, Deserialize)]
+struct OsvRecord {
+    id: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    affected: Vec<OsvAffected>,
+    #[serde(default)]
+    database_specific: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvAffected {
+    package: OsvPackage,
+    #[serde(default)]
+    ranges: Vec<OsvRange>,
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvRange {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    events: Vec<OsvEvent>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OsvEvent {
+    introduced: Option<String>,
+    fixed: Option<String>,
+    last_affected: Option<String>,
+}
+
+/// A package version set affected by an advisory.
+#[derive(Debug, Clone)]
+pub struct AffectedPackage {
+    pub ecosystem: Ecosystem,
+    /// Name normalized with `Ecosystem::normalize_name`
+    pub name: String,
+    pub versions: VersionRange,
+    /// Versions that fix the advisory, lowest first
+    pub fixed: Vec<Version>,
+}
+
+/// A security advisory.
+#[derive(Debug, Clone)]
+pub struct Advisory {
+    pub id: String,
+    /// CVE and other ids of the same advisory
+    pub aliases: Vec<String>,
+    pub summary: String,
+    pub severity: SeverityLevel,
+    pub affected: Vec<AffectedPackage>,
+}
+
+impl Advisory {
+    /// Parse an OSV JSON document: one record or an array of records.
+    ///
+    /// Packages of unknown ecosystems and `GIT` ranges are skipped.
+    pub fn parse_osv(content: &str) -> Result<Vec<Self>> {
+        let value: serde_json::Value = serde_json::from_str(content).context("Invalid OSV JSON")?;
+        let records: Vec<OsvRecord> = if value.is_array() {
+            serde_json::from_value(value)?
+        } else {
+            vec![serde_json::from_value(value)?]
+        };
+        Ok(records.into_iter().map(Self::from_record).collect())
+    }
+
+    fn from_record(record: OsvRecord) -> Self {
+        let affected = record
+            .affected
+            .iter()
+            .filter_map(|affected| {
+                let ecosystem = Ecosystem::from_osv_name(&affected.package.ecosystem)?;
+                let mut intervals: Vec<Interval> = affected
+                    .versions
+                    .iter()
+                    .filter_map(|v| parse_version(v))
+                    .map(Interval::exact)
+                    .collect();
+                let mut fixed = Vec::new();
+                for range in affected.ranges.iter().filter(|r| r.kind != "GIT") {
+                    intervals.extend(range_intervals(&range.events, &mut fixed));
+                }
+                fixed.sort();
+                fixed.dedup();
+                Some(AffectedPackage {
+                    ecosystem,
+                    name: ecosystem.normalize_name(&affected.package.name),
+                    versions: VersionRange::from_intervals(intervals),
+                    fixed,
+                })
+            })
+            .collect();
+
+        let severity = record
+            .database_specific
+            .as_ref()
+            .and_then(|d| d["severity"].as_str())
+            .map_or(SeverityLevel::Warning, |s| {
+                match s.to_ascii_uppercase().as_str() {
+                    "CRITICAL" | "HIGH" => SeverityLevel::Error,
+                    "LOW" => SeverityLevel::Info,
+                    _ => SeverityLevel::Warning,
+                }
+            });
+
+        Self {
+            summary: record.summary.unwrap_or_else(|| record.id.clone()),
+            id: record.id,
+            aliases: record.aliases,
+            severity,
+            affected,
+        }
+    }
+}
+
+/// Affected intervals of an OSV `events` list (`introduced` ... `fixed`).
+fn range_intervals(events: &[OsvEvent], fixed: &mut Vec<Version>) -> Vec<Interval> {
+    let mut intervals = Vec::new();
+    let mut start: Option<Bound<Version>> = None;
+    for event in events {
+        if let Some(ref introduced) = event.introduced {
+            start = Some(match introduced.as_str() {
+                "0" => Bound::Unbounded,
+                v => match parse_version(v) {
+                    Some(version) => Bound::Included(version),
+                    None => continue,
+                },
+            });
+        } else if let Some(version) = event.fixed.as_deref().and_then(parse_version) {
+            if let Some(lower) = start.take() {
+                intervals.push(Interval {
+                    lower,
+                    upper: Bound::Excluded(version.clone()),
+                });
+            }
+            fixed.push(version);
+        } else if let Some(version) = event.last_affected.as_deref().and_then(parse_version) {
+            if let Some(lower) = start.take() {
+                intervals.push(Interval {
+                    lower,
+                    upper: Bound::Included(version),
+                });
+            }
+        }
+    }
+    if let Some(lower) = start {
+        intervals.push(Interval {
+            lower,
+            upper: Bound::Unbounded,
+        });
+    }
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_osv_ranges() {
+        let json = r#"{
+            "id": "GHSA-xxxx-yyyy-zzzz",
+            "summary": "Prototype pollution",
+            "aliases": ["CVE-2099-0001"],
+            "affected": [{
+                "package": {"ecosystem": "npm", "name": "minimist"},
+                "ranges": [{"type": "SEMVER", "events": [
+                    {"introduced": "0"}, {"fixed": "0.2.4"},
+                    {"introduced": "1.0.0"}, {"fixed": "1.2.6"}
+                ]}]
+            }, {
+                "package": {"ecosystem": "Hackage", "name": "other"}
+            }],
+            "database_specific": {"severity": "CRITICAL"}
+        }"#;
+        let advisories = Advisory::parse_osv(json).unwrap();
+        assert_eq!(advisories.len(), 1);
+
+        let advisory = &advisories[0];
+        assert_eq!(advisory.severity, SeverityLevel::Error);
+        assert_eq!(advisory.affected.len(), 1);
+
+        let affected = &advisory.affected[0];
+        assert_eq!(
+            affected.fixed,
+            vec![Version::new(0, 2, 4), Version::new(1, 2, 6)]
+        );
+        assert!(affected.versions.contains(&Version::new(1, 2, 5)));
+        assert!(!affected.versions.contains(&Version::new(0, 2, 4)));
+        assert!(!affected.versions.contains(&Version::new(1, 2, 6)));
+    }
+}
@@ -0,0 +1,635 @@
+//! Ecosystem-aware dependency version ranges.
+//!
+//! Each registry has its own specifier syntax: `^1.2` means `>=1.2.0, <2.0.0`
+//! in npm and Cargo, but a bare `1.2` is a caret range in Cargo, an x-range
+//! in npm, an exact version in Composer and a minimum in NuGet. Specifiers are
+//! parsed into a union of intervals so they can be compared with the latest
+//! release and with OSV affected ranges.
+
+use semver::{Prerelease, Version};
+use std::cmp::Ordering;
+use std::ops::Bound;
+
+use crate::types::ProjectType;
+
+/// A package registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ecosystem {
+    Npm,
+    Cargo,
+    PyPi,
+    Packagist,
+    NuGet,
+    Go,
+    Maven,
+}
+
+impl Ecosystem {
+    /// The registry the dependencies of a project type come from.
+    pub fn for_project(project_type: ProjectType) -> Option<Self> {
+        match project_type {
+            ProjectType::Node => Some(Self::Npm),
+            ProjectType::Rust => Some(Self::Cargo),
+            ProjectType::Python => Some(Self::PyPi),
+            ProjectType::Php => Some(Self::Packagist),
+            ProjectType::DotNet => Some(Self::NuGet),
+            ProjectType::Go => Some(Self::Go),
+            ProjectType::Java => Some(Self::Maven),
+            ProjectType::Unknown => None,
+        }
+    }
+
+    /// Ecosystem name as used by OSV (`npm`, `crates.io`, `PyPI`, ...).
+    pub const fn osv_name(self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Cargo => "crates.io",
+            Self::PyPi => "PyPI",
+            Self::Packagist => "Packagist",
+            Self::NuGet => "NuGet",
+            Self::Go => "Go",
+            Self::Maven => "Maven",
+        }
+    }
+
+    /// Look up an ecosystem by its OSV name (case-insensitive).
+    pub fn from_osv_name(name: &str) -> Option<Self> {
+        [
+            Self::Npm,
+            Self::Cargo,
+            Self::PyPi,
+            Self::Packagist,
+            Self::NuGet,
+            Self::Go,
+            Self::Maven,
+        ]
+        .into_iter()
+        .find(|e| e.osv_name().eq_ignore_ascii_case(name))
+    }
+
+    /// Canonical package name for comparisons (`Flask_Login` -> `flask-login` on PyPI).
+    pub fn normalize_name(self, name: &str) -> String {
+        match self {
+            Self::PyPi => name.to_lowercase().replace(['_', '.'], "-"),
+            Self::Packagist | Self::NuGet => name.to_lowercase(),
+            _ => name.to_string(),
+        }
+    }
+}
+
+/// A contiguous set of versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval {
+    pub lower: Bound<Version>,
+    pub upper: Bound<Version>,
+}
+
+impl Interval {
+    /// Every version.
+    pub const fn any() -> Self {
+        Self {
+            lower: Bound::Unbounded,
+            upper: Bound::Unbounded,
+        }
+    }
+
+    /// Exactly `version`.
+    pub fn exact(version: Version) -> Self {
+        Self {
+            lower: Bound::Included(version.clone()),
+            upper: Bound::Included(version),
+        }
+    }
+
+    pub fn contains(&self, version: &Version) -> bool {
+        let above = match self.lower {
+            Bound::Included(ref v) => version >= v,
+            Bound::Excluded(ref v) => version > v,
+            Bound::Unbounded => true,
+        };
+        let below = match self.upper {
+            Bound::Included(ref v) => version <= v,
+            Bound::Excluded(ref v) => version < v,
+            Bound::Unbounded => true,
+        };
+        above && below
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match (&self.lower, &self.upper) {
+            (Bound::Included(a), Bound::Included(b)) => a > b,
+            (Bound::Included(a) | Bound::Excluded(a), Bound::Included(b) | Bound::Excluded(b)) => {
+                a >= b
+            }
+            _ => false,
+        }
+    }
+
+    /// Versions in both intervals, `None` when there are none.
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let lower = if compare_lower(self.lower.as_ref(), other.lower.as_ref()) == Ordering::Less {
+            other.lower.clone()
+        } else {
+            self.lower.clone()
+        };
+        let upper = if compare_upper(self.upper.as_ref(), other.upper.as_ref()) == Ordering::Greater
+        {
+            other.upper.clone()
+        } else {
+            self.upper.clone()
+        };
+        let interval = Self { lower, upper };
+        (!interval.is_empty()).then_some(interval)
+    }
+
+    /// The parts of this interval outside `other`.
+    fn minus(&self, other: &Self) -> Vec<Self> {
+        let mut parts = Vec::new();
+        let below = match other.lower {
+            Bound::Included(ref v) => Some(Bound::Excluded(v.clone())),
+            Bound::Excluded(ref v) => Some(Bound::Included(v.clone())),
+            Bound::Unbounded => None,
+        };
+        if let Some(upper) = below {
+            let part = Self {
+                lower: Bound::Unbounded,
+                upper,
+            };
+            parts.extend(self.intersect(&part));
+        }
+        let above = match other.upper {
+            Bound::Included(ref v) => Some(Bound::Excluded(v.clone())),
+            Bound::Excluded(ref v) => Some(Bound::Included(v.clone())),
+            Bound::Unbounded => None,
+        };
+        if let Some(lower) = above {
+            let part = Self {
+                lower,
+                upper: Bound::Unbounded,
+            };
+            parts.extend(self.intersect(&part));
+        }
+        parts
+    }
+}
+
+/// Order lower bounds by the smallest version they admit.
+fn compare_lower(a: Bound<&Version>, b: Bound<&Version>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Less,
+        (_, Bound::Unbounded) => Ordering::Greater,
+        (Bound::Included(x), Bound::Excluded(y)) if x == y => Ordering::Less,
+        (Bound::Excluded(x), Bound::Included(y)) if x == y => Ordering::Greater,
+        (Bound::Included(x) | Bound::Excluded(x), Bound::Included(y) | Bound::Excluded(y)) => {
+            x.cmp(y)
+        }
+    }
+}
+
+/// Order upper bounds by the largest version they admit.
+fn compare_upper(a: Bound<&Version>, b: Bound<&Version>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Greater,
+        (_, Bound::Unbounded) => Ordering::Less,
+        (Bound::Included(x), Bound::Excluded(y)) if x == y => Ordering::Greater,
+        (Bound::Excluded(x), Bound::Included(y)) if x == y => Ordering::Less,
+        (Bound::Included(x) | Bound::Excluded(x), Bound::Included(y) | Bound::Excluded(y)) => {
+            x.cmp(y)
+        }
+    }
+}
+
+/// A set of versions: the union of its intervals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRange {
+    intervals: Vec<Interval>,
+}
+
+impl VersionRange {
+    pub fn from_intervals(intervals: Vec<Interval>) -> Self {
+        Self {
+            intervals: intervals.into_iter().filter(|i| !i.is_empty()).collect(),
+        }
+    }
+
+    /// Parse a dependency specifier in the syntax of `ecosystem`.
+    ///
+    /// Returns `None` for specifiers that do not name versions (git, path or
+    /// workspace dependencies, `dev-master`, `latest`).
+    pub fn parse(ecosystem: Ecosystem, spec: &str) -> Option<Self> {
+        let spec = spec.trim();
+        if spec.starts_with(['[', '(']) {
+            return parse_intervals(spec);
+        }
+
+        let alternatives: Vec<&str> = match ecosystem {
+            Ecosystem::Npm | Ecosystem::Packagist => {
+                spec.split("||").flat_map(|s| s.split('|')).collect()
+            }
+            _ => vec![spec],
+        };
+
+        let mut intervals = Vec::new();
+        for alternative in alternatives {
+            intervals.push(parse_conjunction(ecosystem, alternative)?);
+        }
+        Some(Self::from_intervals(intervals))
+    }
+
+    pub fn contains(&self, version: &Version) -> bool {
+        self.intervals.iter().any(|i| i.contains(version))
+    }
+
+    /// Whether some version is in both ranges.
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.intervals
+            .iter()
+            .any(|a| other.intervals.iter().any(|b| a.intersect(b).is_some()))
+    }
+
+    /// Whether every version of this range is also in `other`.
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        let mut remaining = self.intervals.clone();
+        for interval in &other.intervals {
+            remaining = remaining.iter().flat_map(|r| r.minus(interval)).collect();
+        }
+        remaining.is_empty()
+    }
+
+    /// Lowest version the range allows; `None` when it has no lower bound.
+    pub fn lowest(&self) -> Option<&Version> {
+        let mut lowest: Option<&Version> = None;
+        for interval in &self.intervals {
+            match interval.lower {
+                Bound::Included(ref v) | Bound::Excluded(ref v) => {
+                    if lowest.is_none_or(|l| v < l) {
+                        lowest = Some(v);
+                    }
+                }
+                Bound::Unbounded => return None,
+            }
+        }
+        lowest
+    }
+
+    /// Upper bound of the range; `Unbounded` when it allows any newer version.
+    pub fn upper(&self) -> Bound<&Version> {
+        self.intervals
+            .iter()
+            .map(|i| i.upper.as_ref())
+            .max_by(|a, b| compare_upper(*a, *b))
+            .unwrap_or(Bound::Unbounded)
+    }
+}
+
+/// Comparators that must all hold (`>=1.2 <2`, `>=1.2, <2`).
+fn parse_conjunction(ecosystem: Ecosystem, spec: &str) -> Option<Interval> {
+    let spec = spec.trim();
+    // Composer stability flags (`^1.0@beta`)
+    let spec = spec.split('@').next().unwrap_or("").trim();
+    if spec.is_empty() || spec == "*" || spec.eq_ignore_ascii_case("x") {
+        return Some(Interval::any());
+    }
+
+    // Hyphen range: `1.2 - 2.3.4`
+    if matches!(ecosystem, Ecosystem::Npm | Ecosystem::Packagist) {
+        if let Some((from, to)) = spec.split_once(" - ") {
+            let from = Partial::parse(from)?;
+            let to = Partial::parse(to)?;
+            return Some(Interval {
+                lower: Bound::Included(from.floor()),
+                upper: if to.is_full() {
+                    Bound::Included(to.floor())
+                } else {
+                    Bound::Excluded(to.bump_last())
+                },
+            });
+        }
+    }
+
+    let mut interval = Interval::any();
+    for comparator in split_comparators(spec) {
+        let next = parse_comparator(ecosystem, &comparator)?;
+        interval = interval.intersect(&next).unwrap_or(Interval {
+            lower: Bound::Excluded(Version::new(0, 0, 0)),
+            upper: Bound::Excluded(Version::new(0, 0, 0)),
+        });
+    }
+    Some(interval)
+}
+
+/// Split on `,` and whitespace, keeping operators attached to their version (`>= 1.2`).
+fn split_comparators(spec: &str) -> Vec<String> {
+    let mut comparators: Vec<String> = Vec::new();
+    for token in spec.split([',', ' ', '\t']).filter(|t| !t.is_empty()) {
+        match comparators.last_mut() {
+            Some(last) if last.chars().all(|c| "<>=!~^".contains(c)) => last.push_str(token),
+            _ => comparators.push(token.to_string()),
+        }
+    }
+    comparators
+}
+
+fn parse_comparator(ecosystem: Ecosystem, comparator: &str) -> Option<Interval> {
+    let operator_len = comparator
+        .find(|c: char| !"<>=!~^".contains(c))
+        .unwrap_or(comparator.len());
+    let (operator, version) = comparator.split_at(operator_len);
+    if version.trim() == "*" || version.trim().eq_ignore_ascii_case("x") {
+        return Some(Interval::any());
+    }
+    let partial = Partial::parse(version)?;
+    let floor = partial.floor();
+
+    let interval = |lower, upper| Some(Interval { lower, upper });
+    match operator {
+        "" | "=" | "==" => {
+            if !partial.is_full() || partial.wildcard {
+                // `1.2`, `1.2.*`: every version with that prefix, except for
+                // registries where a bare partial version is exact or a minimum
+                match (ecosystem, partial.wildcard) {
+                    (Ecosystem::Cargo, false) if operator.is_empty() => caret(&partial),
+                    (Ecosystem::NuGet, false) if operator.is_empty() => {
+                        interval(Bound::Included(floor), Bound::Unbounded)
+                    }
+                    (Ecosystem::Packagist | Ecosystem::PyPi | Ecosystem::Maven, false) => {
+                        Some(Interval::exact(floor))
+                    }
+                    _ => interval(Bound::Included(floor), Bound::Excluded(partial.bump_last())),
+                }
+            } else {
+                match ecosystem {
+                    Ecosystem::Cargo if operator.is_empty() => caret(&partial),
+                    Ecosystem::NuGet if operator.is_empty() => {
+                        interval(Bound::Included(floor), Bound::Unbounded)
+                    }
+                    _ => Some(Interval::exact(floor)),
+                }
+            }
+        }
+        "===" => Some(Interval::exact(floor)),
+        "^" => caret(&partial),
+        "~" if ecosystem == Ecosystem::Packagist => compatible(&partial),
+        "~" => interval(
+            Bound::Included(floor),
+            Bound::Excluded(if partial.parts.len() == 1 {
+                partial.bump(0)
+            } else {
+                partial.bump(1)
+            }),
+        ),
+        "~=" => compatible(&partial),
+        ">=" => interval(Bound::Included(floor), Bound::Unbounded),
+        ">" if partial.is_full() => interval(Bound::Excluded(floor), Bound::Unbounded),
+        ">" => interval(Bound::Included(partial.bump_last()), Bound::Unbounded),
+        "<=" if partial.is_full() => interval(Bound::Unbounded, Bound::Included(floor)),
+        "<=" => interval(Bound::Unbounded, Bound::Excluded(partial.bump_last())),
+        "<" => interval(Bound::Unbounded, Bound::Excluded(floor)),
+        // `!=` only removes single versions; treat it as no constraint
+        "!=" => Some(Interval::any()),
+        _ => None,
+    }
+}
+
+/// `^1.2.3` -> `>=1.2.3, <2.0.0`; `^0.2` -> `<0.3.0`; `^0.0.3` -> `<0.0.4`.
+fn caret(partial: &Partial) -> Option<Interval> {
+    let index = partial
+        .parts
+        .iter()
+        .position(|&p| p != 0)
+        .unwrap_or(partial.parts.len() - 1);
+    Some(Interval {
+        lower: Bound::Included(partial.floor()),
+        upper: Bound::Excluded(partial.bump(index)),
+    })
+}
+
+/// Composer `~1.2` / PEP 440 `~=1.2`: `>=1.2, <2.0`; `~1.2.3`: `>=1.2.3, <1.3.0`.
+fn compatible(partial: &Partial) -> Option<Interval> {
+    let index = partial.parts.len().saturating_sub(2);
+    Some(Interval {
+        lower: Bound::Included(partial.floor()),
+        upper: Bound::Excluded(partial.bump(index)),
+    })
+}
+
+/// NuGet/Maven interval notation: `[1.0,2.0)`, `(,1.0]`, `[1.2]`, `[1,2),[3,)`.
+fn parse_intervals(spec: &str) -> Option<VersionRange> {
+    let mut intervals = Vec::new();
+    let mut rest = spec.trim();
+    while !rest.is_empty() {
+        let close = rest.find([']', ')'])?;
+        let (body, tail) = rest.split_at(close + 1);
+        let inclusive_lower = body.starts_with('[');
+        let inclusive_upper = body.ends_with(']');
+        let inner = &body[1..body.len() - 1];
+
+        let bound = |text: &str, inclusive: bool| -> Option<Bound<Version>> {
+            let text = text.trim();
+            if text.is_empty() {
+                return Some(Bound::Unbounded);
+            }
+            let version = Partial::parse(text)?.floor();
+            Some(if inclusive {
+                Bound::Included(version)
+            } else {
+                Bound::Excluded(version)
+            })
+        };
+        intervals.push(match inner.split_once(',') {
+            Some((lower, upper)) => Interval {
+                lower: bound(lower, inclusive_lower)?,
+                upper: bound(upper, inclusive_upper)?,
+            },
+            None => Interval::exact(Partial::parse(inner)?.floor()),
+        });
+
+        rest = tail.trim_start_matches([',', ' ']);
+    }
+    Some(VersionRange::from_intervals(intervals))
+}
+
+/// Parse a single version leniently (`v1.2`, `2.0.0.RELEASE`, `6.0.0-preview.1`).
+pub fn parse_version(text: &str) -> Option<Version> {
+    let text = text.trim().trim_start_matches(['v', 'V']);
+    if let Ok(version) = Version::parse(text) {
+        return Some(version);
+    }
+    Some(Partial::parse(text)?.floor())
+}
+
+/// A possibly incomplete version (`1`, `1.2`, `1.2.x`, `1.2.3-beta`).
+#[derive(Debug)]
+struct Partial {
+    parts: Vec<u64>,
+    pre: Prerelease,
+    /// Ended with a wildcard component (`1.2.*`)
+    wildcard: bool,
+}
+
+impl Partial {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches(['v', 'V']);
+        let (core, pre) = match text.split_once('-') {
+            Some((core, pre)) => (core, Prerelease::new(pre).unwrap_or_default()),
+            None => (text, Prerelease::EMPTY),
+        };
+
+        let mut parts = Vec::new();
+        let mut wildcard = false;
+        for component in core.split('.').take(3) {
+            if matches!(component, "*" | "x" | "X") {
+                wildcard = true;
+                break;
+            }
+            let digits: String = component.chars().take_while(char::is_ascii_digit).collect();
+            if digits.is_empty() {
+                break;
+            }
+            parts.push(digits.parse().ok()?);
+            // `1.2rc1`, `2.0.0.RELEASE`: stop at the first non-numeric suffix
+            if digits.len() != component.len() {
+                break;
+            }
+        }
+        if parts.is_empty() {
+            return None;
+        }
+        Some(Self {
+            parts,
+            pre,
+            wildcard,
+        })
+    }
+
+    fn is_full(&self) -> bool {
+        self.parts.len() == 3
+    }
+
+    fn part(&self, index: usize) -> u64 {
+        self.parts.get(index).copied().unwrap_or(0)
+    }
+
+    /// Smallest version with this prefix.
+    fn floor(&self) -> Version {
+        let mut version = Version::new(self.part(0), self.part(1), self.part(2));
+        version.pre = self.pre.clone();
+        version
+    }
+
+    /// The first version after every version sharing components `0..=index`.
+    fn bump(&self, index: usize) -> Version {
+        match index {
+            0 => Version::new(self.part(0) + 1, 0, 0),
+            1 => Version::new(self.part(0), self.part(1) + 1, 0),
+            _ => Version::new(self.part(0), self.part(1), self.part(2) + 1),
+        }
+    }
+
+    fn bump_last(&self) -> Version {
+        self.bump(self.parts.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(ecosystem: Ecosystem, spec: &str) -> VersionRange {
+        VersionRange::parse(ecosystem, spec).unwrap()
+    }
+
+    fn v(text: &str) -> Version {
+        Version::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_bare_versions_follow_the_ecosystem() {
+        // Cargo: caret
+        let cargo = range(Ecosystem::Cargo, "1.2");
+        assert!(cargo.contains(&v("1.9.0")) && !cargo.contains(&v("2.0.0")));
+        // npm: exact for full versions, x-range for partial ones
+        assert!(!range(Ecosystem::Npm, "1.2.3").contains(&v("1.2.4")));
+        assert!(range(Ecosystem::Npm, "1.2").contains(&v("1.2.9")));
+        // Composer: exact
+        assert!(!range(Ecosystem::Packagist, "1.2").contains(&v("1.2.1")));
+        // NuGet: minimum
+        assert!(range(Ecosystem::NuGet, "6.0.1").contains(&v("8.0.0")));
+    }
+
+    #[test]
+    fn test_operators() {
+        let caret = range(Ecosystem::Npm, "^0.2.3");
+        assert!(caret.contains(&v("0.2.9")) && !caret.contains(&v("0.3.0")));
+
+        let tilde = range(Ecosystem::Npm, "~1.2");
+        assert!(tilde.contains(&v("1.2.5")) && !tilde.contains(&v("1.3.0")));
+
+        // Composer `~` and PEP 440 `~=` keep the next-to-last component
+        let composer = range(Ecosystem::Packagist, "~8.2");
+        assert!(composer.contains(&v("8.9.0")) && !composer.contains(&v("9.0.0")));
+        let pep = range(Ecosystem::PyPi, "~=1.4.2");
+        assert!(pep.contains(&v("1.4.9")) && !pep.contains(&v("1.5.0")));
+
+        let and = range(Ecosystem::PyPi, ">=1.0, <2");
+        assert!(and.contains(&v("1.5.0")) && !and.contains(&v("2.0.0")));
+        let spaced = range(Ecosystem::Npm, ">= 1.0 < 2");
+        assert_eq!(spaced, and);
+
+        let or = range(Ecosystem::Packagist, "^7.3|^8.0");
+        assert!(or.contains(&v("7.4.0")) && or.contains(&v("8.1.0")));
+        assert!(!or.contains(&v("9.0.0")));
+
+        let hyphen = range(Ecosystem::Npm, "1.2 - 2.3");
+        assert!(hyphen.contains(&v("2.3.9")) && !hyphen.contains(&v("2.4.0")));
+    }
+
+    #[test]
+    fn test_interval_notation() {
+        let nuget = range(Ecosystem::NuGet, "[1.0,2.0)");
+        assert!(nuget.contains(&v("1.9.9")) && !nuget.contains(&v("2.0.0")));
+        let open = range(Ecosystem::Maven, "(,1.0]");
+        assert!(open.contains(&v("0.5.0")) && open.contains(&v("1.0.0")));
+        let union = range(Ecosystem::Maven, "[1,2),[3,)");
+        assert!(union.contains(&v("3.5.0")) && !union.contains(&v("2.5.0")));
+    }
+
+    #[test]
+    fn test_unparseable_specifiers() {
+        assert!(VersionRange::parse(Ecosystem::Packagist, "dev-master").is_none());
+        assert!(VersionRange::parse(Ecosystem::Npm, "latest").is_none());
+        assert!(VersionRange::parse(Ecosystem::Npm, "github:user/repo").is_none());
+        assert!(VersionRange::parse(Ecosystem::Cargo, "workspace").is_none());
+        assert!(range(Ecosystem::Npm, "*").lowest().is_none());
+    }
+
+    #[test]
+    fn test_lowest_and_upper() {
+        assert_eq!(
+            range(Ecosystem::Packagist, "^8.2").lowest(),
+            Some(&v("8.2.0"))
+        );
+        assert_eq!(range(Ecosystem::Npm, "4.x").lowest(), Some(&v("4.0.0")));
+        assert_eq!(range(Ecosystem::Go, "v1.9.1").lowest(), Some(&v("1.9.1")));
+        assert_eq!(
+            range(Ecosystem::Npm, "^3.21.2").upper(),
+            Bound::Excluded(&v("4.0.0"))
+        );
+        assert_eq!(range(Ecosystem::PyPi, ">=2.0").upper(), Bound::Unbounded);
+    }
+
+    #[test]
+    fn test_subset_and_intersection() {
+        let affected = VersionRange::from_intervals(vec![Interval {
+            lower: Bound::Unbounded,
+            upper: Bound::Excluded(v("4.17.21")),
+        }]);
+        assert!(range(Ecosystem::Npm, "4.17.15").is_subset_of(&affected));
+        let caret = range(Ecosystem::Npm, "^4.17.15");
+        assert!(caret.intersects(&affected));
+        assert!(!caret.is_subset_of(&affected));
+        assert!(!range(Ecosystem::Npm, "^4.17.21").intersects(&affected));
+    }
+}
@@ -73,9 +73,14 @@ pub mod analyzer;
 pub mod config;
 pub mod context;
 pub mod error;
+pub mod health;
 pub mod mcp;
 pub mod observations;
+pub mod rules;
 pub mod rustscp;
+pub mod security;
+#[cfg(test)]
+mod test_support;
 pub mod training;
 pub mod types;
 pub mod utils;
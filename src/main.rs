@@ -1,12 +1,19 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod analyzer;
 mod config;
 mod context;
+mod error;
+mod health;
 mod mcp;
 mod observations;
+mod rules;
 mod rustscp;
+mod security;
+#[cfg(test)]
+mod test_support;
 mod training;
 mod types;
 mod utils;
@@ -34,6 +41,15 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Subcommands: export-patterns / import-patterns <bundle> [options]
+    if args.len() >= 2 && (args[1] == "export-patterns" || args[1] == "import-patterns") {
+        if let Err(e) = run_bundle_command(&args[1], &args[2..]).await {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Default: run MCP server
     // Initialize tracing - ONLY to stderr, no ANSI colors for MCP compatibility
     tracing_subscriber::registry()
@@ -63,3 +79,68 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// `export-patterns <bundle> [--framework F] [--category C] [--tag T]...`, or
+/// `import-patterns` with the same filters and `--on-conflict skip|overwrite|rename`.
+async fn run_bundle_command(command: &str, args: &[String]) -> Result<()> {
+    let mut bundle_path = None;
+    let mut filter = training::BundleFilter::default();
+    let mut policy = training::ConflictPolicy::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .with_context(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--framework" => filter.framework = Some(value()?),
+            "--category" => filter.category = Some(value()?),
+            "--tag" => filter.tags.push(value()?),
+            "--on-conflict" if command == "import-patterns" => {
+                policy = value()?.parse().map_err(anyhow::Error::msg)?;
+            }
+            option if option.starts_with("--") => bail!("Unknown option {}", option),
+            path if bundle_path.is_none() => bundle_path = Some(PathBuf::from(path)),
+            other => bail!("Unexpected argument {}", other),
+        }
+    }
+    let bundle_path = bundle_path.with_context(|| {
+        format!(
+            "Usage: mcp-context-rust {} <bundle.json> [options]",
+            command
+        )
+    })?;
+
+    let config = config::Config::load()?;
+    let mut manager = training::TrainingManager::new(
+        config.storage.base_path.join(&config.storage.patterns_file),
+    );
+    manager.set_author("cli");
+    manager.load_patterns().await?;
+
+    if command == "export-patterns" {
+        let bundle = manager.export_bundle(filter);
+        if bundle.patterns.is_empty() {
+            bail!("No patterns match the given framework, category and tags");
+        }
+        bundle.save(&bundle_path)?;
+        println!(
+            "Exported {} patterns to {} (checksum {})",
+            bundle.patterns.len(),
+            bundle_path.display(),
+            bundle.manifest.checksum
+        );
+    } else {
+        let bundle = training::PatternBundle::load(&bundle_path)?;
+        let report = manager.import_bundle(&bundle, &filter, policy)?;
+        if report.imported() > 0 {
+            manager.save_patterns().await?;
+        }
+        println!("Imported {}: {}", bundle_path.display(), report);
+        for (from, to) in &report.renamed {
+            println!("  {} -> {}", from, to);
+        }
+    }
+    Ok(())
+}
@@ -1,26 +1,49 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 
-use crate::analyzer::GenericAnalyzer;
+use crate::analyzer::grammar::Grammar;
+use crate::analyzer::module_graph::ModuleGraph;
+use crate::analyzer::symbol_index::SymbolIndex;
+use crate::analyzer::symbols::SymbolExtractor;
+use crate::analyzer::{diff, GenericAnalyzer};
 use crate::config::Config;
 use crate::context::ContextBuilder;
+use crate::error::TrainingError;
+use crate::health::DependencyHealth;
 use crate::observations::ObservationStore;
-use crate::training::{SearchCriteria, TrainingManager};
-use crate::types::CodePattern;
+use crate::rules::RuleEngine;
+use crate::security::SecurityScanner;
+use crate::training::{
+    changed_fields, format_timestamp, unified_diff, BundleFilter, ConflictPolicy, Correction,
+    PatternBundle, PatternUpdate, SearchCriteria, SearchMode, TrainingManager,
+};
+use crate::types::{CodePattern, PatternCandidate, Project, SeverityLevel, Symbol, SymbolMatch};
 
 /// MCP Server implementation
 pub struct Server {
     config: Config,
     training_manager: TrainingManager,
+    /// Declarative suggestion rules
+    rules: RuleEngine,
+    /// Offline dataset for outdated, end-of-life and vulnerable dependencies
+    health: DependencyHealth,
     /// When true, all tool responses use compact single-line format (~95% token reduction).
     /// Full outputs are archived on disk and retrievable via `get-observation`.
     endless_mode: bool,
     /// Two-tier storage for Endless Mode observations.
     observations: ObservationStore,
+    /// Patterns listed in the latest compact pattern responses, by obs_id;
+    /// fetching the observation counts as using them.
+    returned_patterns: VecDeque<(String, Vec<String>)>,
 }
 
+/// Compact pattern responses whose listed patterns are remembered for usage
+/// counting; older ones are forgotten.
+const RETURNED_PATTERN_LISTS: usize = 100;
+
 /// JSON-RPC Request structure
 #[derive(Debug, Deserialize)]
 struct JsonRpcRequest {
@@ -64,7 +87,11 @@ impl Server {
         // Initialize training manager
         let patterns_path = config.storage.base_path.join(&config.storage.patterns_file);
         tracing::info!(path = %patterns_path.display(), "Looking for patterns");
+        let cache_path = config.storage.base_path.join(&config.storage.cache_dir);
         let mut training_manager = TrainingManager::new(patterns_path.clone());
+        if config.training.semantic_search {
+            training_manager = training_manager.with_semantic_index(cache_path.join("semantic"));
+        }
 
         // Load existing patterns
         match training_manager.load_patterns().await {
@@ -83,17 +110,39 @@ impl Server {
             }
         }
 
-        let obs_cache_dir = config
-            .storage
-            .base_path
-            .join(&config.storage.cache_dir)
-            .join("observations");
+        let rules_path = config.storage.base_path.join(&config.storage.rules_dir);
+        let rules = RuleEngine::load(&rules_path).unwrap_or_else(|e| {
+            tracing::warn!(
+                path = %rules_path.display(),
+                error = %e,
+                "Error loading rules, using built-in rules"
+            );
+            RuleEngine::builtin()
+        });
+        if rules.is_empty() {
+            tracing::warn!(path = %rules_path.display(), "All suggestion rules are disabled");
+        }
+
+        let health_path = config.storage.base_path.join(&config.storage.health_dir);
+        let health = DependencyHealth::load(&health_path).unwrap_or_else(|e| {
+            tracing::warn!(
+                path = %health_path.display(),
+                error = %e,
+                "Error loading dependency health dataset, using built-in dataset"
+            );
+            DependencyHealth::builtin()
+        });
+
+        let obs_cache_dir = cache_path.join("observations");
 
         Ok(Self {
             config,
             training_manager,
+            rules,
+            health,
             endless_mode: false,
             observations: ObservationStore::new(obs_cache_dir),
+            returned_patterns: VecDeque::new(),
         })
     }
 
@@ -312,7 +361,7 @@ impl Server {
         tracing::info!("Handling method: {}", request.method);
 
         let result = match request.method.as_str() {
-            "initialize" => self.handle_initialize().await,
+            "initialize" => self.handle_initialize(request.params.as_ref()).await,
             "tools/list" => self.handle_tools_list().await,
             "tools/call" => self.handle_tool_call(request.params).await,
             "prompts/list" => self.handle_prompts_list().await,
@@ -340,7 +389,15 @@ impl Server {
         }
     }
 
-    async fn handle_initialize(&self) -> Result<serde_json::Value, String> {
+    async fn handle_initialize(
+        &mut self,
+        params: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value, String> {
+        // Pattern revisions are attributed to the connected client
+        if let Some(client) = params.and_then(|p| p["clientInfo"]["name"].as_str()) {
+            self.training_manager.set_author(client);
+        }
+
         Ok(serde_json::json!({
             "protocolVersion": "2024-11-05",
             "serverInfo": {
@@ -370,6 +427,128 @@ impl Server {
                         "required": ["project_path"]
                     }
                 },
+                {
+                    "name": "analyze-diff",
+                    "description": "Review context for a change set: analyzes only the files changed relative to a git ref (or in a supplied unified diff), lists the touched symbols, and returns only the patterns and suggestions relevant to them",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "project_path": {
+                                "type": "string",
+                                "description": "Path to the project directory"
+                            },
+                            "base": {
+                                "type": "string",
+                                "description": "Git ref to diff the working tree against (e.g., 'main', 'HEAD~3'). Default: 'HEAD' (uncommitted changes)"
+                            },
+                            "diff": {
+                                "type": "string",
+                                "description": "Unified diff text to use instead of git. Paths are relative to project_path"
+                            }
+                        },
+                        "required": ["project_path"]
+                    }
+                },
+                {
+                    "name": "get-module-graph",
+                    "description": "Internal module dependency graph of a project (Rust use/mod, JS/TS import/require, Python, Go, Java, C# usings, PHP use): import cycles, layering violations and the most depended-on modules. Pass 'module' to see what one module imports and what imports it",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "project_path": {
+                                "type": "string",
+                                "description": "Path to the project directory"
+                            },
+                            "module": {
+                                "type": "string",
+                                "description": "Optional module to inspect: a file path relative to the project (e.g. 'src/lib.rs'), a C# namespace or a Go package directory"
+                            }
+                        },
+                        "required": ["project_path"]
+                    }
+                },
+                {
+                    "name": "find-symbol",
+                    "description": "Locate classes, functions, methods, etc. by (fuzzy) name and/or kind across a project. Returns file path and line span, so only the relevant lines need to be read",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "project_path": {
+                                "type": "string",
+                                "description": "Path to the project directory"
+                            },
+                            "query": {
+                                "type": "string",
+                                "description": "Symbol name, partial name or qualified name (e.g. 'OrderService.Save', 'ordsvc')"
+                            },
+                            "kind": {
+                                "type": "string",
+                                "enum": ["class", "interface", "struct", "enum", "trait", "impl", "function", "method", "property", "field", "module", "component", "type", "const", "macro", "delegate"],
+                                "description": "Only return symbols of this kind"
+                            },
+                            "max_results": {
+                                "type": "integer",
+                                "description": "Maximum results (default: 20)"
+                            }
+                        },
+                        "required": ["project_path"]
+                    }
+                },
+                {
+                    "name": "get-file-outline",
+                    "description": "Nested outline of a source file (types, functions, members) with signatures and line spans, without the file contents",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "file_path": {
+                                "type": "string",
+                                "description": "Absolute path to the file, or a path relative to project_path"
+                            },
+                            "project_path": {
+                                "type": "string",
+                                "description": "Optional project directory that file_path is relative to"
+                            }
+                        },
+                        "required": ["file_path"]
+                    }
+                },
+                {
+                    "name": "lint-project",
+                    "description": "Run the tree-sitter lint rules (async void in C#, .unwrap() in Rust non-test code, eval in JS/TS, bare except in Python, plus rules from the rules directory) and list located findings with the pattern showing each fix",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "project_path": {
+                                "type": "string",
+                                "description": "Path to the project directory"
+                            },
+                            "min_severity": {
+                                "type": "string",
+                                "enum": ["info", "warning", "error"],
+                                "description": "Only report findings at or above this severity (default: info)"
+                            },
+                            "rule": {
+                                "type": "string",
+                                "description": "Only run the rule with this id (e.g. 'rust-unwrap')"
+                            }
+                        },
+                        "required": ["project_path"]
+                    }
+                },
+                {
+                    "name": "security-scan",
+                    "description": "Scan a project for committed secrets (known credential formats, high-entropy values, tracked .env and key files), debug flags left on in production config and permissive CORS. Secrets are redacted in the output",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "project_path": {
+                                "type": "string",
+                                "description": "Path to the project directory"
+                            }
+                        },
+                        "required": ["project_path"]
+                    }
+                },
                 {
                     "name": "get-patterns",
                     "description": "Get code patterns for a specific framework and category",
@@ -396,7 +575,7 @@ impl Server {
                         "properties": {
                             "query": {
                                 "type": "string",
-                                "description": "Search query text (searches in title, description, and code)"
+                                "description": "Search query text; words are matched in any order across title, tags, description and code, ranked with BM25"
                             },
                             "framework": {
                                 "type": "string",
@@ -418,6 +597,11 @@ impl Server {
                             "max_results": {
                                 "type": "integer",
                                 "description": "Maximum number of results to return (default: 20)"
+                            },
+                            "mode": {
+                                "type": "string",
+                                "enum": ["keyword", "semantic", "hybrid"],
+                                "description": "keyword (default): BM25 over the query words; semantic: offline vector similarity that also finds synonyms ('DI' → dependency injection, 'login' → authentication); hybrid: both"
                             }
                         }
                     }
@@ -466,237 +650,1696 @@ impl Server {
                     }
                 },
                 {
-                    "name": "get-statistics",
-                    "description": "Get statistics about the pattern database",
+                    "name": "update-pattern",
+                    "description": "Change fields of an existing pattern. Fails if the pattern was modified after expected_updated_at",
                     "inputSchema": {
                         "type": "object",
-                        "properties": {}
+                        "properties": {
+                            "id": {
+                                "type": "string",
+                                "description": "ID of the pattern to change"
+                            },
+                            "expected_updated_at": {
+                                "type": "string",
+                                "description": "The pattern's 'Updated At' timestamp as last read (RFC 3339)"
+                            },
+                            "category": { "type": "string", "description": "New category" },
+                            "framework": { "type": "string", "description": "New target framework" },
+                            "version": { "type": "string", "description": "New framework version" },
+                            "title": { "type": "string", "description": "New title" },
+                            "description": { "type": "string", "description": "New description" },
+                            "code": { "type": "string", "description": "New code example" },
+                            "tags": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "description": "New tags (replace the current ones)"
+                            }
+                        },
+                        "required": ["id", "expected_updated_at"]
                     }
                 },
                 {
-                    "name": "get-help",
-                    "description": "Get usage instructions for this MCP server. Call this first to understand how to use the available tools effectively.",
+                    "name": "delete-pattern",
+                    "description": "Delete a pattern. Fails if the pattern was modified after expected_updated_at",
                     "inputSchema": {
                         "type": "object",
-                        "properties": {}
+                        "properties": {
+                            "id": {
+                                "type": "string",
+                                "description": "ID of the pattern to delete"
+                            },
+                            "expected_updated_at": {
+                                "type": "string",
+                                "description": "The pattern's 'Updated At' timestamp as last read (RFC 3339)"
+                            }
+                        },
+                        "required": ["id", "expected_updated_at"]
                     }
                 },
                 {
-                    "name": "set-endless-mode",
-                    "description": "Toggle Endless Mode to reduce token usage by ~95%. When enabled, all tool responses use a compact single-line format and full outputs are archived on disk with an obs_id. Use get-observation to retrieve full details when needed. Allows up to 20x more tool calls before the context window fills.",
+                    "name": "deprecate-pattern",
+                    "description": "Mark a pattern as deprecated so searches stop suggesting it, or restore it",
                     "inputSchema": {
                         "type": "object",
                         "properties": {
-                            "enabled": {
+                            "id": {
+                                "type": "string",
+                                "description": "ID of the pattern to deprecate"
+                            },
+                            "expected_updated_at": {
+                                "type": "string",
+                                "description": "The pattern's 'Updated At' timestamp as last read (RFC 3339)"
+                            },
+                            "reason": {
+                                "type": "string",
+                                "description": "Why the pattern should no longer be used"
+                            },
+                            "replaced_by": {
+                                "type": "string",
+                                "description": "ID of the pattern to use instead (optional)"
+                            },
+                            "restore": {
                                 "type": "boolean",
-                                "description": "true to enable compact output (Endless Mode ON), false to restore full verbose output"
+                                "description": "Clear the deprecation instead (default: false)"
                             }
                         },
-                        "required": ["enabled"]
+                        "required": ["id", "expected_updated_at"]
                     }
                 },
                 {
-                    "name": "get-observation",
-                    "description": "Retrieve the full archived output of a previous tool call by its obs_id. Use this when Endless Mode is active and you need the complete details that were compressed.",
+                    "name": "list-pattern-revisions",
+                    "description": "List the revision history of a pattern: who changed it, when, how and which fields",
                     "inputSchema": {
                         "type": "object",
                         "properties": {
-                            "obs_id": {
+                            "id": {
                                 "type": "string",
-                                "description": "The observation UUID returned by a previous tool call in Endless Mode"
+                                "description": "ID of the pattern (deleted patterns keep their history)"
                             }
                         },
-                        "required": ["obs_id"]
+                        "required": ["id"]
                     }
-                }
-            ]
-        }))
-    }
-
-    async fn handle_tool_call(
-        &mut self,
-        params: Option<serde_json::Value>,
-    ) -> Result<serde_json::Value, String> {
-        let params = params.ok_or("Missing params")?;
-        let tool_name = params["name"].as_str().ok_or("Missing tool name")?;
-        let arguments = &params["arguments"];
-
-        tracing::info!("Calling tool: {}", tool_name);
-
-        match tool_name {
-            "analyze-project" => self.tool_analyze_project(arguments).await,
-            "get-patterns" => self.tool_get_patterns(arguments).await,
-            "search-patterns" => self.tool_search_patterns(arguments).await,
-            "train-pattern" => self.tool_train_pattern(arguments).await,
-            "get-statistics" => self.tool_get_statistics().await,
-            "get-help" => self.tool_get_help().await,
-            "set-endless-mode" => self.tool_set_endless_mode(arguments).await,
-            "get-observation" => self.tool_get_observation(arguments).await,
-            _ => Err(format!("Unknown tool: {}", tool_name)),
-        }
-    }
-
-    /// Analyzes a project and returns structured context.
-    async fn tool_analyze_project(
-        &self,
-        args: &serde_json::Value,
-    ) -> Result<serde_json::Value, String> {
-        let project_path = args["project_path"]
-            .as_str()
-            .ok_or("Missing project_path")?;
-
-        tracing::debug!(path = %project_path, "Analyzing project");
-
-        // Validate path exists
-        let path = PathBuf::from(project_path);
-        if !path.exists() {
-            return Err(format!(
-                "Project path does not exist: '{}'. Please provide an absolute path to a project directory.",
-                project_path
-            ));
-        }
-
-        if !path.is_dir() {
-            return Err(format!(
-                "Path is not a directory: '{}'. Please provide a directory path, not a file path.",
-                project_path
-            ));
-        }
-
-        tracing::debug!("Path validated, detecting project type");
-
-        // Use the new generic analyzer
-        let project = GenericAnalyzer::analyze(path.as_path())
-            .await
-            .map_err(|e| {
-                tracing::warn!(error = %e, "Analysis failed");
-                format!("Failed to analyze project: {}. Make sure the directory contains a valid project file (Cargo.toml, package.json, .csproj, pyproject.toml, go.mod, or pom.xml).", e)
-            })?;
-
-        tracing::debug!(project_type = ?project.project_type, "Project analyzed successfully");
-
-        // Build context with patterns
-        let context_builder =
-            ContextBuilder::new().with_training_manager(self.training_manager.clone());
-
-        let analysis = context_builder
-            .build_generic_analysis(project)
-            .await
-            .map_err(|e| format!("Failed to build analysis: {}", e))?;
-
-        // Save .rustscp to project directory (non-fatal on failure)
-        match crate::rustscp::ProjectContext::from_analysis(&analysis).save(&path) {
-            Ok(p) => tracing::info!(path = %p.display(), "Saved .rustscp"),
-            Err(e) => tracing::warn!(error = %e, "Failed to save .rustscp (non-fatal)"),
-        }
-
-        // Generate formatted context
-        let full_output = context_builder.build_generic_context_string(&analysis);
-
-        let output = if self.endless_mode {
-            let compact = context_builder.build_compact_context_string(&analysis);
-            let obs_id: String = self
-                .observations
-                .save("analyze-project", &full_output)
-                .await
-                .map_err(|e| format!("Failed to archive observation: {}", e))?;
-            format!(
-                "{}\nobs_id:{} (call get-observation to see full analysis)",
-                compact, obs_id
-            )
-        } else {
-            full_output
-        };
-
-        Ok(serde_json::json!({
-            "content": [{
-                "type": "text",
-                "text": output
-            }],
-            "isError": false
-        }))
-    }
-
-    // Tool: get-patterns
-    async fn tool_get_patterns(
-        &self,
-        args: &serde_json::Value,
+                },
+                {
+                    "name": "diff-pattern-revisions",
+                    "description": "Show a unified diff of a pattern's code and description between two revisions",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "id": {
+                                "type": "string",
+                                "description": "ID of the pattern"
+                            },
+                            "from": {
+                                "type": "integer",
+                                "description": "Revision to compare from"
+                            },
+                            "to": {
+                                "type": "integer",
+                                "description": "Revision to compare to (default: latest)"
+                            }
+                        },
+                        "required": ["id", "from"]
+                    }
+                },
+                {
+                    "name": "rollback-pattern",
+                    "description": "Restore a pattern to the content of an earlier revision, recorded as a new revision. Also re-creates deleted patterns",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "id": {
+                                "type": "string",
+                                "description": "ID of the pattern"
+                            },
+                            "revision": {
+                                "type": "integer",
+                                "description": "Revision to restore"
+                            },
+                            "expected_updated_at": {
+                                "type": "string",
+                                "description": "The pattern's 'Updated At' timestamp as last read (RFC 3339); not needed for deleted patterns"
+                            }
+                        },
+                        "required": ["id", "revision"]
+                    }
+                },
+                {
+                    "name": "rate-pattern",
+                    "description": "Record whether a pattern helped. Votes adjust the pattern's relevance score over time",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "id": {
+                                "type": "string",
+                                "description": "ID of the pattern"
+                            },
+                            "helpful": {
+                                "type": "boolean",
+                                "description": "true if the pattern helped, false if it did not"
+                            }
+                        },
+                        "required": ["id", "helpful"]
+                    }
+                },
+                {
+                    "name": "mark-pattern-used",
+                    "description": "Record that a pattern was applied, raising its usage count (popular patterns rank higher)",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "id": {
+                                "type": "string",
+                                "description": "ID of the pattern"
+                            }
+                        },
+                        "required": ["id"]
+                    }
+                },
+                {
+                    "name": "review-pattern-candidates",
+                    "description": "List the pattern candidates mined from recurring idioms by analyze-project, or accept one as a pattern (optionally renaming or recategorizing it) or reject it so it is not proposed again",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "action": {
+                                "type": "string",
+                                "enum": ["list", "accept", "reject"],
+                                "description": "What to do (default: list)"
+                            },
+                            "id": {
+                                "type": "string",
+                                "description": "ID of the candidate (required for accept and reject)"
+                            },
+                            "title": {
+                                "type": "string",
+                                "description": "Title to use instead of the proposed one (accept only)"
+                            },
+                            "category": {
+                                "type": "string",
+                                "description": "Category to use instead of the proposed one (accept only)"
+                            },
+                            "description": {
+                                "type": "string",
+                                "description": "Description to use instead of the proposed one (accept only)"
+                            },
+                            "tags": {
+                                "type": "array",
+                                "items": {"type": "string"},
+                                "description": "Tags to use instead of the proposed ones (accept only)"
+                            }
+                        }
+                    }
+                },
+                {
+                    "name": "export-patterns",
+                    "description": "Write patterns to a portable JSON bundle with a manifest and SHA-256 checksums, to share them with another installation (deprecated patterns are left out)",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "bundle_path": {
+                                "type": "string",
+                                "description": "Absolute path of the bundle file to write"
+                            },
+                            "framework": {
+                                "type": "string",
+                                "description": "Only patterns of this framework"
+                            },
+                            "category": {
+                                "type": "string",
+                                "description": "Only patterns of this category"
+                            },
+                            "tags": {
+                                "type": "array",
+                                "items": {"type": "string"},
+                                "description": "Only patterns with all of these tags"
+                            }
+                        },
+                        "required": ["bundle_path"]
+                    }
+                },
+                {
+                    "name": "import-patterns",
+                    "description": "Import the patterns of a bundle written by export-patterns after verifying its checksums. IDs that already exist are skipped, overwritten or imported under a new ID",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "bundle_path": {
+                                "type": "string",
+                                "description": "Absolute path of the bundle file"
+                            },
+                            "on_conflict": {
+                                "type": "string",
+                                "enum": ["skip", "overwrite", "rename"],
+                                "description": "What to do when a pattern ID exists (default: skip)"
+                            },
+                            "framework": {
+                                "type": "string",
+                                "description": "Only patterns of this framework"
+                            },
+                            "category": {
+                                "type": "string",
+                                "description": "Only patterns of this category"
+                            },
+                            "tags": {
+                                "type": "array",
+                                "items": {"type": "string"},
+                                "description": "Only patterns with all of these tags"
+                            }
+                        },
+                        "required": ["bundle_path"]
+                    }
+                },
+                {
+                    "name": "get-statistics",
+                    "description": "Get statistics about the pattern database",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {}
+                    }
+                },
+                {
+                    "name": "get-help",
+                    "description": "Get usage instructions for this MCP server. Call this first to understand how to use the available tools effectively.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {}
+                    }
+                },
+                {
+                    "name": "set-endless-mode",
+                    "description": "Toggle Endless Mode to reduce token usage by ~95%. When enabled, all tool responses use a compact single-line format and full outputs are archived on disk with an obs_id. Use get-observation to retrieve full details when needed. Allows up to 20x more tool calls before the context window fills.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "enabled": {
+                                "type": "boolean",
+                                "description": "true to enable compact output (Endless Mode ON), false to restore full verbose output"
+                            }
+                        },
+                        "required": ["enabled"]
+                    }
+                },
+                {
+                    "name": "get-observation",
+                    "description": "Retrieve the full archived output of a previous tool call by its obs_id. Use this when Endless Mode is active and you need the complete details that were compressed.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "obs_id": {
+                                "type": "string",
+                                "description": "The observation UUID returned by a previous tool call in Endless Mode"
+                            }
+                        },
+                        "required": ["obs_id"]
+                    }
+                }
+            ]
+        }))
+    }
+
+    async fn handle_tool_call(
+        &mut self,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, String> {
+        let params = params.ok_or("Missing params")?;
+        let tool_name = params["name"].as_str().ok_or("Missing tool name")?;
+        let arguments = &params["arguments"];
+
+        tracing::info!("Calling tool: {}", tool_name);
+
+        match tool_name {
+            "analyze-project" => self.tool_analyze_project(arguments).await,
+            "analyze-diff" => self.tool_analyze_diff(arguments).await,
+            "get-module-graph" => self.tool_get_module_graph(arguments).await,
+            "find-symbol" => self.tool_find_symbol(arguments).await,
+            "get-file-outline" => self.tool_get_file_outline(arguments).await,
+            "lint-project" => self.tool_lint_project(arguments).await,
+            "security-scan" => self.tool_security_scan(arguments).await,
+            "get-patterns" => self.tool_get_patterns(arguments).await,
+            "search-patterns" => self.tool_search_patterns(arguments).await,
+            "train-pattern" => self.tool_train_pattern(arguments).await,
+            "update-pattern" => self.tool_update_pattern(arguments).await,
+            "delete-pattern" => self.tool_delete_pattern(arguments).await,
+            "deprecate-pattern" => self.tool_deprecate_pattern(arguments).await,
+            "list-pattern-revisions" => self.tool_list_pattern_revisions(arguments).await,
+            "diff-pattern-revisions" => self.tool_diff_pattern_revisions(arguments).await,
+            "rollback-pattern" => self.tool_rollback_pattern(arguments).await,
+            "rate-pattern" => self.tool_rate_pattern(arguments).await,
+            "mark-pattern-used" => self.tool_mark_pattern_used(arguments).await,
+            "review-pattern-candidates" => self.tool_review_pattern_candidates(arguments).await,
+            "export-patterns" => self.tool_export_patterns(arguments).await,
+            "import-patterns" => self.tool_import_patterns(arguments).await,
+            "get-statistics" => self.tool_get_statistics().await,
+            "get-help" => self.tool_get_help().await,
+            "set-endless-mode" => self.tool_set_endless_mode(arguments).await,
+            "get-observation" => self.tool_get_observation(arguments).await,
+            _ => Err(format!("Unknown tool: {}", tool_name)),
+        }
+    }
+
+    /// Analyzes a project and returns structured context.
+    async fn tool_analyze_project(
+        &mut self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let project_path = args["project_path"]
+            .as_str()
+            .ok_or("Missing project_path")?;
+
+        tracing::debug!(path = %project_path, "Analyzing project");
+
+        // Validate path exists
+        let path = PathBuf::from(project_path);
+        if !path.exists() {
+            return Err(format!(
+                "Project path does not exist: '{}'. Please provide an absolute path to a project directory.",
+                project_path
+            ));
+        }
+
+        if !path.is_dir() {
+            return Err(format!(
+                "Path is not a directory: '{}'. Please provide a directory path, not a file path.",
+                project_path
+            ));
+        }
+
+        tracing::debug!("Path validated, detecting project type");
+
+        // Use the new generic analyzer
+        let cache_dir = self
+            .config
+            .storage
+            .base_path
+            .join(&self.config.storage.cache_dir)
+            .join("analysis");
+        let project = GenericAnalyzer::analyze(path.as_path(), self.config.analyzer.clone(), cache_dir)
+            .await
+            .map_err(|e| {
+                tracing::warn!(error = %e, "Analysis failed");
+                format!("Failed to analyze project: {}. Make sure the directory contains a valid project file (Cargo.toml, package.json, .csproj, pyproject.toml, go.mod, or pom.xml).", e)
+            })?;
+
+        tracing::debug!(project_type = ?project.project_type, "Project analyzed successfully");
+
+        // Build context with patterns
+        let context_builder = self.context_builder();
+
+        // Propose recurring idioms as pattern candidates
+        let training = &self.config.training;
+        let candidates = if training.enabled && training.auto_extract_patterns {
+            let mined = context_builder
+                .extract_pattern_candidates(&project, training.min_pattern_occurrences);
+            let ids: Vec<String> = mined.iter().map(|c| c.pattern.id.clone()).collect();
+            let added = self.training_manager.propose_candidates(mined);
+            // Refreshed candidates are saved too, not only new ones
+            if let Err(e) = self.training_manager.save_candidates() {
+                tracing::warn!(error = %e, "Failed to save pattern candidates");
+            }
+            let pending: Vec<PatternCandidate> = self
+                .training_manager
+                .candidates()
+                .iter()
+                .filter(|c| ids.contains(&c.pattern.id))
+                .cloned()
+                .collect();
+            Some((pending, added))
+        } else {
+            None
+        };
+
+        let analysis = context_builder
+            .build_generic_analysis(project)
+            .await
+            .map_err(|e| format!("Failed to build analysis: {}", e))?;
+
+        // Save .rustscp to project directory (non-fatal on failure)
+        match crate::rustscp::ProjectContext::from_analysis(&analysis).save(&path) {
+            Ok(p) => tracing::info!(path = %p.display(), "Saved .rustscp"),
+            Err(e) => tracing::warn!(error = %e, "Failed to save .rustscp (non-fatal)"),
+        }
+
+        // Generate formatted context
+        let mut full_output = context_builder.build_generic_context_string(&analysis);
+        if let Some((ref pending, added)) = candidates {
+            if !pending.is_empty() {
+                full_output.push_str(&format!(
+                    "\n## Pattern Candidates\n\n{} recurring idioms ({} new); accept or reject them with `review-pattern-candidates`:\n\n",
+                    pending.len(),
+                    added
+                ));
+                for candidate in pending {
+                    full_output.push_str(&format!(
+                        "- `{}` {} ({} occurrences, {})\n",
+                        candidate.pattern.id,
+                        candidate.pattern.title,
+                        candidate.occurrences,
+                        candidate.pattern.category
+                    ));
+                }
+            }
+        }
+
+        let output = if self.endless_mode {
+            let mut compact = context_builder.build_compact_context_string(&analysis);
+            if let Some((ref pending, added)) = candidates {
+                if !pending.is_empty() {
+                    compact.push_str(&format!(" candidates:{}(+{})", pending.len(), added));
+                }
+            }
+            let obs_id: String = self
+                .observations
+                .save("analyze-project", &full_output)
+                .await
+                .map_err(|e| format!("Failed to archive observation: {}", e))?;
+            format!(
+                "{}\nobs_id:{} (call get-observation to see full analysis)",
+                compact, obs_id
+            )
+        } else {
+            full_output
+        };
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    /// Analyzes only the files changed in a diff and returns review context.
+    async fn tool_analyze_diff(
+        &self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let project_path = args["project_path"]
+            .as_str()
+            .ok_or("Missing project_path")?;
+
+        let path = PathBuf::from(project_path);
+        if !path.is_dir() {
+            return Err(format!(
+                "Project path is not a directory: '{}'. Please provide an absolute path to a project directory.",
+                project_path
+            ));
+        }
+
+        let (base, changed_files) = if let Some(diff) = args["diff"].as_str() {
+            ("diff".to_string(), diff::parse_unified_diff(diff))
+        } else {
+            let base = args["base"].as_str().unwrap_or("HEAD").to_string();
+            let root = path.clone();
+            let git_base = base.clone();
+            let changed =
+                tokio::task::spawn_blocking(move || diff::changes_against_ref(&root, &git_base))
+                    .await
+                    .map_err(|e| format!("Diff task failed: {}", e))?
+                    .map_err(|e| format!("Failed to diff against '{}': {}", base, e))?;
+            (base, changed)
+        };
+
+        tracing::debug!(path = %project_path, base = %base, files = changed_files.len(), "Analyzing diff");
+
+        let project = GenericAnalyzer::new(self.config.analyzer.clone())
+            .analyze_changed_files(path.as_path(), &changed_files)
+            .await
+            .map_err(|e| format!("Failed to analyze project: {}", e))?;
+
+        let context_builder = self.context_builder();
+        let diff_analysis = context_builder
+            .build_diff_analysis(project, &base, changed_files)
+            .await
+            .map_err(|e| format!("Failed to build analysis: {}", e))?;
+
+        let full_output = context_builder.build_diff_context_string(&diff_analysis);
+
+        let output = if self.endless_mode {
+            let compact = context_builder.build_compact_diff_string(&diff_analysis);
+            let obs_id: String = self
+                .observations
+                .save("analyze-diff", &full_output)
+                .await
+                .map_err(|e| format!("Failed to archive observation: {}", e))?;
+            format!(
+                "{}\nobs_id:{} (call get-observation to see full review context)",
+                compact, obs_id
+            )
+        } else {
+            full_output
+        };
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    fn context_builder(&self) -> ContextBuilder {
+        ContextBuilder::new()
+            .with_training_manager(self.training_manager.clone())
+            .with_rules(self.rules.clone())
+            .with_health(self.health.clone())
+    }
+
+    /// Analyze a project directory through the per-project analysis cache.
+    async fn analyze_cached(&self, project_path: &str) -> Result<Project, String> {
+        let path = PathBuf::from(project_path);
+        if !path.is_dir() {
+            return Err(format!(
+                "Project path is not a directory: '{}'. Please provide an absolute path to a project directory.",
+                project_path
+            ));
+        }
+
+        let cache_dir = self
+            .config
+            .storage
+            .base_path
+            .join(&self.config.storage.cache_dir)
+            .join("analysis");
+        GenericAnalyzer::analyze(path.as_path(), self.config.analyzer.clone(), cache_dir)
+            .await
+            .map_err(|e| format!("Failed to analyze project: {}", e))
+    }
+
+    /// Reports the internal module dependency graph of a project.
+    async fn tool_get_module_graph(
+        &self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let project_path = args["project_path"]
+            .as_str()
+            .ok_or("Missing project_path")?;
+        let module = args["module"].as_str();
+
+        let project = self.analyze_cached(project_path).await?;
+
+        let graph = ModuleGraph::build(&project);
+        let summary = graph.summary();
+
+        let mut output = format!("# Module Graph: {}\n\n", project.name);
+        output.push_str(&format!(
+            "- Modules: {}\n- Internal imports: {}\n- Import cycles: {}\n- Layering violations: {}\n\n",
+            summary.modules, summary.edges, summary.cycle_count, summary.violation_count
+        ));
+
+        if let Some(module) = module {
+            if !graph.contains(module) {
+                return Err(format!(
+                    "Module not found: '{}'. Use a file path relative to the project root, a C# namespace or a Go package directory.",
+                    module
+                ));
+            }
+            output.push_str(&format!("## {}\n\n### Imports\n", module));
+            for dependency in graph.dependencies_of(module) {
+                output.push_str(&format!("- {}\n", dependency));
+            }
+            output.push_str("\n### Imported By\n");
+            for dependent in graph.dependents_of(module) {
+                output.push_str(&format!("- {}\n", dependent));
+            }
+            output.push('\n');
+        }
+
+        if !summary.most_depended.is_empty() {
+            output.push_str("## Most Depended-On\n");
+            for entry in &summary.most_depended {
+                output.push_str(&format!(
+                    "- {}: {} dependents\n",
+                    entry.module, entry.dependents
+                ));
+            }
+            output.push('\n');
+        }
+        if !summary.cycles.is_empty() {
+            output.push_str("## Cycles\n");
+            for cycle in &summary.cycles {
+                output.push_str(&format!("- {}\n", cycle.join(" ↔ ")));
+            }
+            output.push('\n');
+        }
+        if !summary.layer_violations.is_empty() {
+            output.push_str("## Layering Violations\n");
+            for violation in &summary.layer_violations {
+                output.push_str(&format!(
+                    "- {} ({}) → {} ({})\n",
+                    violation.from, violation.from_layer, violation.to, violation.to_layer
+                ));
+            }
+        }
+
+        let output = if self.endless_mode {
+            let obs_id: String = self
+                .observations
+                .save("get-module-graph", &output)
+                .await
+                .map_err(|e| format!("Failed to archive observation: {}", e))?;
+            let top = summary
+                .most_depended
+                .iter()
+                .take(3)
+                .map(|m| format!("{}({})", m.module, m.dependents))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "[GRAPH:{}] modules:{} edges:{} cycles:{} violations:{} top:[{}]\nobs_id:{} (call get-observation for full graph)",
+                project.name,
+                summary.modules,
+                summary.edges,
+                summary.cycle_count,
+                summary.violation_count,
+                top,
+                obs_id
+            )
+        } else {
+            output
+        };
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    /// Locates symbols by (fuzzy) name and/or kind across a project.
+    async fn tool_find_symbol(
+        &self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let project_path = args["project_path"]
+            .as_str()
+            .ok_or("Missing project_path")?;
+        let query = args["query"].as_str().filter(|q| !q.trim().is_empty());
+        let kind = args["kind"].as_str().filter(|k| !k.trim().is_empty());
+        if query.is_none() && kind.is_none() {
+            return Err("Provide a 'query', a 'kind', or both".to_string());
+        }
+        let max_results = args["max_results"].as_u64().unwrap_or(20) as usize;
+
+        let project = self.analyze_cached(project_path).await?;
+        let index = SymbolIndex::build(&project);
+        let matches = index.search(query, kind, max_results);
+
+        let lines = |m: &SymbolMatch| {
+            m.range
+                .map(|r| format!(":{}-{}", r.start.line, r.end.line))
+                .unwrap_or_default()
+        };
+
+        let output = if index.is_empty() {
+            format!(
+                "No symbols found in {}. Symbols are extracted from C#, Rust, Python, JavaScript, TypeScript, Go and Java files.",
+                project.name
+            )
+        } else if matches.is_empty() {
+            format!(
+                "No symbols matching {} among {} symbols in {}.",
+                [
+                    query.map(|q| format!("'{}'", q)),
+                    kind.map(|k| format!("kind '{}'", k))
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" and "),
+                index.len(),
+                project.name
+            )
+        } else if self.endless_mode {
+            // Locations are already compact; signatures are dropped
+            matches
+                .iter()
+                .map(|m| {
+                    format!(
+                        "{} {} {}{}",
+                        m.kind,
+                        m.qualified_name,
+                        m.path.display(),
+                        lines(m)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            let mut output = format!("# Symbols ({} of {})\n\n", matches.len(), index.len());
+            for m in &matches {
+                output.push_str(&format!(
+                    "- {} **{}** — {}{}\n",
+                    m.kind,
+                    m.qualified_name,
+                    m.path.display(),
+                    lines(m)
+                ));
+                if let Some(ref signature) = m.signature {
+                    output.push_str(&format!("  `{}`\n", signature));
+                }
+            }
+            output
+        };
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    /// Runs the tree-sitter lint rules over a project's source files.
+    async fn tool_lint_project(
+        &self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let project_path = args["project_path"]
+            .as_str()
+            .ok_or("Missing project_path")?;
+        let min_severity = match args["min_severity"].as_str() {
+            None | Some("info") => SeverityLevel::Info,
+            Some("warning") => SeverityLevel::Warning,
+            Some("error") => SeverityLevel::Error,
+            Some(other) => {
+                return Err(format!(
+                    "Invalid min_severity '{}': use info, warning or error",
+                    other
+                ))
+            }
+        };
+        let rule_filter = args["rule"].as_str().filter(|r| !r.trim().is_empty());
+
+        if self.rules.lint_rule_count() == 0 {
+            return Err(
+                "No lint rules loaded. Add rules with a 'query' condition to the rules directory."
+                    .to_string(),
+            );
+        }
+
+        let project = self.analyze_cached(project_path).await?;
+        let framework = self
+            .context_builder()
+            .detect_framework_from_project(&project);
+
+        let rules = self.rules.clone();
+        let (project, findings) = tokio::task::spawn_blocking(move || {
+            let findings = rules.lint(&project, &framework);
+            (project, findings)
+        })
+        .await
+        .map_err(|e| format!("Lint task failed: {}", e))?;
+
+        let mut findings: Vec<_> = findings
+            .into_iter()
+            .filter(|f| f.severity >= min_severity)
+            .filter(|f| rule_filter.is_none_or(|r| f.rule_id.as_deref() == Some(r)))
+            .collect();
+        findings.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+
+        let count = |level: SeverityLevel| findings.iter().filter(|f| f.severity == level).count();
+        let (errors, warnings, infos) = (
+            count(SeverityLevel::Error),
+            count(SeverityLevel::Warning),
+            count(SeverityLevel::Info),
+        );
+
+        let mut output = format!("# Lint: {}\n\n", project.name);
+        output.push_str(&format!(
+            "- Findings: {} ({} errors, {} warnings, {} info)\n\n",
+            findings.len(),
+            errors,
+            warnings,
+            infos
+        ));
+        if findings.is_empty() {
+            output.push_str("No findings.\n");
+        }
+
+        let mut current_file = None;
+        for finding in &findings {
+            let Some(ref file) = finding.file else {
+                continue;
+            };
+            if current_file != Some(file) {
+                if current_file.is_some() {
+                    output.push('\n');
+                }
+                let relative = file.strip_prefix(&project.path).unwrap_or(file);
+                output.push_str(&format!("## {}\n", relative.display()));
+                current_file = Some(file);
+            }
+            let position = finding.range.map(|r| r.to_string()).unwrap_or_default();
+            output.push_str(&format!(
+                "- {} {:?} [{}] {}",
+                position,
+                finding.severity,
+                finding.rule_id.as_deref().unwrap_or("-"),
+                finding.message
+            ));
+            if let Some(ref pattern) = finding.pattern_id {
+                output.push_str(&format!(" (pattern: `{}`)", pattern));
+            }
+            output.push('\n');
+        }
+
+        let output = if self.endless_mode {
+            let obs_id: String = self
+                .observations
+                .save("lint-project", &output)
+                .await
+                .map_err(|e| format!("Failed to archive observation: {}", e))?;
+            let mut per_rule: Vec<(&str, usize)> = Vec::new();
+            for finding in &findings {
+                let id = finding.rule_id.as_deref().unwrap_or("-");
+                match per_rule.iter_mut().find(|(r, _)| *r == id) {
+                    Some((_, n)) => *n += 1,
+                    None => per_rule.push((id, 1)),
+                }
+            }
+            per_rule.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+            let top = per_rule
+                .iter()
+                .take(3)
+                .map(|(r, n)| format!("{}({})", r, n))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "[LINT:{}] findings:{} errors:{} warnings:{} info:{} top:[{}]\nobs_id:{} (call get-observation for all findings)",
+                project.name,
+                findings.len(),
+                errors,
+                warnings,
+                infos,
+                top,
+                obs_id
+            )
+        } else {
+            output
+        };
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    /// Scans a project for committed secrets and risky configuration.
+    async fn tool_security_scan(
+        &self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let project_path = args["project_path"]
+            .as_str()
+            .ok_or("Missing project_path")?;
+
+        let scanner = SecurityScanner::new(&self.config.analyzer)
+            .map_err(|e| format!("Failed to create security scanner: {}", e))?;
+        let project = self.analyze_cached(project_path).await?;
+        let (project, scan) = tokio::task::spawn_blocking(move || {
+            let scan = scanner.scan(&project);
+            (project, scan)
+        })
+        .await
+        .map_err(|e| format!("Security scan task failed: {}", e))?;
+
+        let mut output = format!("# Security scan: {}\n\n", project.name);
+        output.push_str(&format!(
+            "- Findings: {} ({} files scanned)\n\n",
+            scan.findings.len(),
+            scan.files_scanned
+        ));
+        if scan.findings.is_empty() {
+            output.push_str("No secrets or risky configuration found.\n");
+        }
+
+        let mut current_file = None;
+        for finding in &scan.findings {
+            let Some(ref file) = finding.file else {
+                continue;
+            };
+            if current_file != Some(file) {
+                if current_file.is_some() {
+                    output.push('\n');
+                }
+                let relative = file.strip_prefix(&project.path).unwrap_or(file);
+                output.push_str(&format!("## {}\n", relative.display()));
+                current_file = Some(file);
+            }
+            let position = finding.range.map(|r| format!("{} ", r)).unwrap_or_default();
+            output.push_str(&format!(
+                "- {}[{}] {}\n",
+                position,
+                finding.rule_id.as_deref().unwrap_or("-"),
+                finding.message
+            ));
+        }
+
+        let output = if self.endless_mode {
+            let obs_id: String = self
+                .observations
+                .save("security-scan", &output)
+                .await
+                .map_err(|e| format!("Failed to archive observation: {}", e))?;
+            let mut files: Vec<String> = Vec::new();
+            for file in scan.findings.iter().filter_map(|f| f.file.as_ref()) {
+                let relative = file.strip_prefix(&project.path).unwrap_or(file);
+                let relative = relative.display().to_string();
+                if !files.contains(&relative) {
+                    files.push(relative);
+                }
+            }
+            format!(
+                "[SECURITY:{}] findings:{} files:{} top:[{}]\nobs_id:{} (call get-observation for all findings)",
+                project.name,
+                scan.findings.len(),
+                scan.files_scanned,
+                files.iter().take(3).cloned().collect::<Vec<_>>().join(", "),
+                obs_id
+            )
+        } else {
+            output
+        };
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    /// Returns the nested symbol outline of one file, without its contents.
+    async fn tool_get_file_outline(
+        &self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let file_path = args["file_path"].as_str().ok_or("Missing file_path")?;
+        let path = match args["project_path"].as_str() {
+            Some(project) => PathBuf::from(project).join(file_path),
+            None => PathBuf::from(file_path),
+        };
+        if !path.is_file() {
+            return Err(format!(
+                "File not found: '{}'. Pass an absolute path, or a path relative to project_path.",
+                path.display()
+            ));
+        }
+
+        let language = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        if Grammar::from_extension(&language).is_none() {
+            return Err(format!(
+                "Outlines are not supported for '.{}' files. Supported: cs, rs, py, js, jsx, ts, tsx, go, java.",
+                language
+            ));
+        }
+
+        let source = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let symbols = SymbolExtractor::new()
+            .extract(&path, &language, &source)
+            .map_err(|e| format!("Failed to parse file: {}", e))?
+            .symbols;
+
+        let output = if self.endless_mode {
+            let mut compact = format!(
+                "[OUTLINE:{}] lines:{} ",
+                path.display(),
+                source.lines().count()
+            );
+            Self::compact_outline(&symbols, &mut compact);
+            compact
+        } else {
+            let mut output = format!(
+                "# Outline: {} ({} lines)\n\n",
+                path.display(),
+                source.lines().count()
+            );
+            if symbols.is_empty() {
+                output.push_str("No symbols found.\n");
+            }
+            Self::markdown_outline(&symbols, 0, &mut output);
+            output
+        };
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    fn markdown_outline(symbols: &[Symbol], depth: usize, out: &mut String) {
+        for symbol in symbols {
+            let lines = symbol
+                .range
+                .map(|r| format!(" (L{}-{})", r.start.line, r.end.line))
+                .unwrap_or_default();
+            let signature = symbol
+                .signature
+                .as_ref()
+                .map(|s| format!(": `{}`", s))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "{}- {} **{}**{}{}\n",
+                "  ".repeat(depth),
+                symbol.kind,
+                symbol.name,
+                lines,
+                signature
+            ));
+            Self::markdown_outline(&symbol.children, depth + 1, out);
+        }
+    }
+
+    /// `struct Config[2-4]{name} impl Config[6-12]{load}`
+    fn compact_outline(symbols: &[Symbol], out: &mut String) {
+        for (i, symbol) in symbols.iter().enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            out.push_str(&format!("{} {}", symbol.kind, symbol.name));
+            if let Some(range) = symbol.range {
+                out.push_str(&format!("[{}-{}]", range.start.line, range.end.line));
+            }
+            if !symbol.children.is_empty() {
+                let names: Vec<&str> = symbol.children.iter().map(|c| c.name.as_str()).collect();
+                out.push_str(&format!("{{{}}}", names.join(",")));
+            }
+        }
+    }
+
+    // Tool: get-patterns
+    async fn tool_get_patterns(
+        &mut self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let framework = args["framework"].as_str().ok_or("Missing framework")?;
+        let category = args["category"].as_str();
+
+        let patterns = if let Some(cat) = category {
+            self.training_manager
+                .search_by_framework_and_category(framework, cat)
+        } else {
+            let criteria = SearchCriteria {
+                query: None,
+                category: None,
+                framework: Some(framework.to_string()),
+                tags: vec![],
+                min_score: 0.0,
+                max_results: None,
+                mode: SearchMode::Keyword,
+            };
+            self.training_manager
+                .search_patterns(&criteria)
+                .into_iter()
+                .map(|(p, _)| p)
+                .collect()
+        };
+
+        // Point out misspelled or unknown names when nothing matched
+        let hint = if patterns.is_empty() {
+            let criteria = SearchCriteria {
+                query: None,
+                category: category.map(str::to_string),
+                framework: Some(framework.to_string()),
+                tags: vec![],
+                min_score: 0.0,
+                max_results: None,
+                mode: SearchMode::Keyword,
+            };
+            let frameworks = self.training_manager.get_frameworks();
+            did_you_mean(&self.training_manager.suggest_corrections(&criteria)).or_else(|| {
+                (!frameworks.is_empty() && !frameworks.iter().any(|f| f == framework))
+                    .then(|| format!("Available frameworks: {}", frameworks.join(", ")))
+            })
+        } else {
+            None
+        };
+
+        // Build verbose output (always needed: either returned directly or archived)
+        let mut full_output = String::new();
+        full_output.push_str(&format!("# Patterns for {}\n\n", framework));
+
+        if patterns.is_empty() {
+            full_output.push_str("No patterns found.\n");
+            if let Some(ref hint) = hint {
+                full_output.push_str(&format!("\n{}\n", hint));
+            }
+        } else {
+            for pattern in &patterns {
+                full_output.push_str(&format!("## {}\n\n", pattern.title));
+                full_output.push_str(&format!("**Category:** {}\n", pattern.category));
+                full_output.push_str(&format!("**ID:** {}\n", pattern.id));
+                full_output.push_str(&format!("{}\n\n", pattern.description));
+                full_output.push_str("```csharp\n");
+                full_output.push_str(&pattern.code);
+                full_output.push_str("\n```\n\n");
+                full_output.push_str(&format!("**Tags:** {}\n", pattern.tags.join(", ")));
+                full_output.push_str(&format!("**Usage Count:** {}\n", pattern.usage_count));
+                full_output.push_str(&format!("**Relevance:** {:.2}\n", pattern.relevance_score));
+                if let Some(votes) = self.training_manager.feedback(&pattern.id) {
+                    full_output.push_str(&format!(
+                        "**Votes:** {} helpful, {} not helpful\n",
+                        votes.helpful, votes.not_helpful
+                    ));
+                }
+                full_output.push_str(&format!(
+                    "**Updated At:** {}\n\n",
+                    format_timestamp(pattern.updated_at)
+                ));
+                full_output.push_str("---\n\n");
+            }
+        }
+
+        let output = if self.endless_mode {
+            let compact = if patterns.is_empty() {
+                match hint {
+                    Some(ref hint) => format!("Patterns {}(0): none | {}", framework, hint),
+                    None => format!("Patterns {}(0): none", framework),
+                }
+            } else {
+                let entries: Vec<String> = patterns
+                    .iter()
+                    .take(10)
+                    .enumerate()
+                    .map(|(i, p)| {
+                        let tag = p.tags.first().map(|t| t.as_str()).unwrap_or("general");
+                        format!("{}.{}[{},{:.2}]", i + 1, p.title, tag, p.relevance_score)
+                    })
+                    .collect();
+                format!(
+                    "Patterns {}({}): {}",
+                    framework,
+                    patterns.len(),
+                    entries.join(" ")
+                )
+            };
+            let listed: Vec<String> = patterns.iter().take(10).map(|p| p.id.clone()).collect();
+            let obs_id: String = self
+                .observations
+                .save("get-patterns", &full_output)
+                .await
+                .map_err(|e| format!("Failed to archive observation: {}", e))?;
+            self.remember_returned(&obs_id, listed);
+            format!(
+                "{}\nobs_id:{} (call get-observation for full code examples)",
+                compact, obs_id
+            )
+        } else {
+            full_output
+        };
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    // Tool: search-patterns
+    async fn tool_search_patterns(
+        &mut self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let mode = match args["mode"].as_str() {
+            None | Some("keyword") => SearchMode::Keyword,
+            Some("semantic") => SearchMode::Semantic,
+            Some("hybrid") => SearchMode::Hybrid,
+            Some(other) => {
+                return Err(format!(
+                    "Invalid mode '{}': use keyword, semantic or hybrid",
+                    other
+                ))
+            }
+        };
+        let criteria = SearchCriteria {
+            query: args["query"].as_str().map(|s| s.to_string()),
+            category: args["category"].as_str().map(|s| s.to_string()),
+            framework: args["framework"].as_str().map(|s| s.to_string()),
+            tags: args["tags"]
+                .as_array()
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            min_score: args["min_score"].as_f64().unwrap_or(0.0) as f32,
+            max_results: args["max_results"].as_u64().map(|n| n as usize),
+            mode,
+        };
+
+        let results = self.training_manager.search_patterns(&criteria);
+        // Typos in the query still find results, but are pointed out too
+        let hint = did_you_mean(&self.training_manager.suggest_corrections(&criteria));
+
+        // Build verbose output (always needed: returned directly or archived)
+        let mut full_output = String::new();
+        full_output.push_str("# Pattern Search Results\n\n");
+        full_output.push_str(&format!("Found {} patterns\n\n", results.len()));
+        if mode != SearchMode::Keyword && !self.training_manager.has_semantic_index() {
+            full_output.push_str(
+                "Semantic search is disabled (training.semantic_search); showing keyword matches.\n\n",
+            );
+        }
+        if let Some(ref hint) = hint {
+            full_output.push_str(&format!("{}\n\n", hint));
+        }
+
+        for (pattern, score) in &results {
+            full_output.push_str(&format!("## {} (Score: {:.2})\n\n", pattern.title, score));
+            full_output.push_str(&format!(
+                "**ID:** {} | **Framework:** {} | **Category:** {} | **Updated At:** {}\n",
+                pattern.id,
+                pattern.framework,
+                pattern.category,
+                format_timestamp(pattern.updated_at)
+            ));
+            full_output.push_str(&format!("{}\n\n", pattern.description));
+            full_output.push_str("```csharp\n");
+            full_output.push_str(&pattern.code);
+            full_output.push_str("\n```\n\n");
+            full_output.push_str("---\n\n");
+        }
+
+        let output = if self.endless_mode {
+            let mut compact = if results.is_empty() {
+                "Found 0: (no matches)".to_string()
+            } else {
+                let entries: Vec<String> = results
+                    .iter()
+                    .take(10)
+                    .map(|(p, score)| {
+                        format!("[{:.2}]{}|{}|{}", score, p.title, p.category, p.framework)
+                    })
+                    .collect();
+                format!("Found {}: {}", results.len(), entries.join(" "))
+            };
+            if let Some(ref hint) = hint {
+                compact.push_str(&format!(" | {}", hint));
+            }
+            let listed: Vec<String> = results.iter().take(10).map(|(p, _)| p.id.clone()).collect();
+            let obs_id: String = self
+                .observations
+                .save("search-patterns", &full_output)
+                .await
+                .map_err(|e| format!("Failed to archive observation: {}", e))?;
+            self.remember_returned(&obs_id, listed);
+            format!(
+                "{}\nobs_id:{} (call get-observation for full code examples)",
+                compact, obs_id
+            )
+        } else {
+            full_output
+        };
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    // Tool: train-pattern
+    async fn tool_train_pattern(
+        &mut self,
+        args: &serde_json::Value,
     ) -> Result<serde_json::Value, String> {
-        let framework = args["framework"].as_str().ok_or("Missing framework")?;
-        let category = args["category"].as_str();
+        let pattern = CodePattern {
+            id: args["id"].as_str().ok_or("Missing id")?.to_string(),
+            category: args["category"]
+                .as_str()
+                .ok_or("Missing category")?
+                .to_string(),
+            framework: args["framework"]
+                .as_str()
+                .ok_or("Missing framework")?
+                .to_string(),
+            version: args["version"].as_str().unwrap_or("10.0").to_string(),
+            title: args["title"].as_str().ok_or("Missing title")?.to_string(),
+            description: args["description"]
+                .as_str()
+                .ok_or("Missing description")?
+                .to_string(),
+            code: args["code"].as_str().ok_or("Missing code")?.to_string(),
+            tags: args["tags"]
+                .as_array()
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            usage_count: 0,
+            relevance_score: 0.8, // Default relevance
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            deprecated: None,
+        };
 
-        let patterns = if let Some(cat) = category {
-            self.training_manager
-                .search_by_framework_and_category(framework, cat)
+        // Add pattern with validation (prevents path traversal)
+        self.training_manager
+            .add_pattern(pattern.clone())
+            .map_err(|e| match e {
+                TrainingError::DuplicatePattern(_) => {
+                    format!("{}. Use update-pattern to change it", e)
+                }
+                _ => format!("Invalid pattern: {}", e),
+            })?;
+
+        // Save to disk
+        self.training_manager
+            .save_patterns()
+            .await
+            .map_err(|e| format!("Failed to save patterns: {}", e))?;
+
+        let updated_at = self
+            .training_manager
+            .get_pattern_by_id(&pattern.id)
+            .map(|p| format_timestamp(p.updated_at))
+            .unwrap_or_default();
+        let output = format!(
+            "✅ Pattern '{}' added successfully!\n\n**ID:** {}\n**Category:** {}\n**Framework:** {}\n**Updated At:** {}",
+            pattern.title, pattern.id, pattern.category, pattern.framework, updated_at
+        );
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    // Tool: update-pattern
+    async fn tool_update_pattern(
+        &mut self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let id = args["id"].as_str().ok_or("Missing id")?;
+        let expected = expected_updated_at(args)?;
+        let text = |key: &str| args[key].as_str().map(str::to_string);
+        let update = PatternUpdate {
+            category: text("category"),
+            framework: text("framework"),
+            version: text("version"),
+            title: text("title"),
+            description: text("description"),
+            code: text("code"),
+            tags: args["tags"].as_array().map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect()
+            }),
+        };
+
+        let pattern = self
+            .training_manager
+            .update_pattern(id, expected, update)
+            .map_err(|e| format!("Failed to update pattern: {}", e))?
+            .clone();
+        self.training_manager
+            .save_patterns()
+            .await
+            .map_err(|e| format!("Failed to save patterns: {}", e))?;
+
+        let output = format!(
+            "✅ Pattern '{}' updated\n\n**ID:** {}\n**Category:** {}\n**Framework:** {}\n**Updated At:** {}",
+            pattern.title,
+            pattern.id,
+            pattern.category,
+            pattern.framework,
+            format_timestamp(pattern.updated_at)
+        );
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    // Tool: delete-pattern
+    async fn tool_delete_pattern(
+        &mut self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let id = args["id"].as_str().ok_or("Missing id")?;
+        let expected = expected_updated_at(args)?;
+
+        let pattern = self
+            .training_manager
+            .delete_pattern(id, expected)
+            .map_err(|e| format!("Failed to delete pattern: {}", e))?;
+        self.training_manager
+            .save_patterns()
+            .await
+            .map_err(|e| format!("Failed to save patterns: {}", e))?;
+
+        let output = format!(
+            "🗑️ Pattern '{}' deleted\n\n**ID:** {}\n**Framework:** {}",
+            pattern.title, pattern.id, pattern.framework
+        );
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    // Tool: deprecate-pattern
+    async fn tool_deprecate_pattern(
+        &mut self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let id = args["id"].as_str().ok_or("Missing id")?;
+        let expected = expected_updated_at(args)?;
+
+        let pattern = if args["restore"].as_bool().unwrap_or(false) {
+            self.training_manager.restore_pattern(id, expected)
         } else {
-            let criteria = SearchCriteria {
-                query: None,
-                category: None,
-                framework: Some(framework.to_string()),
-                tags: vec![],
-                min_score: 0.0,
-                max_results: None,
+            let reason = args["reason"]
+                .as_str()
+                .ok_or("Missing reason (or pass restore: true)")?;
+            self.training_manager.deprecate_pattern(
+                id,
+                expected,
+                reason,
+                args["replaced_by"].as_str(),
+            )
+        }
+        .map_err(|e| format!("Failed to deprecate pattern: {}", e))?
+        .clone();
+        self.training_manager
+            .save_patterns()
+            .await
+            .map_err(|e| format!("Failed to save patterns: {}", e))?;
+
+        let status = match pattern.deprecated {
+            Some(ref deprecation) => format!(
+                "⚠️ Pattern '{}' deprecated: {}{}",
+                pattern.title,
+                deprecation.reason,
+                deprecation
+                    .replaced_by
+                    .as_ref()
+                    .map(|r| format!(" (use '{}' instead)", r))
+                    .unwrap_or_default()
+            ),
+            None => format!("✅ Pattern '{}' restored", pattern.title),
+        };
+        let output = format!(
+            "{}\n\n**ID:** {}\n**Updated At:** {}",
+            status,
+            pattern.id,
+            format_timestamp(pattern.updated_at)
+        );
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    // Tool: list-pattern-revisions
+    async fn tool_list_pattern_revisions(
+        &self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let id = args["id"].as_str().ok_or("Missing id")?;
+        let revisions = self
+            .training_manager
+            .revisions(id)
+            .ok_or_else(|| format!("No revision history for pattern '{}'", id))?;
+
+        let describe = |index: usize| {
+            let revision = &revisions[index];
+            let action = match revision.rolled_back_to {
+                Some(target) => format!("{} to r{}", revision.action.as_str(), target),
+                None => revision.action.as_str().to_string(),
             };
-            self.training_manager
-                .search_patterns(&criteria)
-                .into_iter()
-                .map(|(p, _)| p)
-                .collect()
+            let fields = match index.checked_sub(1) {
+                Some(prev) => {
+                    changed_fields(&revisions[prev].pattern, &revision.pattern).join(", ")
+                }
+                None => String::new(),
+            };
+            (action, fields)
         };
 
-        // Build verbose output (always needed: either returned directly or archived)
-        let mut full_output = String::new();
-        full_output.push_str(&format!("# Patterns for {}\n\n", framework));
+        let mut full_output = format!("# Revisions of '{}'\n\n", id);
+        full_output.push_str("| Rev | Timestamp | Author | Action | Changed |\n");
+        full_output.push_str("|-----|-----------|--------|--------|---------|\n");
+        for (index, revision) in revisions.iter().enumerate() {
+            let (action, fields) = describe(index);
+            full_output.push_str(&format!(
+                "| r{} | {} | {} | {} | {} |\n",
+                revision.revision,
+                format_timestamp(revision.timestamp),
+                revision.author,
+                action,
+                if fields.is_empty() { "-" } else { &fields }
+            ));
+        }
+        if self.training_manager.get_pattern_by_id(id).is_none() {
+            full_output.push_str("\nThe pattern is deleted; rollback-pattern can re-create it.\n");
+        }
 
-        if patterns.is_empty() {
-            full_output.push_str("No patterns found.\n");
+        let output = if self.endless_mode {
+            let entries: Vec<String> = (0..revisions.len())
+                .rev()
+                .take(10)
+                .map(|index| {
+                    let (action, fields) = describe(index);
+                    format!("r{}:{}[{}]", revisions[index].revision, action, fields)
+                })
+                .collect();
+            let obs_id: String = self
+                .observations
+                .save("list-pattern-revisions", &full_output)
+                .await
+                .map_err(|e| format!("Failed to archive observation: {}", e))?;
+            format!(
+                "{} revisions: {}\nobs_id:{} (call get-observation for authors and timestamps)",
+                revisions.len(),
+                entries.join(" "),
+                obs_id
+            )
         } else {
-            for pattern in &patterns {
-                full_output.push_str(&format!("## {}\n\n", pattern.title));
-                full_output.push_str(&format!("**Category:** {}\n", pattern.category));
-                full_output.push_str(&format!("**ID:** {}\n", pattern.id));
-                full_output.push_str(&format!("{}\n\n", pattern.description));
-                full_output.push_str("```csharp\n");
-                full_output.push_str(&pattern.code);
-                full_output.push_str("\n```\n\n");
-                full_output.push_str(&format!("**Tags:** {}\n", pattern.tags.join(", ")));
-                full_output.push_str(&format!("**Usage Count:** {}\n", pattern.usage_count));
-                full_output.push_str(&format!(
-                    "**Relevance:** {:.2}\n\n",
-                    pattern.relevance_score
-                ));
-                full_output.push_str("---\n\n");
+            full_output
+        };
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    // Tool: diff-pattern-revisions
+    async fn tool_diff_pattern_revisions(
+        &self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let id = args["id"].as_str().ok_or("Missing id")?;
+        let revisions = self
+            .training_manager
+            .revisions(id)
+            .ok_or_else(|| format!("No revision history for pattern '{}'", id))?;
+        let find = |number: u64| {
+            revisions
+                .iter()
+                .find(|r| u64::from(r.revision) == number)
+                .ok_or_else(|| format!("Pattern '{}' has no revision {}", id, number))
+        };
+        let from = find(args["from"].as_u64().ok_or("Missing from")?)?;
+        let to = match args["to"].as_u64() {
+            Some(number) => find(number)?,
+            None => revisions.last().ok_or("Empty revision history")?,
+        };
+        let (old, new) = (&from.pattern, &to.pattern);
+        let fields = changed_fields(old, new);
+
+        let mut full_output = format!(
+            "# Diff of '{}': r{} → r{}\n\n",
+            id, from.revision, to.revision
+        );
+        if fields.is_empty() {
+            full_output.push_str("No changes.\n");
+        }
+        for field in &fields {
+            let (before, after) = match *field {
+                "category" => (old.category.clone(), new.category.clone()),
+                "framework" => (old.framework.clone(), new.framework.clone()),
+                "version" => (old.version.clone(), new.version.clone()),
+                "title" => (old.title.clone(), new.title.clone()),
+                "tags" => (old.tags.join(", "), new.tags.join(", ")),
+                "deprecated" => (
+                    old.deprecated.is_some().to_string(),
+                    new.deprecated.is_some().to_string(),
+                ),
+                _ => continue,
+            };
+            full_output.push_str(&format!("- **{}:** `{}` → `{}`\n", field, before, after));
+        }
+        for (name, before, after) in [
+            ("Description", &old.description, &new.description),
+            ("Code", &old.code, &new.code),
+        ] {
+            let diff = unified_diff(before, after);
+            if !diff.is_empty() {
+                if !full_output.ends_with("\n\n") {
+                    full_output.push('\n');
+                }
+                full_output.push_str(&format!("## {}\n\n```diff\n{}```\n", name, diff));
             }
         }
 
         let output = if self.endless_mode {
-            let compact = if patterns.is_empty() {
-                format!("Patterns {}(0): none", framework)
-            } else {
-                let entries: Vec<String> = patterns
-                    .iter()
-                    .take(10)
-                    .enumerate()
-                    .map(|(i, p)| {
-                        let tag = p.tags.first().map(|t| t.as_str()).unwrap_or("general");
-                        format!("{}.{}[{},{:.2}]", i + 1, p.title, tag, p.relevance_score)
-                    })
-                    .collect();
-                format!(
-                    "Patterns {}({}): {}",
-                    framework,
-                    patterns.len(),
-                    entries.join(" ")
-                )
-            };
             let obs_id: String = self
                 .observations
-                .save("get-patterns", &full_output)
+                .save("diff-pattern-revisions", &full_output)
                 .await
                 .map_err(|e| format!("Failed to archive observation: {}", e))?;
             format!(
-                "{}\nobs_id:{} (call get-observation for full code examples)",
-                compact, obs_id
+                "r{}→r{} changed:[{}]\nobs_id:{} (call get-observation for the diff)",
+                from.revision,
+                to.revision,
+                fields.join(","),
+                obs_id
             )
         } else {
             full_output
@@ -711,72 +2354,302 @@ impl Server {
         }))
     }
 
-    // Tool: search-patterns
-    async fn tool_search_patterns(
-        &self,
+    // Tool: rate-pattern
+    async fn tool_rate_pattern(
+        &mut self,
         args: &serde_json::Value,
     ) -> Result<serde_json::Value, String> {
-        let criteria = SearchCriteria {
-            query: args["query"].as_str().map(|s| s.to_string()),
-            category: args["category"].as_str().map(|s| s.to_string()),
-            framework: args["framework"].as_str().map(|s| s.to_string()),
-            tags: args["tags"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            min_score: args["min_score"].as_f64().unwrap_or(0.0) as f32,
-            max_results: args["max_results"].as_u64().map(|n| n as usize),
+        let id = args["id"].as_str().ok_or("Missing id")?;
+        let helpful = args["helpful"]
+            .as_bool()
+            .ok_or("Missing helpful (true or false)")?;
+
+        let before = self
+            .training_manager
+            .get_pattern_by_id(id)
+            .map(|p| p.relevance_score);
+        let pattern = self
+            .training_manager
+            .rate_pattern(id, helpful)
+            .map_err(|e| format!("Failed to rate pattern: {}", e))?
+            .clone();
+        self.training_manager
+            .save_feedback()
+            .map_err(|e| format!("Failed to save feedback: {}", e))?;
+
+        let votes = self
+            .training_manager
+            .feedback(id)
+            .cloned()
+            .unwrap_or_default();
+        let output = format!(
+            "{} Recorded {} vote for '{}' ({} helpful, {} not helpful)\n\n**Relevance:** {:.2} → {:.2}",
+            if helpful { "👍" } else { "👎" },
+            if helpful { "helpful" } else { "not helpful" },
+            pattern.title,
+            votes.helpful,
+            votes.not_helpful,
+            before.unwrap_or(pattern.relevance_score),
+            pattern.relevance_score
+        );
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    // Tool: mark-pattern-used
+    async fn tool_mark_pattern_used(
+        &mut self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let id = args["id"].as_str().ok_or("Missing id")?;
+
+        let pattern = self
+            .training_manager
+            .increment_usage(id)
+            .map_err(|e| format!("Failed to mark pattern as used: {}", e))?
+            .clone();
+        self.training_manager
+            .save_feedback()
+            .map_err(|e| format!("Failed to save feedback: {}", e))?;
+
+        let output = format!(
+            "✅ Marked '{}' as used\n\n**Usage Count:** {}",
+            pattern.title, pattern.usage_count
+        );
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    // Tool: review-pattern-candidates
+    async fn tool_review_pattern_candidates(
+        &mut self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let action = args["action"].as_str().unwrap_or("list");
+        let output = match action {
+            "list" => self.list_pattern_candidates().await?,
+            "accept" => {
+                let id = args["id"].as_str().ok_or("Missing id")?;
+                let text = |key: &str| args[key].as_str().map(str::to_string);
+                let overrides = PatternUpdate {
+                    title: text("title"),
+                    category: text("category"),
+                    description: text("description"),
+                    tags: args["tags"].as_array().map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                            .collect()
+                    }),
+                    ..Default::default()
+                };
+                let pattern = self
+                    .training_manager
+                    .accept_candidate(id, overrides)
+                    .map_err(|e| format!("Failed to accept candidate: {}", e))?
+                    .clone();
+                self.training_manager
+                    .save_patterns()
+                    .await
+                    .map_err(|e| format!("Failed to save patterns: {}", e))?;
+                format!(
+                    "✅ Candidate accepted as pattern '{}'\n\n**ID:** {}\n**Category:** {}\n**Framework:** {}\n**Updated At:** {}",
+                    pattern.title,
+                    pattern.id,
+                    pattern.category,
+                    pattern.framework,
+                    format_timestamp(pattern.updated_at)
+                )
+            }
+            "reject" => {
+                let id = args["id"].as_str().ok_or("Missing id")?;
+                let candidate = self
+                    .training_manager
+                    .reject_candidate(id)
+                    .map_err(|e| format!("Failed to reject candidate: {}", e))?;
+                self.training_manager
+                    .save_patterns()
+                    .await
+                    .map_err(|e| format!("Failed to save patterns: {}", e))?;
+                format!(
+                    "🗑️ Candidate '{}' rejected; it will not be proposed again\n\n**ID:** {}",
+                    candidate.pattern.title, candidate.pattern.id
+                )
+            }
+            other => {
+                return Err(format!(
+                    "Unknown action '{}': expected list, accept or reject",
+                    other
+                ))
+            }
         };
 
-        let results = self.training_manager.search_patterns(&criteria);
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
 
-        // Build verbose output (always needed: returned directly or archived)
-        let mut full_output = String::new();
-        full_output.push_str("# Pattern Search Results\n\n");
-        full_output.push_str(&format!("Found {} patterns\n\n", results.len()));
+    /// Pending candidates with their templates and where they occur.
+    async fn list_pattern_candidates(&self) -> Result<String, String> {
+        let candidates = self.training_manager.candidates();
+        if candidates.is_empty() {
+            return Ok(
+                "No pattern candidates pending. analyze-project proposes them when training.auto_extract_patterns is enabled."
+                    .to_string(),
+            );
+        }
 
-        for (pattern, score) in &results {
-            full_output.push_str(&format!("## {} (Score: {:.2})\n\n", pattern.title, score));
+        let mut full_output = format!("# Pattern Candidates ({})\n\n", candidates.len());
+        for candidate in candidates {
+            let pattern = &candidate.pattern;
+            let language = pattern.tags.last().map(String::as_str).unwrap_or_default();
             full_output.push_str(&format!(
-                "**Framework:** {} | **Category:** {}\n",
-                pattern.framework, pattern.category
+                "## {}\n\n**ID:** {}\n**Category:** {}\n**Framework:** {}\n**Occurrences:** {} in {} ({})\n\n```{}\n{}\n```\n\n",
+                pattern.title,
+                pattern.id,
+                pattern.category,
+                pattern.framework,
+                candidate.occurrences,
+                candidate.project,
+                candidate.locations.join(", "),
+                language,
+                candidate.template
             ));
-            full_output.push_str(&format!("{}\n\n", pattern.description));
-            full_output.push_str("```csharp\n");
-            full_output.push_str(&pattern.code);
-            full_output.push_str("\n```\n\n");
-            full_output.push_str("---\n\n");
         }
+        full_output.push_str(
+            "Accept with `{\"action\": \"accept\", \"id\": ...}` (title, category, description and tags can be overridden) or reject with `{\"action\": \"reject\", \"id\": ...}`.\n",
+        );
 
-        let output = if self.endless_mode {
-            let compact = if results.is_empty() {
-                "Found 0: (no matches)".to_string()
-            } else {
-                let entries: Vec<String> = results
-                    .iter()
-                    .take(10)
-                    .map(|(p, score)| {
-                        format!("[{:.2}]{}|{}|{}", score, p.title, p.category, p.framework)
-                    })
-                    .collect();
-                format!("Found {}: {}", results.len(), entries.join(" "))
-            };
-            let obs_id: String = self
-                .observations
-                .save("search-patterns", &full_output)
+        if !self.endless_mode {
+            return Ok(full_output);
+        }
+        let entries: Vec<String> = candidates
+            .iter()
+            .take(10)
+            .map(|c| format!("{}[{}x{}]", c.pattern.id, c.pattern.category, c.occurrences))
+            .collect();
+        let obs_id: String = self
+            .observations
+            .save("review-pattern-candidates", &full_output)
+            .await
+            .map_err(|e| format!("Failed to archive observation: {}", e))?;
+        Ok(format!(
+            "{} candidates: {}\nobs_id:{} (call get-observation for templates)",
+            candidates.len(),
+            entries.join(" "),
+            obs_id
+        ))
+    }
+
+    // Tool: export-patterns
+    async fn tool_export_patterns(
+        &self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let bundle_path = PathBuf::from(args["bundle_path"].as_str().ok_or("Missing bundle_path")?);
+        if bundle_path.is_dir() {
+            return Err(format!(
+                "bundle_path is a directory: '{}'. Please provide a file path.",
+                bundle_path.display()
+            ));
+        }
+
+        let bundle = self.training_manager.export_bundle(bundle_filter(args));
+        if bundle.patterns.is_empty() {
+            return Err("No patterns match the given framework, category and tags".to_string());
+        }
+        bundle
+            .save(&bundle_path)
+            .map_err(|e| format!("Failed to export patterns: {}", e))?;
+
+        let mut frameworks: Vec<&str> = bundle
+            .manifest
+            .entries
+            .iter()
+            .map(|e| e.framework.as_str())
+            .collect();
+        frameworks.sort_unstable();
+        frameworks.dedup();
+        let output = format!(
+            "📦 Exported {} patterns to {}\n\n**Frameworks:** {}\n**Format Version:** {}\n**Checksum:** {}",
+            bundle.patterns.len(),
+            bundle_path.display(),
+            frameworks.join(", "),
+            bundle.manifest.format_version,
+            bundle.manifest.checksum
+        );
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": output
+            }],
+            "isError": false
+        }))
+    }
+
+    // Tool: import-patterns
+    async fn tool_import_patterns(
+        &mut self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let bundle_path = PathBuf::from(args["bundle_path"].as_str().ok_or("Missing bundle_path")?);
+        let policy: ConflictPolicy = match args["on_conflict"].as_str() {
+            Some(policy) => policy.parse()?,
+            None => ConflictPolicy::default(),
+        };
+
+        let bundle = PatternBundle::load(&bundle_path)
+            .map_err(|e| format!("Failed to read bundle: {:#}", e))?;
+        let report = self
+            .training_manager
+            .import_bundle(&bundle, &bundle_filter(args), policy)
+            .map_err(|e| format!("Failed to import patterns: {}", e))?;
+        if report.imported() > 0 {
+            self.training_manager
+                .save_patterns()
                 .await
-                .map_err(|e| format!("Failed to archive observation: {}", e))?;
-            format!(
-                "{}\nobs_id:{} (call get-observation for full code examples)",
-                compact, obs_id
-            )
-        } else {
-            full_output
+                .map_err(|e| format!("Failed to save patterns: {}", e))?;
+        }
+
+        let mut output = format!(
+            "📥 Imported {} from {} ({}, on conflict: {})\n",
+            bundle_path.display(),
+            bundle.manifest.generator,
+            report,
+            policy.as_str()
+        );
+        let mut list = |label: &str, ids: Vec<String>| {
+            if !ids.is_empty() {
+                output.push_str(&format!("\n**{}:** {}", label, ids.join(", ")));
+            }
         };
+        list("Added", report.added.clone());
+        list("Overwritten", report.overwritten.clone());
+        list(
+            "Renamed",
+            report
+                .renamed
+                .iter()
+                .map(|(from, to)| format!("{} → {}", from, to))
+                .collect(),
+        );
+        list("Skipped", report.skipped.clone());
 
         Ok(serde_json::json!({
             "content": [{
@@ -787,56 +2660,39 @@ impl Server {
         }))
     }
 
-    // Tool: train-pattern
-    async fn tool_train_pattern(
+    // Tool: rollback-pattern
+    async fn tool_rollback_pattern(
         &mut self,
         args: &serde_json::Value,
     ) -> Result<serde_json::Value, String> {
-        let pattern = CodePattern {
-            id: args["id"].as_str().ok_or("Missing id")?.to_string(),
-            category: args["category"]
-                .as_str()
-                .ok_or("Missing category")?
-                .to_string(),
-            framework: args["framework"]
-                .as_str()
-                .ok_or("Missing framework")?
-                .to_string(),
-            version: args["version"].as_str().unwrap_or("10.0").to_string(),
-            title: args["title"].as_str().ok_or("Missing title")?.to_string(),
-            description: args["description"]
-                .as_str()
-                .ok_or("Missing description")?
-                .to_string(),
-            code: args["code"].as_str().ok_or("Missing code")?.to_string(),
-            tags: args["tags"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            usage_count: 0,
-            relevance_score: 0.8, // Default relevance
-            created_at: chrono::Utc::now(),
-            updated_at: chrono::Utc::now(),
+        let id = args["id"].as_str().ok_or("Missing id")?;
+        let revision = args["revision"]
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or("Missing revision")?;
+        let expected = if args["expected_updated_at"].is_null() {
+            None
+        } else {
+            Some(expected_updated_at(args)?)
         };
 
-        // Add pattern with validation (prevents path traversal)
-        self.training_manager
-            .add_pattern(pattern.clone())
-            .map_err(|e| format!("Invalid pattern: {}", e))?;
-
-        // Save to disk
+        let pattern = self
+            .training_manager
+            .rollback_pattern(id, revision, expected)
+            .map_err(|e| format!("Failed to roll back pattern: {}", e))?
+            .clone();
         self.training_manager
             .save_patterns()
             .await
             .map_err(|e| format!("Failed to save patterns: {}", e))?;
 
         let output = format!(
-            "✅ Pattern '{}' added successfully!\n\n**ID:** {}\n**Category:** {}\n**Framework:** {}",
-            pattern.title, pattern.id, pattern.category, pattern.framework
+            "⏪ Pattern '{}' rolled back to r{}\n\n**ID:** {}\n**Framework:** {}\n**Updated At:** {}",
+            pattern.title,
+            revision,
+            pattern.id,
+            pattern.framework,
+            format_timestamp(pattern.updated_at)
         );
 
         Ok(serde_json::json!({
@@ -935,7 +2791,7 @@ Servidor MCP que analiza proyectos de código y proporciona patrones de buenas p
 analyze-project { "project_path": "C:/ruta/al/proyecto" }
 ```
 - Detecta automáticamente: Rust, Node, Python, PHP, Go, Java, .NET
-- Devuelve: estructura, dependencias, framework detectado, sugerencias
+- Devuelve: estructura, dependencias, framework detectado, tests (framework, comando, módulos sin tests), infraestructura (Docker, Compose, Kubernetes, Terraform, CI), sugerencias
 
 ### 2. search-patterns
 **Cuándo usar:** El usuario pregunta "cómo hacer X" o busca buenas prácticas.
@@ -943,6 +2799,10 @@ analyze-project { "project_path": "C:/ruta/al/proyecto" }
 search-patterns { "query": "autenticación jwt" }
 search-patterns { "query": "manejo errores", "framework": "laravel" }
 ```
+- Las palabras se buscan por separado y en cualquier orden (título, tags, descripción y código); "jwt authentication" encuentra "Authentication with JWT"
+- Las consultas en inglés funcionan mejor: los patrones incluidos están en inglés
+- Tolera errores de escritura en la consulta y en los tags ("lifecyle" encuentra "lifecycle"); la respuesta incluye "Did you mean …?" con la corrección
+- `"mode": "hybrid"` (o `"semantic"`) también encuentra sinónimos y conceptos relacionados: "DI" → dependency injection, "auth"/"login" → authentication. Funciona sin red ni modelos
 
 ### 3. get-patterns
 **Cuándo usar:** El usuario quiere patrones de un framework específico.
@@ -950,6 +2810,7 @@ search-patterns { "query": "manejo errores", "framework": "laravel" }
 get-patterns { "framework": "laravel" }
 get-patterns { "framework": "react", "category": "hooks" }
 ```
+- Si el framework o la categoría no existen, la respuesta sugiere el nombre más parecido o lista los frameworks disponibles
 
 ### 4. train-pattern
 **Cuándo usar:** El usuario quiere guardar código como patrón reutilizable.
@@ -971,20 +2832,131 @@ train-pattern {
 get-statistics {}
 ```
 
+### 6. analyze-diff
+**Cuándo usar:** El usuario pide revisar cambios (code review, PR, commit).
+```
+analyze-diff { "project_path": "C:/ruta/al/proyecto", "base": "main" }
+analyze-diff { "project_path": "C:/ruta/al/proyecto", "diff": "<diff unificado>" }
+```
+- Analiza solo los archivos modificados respecto a `base` (por defecto `HEAD`)
+- Devuelve: archivos cambiados, símbolos tocados, patrones y sugerencias relevantes
+
+### 7. get-module-graph
+**Cuándo usar:** El usuario pregunta por la arquitectura, dependencias internas o ciclos.
+```
+get-module-graph { "project_path": "C:/ruta/al/proyecto" }
+get-module-graph { "project_path": "C:/ruta/al/proyecto", "module": "src/lib.rs" }
+```
+- Devuelve: ciclos de imports, violaciones de capas y módulos más usados
+
+### 8. find-symbol
+**Cuándo usar:** Buscar dónde está definida una clase, función o método sin leer archivos.
+```
+find-symbol { "project_path": "C:/ruta/al/proyecto", "query": "OrderService" }
+find-symbol { "project_path": "C:/ruta/al/proyecto", "kind": "interface" }
+```
+- Devuelve: ruta y rango de líneas de cada coincidencia (búsqueda aproximada)
+
+### 9. get-file-outline
+**Cuándo usar:** Ver la estructura de un archivo antes de leerlo.
+```
+get-file-outline { "file_path": "src/services/order.rs", "project_path": "C:/ruta/al/proyecto" }
+```
+- Devuelve: árbol de símbolos con firmas y líneas, sin el contenido del archivo
+
+### 10. lint-project
+**Cuándo usar:** El usuario pide revisar la calidad del código o buscar malas prácticas.
+```
+lint-project { "project_path": "C:/ruta/al/proyecto" }
+lint-project { "project_path": "C:/ruta/al/proyecto", "min_severity": "warning", "rule": "rust-unwrap" }
+```
+- Ejecuta reglas de consultas tree-sitter (data/rules/lint.toml)
+- Devuelve: hallazgos por archivo con línea:columna, regla y el patrón que muestra la corrección
+
+### 11. security-scan
+**Cuándo usar:** Antes de publicar o desplegar, o si el usuario pregunta por secretos o configuración insegura.
+```
+security-scan { "project_path": "C:/ruta/al/proyecto" }
+```
+- Detecta credenciales (AWS, GitHub, Stripe, claves privadas...), valores de alta entropía, `.env` versionados, `APP_DEBUG=true`, `ASPNETCORE_ENVIRONMENT=Development` y CORS permisivo
+- Devuelve: hallazgos de severidad Error por archivo, con los secretos ocultos
+
+### 12. update-pattern / delete-pattern / deprecate-pattern
+**Cuándo usar:** El usuario quiere corregir, borrar o retirar un patrón guardado.
+```
+update-pattern { "id": "use-async-init", "expected_updated_at": "2025-10-25T00:00:00Z", "code": "..." }
+delete-pattern { "id": "use-async-init", "expected_updated_at": "2025-10-25T00:00:00Z" }
+deprecate-pattern { "id": "old-pattern", "expected_updated_at": "...", "reason": "Obsoleto en .NET 8", "replaced_by": "new-pattern" }
+```
+- `expected_updated_at` es el valor **Updated At** que muestran get-patterns y search-patterns; si el patrón cambió desde entonces, la edición se rechaza y hay que volver a leerlo
+- Los patrones deprecados dejan de aparecer en búsquedas; `"restore": true` los recupera
+- `train-pattern` con un ID existente falla: usar update-pattern
+
+### 13. list-pattern-revisions / diff-pattern-revisions / rollback-pattern
+**Cuándo usar:** Un patrón se sobrescribió o borró por error y hay que ver qué cambió o deshacerlo.
+```
+list-pattern-revisions { "id": "use-async-init" }
+diff-pattern-revisions { "id": "use-async-init", "from": 2, "to": 3 }
+rollback-pattern { "id": "use-async-init", "revision": 2, "expected_updated_at": "..." }
+```
+- Cada cambio guarda una revisión con autor (el cliente MCP), fecha, acción y el patrón completo
+- Sin `to`, el diff compara con la última revisión
+- El rollback crea una revisión nueva; los patrones borrados se recrean sin `expected_updated_at`
+
+### 14. rate-pattern / mark-pattern-used
+**Cuándo usar:** Después de aplicar un patrón, o cuando el usuario dice si le sirvió o no.
+```
+mark-pattern-used { "id": "use-async-init" }
+rate-pattern { "id": "use-async-init", "helpful": true }
+```
+- `mark-pattern-used` suma un uso; los patrones más usados aparecen antes en las búsquedas
+- En modo endless, recuperar con `get-observation` la lista de get-patterns o search-patterns cuenta como uso de los patrones listados
+- Los votos ajustan la relevancia poco a poco: un voto la mueve poco, muchos votos en el mismo sentido la mueven casi del todo
+
+### 15. review-pattern-candidates
+**Cuándo usar:** Cuando `analyze-project` muestra la sección "Pattern Candidates".
+```
+review-pattern-candidates {}
+review-pattern-candidates { "action": "accept", "id": "extracted-3f2a9c1b7d4e", "title": "Registro de servicios scoped" }
+review-pattern-candidates { "action": "reject", "id": "extracted-3f2a9c1b7d4e" }
+```
+- `analyze-project` busca estructuras que se repiten al menos `training.min_pattern_occurrences` veces (registros de DI, envoltorios try/catch, métodos de ciclo de vida) y las propone como candidatos
+- Se desactiva con `training.auto_extract_patterns = false`
+- Al aceptar se pueden cambiar `title`, `category`, `description` y `tags`; un candidato rechazado no se vuelve a proponer
+
+### 16. export-patterns / import-patterns
+**Cuándo usar:** Para compartir patrones con otro equipo o instalación.
+```
+export-patterns { "bundle_path": "/tmp/blazor.json", "framework": "blazor-server", "tags": ["async"] }
+import-patterns { "bundle_path": "/tmp/blazor.json", "on_conflict": "rename" }
+```
+- El bundle es un único JSON con un manifiesto (versión de formato, filtros) y checksums SHA-256; si no cuadran no se importa nada
+- `on_conflict`: `skip` (por defecto) mantiene el patrón existente, `overwrite` lo reemplaza guardando una revisión, `rename` lo importa como `<id>-imported`
+- Ambos aceptan los filtros `framework`, `category` y `tags`; también desde la línea de comandos: `mcp-context-rust export-patterns <archivo> [--framework F] [--category C] [--tag T]` e `import-patterns <archivo> [--on-conflict rename]`
+
 ## Flujo recomendado
 
 1. **Usuario menciona proyecto** → `analyze-project`
 2. **Usuario pregunta cómo hacer algo** → `search-patterns`
 3. **Usuario quiere ejemplos de framework** → `get-patterns`
-4. **Usuario comparte código útil** → `train-pattern`
+4. **Usuario comparte código útil** → `train-pattern` (o `update-pattern` si ya existe)
+5. **Usuario pide revisar cambios** → `analyze-diff`
+6. **Usuario busca dónde está algo** → `find-symbol` → `get-file-outline`
+7. **Usuario pide revisar la calidad del código** → `lint-project`
+8. **Usuario va a publicar o desplegar** → `security-scan`
+9. **Un patrón se cambió o borró por error** → `list-pattern-revisions` → `rollback-pattern`
+10. **Se aplicó un patrón** → `mark-pattern-used`; si el usuario opina sobre él → `rate-pattern`
+11. **El análisis propone candidatos** → `review-pattern-candidates` para aceptarlos o rechazarlos
+12. **Usuario quiere compartir patrones** → `export-patterns`; quien los recibe → `import-patterns`
 
 ## Frameworks soportados
-- **PHP:** laravel, symfony, wordpress
-- **JavaScript:** react, vue, nextjs, express
+Nombres canónicos (los mismos que usa el campo `framework` de los patrones), definidos en data/frameworks.toml:
+- **PHP:** laravel, symfony, wordpress, codeigniter, yii, cakephp, slim, drupal
+- **JavaScript:** nextjs, remix, gatsby, react, nuxt, vue, sveltekit, svelte, angular, angularjs, nestjs, express
 - **Python:** django, flask, fastapi
-- **Rust:** actix-web, axum, tokio
+- **Rust:** actix-web, axum, rocket, tokio
 - **.NET:** blazor-server, aspnet-core
-- **Go:** gin, fiber
+- **Go:** gin, fiber, echo
 - **Java:** spring
 
 ## Notas
@@ -1000,7 +2972,7 @@ get-statistics {}
                 .await
                 .map_err(|e| format!("Failed to archive observation: {}", e))?;
             format!(
-                "Tools: analyze-project|get-patterns|search-patterns|train-pattern|get-statistics|set-endless-mode|get-observation\nobs_id:{} (call get-observation for full usage guide)",
+                "Tools: analyze-project|analyze-diff|get-module-graph|find-symbol|get-file-outline|lint-project|security-scan|get-patterns|search-patterns|train-pattern|update-pattern|delete-pattern|deprecate-pattern|list-pattern-revisions|diff-pattern-revisions|rollback-pattern|rate-pattern|mark-pattern-used|review-pattern-candidates|export-patterns|import-patterns|get-statistics|set-endless-mode|get-observation\nobs_id:{} (call get-observation for full usage guide)",
                 obs_id
             )
         } else {
@@ -1045,8 +3017,11 @@ get-statistics {}
 
     /// Tool: get-observation
     /// Retrieves a previously archived full tool output by its obs_id.
+    ///
+    /// Fetching the full code of a pattern list counts as using the listed
+    /// patterns, once per observation.
     async fn tool_get_observation(
-        &self,
+        &mut self,
         args: &serde_json::Value,
     ) -> Result<serde_json::Value, String> {
         let obs_id = args["obs_id"].as_str().ok_or("Missing obs_id")?;
@@ -1057,6 +3032,19 @@ get-statistics {}
             .await
             .map_err(|e| format!("Invalid obs_id: {}", e))?;
 
+        if let Some(ids) = content.as_ref().and_then(|_| self.take_returned(obs_id)) {
+            // Patterns deleted since they were listed are simply not counted
+            let used = ids
+                .iter()
+                .filter(|id| self.training_manager.increment_usage(id).is_ok())
+                .count();
+            if used > 0 {
+                if let Err(e) = self.training_manager.save_feedback() {
+                    tracing::warn!(error = %e, "Failed to save pattern usage");
+                }
+            }
+        }
+
         let output = match content {
             Some(text) => text,
             None => format!(
@@ -1074,6 +3062,24 @@ get-statistics {}
         }))
     }
 
+    /// Remembers the patterns listed under `obs_id`, forgetting the oldest
+    /// list beyond `RETURNED_PATTERN_LISTS`.
+    fn remember_returned(&mut self, obs_id: &str, ids: Vec<String>) {
+        if self.returned_patterns.len() == RETURNED_PATTERN_LISTS {
+            self.returned_patterns.pop_front();
+        }
+        self.returned_patterns.push_back((obs_id.to_string(), ids));
+    }
+
+    /// The patterns listed under `obs_id`, if still remembered.
+    fn take_returned(&mut self, obs_id: &str) -> Option<Vec<String>> {
+        let idx = self
+            .returned_patterns
+            .iter()
+            .position(|(id, _)| id == obs_id)?;
+        self.returned_patterns.remove(idx).map(|(_, ids)| ids)
+    }
+
     async fn handle_prompts_list(&self) -> Result<serde_json::Value, String> {
         // Return empty prompts list (not implemented yet)
         Ok(serde_json::json!({
@@ -1088,3 +3094,41 @@ get-statistics {}
         }))
     }
 }
+
+/// "Did you mean …?" line for search corrections, if there are any.
+fn did_you_mean(corrections: &[Correction]) -> Option<String> {
+    if corrections.is_empty() {
+        return None;
+    }
+    let suggestions: Vec<String> = corrections
+        .iter()
+        .map(|c| format!("{} '{}'", c.field, c.suggestion))
+        .collect();
+    Some(format!("Did you mean {}?", suggestions.join(", ")))
+}
+
+/// The required `expected_updated_at` argument of pattern edits.
+fn expected_updated_at(args: &serde_json::Value) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    let value = args["expected_updated_at"]
+        .as_str()
+        .ok_or("Missing expected_updated_at (the pattern's 'Updated At' value)")?;
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .map_err(|e| format!("Invalid expected_updated_at '{}': {}", value, e))
+}
+
+/// The optional `framework`, `category` and `tags` arguments of bundle tools.
+fn bundle_filter(args: &serde_json::Value) -> BundleFilter {
+    BundleFilter {
+        framework: args["framework"].as_str().map(str::to_string),
+        category: args["category"].as_str().map(str::to_string),
+        tags: args["tags"]
+            .as_array()
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
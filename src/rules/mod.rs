@@ -0,0 +1,987 @@
+//! Declarative suggestion rules.
+//!
+//! Rules are data: TOML or JSON files next to the pattern files
+//! (`<base_path>/rules/*.toml`), each holding a `rules` array. A rule fires
+//! when all of its `when` conditions hold for the analyzed project and
+//! produces one `Suggestion`, or one per matching symbol when it has a
+//! `symbol` condition. The built-in rules ship as `data/rules/default.toml`
+//! and are compiled in as a fallback for when no rules directory exists.
+//!
+//! Rules with a `query` condition are lint rules: instead of being evaluated
+//! against the analysis, they run a tree-sitter query over the source files
+//! (`RuleEngine::lint`) and produce one suggestion per match, linked to the
+//! pattern that shows the fix (`data/rules/lint.toml`).
+
+use anyhow::{bail, Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use semver::VersionReq;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tree_sitter::{Node, Parser, Query, QueryCursor};
+
+use crate::analyzer::grammar::{node_range, Grammar};
+use crate::analyzer::test_suite::is_test_path;
+use crate::health::version::{Ecosystem, VersionRange};
+use crate::types::{Dependency, Project, SeverityLevel, SourceRange, Suggestion, Symbol};
+
+/// Rule files compiled into the binary, in load order.
+const BUILTIN_RULES: &[&str] = &[
+    include_str!("../../data/rules/default.toml"),
+    include_str!("../../data/rules/lint.toml"),
+];
+
+/// Suggestions a single symbol rule may produce.
+const MAX_MATCHES_PER_RULE: usize = 20;
+
+/// Suggestions a single lint rule may produce per project.
+const MAX_FINDINGS_PER_RULE: usize = 200;
+
+/// Capture text longer than this is cut when filling message placeholders.
+const MAX_CAPTURE_CHARS: usize = 60;
+
+/// A rule file: `[[rules]]` in TOML, `{"rules": [...]}` in JSON.
+#[derive(Debug, Deserialize)]
+struct RuleFile {
+    rules: Vec<Rule>,
+}
+
+/// A declarative suggestion rule.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// Unique id; later files override earlier rules with the same id
+    pub id: String,
+    /// Set to `false` to switch off a rule defined in an earlier file
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_severity")]
+    pub severity: SeverityLevel,
+    pub category: String,
+    /// Message; `{project}`, `{framework}`, `{file_count}`, `{dependency}`,
+    /// `{version}`, `{symbol}` and `{kind}` are filled in when known; lint
+    /// rules also fill in `{<capture>}` with the text of each query capture
+    pub message: String,
+    /// Id of the pattern showing the recommended fix
+    pub pattern_id: Option<String>,
+    #[serde(default)]
+    pub when: Conditions,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_severity() -> SeverityLevel {
+    SeverityLevel::Info
+}
+
+/// Conditions of a rule; all given conditions must hold.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Conditions {
+    /// Project type is one of these (`dotnet`, `rust`, `node`, ...)
+    #[serde(default)]
+    pub project_type: Vec<String>,
+    /// Detected framework is one of these (`blazor-server`, `laravel`, ...)
+    #[serde(default)]
+    pub framework: Vec<String>,
+    /// A dependency with this name (and version) is declared
+    pub dependency: Option<DependencyCondition>,
+    /// None of these dependencies is declared
+    #[serde(default)]
+    pub no_dependency: Vec<String>,
+    /// At least this many dependencies are declared
+    pub min_dependencies: Option<usize>,
+    /// All of these paths exist (relative to the project root; globs match discovered files)
+    #[serde(default)]
+    pub files_exist: Vec<String>,
+    /// None of these paths exist
+    #[serde(default)]
+    pub files_missing: Vec<String>,
+    /// At least this many source files were analyzed
+    pub min_files: Option<usize>,
+    /// Metadata values (`target_framework`, `rust_edition`, ... or `extra` keys) equal these
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// Fire once per symbol matching these properties
+    pub symbol: Option<SymbolCondition>,
+    /// Fire once per tree-sitter query match (lint rule, see `RuleEngine::lint`)
+    pub query: Option<QueryCondition>,
+}
+
+/// Dependency condition.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DependencyCondition {
+    pub name: String,
+    /// Semver requirement (`<4`, `>=8, <9`) checked against the lowest version
+    /// the declared specifier allows in the project's ecosystem (`^8.2` -> 8.2.0)
+    pub version: Option<String>,
+    /// Only match development (`true`) or runtime (`false`) dependencies
+    pub dev_only: Option<bool>,
+}
+
+/// Symbol condition; all given properties must match.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SymbolCondition {
+    /// File languages (extensions) to look in
+    #[serde(default)]
+    pub language: Vec<String>,
+    /// Symbol kind (`class`, `method`, ...)
+    pub kind: Option<String>,
+    /// Regex on the symbol name
+    pub name: Option<String>,
+    /// Regex on the symbol signature
+    pub signature: Option<String>,
+    /// Modifiers the symbol must have
+    #[serde(default)]
+    pub modifiers: Vec<String>,
+    /// Modifiers the symbol must not have
+    #[serde(default)]
+    pub without_modifiers: Vec<String>,
+}
+
+/// Tree-sitter query condition of a lint rule.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueryCondition {
+    /// File languages (extensions) to run the query on; each needs a bundled grammar
+    pub language: Vec<String>,
+    /// S-expression query; `#eq?` and `#match?` predicates are supported. The
+    /// `@match` capture (or else the first capture) locates the suggestion
+    pub query: String,
+    /// Skip test files, and Rust `#[test]` functions and `#[cfg(test)]` modules
+    #[serde(default)]
+    pub skip_tests: bool,
+    /// Globs (relative to the project root) of files to leave out
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// A lint query compiled for each grammar it applies to.
+type GrammarQueries = Vec<(Grammar, Arc<Query>)>;
+
+/// A rule with its regexes, version requirement and queries compiled.
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: Rule,
+    version: Option<VersionReq>,
+    name: Option<Regex>,
+    signature: Option<Regex>,
+    queries: GrammarQueries,
+    exclude: Option<GlobSet>,
+}
+
+/// Evaluates suggestion rules against analyzed projects.
+#[derive(Debug, Clone)]
+pub struct RuleEngine {
+    rules: Vec<CompiledRule>,
+}
+
+impl Default for RuleEngine {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl RuleEngine {
+    /// The rules compiled into the binary (`data/rules/default.toml` and `lint.toml`).
+    pub fn builtin() -> Self {
+        let rules = BUILTIN_RULES
+            .iter()
+            .flat_map(|content| Self::parse(content, "toml").expect("built-in rules are valid"))
+            .collect();
+        Self::from_rules(rules).expect("built-in rules are valid")
+    }
+
+    /// Load every `*.toml` and `*.json` rule file in `dir`, in file-name order.
+    ///
+    /// Falls back to the built-in rules when `dir` does not exist.
+    ///
+    /// # Errors
+    /// Returns an error if a file cannot be read or parsed, or a rule is invalid
+    pub fn load(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            tracing::debug!(path = %dir.display(), "No rules directory, using built-in rules");
+            return Ok(Self::builtin());
+        }
+
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read rules directory {}", dir.display()))?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.is_file()
+                    && matches!(
+                        p.extension().and_then(|e| e.to_str()),
+                        Some("toml" | "json")
+                    )
+            })
+            .collect();
+        files.sort();
+
+        let mut rules: Vec<Rule> = Vec::new();
+        for path in &files {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read rule file {}", path.display()))?;
+            let format = path.extension().and_then(|e| e.to_str()).unwrap_or("toml");
+            let parsed = Self::parse(&content, format)
+                .with_context(|| format!("Failed to parse rule file {}", path.display()))?;
+            for rule in parsed {
+                match rules.iter_mut().find(|r| r.id == rule.id) {
+                    Some(existing) => *existing = rule,
+                    None => rules.push(rule),
+                }
+            }
+        }
+
+        let engine = Self::from_rules(rules)?;
+        tracing::info!(count = engine.len(), path = %dir.display(), "Loaded suggestion rules");
+        Ok(engine)
+    }
+
+    fn parse(content: &str, format: &str) -> Result<Vec<Rule>> {
+        let file: RuleFile = if format == "json" {
+            serde_json::from_str(content)?
+        } else {
+            toml::from_str(content)?
+        };
+        Ok(file.rules)
+    }
+
+    /// Validate and compile rules, dropping disabled ones.
+    fn from_rules(rules: Vec<Rule>) -> Result<Self> {
+        let mut compiled = Vec::new();
+        for rule in rules.into_iter().filter(|r| r.enabled) {
+            if rule.id.trim().is_empty() {
+                bail!("Rule id cannot be empty");
+            }
+            let version = match rule
+                .when
+                .dependency
+                .as_ref()
+                .and_then(|d| d.version.as_ref())
+            {
+                Some(req) => Some(VersionReq::parse(req).with_context(|| {
+                    format!("Rule '{}': invalid version requirement '{}'", rule.id, req)
+                })?),
+                None => None,
+            };
+            let regex = |pattern: Option<&String>| -> Result<Option<Regex>> {
+                pattern
+                    .map(|p| {
+                        Regex::new(p)
+                            .with_context(|| format!("Rule '{}': invalid regex '{}'", rule.id, p))
+                    })
+                    .transpose()
+            };
+            let symbol = rule.when.symbol.as_ref();
+            let name = regex(symbol.and_then(|s| s.name.as_ref()))?;
+            let signature = regex(symbol.and_then(|s| s.signature.as_ref()))?;
+            let (queries, exclude) = match rule.when.query {
+                Some(ref condition) => compile_query(&rule.id, condition)?,
+                None => (vec![], None),
+            };
+
+            compiled.push(CompiledRule {
+                rule,
+                version,
+                name,
+                signature,
+                queries,
+                exclude,
+            });
+        }
+        Ok(Self { rules: compiled })
+    }
+
+    /// Number of active rules.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Number of active lint (query) rules.
+    pub fn lint_rule_count(&self) -> usize {
+        self.rules.iter().filter(|r| r.is_lint()).count()
+    }
+
+    /// Suggestions of every rule that fires for `project`. Lint rules are left
+    /// to `lint`, since they need the source files.
+    pub fn evaluate(&self, project: &Project, framework: &str) -> Vec<Suggestion> {
+        self.rules
+            .iter()
+            .filter(|rule| !rule.is_lint())
+            .flat_map(|rule| rule.evaluate(project, framework))
+            .collect()
+    }
+
+    /// Run the lint rules whose project conditions hold over the source files
+    /// of `project`, reading and parsing each file once.
+    ///
+    /// Findings are in file order; files that cannot be read are skipped.
+    pub fn lint(&self, project: &Project, framework: &str) -> Vec<Suggestion> {
+        let active: Vec<(&CompiledRule, BTreeMap<&str, String>)> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.is_lint())
+            .filter_map(|rule| Some((rule, rule.project_values(project, framework)?)))
+            .collect();
+        if active.is_empty() {
+            return vec![];
+        }
+
+        let mut counts = vec![0usize; active.len()];
+        let mut suggestions = Vec::new();
+        let mut parser = Parser::new();
+        let mut cursor = QueryCursor::new();
+
+        for file in &project.files {
+            let Some(grammar) = Grammar::from_extension(&file.language) else {
+                continue;
+            };
+            let relative = file.path.strip_prefix(&project.path).unwrap_or(&file.path);
+            let is_test_file = is_test_path(relative);
+
+            let applicable: Vec<(usize, &Query)> = active
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| counts[*i] < MAX_FINDINGS_PER_RULE)
+                .filter_map(|(i, (rule, _))| Some((i, rule.query_for(grammar)?)))
+                .filter(|(i, _)| {
+                    let rule = active[*i].0;
+                    let excluded = (rule.skips_tests() && is_test_file)
+                        || rule.exclude.as_ref().is_some_and(|g| g.is_match(relative));
+                    !excluded
+                })
+                .collect();
+            if applicable.is_empty() {
+                continue;
+            }
+
+            let source = match fs::read_to_string(&file.path) {
+                Ok(source) => source,
+                Err(e) => {
+                    tracing::debug!(path = %file.path.display(), error = %e, "Skipping unreadable file");
+                    continue;
+                }
+            };
+            if parser.set_language(grammar.language()).is_err() {
+                continue;
+            }
+            let Some(tree) = parser.parse(&source, None) else {
+                continue;
+            };
+
+            for (i, query) in applicable {
+                let (rule, ref values) = active[i];
+                let target = query.capture_index_for_name("match");
+                let mut seen: Vec<usize> = Vec::new();
+
+                for m in cursor.matches(query, tree.root_node(), source.as_bytes()) {
+                    let Some(located) = target
+                        .and_then(|t| m.captures.iter().find(|c| c.index == t))
+                        .or_else(|| m.captures.first())
+                    else {
+                        continue;
+                    };
+                    let node = located.node;
+                    // Several patterns of one query may match the same node
+                    if seen.contains(&node.start_byte())
+                        || (rule.skips_tests() && in_test_code(node, &source))
+                    {
+                        continue;
+                    }
+                    seen.push(node.start_byte());
+
+                    let mut values = values.clone();
+                    for capture in m.captures {
+                        let name = &query.capture_names()[capture.index as usize];
+                        let text = capture.node.utf8_text(source.as_bytes()).unwrap_or("");
+                        values.insert(name.as_str(), capture_text(text));
+                    }
+                    suggestions.push(rule.suggestion(
+                        &values,
+                        Some(&file.path),
+                        Some(node_range(node)),
+                    ));
+
+                    counts[i] += 1;
+                    if counts[i] >= MAX_FINDINGS_PER_RULE {
+                        break;
+                    }
+                }
+            }
+        }
+        suggestions
+    }
+}
+
+/// Compile a query condition for each of its languages.
+fn compile_query(
+    id: &str,
+    condition: &QueryCondition,
+) -> Result<(GrammarQueries, Option<GlobSet>)> {
+    if condition.language.is_empty() {
+        bail!("Rule '{}': a query needs at least one language", id);
+    }
+    let mut queries: GrammarQueries = Vec::new();
+    for language in &condition.language {
+        let Some(grammar) = Grammar::from_extension(language) else {
+            bail!("Rule '{}': no grammar for language '{}'", id, language);
+        };
+        if queries.iter().any(|(g, _)| *g == grammar) {
+            continue;
+        }
+        let query = Query::new(grammar.language(), &condition.query)
+            .map_err(|e| anyhow::anyhow!("Rule '{}': invalid {} query: {}", id, language, e))?;
+        queries.push((grammar, Arc::new(query)));
+    }
+
+    let exclude = if condition.exclude.is_empty() {
+        None
+    } else {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &condition.exclude {
+            builder.add(
+                Glob::new(pattern)
+                    .with_context(|| format!("Rule '{}': invalid glob '{}'", id, pattern))?,
+            );
+        }
+        Some(builder.build()?)
+    };
+    Ok((queries, exclude))
+}
+
+impl CompiledRule {
+    fn is_lint(&self) -> bool {
+        self.rule.when.query.is_some()
+    }
+
+    fn skips_tests(&self) -> bool {
+        self.rule.when.query.as_ref().is_some_and(|q| q.skip_tests)
+    }
+
+    fn query_for(&self, grammar: Grammar) -> Option<&Query> {
+        self.queries
+            .iter()
+            .find(|(g, _)| *g == grammar)
+            .map(|(_, q)| q.as_ref())
+    }
+
+    fn evaluate(&self, project: &Project, framework: &str) -> Vec<Suggestion> {
+        let Some(mut values) = self.project_values(project, framework) else {
+            return vec![];
+        };
+
+        let Some(ref condition) = self.rule.when.symbol else {
+            return vec![self.suggestion(&values, None, None)];
+        };
+
+        let mut suggestions = Vec::new();
+        for file in &project.files {
+            if !condition.language.is_empty()
+                && !condition
+                    .language
+                    .iter()
+                    .any(|l| l.eq_ignore_ascii_case(&file.language))
+            {
+                continue;
+            }
+            let mut matches = Vec::new();
+            for symbol in &file.symbols {
+                self.collect_symbols(condition, symbol, None, &mut matches);
+            }
+            for (qualified_name, symbol) in matches {
+                values.insert("symbol", qualified_name);
+                values.insert("kind", symbol.kind.to_string());
+                suggestions.push(self.suggestion(&values, Some(&file.path), symbol.range));
+                if suggestions.len() >= MAX_MATCHES_PER_RULE {
+                    return suggestions;
+                }
+            }
+        }
+        suggestions
+    }
+
+    /// Placeholder values of the rule when its project-level conditions hold.
+    fn project_values(&self, project: &Project, framework: &str) -> Option<BTreeMap<&str, String>> {
+        let when = &self.rule.when;
+
+        let type_matches = when.project_type.is_empty()
+            || when
+                .project_type
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(project.project_type.as_str()));
+        let framework_matches = when.framework.is_empty()
+            || when
+                .framework
+                .iter()
+                .any(|f| f.eq_ignore_ascii_case(framework));
+        if !type_matches || !framework_matches {
+            return None;
+        }
+
+        let dependency = match when.dependency {
+            Some(ref condition) => match self.matching_dependency(project, condition) {
+                Some(dependency) => Some(dependency),
+                None => return None,
+            },
+            None => None,
+        };
+
+        let holds = when
+            .no_dependency
+            .iter()
+            .all(|name| !project.dependencies.iter().any(|d| &d.name == name))
+            && when
+                .min_dependencies
+                .is_none_or(|min| project.dependencies.len() >= min)
+            && when.min_files.is_none_or(|min| project.files.len() >= min)
+            && when.files_exist.iter().all(|p| file_exists(project, p))
+            && !when.files_missing.iter().any(|p| file_exists(project, p))
+            && when
+                .metadata
+                .iter()
+                .all(|(key, value)| metadata_value(project, key) == Some(value.as_str()));
+        if !holds {
+            return None;
+        }
+
+        let mut values: BTreeMap<&str, String> = BTreeMap::new();
+        values.insert("project", project.name.clone());
+        values.insert("framework", framework.to_string());
+        values.insert("file_count", project.files.len().to_string());
+        if let Some(dependency) = dependency {
+            values.insert("dependency", dependency.name.clone());
+            values.insert("version", dependency.version.clone());
+        }
+
+        Some(values)
+    }
+
+    /// The declared dependency satisfying `condition`, if any.
+    fn matching_dependency<'a>(
+        &self,
+        project: &'a Project,
+        condition: &DependencyCondition,
+    ) -> Option<&'a Dependency> {
+        let ecosystem = Ecosystem::for_project(project.project_type).unwrap_or(Ecosystem::Npm);
+        project.dependencies.iter().find(|d| {
+            d.name == condition.name
+                && condition.dev_only.is_none_or(|dev| d.dev_only == dev)
+                && self.version.as_ref().is_none_or(|req| {
+                    VersionRange::parse(ecosystem, &d.version)
+                        .and_then(|range| range.lowest().cloned())
+                        .is_some_and(|v| req.matches(&v))
+                })
+        })
+    }
+
+    fn collect_symbols<'a>(
+        &self,
+        condition: &SymbolCondition,
+        symbol: &'a Symbol,
+        parent: Option<&str>,
+        out: &mut Vec<(String, &'a Symbol)>,
+    ) {
+        let qualified_name = match parent {
+            Some(parent) => format!("{}.{}", parent, symbol.name),
+            None => symbol.name.clone(),
+        };
+
+        let has = |modifier: &String| symbol.modifiers.contains(modifier);
+        let matches = condition
+            .kind
+            .as_ref()
+            .is_none_or(|k| symbol.kind.to_string().eq_ignore_ascii_case(k))
+            && self.name.as_ref().is_none_or(|r| r.is_match(&symbol.name))
+            && self
+                .signature
+                .as_ref()
+                .is_none_or(|r| symbol.signature.as_deref().is_some_and(|s| r.is_match(s)))
+            && condition.modifiers.iter().all(has)
+            && !condition.without_modifiers.iter().any(has);
+
+        for child in &symbol.children {
+            self.collect_symbols(condition, child, Some(&qualified_name), out);
+        }
+        if matches {
+            out.push((qualified_name, symbol));
+        }
+    }
+
+    fn suggestion(
+        &self,
+        values: &BTreeMap<&str, String>,
+        file: Option<&Path>,
+        range: Option<SourceRange>,
+    ) -> Suggestion {
+        Suggestion {
+            severity: self.rule.severity,
+            category: self.rule.category.clone(),
+            message: render(&self.rule.message, values),
+            file: file.map(Path::to_path_buf),
+            line: range.map(|r| r.start.line),
+            range,
+            rule_id: Some(self.rule.id.clone()),
+            pattern_id: self.rule.pattern_id.clone(),
+        }
+    }
+}
+
+/// Replace `{name}` placeholders; unknown placeholders are left as they are.
+fn render(template: &str, values: &BTreeMap<&str, String>) -> String {
+    let mut message = template.to_string();
+    for (key, value) in values {
+        message = message.replace(&format!("{{{}}}", key), value);
+    }
+    message
+}
+
+/// First line of a capture, shortened for use in a message.
+fn capture_text(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("").trim();
+    if line.chars().count() > MAX_CAPTURE_CHARS {
+        let cut: String = line.chars().take(MAX_CAPTURE_CHARS).collect();
+        format!("{}...", cut)
+    } else {
+        line.to_string()
+    }
+}
+
+/// Whether `node` sits in a Rust `#[test]` function or `#[cfg(test)]` module.
+fn in_test_code(node: Node, source: &str) -> bool {
+    let mut current = Some(node);
+    while let Some(n) = current {
+        if matches!(n.kind(), "function_item" | "mod_item") {
+            // Attributes are the preceding siblings of the item
+            let mut sibling = n.prev_named_sibling();
+            while let Some(attribute) = sibling.filter(|s| s.kind() == "attribute_item") {
+                let text = attribute.utf8_text(source.as_bytes()).unwrap_or("");
+                if text.contains("test") && !text.contains("not(test") {
+                    return true;
+                }
+                sibling = attribute.prev_named_sibling();
+            }
+        }
+        current = n.parent();
+    }
+    false
+}
+
+/// Whether `path` exists in the project. Globs match the discovered files.
+fn file_exists(project: &Project, path: &str) -> bool {
+    if !path.contains(['*', '?', '[']) {
+        return project.path.join(path).exists();
+    }
+    let Ok(glob) = globset::Glob::new(path) else {
+        return false;
+    };
+    let matcher = glob.compile_matcher();
+    project
+        .files
+        .iter()
+        .any(|f| matcher.is_match(f.path.strip_prefix(&project.path).unwrap_or(&f.path)))
+}
+
+fn metadata_value<'a>(project: &'a Project, key: &str) -> Option<&'a str> {
+    let metadata = &project.metadata;
+    match key {
+        "target_framework" => metadata.target_framework.as_deref(),
+        "node_version" => metadata.node_version.as_deref(),
+        "python_version" => metadata.python_version.as_deref(),
+        "rust_edition" => metadata.rust_edition.as_deref(),
+        "entry_point" => metadata.entry_point.as_deref(),
+        "build_command" => metadata.build_command.as_deref(),
+        _ => metadata.extra.get(key).map(String::as_str),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{dependency, source_file};
+    use crate::types::{ProjectType, SourceFile, SourcePosition, SymbolKind};
+
+    fn project(project_type: ProjectType, dependencies: &[(&str, &str)]) -> Project {
+        Project {
+            project_type,
+            dependencies: dependencies
+                .iter()
+                .map(|(name, version)| dependency(name, version))
+                .collect(),
+            ..crate::test_support::project("/nonexistent/project")
+        }
+    }
+
+    #[test]
+    fn test_dependency_rules() {
+        let toml = r#"
+[[rules]]
+id = "express-outdated"
+severity = "warning"
+category = "security"
+message = "Express {version} is outdated. Consider upgrading to Express 4.x or 5.x"
+[rules.when]
+project_type = ["node"]
+dependency = { name = "express", version = "<4" }
+"#;
+        let engine = RuleEngine::from_rules(RuleEngine::parse(toml, "toml").unwrap()).unwrap();
+
+        let old = engine.evaluate(
+            &project(ProjectType::Node, &[("express", "^3.21.2")]),
+            "express",
+        );
+        assert_eq!(old.len(), 1);
+        assert_eq!(
+            old[0].message,
+            "Express ^3.21.2 is outdated. Consider upgrading to Express 4.x or 5.x"
+        );
+
+        let current = engine.evaluate(
+            &project(ProjectType::Node, &[("express", "^4.18.0")]),
+            "express",
+        );
+        assert!(current.is_empty());
+
+        // Rules are scoped by project type
+        let rust = engine.evaluate(&project(ProjectType::Rust, &[("express", "3.0.0")]), "rust");
+        assert!(rust.is_empty());
+    }
+
+    #[test]
+    fn test_files_missing_and_metadata() {
+        let engine = RuleEngine::builtin();
+
+        let python = engine.evaluate(&project(ProjectType::Python, &[]), "python");
+        assert_eq!(python[0].category, "best-practices");
+
+        let mut laravel = project(ProjectType::Php, &[("laravel/framework", "^8.75")]);
+        laravel
+            .metadata
+            .extra
+            .insert("framework".to_string(), "laravel".to_string());
+        let suggestions = engine.evaluate(&laravel, "laravel");
+        let categories: Vec<&str> = suggestions.iter().map(|s| s.category.as_str()).collect();
+        assert_eq!(categories, vec!["configuration"]);
+    }
+
+    #[test]
+    fn test_symbol_rule_sets_location() {
+        let toml = r#"
+[[rules]]
+id = "no-managers"
+severity = "warning"
+category = "naming"
+message = "{kind} '{symbol}' is a vague name"
+[rules.when]
+symbol = { kind = "class", name = "Manager$", without_modifiers = ["abstract"] }
+"#;
+        let engine = RuleEngine::from_rules(RuleEngine::parse(toml, "toml").unwrap()).unwrap();
+
+        let range = SourceRange {
+            start: SourcePosition {
+                line: 3,
+                column: 1,
+                byte: 20,
+            },
+            end: SourcePosition {
+                line: 9,
+                column: 2,
+                byte: 120,
+            },
+        };
+        let class = |name: &str, modifiers: &[&str]| Symbol {
+            name: name.to_string(),
+            kind: SymbolKind::Class,
+            modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+            children: vec![],
+            range: Some(range),
+            signature: None,
+        };
+        let mut project = project(ProjectType::DotNet, &[]);
+        project.files.push(SourceFile {
+            symbols: vec![
+                class("UserManager", &["public"]),
+                class("BaseManager", &["public", "abstract"]),
+                class("UserService", &["public"]),
+            ],
+            ..source_file("/nonexistent/project/Users.cs")
+        });
+
+        let suggestions = engine.evaluate(&project, "dotnet");
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(
+            suggestions[0].message,
+            "class 'UserManager' is a vague name"
+        );
+        assert_eq!(suggestions[0].line, Some(3));
+        assert_eq!(suggestions[0].range, Some(range));
+    }
+
+    #[test]
+    fn test_later_files_override_and_disable() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("00-default.toml"), BUILTIN_RULES[0]).unwrap();
+        fs::write(dir.path().join("01-lint.toml"), BUILTIN_RULES[1]).unwrap();
+        fs::write(
+            dir.path().join("10-house.json"),
+            r#"{"rules": [
+                {"id": "large-project", "enabled": false, "category": "", "message": ""},
+                {"id": "php-crypto-packages", "severity": "error", "category": "security",
+                 "message": "Express {version} is banned", "when": {"dependency": {"name": "express", "version": "<5"}}}
+            ]}"#,
+        )
+        .unwrap();
+
+        let engine = RuleEngine::load(dir.path()).unwrap();
+        assert_eq!(engine.len(), RuleEngine::builtin().len() - 1);
+
+        let suggestions = engine.evaluate(
+            &project(ProjectType::Node, &[("express", "4.18.2")]),
+            "express",
+        );
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].severity, SeverityLevel::Error);
+        assert_eq!(suggestions[0].message, "Express 4.18.2 is banned");
+    }
+
+    fn written_file(root: &Path, relative: &str, content: &str) -> SourceFile {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        SourceFile {
+            size_bytes: content.len() as u64,
+            ..source_file(path)
+        }
+    }
+
+    #[test]
+    fn test_builtin_lint_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let mut project = project(ProjectType::Unknown, &[]);
+        project.path = root.to_path_buf();
+        project.files = vec![
+            written_file(
+                root,
+                "Services/OrderService.cs",
+                "class OrderService\n{\n    public async void Save() { await Task.Delay(1); }\n    public async Task LoadAsync() { }\n    private async void OnClick(object sender, EventArgs e) { }\n}\n",
+            ),
+            written_file(
+                root,
+                "src/lib.rs",
+                "pub fn port() -> u16 {\n    std::env::var(\"PORT\").unwrap().parse().unwrap_or(80)\n}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn parses() {\n        super::port().to_string().parse::<u16>().unwrap();\n    }\n}\n",
+            ),
+            written_file(root, "tests/api.rs", "fn main() { Some(1).unwrap(); }\n"),
+            written_file(
+                root,
+                "web/app.js",
+                "const config = eval(\"(\" + text + \")\");\nwindow.eval(code);\n",
+            ),
+            written_file(
+                root,
+                "jobs/run.py",
+                "try:\n    run()\nexcept:\n    pass\n\ntry:\n    run()\nexcept ValueError:\n    pass\n",
+            ),
+        ];
+
+        let engine = RuleEngine::builtin();
+        assert!(engine.evaluate(&project, "unknown").is_empty());
+
+        let findings = engine.lint(&project, "unknown");
+        let found: Vec<(&str, usize)> = findings
+            .iter()
+            .map(|f| (f.rule_id.as_deref().unwrap(), f.line.unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("csharp-async-void", 3),
+                ("rust-unwrap", 2),
+                ("js-eval", 1),
+                ("python-bare-except", 3)
+            ]
+        );
+
+        let async_void = &findings[0];
+        assert!(async_void.message.starts_with("'Save' is async void"));
+        assert_eq!(async_void.pattern_id.as_deref(), Some("csharp-async-task"));
+        assert_eq!(async_void.range.unwrap().start.column, 5);
+        assert_eq!(findings[2].severity, SeverityLevel::Error);
+    }
+
+    #[test]
+    fn test_lint_rule_conditions_and_excludes() {
+        let toml = r#"
+[[rules]]
+id = "no-println"
+category = "logging"
+message = "Use tracing instead of {macro}!"
+[rules.when]
+project_type = ["rust"]
+[rules.when.query]
+language = ["rs"]
+exclude = ["src/bin/**"]
+query = '((macro_invocation macro: (identifier) @macro) @match (#eq? @macro "println"))'
+"#;
+        let engine = RuleEngine::from_rules(RuleEngine::parse(toml, "toml").unwrap()).unwrap();
+        assert_eq!(engine.lint_rule_count(), 1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let source = "fn main() {\n    println!(\"hi\");\n    eprintln!(\"err\");\n}\n";
+        let mut project = project(ProjectType::Rust, &[]);
+        project.path = root.to_path_buf();
+        project.files = vec![
+            written_file(root, "src/main.rs", source),
+            written_file(root, "src/bin/tool.rs", source),
+        ];
+
+        let findings = engine.lint(&project, "rust");
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].message, "Use tracing instead of println!");
+        assert_eq!(findings[0].file, Some(root.join("src/main.rs")));
+        assert_eq!(findings[0].line, Some(2));
+
+        // Project conditions still apply
+        project.project_type = ProjectType::Node;
+        assert!(engine.lint(&project, "node").is_empty());
+    }
+
+    #[test]
+    fn test_invalid_query_is_rejected() {
+        let rule = |language: &str, query: &str| {
+            format!(
+                "[[rules]]\nid = \"q\"\ncategory = \"c\"\nmessage = \"m\"\n[rules.when.query]\nlanguage = [\"{}\"]\nquery = '{}'\n",
+                language, query
+            )
+        };
+        let compile =
+            |toml: String| RuleEngine::from_rules(RuleEngine::parse(&toml, "toml").unwrap());
+        assert!(compile(rule("rs", "(call_expression) @match")).is_ok());
+        assert!(compile(rule("rs", "(no_such_node) @match")).is_err());
+        assert!(compile(rule("php", "(call_expression) @match")).is_err());
+    }
+
+    #[test]
+    fn test_invalid_rule_is_rejected() {
+        let toml = "[[rules]]\nid = \"x\"\ncategory = \"c\"\nmessage = \"m\"\n[rules.when]\ndependency = { name = \"a\", version = \"not a range\" }\n";
+        let rules = RuleEngine::parse(toml, "toml").unwrap();
+        assert!(RuleEngine::from_rules(rules).is_err());
+
+        assert!(RuleEngine::parse(
+            "[[rules]]\nid = \"x\"\ncategory = \"c\"\nmessage = \"m\"\ntypo = 1\n",
+            "toml"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Fixtures shared by unit tests.
+//!
+//! Each builder fills every field with a neutral value, so tests set only
+//! what they exercise with struct update syntax:
+//! `Project { files, ..project("/repo") }`.
+
+use chrono::Utc;
+use std::path::PathBuf;
+
+use crate::types::{
+    CodePattern, Dependency, FileMetrics, Project, ProjectMetadata, ProjectType, ScanSummary,
+    SourceFile,
+};
+
+/// An empty project of unknown type at `path`, named "demo".
+pub(crate) fn project(path: impl Into<PathBuf>) -> Project {
+    Project {
+        path: path.into(),
+        name: "demo".to_string(),
+        project_type: ProjectType::Unknown,
+        version: None,
+        dependencies: vec![],
+        files: vec![],
+        metadata: ProjectMetadata::default(),
+        scan: ScanSummary::default(),
+        git: None,
+    }
+}
+
+/// A source file at `path` without symbols; the language is its extension.
+pub(crate) fn source_file(path: impl Into<PathBuf>) -> SourceFile {
+    let path = path.into();
+    SourceFile {
+        language: path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_string(),
+        path,
+        size_bytes: 0,
+        symbols: vec![],
+        metrics: FileMetrics::default(),
+        namespace: None,
+        imports: vec![],
+    }
+}
+
+/// A runtime dependency.
+pub(crate) fn dependency(name: &str, version: &str) -> Dependency {
+    Dependency {
+        name: name.to_string(),
+        version: version.to_string(),
+        dev_only: false,
+    }
+}
+
+/// A blazor-server pattern titled `id`, created and updated now.
+pub(crate) fn pattern(id: &str) -> CodePattern {
+    let now = Utc::now();
+    CodePattern {
+        id: id.to_string(),
+        category: "patterns".to_string(),
+        framework: "blazor-server".to_string(),
+        version: "10.0".to_string(),
+        title: id.to_string(),
+        description: String::new(),
+        code: String::new(),
+        tags: vec![],
+        usage_count: 0,
+        relevance_score: 0.8,
+        created_at: now,
+        updated_at: now,
+        deprecated: None,
+    }
+}
+
+/// `tags` as owned strings.
+pub(crate) fn tags(tags: &[&str]) -> Vec<String> {
+    tags.iter().map(|t| t.to_string()).collect()
+}
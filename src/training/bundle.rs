@@ -0,0 +1,360 @@
+//! Portable pattern bundles.
+//!
+//! A bundle is one JSON file holding patterns and a manifest: the format
+//! version, the filter the patterns were exported with, and a SHA-256
+//! checksum per pattern plus one over all of them. Checksums cover the
+//! pattern JSON as stored in the bundle, so bundles written by versions with
+//! other pattern fields still verify. Bundles are verified before anything
+//! is imported, so a truncated or hand-edited file is rejected as a whole.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::error::{TrainingError, TrainingResult};
+use crate::types::CodePattern;
+use crate::utils::hash_string;
+
+/// Identifies a file as a pattern bundle.
+pub const BUNDLE_FORMAT: &str = "mcp-context-patterns";
+/// Bumped when the bundle layout changes; older versions stay readable.
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Patterns to export or import; empty fields match everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub framework: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// A pattern must have all of them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl BundleFilter {
+    /// Whether `pattern` passes the filter; names compare case-insensitively.
+    pub fn matches(&self, pattern: &CodePattern) -> bool {
+        let same = |expected: &Option<String>, actual: &str| {
+            expected
+                .as_ref()
+                .is_none_or(|e| e.eq_ignore_ascii_case(actual))
+        };
+        same(&self.framework, &pattern.framework)
+            && same(&self.category, &pattern.category)
+            && self
+                .tags
+                .iter()
+                .all(|tag| pattern.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+    }
+}
+
+/// What to do with an imported pattern whose ID already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the existing pattern
+    #[default]
+    Skip,
+    /// Replace the existing pattern, recording a revision
+    Overwrite,
+    /// Import under a free ID (`<id>-imported`, `<id>-imported-2`, ...)
+    Rename,
+}
+
+impl ConflictPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::Overwrite => "overwrite",
+            Self::Rename => "rename",
+        }
+    }
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            "rename" => Ok(Self::Rename),
+            other => Err(format!(
+                "Unknown conflict policy '{}': expected skip, overwrite or rename",
+                other
+            )),
+        }
+    }
+}
+
+/// One pattern listed in a manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleEntry {
+    pub id: String,
+    pub framework: String,
+    pub category: String,
+    /// SHA-256 of the pattern's JSON as stored in the bundle
+    pub checksum: String,
+}
+
+/// Describes and checksums the patterns of a bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// Always `BUNDLE_FORMAT`
+    pub format: String,
+    pub format_version: u32,
+    /// Program and version that wrote the bundle
+    pub generator: String,
+    pub exported_at: DateTime<Utc>,
+    /// Filter the patterns were exported with
+    #[serde(default)]
+    pub filter: BundleFilter,
+    pub pattern_count: usize,
+    /// SHA-256 over the pattern checksums, in order
+    pub checksum: String,
+    pub entries: Vec<BundleEntry>,
+}
+
+/// Patterns with their manifest, as written to a bundle file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternBundle {
+    pub manifest: BundleManifest,
+    /// Patterns as stored; `verified_patterns` checks and decodes them
+    pub patterns: Vec<serde_json::Value>,
+}
+
+impl PatternBundle {
+    /// Bundle `patterns`, exported with `filter`.
+    pub fn new<'a>(
+        patterns: impl IntoIterator<Item = &'a CodePattern>,
+        filter: BundleFilter,
+    ) -> Self {
+        let (entries, patterns): (Vec<BundleEntry>, Vec<serde_json::Value>) = patterns
+            .into_iter()
+            .map(|p| {
+                let json = stored(p);
+                let entry = BundleEntry {
+                    id: p.id.clone(),
+                    framework: p.framework.clone(),
+                    category: p.category.clone(),
+                    checksum: pattern_checksum(&json),
+                };
+                (entry, json)
+            })
+            .unzip();
+        Self {
+            manifest: BundleManifest {
+                format: BUNDLE_FORMAT.to_string(),
+                format_version: BUNDLE_FORMAT_VERSION,
+                generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+                exported_at: Utc::now(),
+                filter,
+                pattern_count: patterns.len(),
+                checksum: combined_checksum(&entries),
+                entries,
+            },
+            patterns,
+        }
+    }
+
+    /// Read and verify a bundle file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read bundle {}", path.display()))?;
+        let bundle: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse bundle {}", path.display()))?;
+        bundle.verify()?;
+        Ok(bundle)
+    }
+
+    /// Write the bundle to `path`, replacing any file there.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize bundle")?;
+        fs::write(path, json).with_context(|| format!("Failed to write bundle {}", path.display()))
+    }
+
+    /// Check the format, the pattern count and every checksum.
+    pub fn verify(&self) -> TrainingResult<()> {
+        let invalid = |reason: String| Err(TrainingError::InvalidBundle(reason));
+        let manifest = &self.manifest;
+        if manifest.format != BUNDLE_FORMAT {
+            return invalid(format!(
+                "not a pattern bundle (format '{}')",
+                manifest.format
+            ));
+        }
+        if manifest.format_version > BUNDLE_FORMAT_VERSION {
+            return invalid(format!(
+                "format version {} is newer than the supported {}",
+                manifest.format_version, BUNDLE_FORMAT_VERSION
+            ));
+        }
+        if manifest.pattern_count != self.patterns.len()
+            || manifest.entries.len() != self.patterns.len()
+        {
+            return invalid(format!(
+                "manifest lists {} patterns, bundle holds {}",
+                manifest.pattern_count,
+                self.patterns.len()
+            ));
+        }
+        for (entry, pattern) in manifest.entries.iter().zip(&self.patterns) {
+            if pattern["id"] != entry.id.as_str() || entry.checksum != pattern_checksum(pattern) {
+                return invalid(format!("checksum mismatch for pattern '{}'", entry.id));
+            }
+        }
+        if manifest.checksum != combined_checksum(&manifest.entries) {
+            return invalid("manifest checksum mismatch".to_string());
+        }
+        Ok(())
+    }
+
+    /// The patterns of the bundle, once it passes `verify`.
+    pub fn verified_patterns(&self) -> TrainingResult<Vec<CodePattern>> {
+        self.verify()?;
+        self.manifest
+            .entries
+            .iter()
+            .zip(&self.patterns)
+            .map(|(entry, pattern)| {
+                CodePattern::deserialize(pattern).map_err(|e| {
+                    TrainingError::InvalidBundle(format!("pattern '{}': {}", entry.id, e))
+                })
+            })
+            .collect()
+    }
+}
+
+/// What an import did with each pattern of a bundle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub added: Vec<String>,
+    pub overwritten: Vec<String>,
+    /// (ID in the bundle, ID imported as)
+    pub renamed: Vec<(String, String)>,
+    /// Existing IDs kept under `ConflictPolicy::Skip`, or identical to the
+    /// bundled pattern under `ConflictPolicy::Overwrite`
+    pub skipped: Vec<String>,
+}
+
+impl ImportReport {
+    /// Patterns that were added or changed.
+    pub fn imported(&self) -> usize {
+        self.added.len() + self.overwritten.len() + self.renamed.len()
+    }
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} added, {} overwritten, {} renamed, {} skipped",
+            self.added.len(),
+            self.overwritten.len(),
+            self.renamed.len(),
+            self.skipped.len()
+        )
+    }
+}
+
+/// `pattern` as stored in a bundle file.
+fn stored(pattern: &CodePattern) -> serde_json::Value {
+    // Through the JSON text, so scores are stored as written rather than
+    // widened from f32
+    let json = serde_json::to_string(pattern).expect("patterns serialize to JSON");
+    serde_json::from_str(&json).expect("serialized patterns parse")
+}
+
+fn pattern_checksum(pattern: &serde_json::Value) -> String {
+    hash_string(&pattern.to_string())
+}
+
+fn combined_checksum(entries: &[BundleEntry]) -> String {
+    let checksums: Vec<&str> = entries.iter().map(|e| e.checksum.as_str()).collect();
+    hash_string(&checksums.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tags;
+
+    fn pattern(id: &str, framework: &str, pattern_tags: &[&str]) -> CodePattern {
+        CodePattern {
+            framework: framework.to_string(),
+            code: "fn main() {}".to_string(),
+            tags: tags(pattern_tags),
+            ..crate::test_support::pattern(id)
+        }
+    }
+
+    #[test]
+    fn test_bundle_round_trip_and_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.json");
+        let bundle = PatternBundle::new(
+            &[pattern("a", "rust", &[]), pattern("b", "rust", &["async"])],
+            BundleFilter::default(),
+        );
+        bundle.save(&path).unwrap();
+
+        let loaded = PatternBundle::load(&path).unwrap();
+        assert_eq!(loaded.manifest.checksum, bundle.manifest.checksum);
+        assert_eq!(loaded.patterns.len(), 2);
+
+        // Editing a pattern without updating the manifest is caught
+        let edited = fs::read_to_string(&path)
+            .unwrap()
+            .replace("fn main() {}", "fn main() { evil() }");
+        fs::write(&path, edited).unwrap();
+        let error = PatternBundle::load(&path).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("checksum mismatch for pattern 'a'"));
+
+        let mut dropped = bundle.clone();
+        dropped.patterns.pop();
+        assert!(matches!(
+            dropped.verify(),
+            Err(TrainingError::InvalidBundle(_))
+        ));
+        let mut newer = bundle;
+        newer.manifest.format_version = BUNDLE_FORMAT_VERSION + 1;
+        assert!(newer.verify().is_err());
+    }
+
+    #[test]
+    fn test_bundle_with_other_pattern_fields() {
+        // A pattern written by a version with a field this one does not know
+        let mut bundle = PatternBundle::new(&[pattern("a", "rust", &[])], BundleFilter::default());
+        bundle.patterns[0]["legacy_score"] = 3.into();
+        bundle.manifest.entries[0].checksum = pattern_checksum(&bundle.patterns[0]);
+        bundle.manifest.checksum = combined_checksum(&bundle.manifest.entries);
+
+        let patterns = bundle.verified_patterns().unwrap();
+        assert_eq!(patterns[0].id, "a");
+
+        bundle.patterns[0]["code"] = "evil".into();
+        assert!(bundle.verified_patterns().is_err());
+    }
+
+    #[test]
+    fn test_filter_and_policy() {
+        let filter = BundleFilter {
+            framework: Some("Rust".to_string()),
+            category: None,
+            tags: vec!["async".to_string()],
+        };
+        assert!(filter.matches(&pattern("a", "rust", &["Async", "tokio"])));
+        assert!(!filter.matches(&pattern("b", "rust", &["tokio"])));
+        assert!(!filter.matches(&pattern("c", "react", &["async"])));
+        assert!(BundleFilter::default().matches(&pattern("d", "react", &[])));
+
+        assert_eq!("rename".parse(), Ok(ConflictPolicy::Rename));
+        assert!("merge".parse::<ConflictPolicy>().is_err());
+    }
+}
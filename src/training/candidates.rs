@@ -0,0 +1,53 @@
+//! Pattern candidates awaiting review.
+//!
+//! Candidates mined from analyzed projects are kept in
+//! `<storage>/.candidates.json` until they are accepted, which turns them
+//! into patterns, or rejected. Rejected IDs are remembered so the same idiom
+//! is not proposed again on the next analysis.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::types::PatternCandidate;
+
+/// File under the pattern storage that holds the candidates.
+const CANDIDATES_FILE: &str = ".candidates.json";
+
+#[derive(Serialize, Deserialize)]
+struct CandidatesFile<'a> {
+    candidates: Cow<'a, [PatternCandidate]>,
+    rejected: Cow<'a, BTreeSet<String>>,
+}
+
+/// Read the pending candidates and rejected IDs stored under `storage`.
+pub(super) fn load(storage: &Path) -> Result<(Vec<PatternCandidate>, BTreeSet<String>)> {
+    let path = storage.join(CANDIDATES_FILE);
+    if !path.is_file() {
+        return Ok(Default::default());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read candidates file {}", path.display()))?;
+    let file: CandidatesFile = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse candidates file {}", path.display()))?;
+    Ok((file.candidates.into_owned(), file.rejected.into_owned()))
+}
+
+/// Write the pending candidates and rejected IDs.
+pub(super) fn save(
+    storage: &Path,
+    candidates: &[PatternCandidate],
+    rejected: &BTreeSet<String>,
+) -> Result<()> {
+    let path = storage.join(CANDIDATES_FILE);
+    let file = CandidatesFile {
+        candidates: Cow::Borrowed(candidates),
+        rejected: Cow::Borrowed(rejected),
+    };
+    let json = serde_json::to_string_pretty(&file).context("Failed to serialize candidates")?;
+    fs::write(&path, json)
+        .with_context(|| format!("Failed to write candidates file {}", path.display()))
+}
@@ -0,0 +1,133 @@
+//! Pattern feedback: helpful / not helpful votes and usage counts.
+//!
+//! Votes and uses are kept per pattern ID in `<storage>/.feedback.json`,
+//! apart from the pattern files, so recording one writes a single small
+//! file and never shows up as a content change. They are applied to the
+//! loaded patterns and taken out again when pattern files are written. A
+//! pattern's `relevance_score` is derived from the score it had before the
+//! first vote and the share of helpful votes, weighted by how many votes
+//! there are: a single vote nudges it, a consistent record moves it most of
+//! the way.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::types::{CodePattern, PatternFeedback};
+
+/// File under the pattern storage that holds the votes.
+const FEEDBACK_FILE: &str = ".feedback.json";
+
+/// Votes that weigh as much as the base relevance score.
+const PRIOR_VOTES: f32 = 5.0;
+
+#[derive(Serialize, Deserialize)]
+struct FeedbackFile<'a> {
+    patterns: Cow<'a, HashMap<String, PatternFeedback>>,
+}
+
+/// Read the votes stored under `storage`, keyed by pattern ID.
+pub(super) fn load(storage: &Path) -> Result<HashMap<String, PatternFeedback>> {
+    let path = storage.join(FEEDBACK_FILE);
+    if !path.is_file() {
+        return Ok(HashMap::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read feedback file {}", path.display()))?;
+    let file: FeedbackFile = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse feedback file {}", path.display()))?;
+    Ok(file.patterns.into_owned())
+}
+
+/// Write the votes of every pattern.
+pub(super) fn save(storage: &Path, feedback: &HashMap<String, PatternFeedback>) -> Result<()> {
+    let path = storage.join(FEEDBACK_FILE);
+    let file = FeedbackFile {
+        patterns: Cow::Borrowed(feedback),
+    };
+    let json = serde_json::to_string_pretty(&file).context("Failed to serialize feedback")?;
+    fs::write(&path, json)
+        .with_context(|| format!("Failed to write feedback file {}", path.display()))
+}
+
+/// `pattern` as loaded from its file, with the recorded uses and votes applied.
+pub(super) fn apply(pattern: &mut CodePattern, feedback: &PatternFeedback) {
+    pattern.usage_count += feedback.uses as usize;
+    if feedback.helpful + feedback.not_helpful > 0 {
+        pattern.relevance_score = adjusted_relevance(feedback);
+    }
+}
+
+/// `pattern` as its file stores it: `apply` undone.
+pub(super) fn stored<'a>(
+    pattern: &'a CodePattern,
+    feedback: Option<&PatternFeedback>,
+) -> Cow<'a, CodePattern> {
+    let Some(feedback) = feedback else {
+        return Cow::Borrowed(pattern);
+    };
+    let mut stored = pattern.clone();
+    stored.usage_count = stored.usage_count.saturating_sub(feedback.uses as usize);
+    if feedback.helpful + feedback.not_helpful > 0 {
+        stored.relevance_score = feedback.base_relevance;
+    }
+    Cow::Owned(stored)
+}
+
+/// Relevance score of a pattern with `feedback`, between 0.0 and 1.0.
+pub(super) fn adjusted_relevance(feedback: &PatternFeedback) -> f32 {
+    let votes = (feedback.helpful + feedback.not_helpful) as f32;
+    if votes == 0.0 {
+        return feedback.base_relevance;
+    }
+    let helpful_share = feedback.helpful as f32 / votes;
+    let weight = votes / (votes + PRIOR_VOTES);
+    (feedback.base_relevance * (1.0 - weight) + helpful_share * weight).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feedback(helpful: u32, not_helpful: u32) -> PatternFeedback {
+        PatternFeedback {
+            helpful,
+            not_helpful,
+            uses: 0,
+            base_relevance: 0.8,
+        }
+    }
+
+    #[test]
+    fn test_votes_move_relevance_gradually() {
+        assert!((adjusted_relevance(&feedback(0, 0)) - 0.8).abs() < f32::EPSILON);
+
+        let one_up = adjusted_relevance(&feedback(1, 0));
+        let one_down = adjusted_relevance(&feedback(0, 1));
+        assert!(one_up > 0.8 && one_up < 0.85);
+        assert!(one_down < 0.8 && one_down > 0.6);
+
+        // More of the same vote keeps moving the score, without overshooting
+        let many_down = adjusted_relevance(&feedback(0, 20));
+        assert!((0.0..0.2).contains(&many_down));
+        assert!(adjusted_relevance(&feedback(50, 0)) <= 1.0);
+
+        // Mixed votes settle near the helpful share
+        let mixed = adjusted_relevance(&feedback(30, 30));
+        assert!((mixed - 0.52).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_feedback_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load(dir.path()).unwrap().is_empty());
+
+        let mut votes = HashMap::new();
+        votes.insert("blazor-lifecycle-oninit".to_string(), feedback(2, 1));
+        save(dir.path(), &votes).unwrap();
+        assert_eq!(load(dir.path()).unwrap(), votes);
+    }
+}
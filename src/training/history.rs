@@ -0,0 +1,251 @@
+//! Pattern revision history.
+//!
+//! Every change made through `TrainingManager` appends a `PatternRevision`
+//! holding the full pattern after the change, so any revision can be shown,
+//! compared or restored. Histories live next to the pattern files in
+//! `<storage>/.history/`, one JSON file per pattern ID, and outlive the
+//! pattern itself so deletions can be rolled back too.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::types::{CodePattern, PatternRevision};
+use crate::utils::hash_string;
+
+/// Directory under the pattern storage that holds the histories.
+pub(super) const HISTORY_DIR: &str = ".history";
+
+/// Lines of unchanged context around each diff hunk.
+const CONTEXT_LINES: usize = 3;
+
+/// Above this many line pairs, diffs replace the whole text instead of aligning lines.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// A history file: the revisions of one pattern.
+#[derive(Serialize, Deserialize)]
+struct HistoryFile<'a> {
+    id: String,
+    revisions: Cow<'a, [PatternRevision]>,
+}
+
+/// History file of pattern `id`; named by hash since IDs may hold any character.
+fn history_file(storage: &Path, id: &str) -> PathBuf {
+    storage
+        .join(HISTORY_DIR)
+        .join(format!("{}.json", &hash_string(id)[..16]))
+}
+
+/// Read every history under `storage`, keyed by pattern ID.
+pub(super) fn load(storage: &Path) -> Result<HashMap<String, Vec<PatternRevision>>> {
+    let dir = storage.join(HISTORY_DIR);
+    let mut histories = HashMap::new();
+    if !dir.is_dir() {
+        return Ok(histories);
+    }
+
+    for entry in fs::read_dir(&dir).context("Failed to read pattern history directory")? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read history file {}", path.display()))?;
+        let file: HistoryFile = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse history file {}", path.display()))?;
+        histories.insert(file.id, file.revisions.into_owned());
+    }
+    Ok(histories)
+}
+
+/// Write the history of pattern `id`.
+pub(super) fn save(storage: &Path, id: &str, revisions: &[PatternRevision]) -> Result<()> {
+    let path = history_file(storage, id);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("Failed to create pattern history directory")?;
+    }
+    let file = HistoryFile {
+        id: id.to_string(),
+        revisions: revisions.into(),
+    };
+    let json = serde_json::to_string_pretty(&file).context("Failed to serialize history")?;
+    fs::write(&path, json)
+        .with_context(|| format!("Failed to write history file {}", path.display()))
+}
+
+/// Names of the fields that differ between two states of a pattern.
+pub fn changed_fields(old: &CodePattern, new: &CodePattern) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    let mut check = |name, changed: bool| {
+        if changed {
+            fields.push(name);
+        }
+    };
+    check("category", old.category != new.category);
+    check("framework", old.framework != new.framework);
+    check("version", old.version != new.version);
+    check("title", old.title != new.title);
+    check("description", old.description != new.description);
+    check("code", old.code != new.code);
+    check("tags", old.tags != new.tags);
+    check(
+        "deprecated",
+        old.deprecated.is_some() != new.deprecated.is_some(),
+    );
+    fields
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Unified line diff of `old` and `new`; empty when they are equal.
+pub fn unified_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let ops = line_ops(&old, &new);
+
+    // Line numbers reached before each operation
+    let mut positions = Vec::with_capacity(ops.len());
+    let (mut old_line, mut new_line) = (0, 0);
+    for (op, _) in &ops {
+        positions.push((old_line, new_line));
+        match op {
+            Op::Equal => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Op::Delete => old_line += 1,
+            Op::Insert => new_line += 1,
+        }
+    }
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _))| *op != Op::Equal)
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut out = String::new();
+    let mut k = 0;
+    while k < changes.len() {
+        let start = changes[k].saturating_sub(CONTEXT_LINES);
+        let mut last = changes[k];
+        // Changes separated by at most twice the context share a hunk
+        while k + 1 < changes.len() && changes[k + 1] <= last + 2 * CONTEXT_LINES + 1 {
+            k += 1;
+            last = changes[k];
+        }
+        k += 1;
+        let end = (last + CONTEXT_LINES + 1).min(ops.len());
+
+        let hunk = &ops[start..end];
+        let (old_start, new_start) = positions[start];
+        let old_len = hunk.iter().filter(|(op, _)| *op != Op::Insert).count();
+        let new_len = hunk.iter().filter(|(op, _)| *op != Op::Delete).count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            old_len,
+            new_start + 1,
+            new_len
+        ));
+        for (op, line) in hunk {
+            out.push(match op {
+                Op::Equal => ' ',
+                Op::Delete => '-',
+                Op::Insert => '+',
+            });
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Align two texts line by line along their longest common subsequence.
+fn line_ops<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Op, &'a str)> {
+    let (n, m) = (old.len(), new.len());
+    if n * m > MAX_DIFF_CELLS {
+        return old
+            .iter()
+            .map(|l| (Op::Delete, *l))
+            .chain(new.iter().map(|l| (Op::Insert, *l)))
+            .collect();
+    }
+
+    // lcs[i * (m + 1) + j]: common subsequence length of old[i..] and new[j..]
+    let width = m + 1;
+    let mut lcs = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * width + j] = if old[i] == new[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            ops.push((Op::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            ops.push((Op::Delete, old[i]));
+            i += 1;
+        } else {
+            ops.push((Op::Insert, new[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|l| (Op::Delete, *l)));
+    ops.extend(new[j..].iter().map(|l| (Op::Insert, *l)));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n"), "");
+
+        let old = "fn main() {\n    let x = 1;\n    println!(\"{}\", x);\n}\n";
+        let new = "fn main() {\n    let x = 2;\n    println!(\"{}\", x);\n}\n";
+        assert_eq!(
+            unified_diff(old, new),
+            "@@ -1,4 +1,4 @@\n fn main() {\n-    let x = 1;\n+    let x = 2;\n     println!(\"{}\", x);\n }\n"
+        );
+
+        // Distant changes get separate hunks with three lines of context
+        let mut lines: Vec<String> = (1..=20).map(|i| i.to_string()).collect();
+        let old = lines.join("\n");
+        lines[1] = "two".to_string();
+        lines[18] = "nineteen".to_string();
+        let diff = unified_diff(&old, &lines.join("\n"));
+        let headers: Vec<&str> = diff.lines().filter(|l| l.starts_with("@@")).collect();
+        assert_eq!(headers, vec!["@@ -1,5 +1,5 @@", "@@ -16,5 +16,5 @@"]);
+        assert!(diff.contains("-19\n+nineteen\n"));
+    }
+
+    #[test]
+    fn test_unified_diff_insertions_and_deletions() {
+        assert_eq!(unified_diff("", "a\n"), "@@ -1,0 +1,1 @@\n+a\n");
+        assert_eq!(
+            unified_diff("a\nb\nc\n", "a\nc\n"),
+            "@@ -1,3 +1,2 @@\n a\n-b\n c\n"
+        );
+    }
+}
@@ -1,11 +1,28 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
-use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, TimeDelta, Utc};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-use crate::types::CodePattern;
+use crate::error::{TrainingError, TrainingResult};
+use crate::types::{
+    CodePattern, Deprecation, PatternCandidate, PatternFeedback, PatternRevision, RevisionAction,
+};
+
+mod bundle;
+mod candidates;
+mod feedback;
+mod history;
+mod search;
+mod semantic;
+
+pub use bundle::{BundleFilter, ConflictPolicy, ImportReport, PatternBundle};
+pub use history::{changed_fields, unified_diff};
+
+use search::{closest, similar, SearchIndex};
+use semantic::SemanticIndex;
 
 /// Validates and sanitizes a framework name to prevent path traversal attacks.
 ///
@@ -74,9 +91,80 @@ fn sanitize_framework_name(framework: &str) -> Result<String, String> {
 pub struct TrainingManager {
     patterns: Vec<CodePattern>,
     storage_path: PathBuf,
-    // Index for fast lookups
+    // Index for fast lookups; positions in `patterns`, rebuilt on removal
     category_index: HashMap<String, Vec<usize>>,
     framework_index: HashMap<String, Vec<usize>>,
+    // Full-text index over the same positions
+    text_index: SearchIndex,
+    // Optional vector index over the same positions
+    semantic: Option<SemanticIndex>,
+    // File each pattern is saved to, relative to `storage_path`
+    sources: HashMap<String, PathBuf>,
+    // Every pattern file loaded or created, so emptied files are removed on save
+    files: BTreeSet<PathBuf>,
+    // Revisions per pattern ID, kept after deletion so it can be rolled back
+    history: HashMap<String, Vec<PatternRevision>>,
+    // Patterns whose history changed since the last save
+    changed_history: HashSet<String>,
+    // Helpful / not helpful votes per pattern ID
+    feedback: HashMap<String, PatternFeedback>,
+    // Whether votes changed since the last save
+    changed_feedback: bool,
+    // Mined patterns awaiting review
+    candidates: Vec<PatternCandidate>,
+    // IDs of rejected candidates, never proposed again
+    rejected_candidates: BTreeSet<String>,
+    // Whether candidates changed since the last save
+    changed_candidates: bool,
+    // Recorded as the author of new revisions
+    author: String,
+}
+
+/// Fields of a pattern to change; `None` keeps the current value.
+#[derive(Debug, Clone, Default)]
+pub struct PatternUpdate {
+    pub category: Option<String>,
+    pub framework: Option<String>,
+    pub version: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub code: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl PatternUpdate {
+    fn apply(self, pattern: &mut CodePattern) {
+        if let Some(category) = self.category {
+            pattern.category = category;
+        }
+        if let Some(framework) = self.framework {
+            pattern.framework = framework;
+        }
+        if let Some(version) = self.version {
+            pattern.version = version;
+        }
+        if let Some(title) = self.title {
+            pattern.title = title;
+        }
+        if let Some(description) = self.description {
+            pattern.description = description;
+        }
+        if let Some(code) = self.code {
+            pattern.code = code;
+        }
+        if let Some(tags) = self.tags {
+            pattern.tags = tags;
+        }
+    }
+}
+
+/// A likely intended value for a search input that matched nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Correction {
+    /// `framework`, `category`, `tag` or `query`
+    pub field: &'static str,
+    pub given: String,
+    pub suggestion: String,
 }
 
 /// Search criteria for patterns
@@ -88,6 +176,19 @@ pub struct SearchCriteria {
     pub tags: Vec<String>,
     pub min_score: f32,
     pub max_results: Option<usize>,
+    pub mode: SearchMode,
+}
+
+/// How the query of a search is matched against patterns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// BM25 over the words of the query
+    #[default]
+    Keyword,
+    /// Vector similarity, which also finds synonyms and related concepts
+    Semantic,
+    /// Both, weighted equally
+    Hybrid,
 }
 
 impl TrainingManager {
@@ -97,13 +198,54 @@ impl TrainingManager {
             storage_path: storage_path.into(),
             category_index: HashMap::new(),
             framework_index: HashMap::new(),
+            text_index: SearchIndex::default(),
+            semantic: None,
+            sources: HashMap::new(),
+            files: BTreeSet::new(),
+            history: HashMap::new(),
+            changed_history: HashSet::new(),
+            feedback: HashMap::new(),
+            changed_feedback: false,
+            candidates: vec![],
+            rejected_candidates: BTreeSet::new(),
+            changed_candidates: false,
+            author: "unknown".to_string(),
         }
     }
 
+    /// Enables semantic search, caching pattern vectors in `cache_dir`.
+    pub fn with_semantic_index(mut self, cache_dir: impl AsRef<Path>) -> Self {
+        let location = semantic::cache_location(cache_dir.as_ref(), &self.storage_path);
+        let mut index = SemanticIndex::load(location);
+        index.build(&self.patterns);
+        self.semantic = Some(index);
+        self
+    }
+
+    /// Whether `SearchMode::Semantic` and `SearchMode::Hybrid` use vectors;
+    /// without a semantic index they fall back to keyword search.
+    pub fn has_semantic_index(&self) -> bool {
+        self.semantic.is_some()
+    }
+
+    /// Sets the author recorded in revisions made from now on.
+    pub fn set_author(&mut self, author: impl Into<String>) {
+        self.author = author.into();
+    }
+
     pub async fn load_patterns(&mut self) -> Result<()> {
         self.patterns.clear();
         self.category_index.clear();
         self.framework_index.clear();
+        self.sources.clear();
+        self.files.clear();
+        self.history.clear();
+        self.changed_history.clear();
+        self.feedback.clear();
+        self.changed_feedback = false;
+        self.candidates.clear();
+        self.rejected_candidates.clear();
+        self.changed_candidates = false;
 
         if !self.storage_path.exists() {
             tracing::warn!(
@@ -115,10 +257,12 @@ impl TrainingManager {
 
         let mut seen_ids: HashSet<String> = HashSet::new();
 
-        // Walk through all JSON files in the patterns directory
+        // Walk through all JSON files in the patterns directory, skipping
+        // hidden directories such as the revision history
         for entry in WalkDir::new(&self.storage_path)
             .follow_links(false)
             .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
             .filter_map(|e| e.ok())
         {
             let path = entry.path();
@@ -129,8 +273,18 @@ impl TrainingManager {
             }
         }
 
+        self.history = history::load(&self.storage_path)?;
+        self.feedback = feedback::load(&self.storage_path)?;
+        for pattern in &mut self.patterns {
+            if let Some(votes) = self.feedback.get(&pattern.id) {
+                feedback::apply(pattern, votes);
+            }
+        }
+        (self.candidates, self.rejected_candidates) = candidates::load(&self.storage_path)?;
+
         // Build indexes
         self.rebuild_indexes();
+        self.save_semantic_index();
 
         tracing::info!(
             "Loaded {} unique patterns from {:?}",
@@ -151,9 +305,16 @@ impl TrainingManager {
         let file: PatternFile =
             serde_json::from_str(&content).context("Failed to parse pattern JSON")?;
 
+        let relative = path
+            .strip_prefix(&self.storage_path)
+            .unwrap_or(path)
+            .to_path_buf();
+        self.files.insert(relative.clone());
+
         let mut duplicates = 0;
         for pattern in file.patterns {
             if seen_ids.insert(pattern.id.clone()) {
+                self.sources.insert(pattern.id.clone(), relative.clone());
                 self.patterns.push(pattern);
             } else {
                 duplicates += 1;
@@ -174,6 +335,10 @@ impl TrainingManager {
     fn rebuild_indexes(&mut self) {
         self.category_index.clear();
         self.framework_index.clear();
+        self.text_index = SearchIndex::build(&self.patterns);
+        if let Some(ref mut semantic) = self.semantic {
+            semantic.build(&self.patterns);
+        }
 
         for (idx, pattern) in self.patterns.iter().enumerate() {
             // Index by category
@@ -190,7 +355,13 @@ impl TrainingManager {
         }
     }
 
-    pub async fn save_patterns(&self) -> Result<()> {
+    /// Write every pattern back to the file it was loaded from.
+    ///
+    /// New patterns go to `<framework>-patterns.json`; files left without
+    /// patterns are removed so deleted patterns do not reappear on reload.
+    /// Histories, votes and candidates changed since the last save are
+    /// written too.
+    pub async fn save_patterns(&mut self) -> Result<()> {
         fs::create_dir_all(&self.storage_path).context("Failed to create storage directory")?;
 
         // Canonicalize storage path for security validation (after ensuring it exists)
@@ -199,38 +370,53 @@ impl TrainingManager {
             .canonicalize()
             .context("Failed to canonicalize storage path")?;
 
-        // Group patterns by framework
-        let mut by_framework: HashMap<String, Vec<&CodePattern>> = HashMap::new();
-
+        // Files hold patterns without recorded usage and votes
+        let mut by_file: BTreeMap<&Path, Vec<Cow<CodePattern>>> = self
+            .files
+            .iter()
+            .map(|file| (file.as_path(), Vec::new()))
+            .collect();
         for pattern in &self.patterns {
-            by_framework
-                .entry(pattern.framework.clone())
+            let file = self
+                .sources
+                .get(&pattern.id)
+                .with_context(|| format!("No pattern file for '{}'", pattern.id))?;
+            by_file
+                .entry(file)
                 .or_default()
-                .push(pattern);
+                .push(feedback::stored(pattern, self.feedback.get(&pattern.id)));
         }
 
-        // Save each framework to its own file
-        for (framework, patterns) in by_framework {
-            // SECURITY: Validate and sanitize framework name to prevent path traversal
-            let safe_framework = sanitize_framework_name(&framework)
-                .map_err(|e| anyhow::anyhow!("Invalid framework name '{}': {}", framework, e))?;
+        for (file, patterns) in by_file {
+            let file_path = canonical_storage.join(file);
 
-            let filename = format!("{}-patterns.json", safe_framework);
-            let file_path = canonical_storage.join(&filename);
-
-            // SECURITY: Double-check that the constructed path is within storage_path
-            // Since we already validated the framework name and are using canonical_storage,
-            // this should always be true, but we verify as defense in depth
-            if !file_path.starts_with(&canonical_storage) {
+            // SECURITY: Verify that the constructed path is within storage_path.
+            // New file names come from validated framework names, so this
+            // should always be true, but we verify as defense in depth
+            if !file_path.starts_with(&canonical_storage)
+                || file
+                    .components()
+                    .any(|c| matches!(c, std::path::Component::ParentDir))
+            {
                 anyhow::bail!(
-                    "Security error: Path traversal attempt detected for framework '{}'",
-                    framework
+                    "Security error: Path traversal attempt detected for '{}'",
+                    file.display()
                 );
             }
 
+            if patterns.is_empty() {
+                if file_path.exists() {
+                    fs::remove_file(&file_path).context(format!(
+                        "Failed to remove pattern file: {}",
+                        file_path.display()
+                    ))?;
+                }
+                continue;
+            }
+
             #[derive(serde::Serialize)]
             struct PatternFile<'a> {
-                patterns: Vec<&'a CodePattern>,
+                patterns: Vec<Cow<'a, CodePattern>>,
             }
 
             let file = PatternFile { patterns };
@@ -243,6 +429,15 @@ impl TrainingManager {
             ))?;
         }
 
+        for id in std::mem::take(&mut self.changed_history) {
+            if let Some(revisions) = self.history.get(&id) {
+                history::save(&canonical_storage, &id, revisions)?;
+            }
+        }
+        self.save_feedback()?;
+        self.save_candidates()?;
+        self.save_semantic_index();
+
         tracing::info!(
             "Saved {} patterns to {:?}",
             self.patterns.len(),
@@ -251,26 +446,51 @@ impl TrainingManager {
         Ok(())
     }
 
+    /// Persist new pattern vectors; the cache is rebuilt when missing, so a
+    /// failure only costs time on the next start.
+    fn save_semantic_index(&mut self) {
+        if let Some(ref mut semantic) = self.semantic {
+            if let Err(e) = semantic.save() {
+                tracing::warn!(error = %e, "Failed to save semantic index");
+            }
+        }
+    }
+
     /// Validates a pattern before adding it.
     /// Returns an error if the pattern contains invalid data.
-    fn validate_pattern(pattern: &CodePattern) -> Result<(), String> {
+    fn validate_pattern(pattern: &CodePattern) -> TrainingResult<()> {
         // Validate framework name
-        sanitize_framework_name(&pattern.framework)?;
+        sanitize_framework_name(&pattern.framework).map_err(|reason| {
+            TrainingError::InvalidFrameworkName {
+                name: pattern.framework.clone(),
+                reason,
+            }
+        })?;
 
-        // Validate ID (same rules as framework)
+        // Validate ID
+        let invalid_id = |reason: &str| TrainingError::InvalidPatternId {
+            id: pattern.id.clone(),
+            reason: reason.to_string(),
+        };
         if pattern.id.is_empty() {
-            return Err("Pattern ID cannot be empty".to_string());
+            return Err(invalid_id("Pattern ID cannot be empty"));
         }
         if pattern.id.len() > 128 {
-            return Err("Pattern ID too long (max 128 characters)".to_string());
+            return Err(invalid_id("Pattern ID too long (max 128 characters)"));
         }
 
         // Validate category
+        let invalid_category = |reason: &str| TrainingError::InvalidCategory {
+            name: pattern.category.clone(),
+            reason: reason.to_string(),
+        };
         if pattern.category.is_empty() {
-            return Err("Pattern category cannot be empty".to_string());
+            return Err(invalid_category("Pattern category cannot be empty"));
         }
         if pattern.category.len() > 64 {
-            return Err("Pattern category too long (max 64 characters)".to_string());
+            return Err(invalid_category(
+                "Pattern category too long (max 64 characters)",
+            ));
         }
 
         Ok(())
@@ -282,12 +502,15 @@ impl TrainingManager {
     /// The pattern's framework, id, and category are validated to prevent
     /// path traversal and other injection attacks.
     ///
-    /// # Returns
-    /// - `Ok(())` if the pattern was added successfully
-    /// - `Err` if the pattern contains invalid data
-    pub fn add_pattern(&mut self, mut pattern: CodePattern) -> Result<(), String> {
+    /// # Errors
+    /// Returns an error if the pattern contains invalid data or a pattern
+    /// with the same ID exists (use `update_pattern` to change it).
+    pub fn add_pattern(&mut self, mut pattern: CodePattern) -> TrainingResult<()> {
         // SECURITY: Validate pattern before adding
         Self::validate_pattern(&pattern)?;
+        if self.sources.contains_key(&pattern.id) {
+            return Err(TrainingError::DuplicatePattern(pattern.id));
+        }
 
         // Set timestamps if not set
         if pattern.created_at.timestamp() == 0 {
@@ -295,10 +518,20 @@ impl TrainingManager {
         }
         pattern.updated_at = Utc::now();
 
+        let file = PathBuf::from(format!("{}-patterns.json", pattern.framework));
+        self.files.insert(file.clone());
+        self.sources.insert(pattern.id.clone(), file);
+
+        self.record(&pattern, RevisionAction::Created, None);
+
         let idx = self.patterns.len();
         self.patterns.push(pattern.clone());
 
         // Update indexes
+        self.text_index.push(&pattern);
+        if let Some(ref mut semantic) = self.semantic {
+            semantic.push(&pattern);
+        }
         self.category_index
             .entry(pattern.category)
             .or_default()
@@ -312,10 +545,239 @@ impl TrainingManager {
         Ok(())
     }
 
+    /// Changes the given fields of a pattern.
+    ///
+    /// `expected_updated_at` is the `updated_at` the caller last read; the
+    /// edit is rejected with `TrainingError::Conflict` if the pattern changed
+    /// since.
+    pub fn update_pattern(
+        &mut self,
+        id: &str,
+        expected_updated_at: DateTime<Utc>,
+        update: PatternUpdate,
+    ) -> TrainingResult<&CodePattern> {
+        let idx = self.checked_index(id, expected_updated_at)?;
+        let mut pattern = self.patterns[idx].clone();
+        update.apply(&mut pattern);
+        Self::validate_pattern(&pattern)?;
+
+        touch(&mut pattern);
+        let previous = std::mem::replace(&mut self.patterns[idx], pattern);
+        self.record(
+            &self.patterns[idx].clone(),
+            RevisionAction::Updated,
+            Some(previous),
+        );
+        self.rebuild_indexes();
+        Ok(&self.patterns[idx])
+    }
+
+    /// Removes a pattern, checking `expected_updated_at` like `update_pattern`.
+    pub fn delete_pattern(
+        &mut self,
+        id: &str,
+        expected_updated_at: DateTime<Utc>,
+    ) -> TrainingResult<CodePattern> {
+        let idx = self.checked_index(id, expected_updated_at)?;
+        let pattern = self.patterns.remove(idx);
+        self.sources.remove(id);
+        // A pattern created later under the same ID starts without votes
+        if self.feedback.remove(id).is_some() {
+            self.changed_feedback = true;
+        }
+        self.record(&pattern, RevisionAction::Deleted, Some(pattern.clone()));
+        self.rebuild_indexes();
+        Ok(pattern)
+    }
+
+    /// Marks a pattern as deprecated so searches no longer return it.
+    ///
+    /// `replaced_by` must name another existing pattern.
+    pub fn deprecate_pattern(
+        &mut self,
+        id: &str,
+        expected_updated_at: DateTime<Utc>,
+        reason: &str,
+        replaced_by: Option<&str>,
+    ) -> TrainingResult<&CodePattern> {
+        let idx = self.checked_index(id, expected_updated_at)?;
+        if let Some(replacement) = replaced_by {
+            if replacement == id {
+                return Err(TrainingError::InvalidPatternId {
+                    id: replacement.to_string(),
+                    reason: "A pattern cannot replace itself".to_string(),
+                });
+            }
+            if self.get_pattern_by_id(replacement).is_none() {
+                return Err(TrainingError::PatternNotFound(replacement.to_string()));
+            }
+        }
+
+        let previous = self.patterns[idx].clone();
+        let pattern = &mut self.patterns[idx];
+        pattern.deprecated = Some(Deprecation {
+            reason: reason.to_string(),
+            replaced_by: replaced_by.map(str::to_string),
+            deprecated_at: Utc::now(),
+        });
+        touch(pattern);
+        self.record(
+            &self.patterns[idx].clone(),
+            RevisionAction::Deprecated,
+            Some(previous),
+        );
+        Ok(&self.patterns[idx])
+    }
+
+    /// Clears the deprecation of a pattern.
+    pub fn restore_pattern(
+        &mut self,
+        id: &str,
+        expected_updated_at: DateTime<Utc>,
+    ) -> TrainingResult<&CodePattern> {
+        let idx = self.checked_index(id, expected_updated_at)?;
+        let previous = self.patterns[idx].clone();
+        let pattern = &mut self.patterns[idx];
+        pattern.deprecated = None;
+        touch(pattern);
+        self.record(
+            &self.patterns[idx].clone(),
+            RevisionAction::Restored,
+            Some(previous),
+        );
+        Ok(&self.patterns[idx])
+    }
+
+    /// Revisions of a pattern, oldest first, including deleted patterns.
+    pub fn revisions(&self, id: &str) -> Option<&[PatternRevision]> {
+        self.history.get(id).map(Vec::as_slice)
+    }
+
+    /// Restores the content of a past revision as a new revision.
+    ///
+    /// A deleted pattern is re-created, in which case there is no
+    /// `updated_at` to check and `expected_updated_at` may be `None`; for an
+    /// existing pattern it is required, as in `update_pattern`. The usage
+    /// count and relevance score are kept since they come from usage and
+    /// votes, not from the pattern's content.
+    pub fn rollback_pattern(
+        &mut self,
+        id: &str,
+        revision: u32,
+        expected_updated_at: Option<DateTime<Utc>>,
+    ) -> TrainingResult<&CodePattern> {
+        let target = self
+            .history
+            .get(id)
+            .and_then(|revisions| revisions.iter().find(|r| r.revision == revision))
+            .ok_or_else(|| TrainingError::RevisionNotFound {
+                id: id.to_string(),
+                revision,
+            })?
+            .pattern
+            .clone();
+
+        let existing = self.patterns.iter().position(|p| p.id == id);
+        let idx = match (existing, expected_updated_at) {
+            (Some(_), Some(expected)) => Some(self.checked_index(id, expected)?),
+            (Some(idx), None) => {
+                return Err(TrainingError::Conflict {
+                    id: id.to_string(),
+                    expected: "(not given)".to_string(),
+                    actual: format_timestamp(self.patterns[idx].updated_at),
+                })
+            }
+            (None, _) => None,
+        };
+
+        let mut pattern = target;
+        let previous = idx.map(|idx| self.patterns[idx].clone());
+        if let Some(ref previous) = previous {
+            pattern.usage_count = previous.usage_count;
+            pattern.relevance_score = previous.relevance_score;
+            pattern.updated_at = previous.updated_at;
+        }
+        touch(&mut pattern);
+        Self::validate_pattern(&pattern)?;
+
+        let idx = if let Some(idx) = idx {
+            self.patterns[idx] = pattern.clone();
+            idx
+        } else {
+            let file = PathBuf::from(format!("{}-patterns.json", pattern.framework));
+            self.files.insert(file.clone());
+            self.sources.insert(id.to_string(), file);
+            self.patterns.push(pattern.clone());
+            self.patterns.len() - 1
+        };
+
+        self.record(&pattern, RevisionAction::RolledBack, previous);
+        if let Some(last) = self.history.get_mut(id).and_then(|r| r.last_mut()) {
+            last.rolled_back_to = Some(revision);
+        }
+        self.rebuild_indexes();
+        Ok(&self.patterns[idx])
+    }
+
+    /// Appends a revision holding `pattern` to its history.
+    ///
+    /// Patterns that predate history tracking get their `previous` state
+    /// recorded first, so the change can still be rolled back.
+    fn record(
+        &mut self,
+        pattern: &CodePattern,
+        action: RevisionAction,
+        previous: Option<CodePattern>,
+    ) {
+        let revisions = self.history.entry(pattern.id.clone()).or_default();
+        if revisions.is_empty() {
+            if let Some(previous) = previous {
+                revisions.push(PatternRevision {
+                    revision: 1,
+                    action: RevisionAction::Imported,
+                    rolled_back_to: None,
+                    author: "unknown".to_string(),
+                    timestamp: previous.updated_at,
+                    pattern: previous,
+                });
+            }
+        }
+        let revision = revisions.last().map_or(1, |r| r.revision + 1);
+        revisions.push(PatternRevision {
+            revision,
+            action,
+            rolled_back_to: None,
+            author: self.author.clone(),
+            timestamp: Utc::now(),
+            pattern: pattern.clone(),
+        });
+        self.changed_history.insert(pattern.id.clone());
+    }
+
+    /// Position of pattern `id`, if it is still at `expected_updated_at`.
+    fn checked_index(&self, id: &str, expected_updated_at: DateTime<Utc>) -> TrainingResult<usize> {
+        let idx = self
+            .patterns
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or_else(|| TrainingError::PatternNotFound(id.to_string()))?;
+        let actual = self.patterns[idx].updated_at;
+        if actual != expected_updated_at {
+            return Err(TrainingError::Conflict {
+                id: id.to_string(),
+                expected: format_timestamp(expected_updated_at),
+                actual: format_timestamp(actual),
+            });
+        }
+        Ok(idx)
+    }
+
     pub fn search_patterns(&self, criteria: &SearchCriteria) -> Vec<(&CodePattern, f32)> {
         let mut candidates: Vec<usize>;
 
-        // Filter by framework first (most selective)
+        // Filter by framework first (most selective). Framework and category
+        // match exactly, as in project ranking; close names are only offered
+        // through `suggest_corrections`.
         if let Some(ref framework) = criteria.framework {
             if let Some(indices) = self.framework_index.get(framework) {
                 candidates = indices.clone();
@@ -336,12 +798,23 @@ impl TrainingManager {
             }
         }
 
+        candidates.retain(|&idx| self.patterns[idx].deprecated.is_none());
+
+        // Query relevance of each candidate, relative to the best match
+        let text_matches = criteria
+            .query
+            .as_deref()
+            .map(|query| self.query_matches(query, criteria.mode, &candidates));
+
         // Score and filter candidates
         let mut scored: Vec<(&CodePattern, f32)> = candidates
             .iter()
             .map(|&idx| {
                 let pattern = &self.patterns[idx];
-                let score = self.score_pattern(pattern, criteria);
+                let text_match = text_matches
+                    .as_ref()
+                    .map(|matches| matches.get(&idx).copied().unwrap_or(0.0));
+                let score = self.score_pattern(pattern, criteria, text_match);
                 (pattern, score)
             })
             .filter(|(_, score)| *score >= criteria.min_score)
@@ -357,15 +830,61 @@ impl TrainingManager {
         scored
     }
 
+    /// How well each candidate matches `query`, from 0.0 to 1.0 for the best
+    /// match. Keyword scores are BM25, semantic ones cosine similarity, and
+    /// hybrid search averages both.
+    fn query_matches(
+        &self,
+        query: &str,
+        mode: SearchMode,
+        candidates: &[usize],
+    ) -> HashMap<usize, f32> {
+        let relative = |scores: HashMap<usize, f32>| {
+            let best = candidates
+                .iter()
+                .filter_map(|idx| scores.get(idx))
+                .fold(0.0, |best: f32, &s| best.max(s));
+            scores
+                .into_iter()
+                .map(|(idx, s)| (idx, s / best))
+                .collect::<HashMap<usize, f32>>()
+        };
+
+        let keyword = || relative(self.text_index.scores(query));
+        let Some(ref semantic) = self.semantic else {
+            return keyword();
+        };
+        match mode {
+            SearchMode::Keyword => keyword(),
+            SearchMode::Semantic => relative(semantic.similarities(query)),
+            SearchMode::Hybrid => {
+                let mut combined = keyword();
+                for (idx, s) in relative(semantic.similarities(query)) {
+                    *combined.entry(idx).or_default() += s;
+                }
+                for s in combined.values_mut() {
+                    *s /= 2.0;
+                }
+                combined
+            }
+        }
+    }
+
     /// Calculates a relevance score for a pattern based on search criteria.
     ///
     /// The score is composed of:
     /// - Base relevance score from the pattern
     /// - Usage count boost (popular patterns)
-    /// - Query match bonuses (title, description, code)
+    /// - Query match bonus: how well the pattern matches the query relative
+    ///   to the best match (`text_match`, 0.0 to 1.0; `None` without a query)
     /// - Tag match bonus
     /// - Recency boost (patterns updated in last 30 days)
-    fn score_pattern(&self, pattern: &CodePattern, criteria: &SearchCriteria) -> f32 {
+    fn score_pattern(
+        &self,
+        pattern: &CodePattern,
+        criteria: &SearchCriteria,
+        text_match: Option<f32>,
+    ) -> f32 {
         let mut score = pattern.relevance_score;
 
         // Boost score based on usage count (popular patterns)
@@ -376,52 +895,23 @@ impl TrainingManager {
             score += usage_boost;
         }
 
-        // Check query match (if provided)
-        if let Some(ref query) = criteria.query {
-            let query_lower = query.to_lowercase();
-            let mut query_matched = false;
-
-            // Title match (high weight) - most important
-            if pattern.title.to_lowercase().contains(&query_lower) {
-                score += 0.3;
-                query_matched = true;
-            }
-
-            // Description match (medium weight)
-            if pattern.description.to_lowercase().contains(&query_lower) {
-                score += 0.15;
-                query_matched = true;
-            }
-
-            // Code match (low weight, as code can be verbose)
-            if pattern.code.to_lowercase().contains(&query_lower) {
-                score += 0.05;
-                query_matched = true;
-            }
-
-            // Tag match
-            if pattern
-                .tags
-                .iter()
-                .any(|t| t.to_lowercase().contains(&query_lower))
-            {
-                score += 0.2;
-                query_matched = true;
-            }
-
-            // Penalize patterns with no query match at all
-            if !query_matched {
+        // Query match (if provided): up to 0.7 for the best matching pattern
+        if let Some(text_match) = text_match {
+            if text_match > 0.0 {
+                score += text_match * 0.7;
+            } else {
+                // Penalize patterns with no query match at all
                 score *= 0.3;
             }
         }
 
-        // Check tag matches - optimized to avoid allocations
-        // Instead of creating HashSets, we count matches directly
+        // Check tag matches, tolerating typos - counted directly to avoid
+        // allocating sets
         if !criteria.tags.is_empty() {
             let matching_tags = criteria
                 .tags
                 .iter()
-                .filter(|tag| pattern.tags.contains(tag))
+                .filter(|tag| pattern.tags.iter().any(|t| similar(t, tag)))
                 .count();
 
             #[allow(clippy::cast_precision_loss)]
@@ -438,19 +928,74 @@ impl TrainingManager {
         score
     }
 
+    /// "Did you mean" corrections for the inputs of `criteria` that match no
+    /// pattern: unknown framework, category or tag names with a close known
+    /// one, and query words replaced by the closest indexed words.
+    pub fn suggest_corrections(&self, criteria: &SearchCriteria) -> Vec<Correction> {
+        let mut corrections = Vec::new();
+        let mut suggest = |field, given: &str, suggestion: Option<&str>| {
+            if let Some(suggestion) = suggestion {
+                corrections.push(Correction {
+                    field,
+                    given: given.to_string(),
+                    suggestion: suggestion.to_string(),
+                });
+            }
+        };
+
+        if let Some(ref framework) = criteria.framework {
+            if !self.framework_index.contains_key(framework) {
+                let known = self.framework_index.keys().map(String::as_str);
+                suggest("framework", framework, closest(framework, known));
+            }
+        }
+        if let Some(ref category) = criteria.category {
+            if !self.category_index.contains_key(category) {
+                let known = self.category_index.keys().map(String::as_str);
+                suggest("category", category, closest(category, known));
+            }
+        }
+        let tags: BTreeSet<&str> = self
+            .patterns
+            .iter()
+            .flat_map(|p| p.tags.iter().map(String::as_str))
+            .collect();
+        for tag in &criteria.tags {
+            if !tags.contains(tag.as_str()) {
+                suggest("tag", tag, closest(tag, tags.iter().copied()));
+            }
+        }
+        if let Some(ref query) = criteria.query {
+            let corrected = self.text_index.correct_query(query);
+            suggest("query", query, corrected.as_deref());
+        }
+
+        corrections
+    }
+
     /// Convenience method for simple searches
     pub fn search_by_framework_and_category(
         &self,
         framework: &str,
         category: &str,
     ) -> Vec<&CodePattern> {
+        self.search_by(framework, Some(category))
+    }
+
+    /// All patterns for `framework`, in any category
+    pub fn search_by_framework(&self, framework: &str) -> Vec<&CodePattern> {
+        self.search_by(framework, None)
+    }
+
+    fn search_by(&self, framework: &str, category: Option<&str>) -> Vec<&CodePattern> {
         let criteria = SearchCriteria {
             query: None,
-            category: Some(category.to_string()),
+            category: category.map(str::to_string),
             framework: Some(framework.to_string()),
             tags: vec![],
             min_score: 0.0,
             max_results: None,
+            mode: SearchMode::Keyword,
         };
 
         self.search_patterns(&criteria)
@@ -460,33 +1005,241 @@ impl TrainingManager {
     }
 
     /// Get patterns by ID
-    #[allow(dead_code)]
     pub fn get_pattern_by_id(&self, id: &str) -> Option<&CodePattern> {
         self.patterns.iter().find(|p| p.id == id)
     }
 
     /// Update pattern usage count
-    #[allow(dead_code)]
-    pub fn increment_usage(&mut self, pattern_id: &str) -> Result<()> {
-        if let Some(pattern) = self.patterns.iter_mut().find(|p| p.id == pattern_id) {
-            pattern.usage_count += 1;
-            pattern.updated_at = Utc::now();
-            Ok(())
+    ///
+    /// Usage is not an edit: `updated_at` and the history are left alone, so
+    /// concurrent edits do not conflict with it. It is kept with the votes;
+    /// `save_feedback` persists it without rewriting pattern files.
+    pub fn increment_usage(&mut self, pattern_id: &str) -> TrainingResult<&CodePattern> {
+        let (pattern, votes) = self.feedback_entry(pattern_id)?;
+        votes.uses += 1;
+        pattern.usage_count += 1;
+        Ok(pattern)
+    }
+
+    /// Records a helpful or not helpful vote and adjusts the pattern's
+    /// relevance score accordingly; like usage, votes are not edits.
+    pub fn rate_pattern(
+        &mut self,
+        pattern_id: &str,
+        helpful: bool,
+    ) -> TrainingResult<&CodePattern> {
+        let (pattern, votes) = self.feedback_entry(pattern_id)?;
+        if helpful {
+            votes.helpful += 1;
         } else {
-            anyhow::bail!("Pattern not found: {}", pattern_id)
+            votes.not_helpful += 1;
+        }
+        pattern.relevance_score = feedback::adjusted_relevance(votes);
+        Ok(pattern)
+    }
+
+    /// Pattern `pattern_id` and its feedback, which is marked for saving.
+    fn feedback_entry(
+        &mut self,
+        pattern_id: &str,
+    ) -> TrainingResult<(&mut CodePattern, &mut PatternFeedback)> {
+        let pattern = self
+            .patterns
+            .iter_mut()
+            .find(|p| p.id == pattern_id)
+            .ok_or_else(|| TrainingError::PatternNotFound(pattern_id.to_string()))?;
+        let votes = self
+            .feedback
+            .entry(pattern_id.to_string())
+            .or_insert_with(|| PatternFeedback {
+                base_relevance: pattern.relevance_score,
+                ..Default::default()
+            });
+        self.changed_feedback = true;
+        Ok((pattern, votes))
+    }
+
+    /// Write the usage and votes recorded since the last save, leaving the
+    /// pattern files alone.
+    pub fn save_feedback(&mut self) -> Result<()> {
+        if std::mem::take(&mut self.changed_feedback) {
+            fs::create_dir_all(&self.storage_path).context("Failed to create storage directory")?;
+            feedback::save(&self.storage_path, &self.feedback)?;
+        }
+        Ok(())
+    }
+
+    /// Votes recorded for a pattern, if any.
+    pub fn feedback(&self, pattern_id: &str) -> Option<&PatternFeedback> {
+        self.feedback.get(pattern_id)
+    }
+
+    /// Queues mined candidates for review and returns how many are new.
+    ///
+    /// Candidates whose ID is already a pattern or was rejected are dropped;
+    /// pending ones are refreshed with the latest occurrences.
+    pub fn propose_candidates(&mut self, proposed: Vec<PatternCandidate>) -> usize {
+        let mut added = 0;
+        for candidate in proposed {
+            let id = &candidate.pattern.id;
+            if self.sources.contains_key(id) || self.rejected_candidates.contains(id) {
+                continue;
+            }
+            if let Some(pending) = self.candidates.iter_mut().find(|c| c.pattern.id == *id) {
+                *pending = candidate;
+            } else {
+                self.candidates.push(candidate);
+                added += 1;
+            }
+            self.changed_candidates = true;
+        }
+        added
+    }
+
+    /// Write the candidates queued or reviewed since the last save, leaving
+    /// the pattern files alone.
+    pub fn save_candidates(&mut self) -> Result<()> {
+        if std::mem::take(&mut self.changed_candidates) {
+            fs::create_dir_all(&self.storage_path).context("Failed to create storage directory")?;
+            candidates::save(
+                &self.storage_path,
+                &self.candidates,
+                &self.rejected_candidates,
+            )?;
         }
+        Ok(())
+    }
+
+    /// Candidates awaiting review, oldest first.
+    pub fn candidates(&self) -> &[PatternCandidate] {
+        &self.candidates
+    }
+
+    /// Turns a candidate into a pattern, with `overrides` applied to it.
+    pub fn accept_candidate(
+        &mut self,
+        id: &str,
+        overrides: PatternUpdate,
+    ) -> TrainingResult<&CodePattern> {
+        let idx = self
+            .candidates
+            .iter()
+            .position(|c| c.pattern.id == id)
+            .ok_or_else(|| TrainingError::CandidateNotFound(id.to_string()))?;
+        let mut pattern = self.candidates[idx].pattern.clone();
+        overrides.apply(&mut pattern);
+        pattern.created_at = Utc::now();
+        self.add_pattern(pattern)?;
+
+        self.candidates.remove(idx);
+        self.changed_candidates = true;
+        Ok(&self.patterns[self.patterns.len() - 1])
+    }
+
+    /// Drops a candidate and keeps it from being proposed again.
+    pub fn reject_candidate(&mut self, id: &str) -> TrainingResult<PatternCandidate> {
+        let idx = self
+            .candidates
+            .iter()
+            .position(|c| c.pattern.id == id)
+            .ok_or_else(|| TrainingError::CandidateNotFound(id.to_string()))?;
+        let candidate = self.candidates.remove(idx);
+        self.rejected_candidates
+            .insert(candidate.pattern.id.clone());
+        self.changed_candidates = true;
+        Ok(candidate)
+    }
+
+    /// Bundles the patterns passing `filter`; deprecated patterns are left out.
+    pub fn export_bundle(&self, filter: BundleFilter) -> PatternBundle {
+        let patterns: Vec<&CodePattern> = self
+            .patterns
+            .iter()
+            .filter(|p| p.deprecated.is_none() && filter.matches(p))
+            .collect();
+        PatternBundle::new(patterns, filter)
+    }
+
+    /// Imports the patterns of a verified bundle that pass `filter`.
+    ///
+    /// `policy` decides what happens to patterns whose ID already exists.
+    /// Every pattern is validated first, so an invalid one imports nothing.
+    pub fn import_bundle(
+        &mut self,
+        bundle: &PatternBundle,
+        filter: &BundleFilter,
+        policy: ConflictPolicy,
+    ) -> TrainingResult<ImportReport> {
+        let patterns = bundle.verified_patterns()?;
+        let selected: Vec<&CodePattern> = patterns.iter().filter(|p| filter.matches(p)).collect();
+        for pattern in &selected {
+            Self::validate_pattern(pattern)?;
+        }
+
+        let mut report = ImportReport::default();
+        for pattern in selected {
+            let mut pattern = pattern.clone();
+            let Some(idx) = self.patterns.iter().position(|p| p.id == pattern.id) else {
+                report.added.push(pattern.id.clone());
+                self.add_pattern(pattern)?;
+                continue;
+            };
+            match policy {
+                ConflictPolicy::Skip => report.skipped.push(pattern.id),
+                // Nothing to overwrite with: no revision for an identical copy
+                ConflictPolicy::Overwrite
+                    if changed_fields(&self.patterns[idx], &pattern).is_empty() =>
+                {
+                    report.skipped.push(pattern.id);
+                }
+                ConflictPolicy::Overwrite => {
+                    pattern.created_at = self.patterns[idx].created_at;
+                    touch(&mut pattern);
+                    report.overwritten.push(pattern.id.clone());
+                    let previous = std::mem::replace(&mut self.patterns[idx], pattern);
+                    self.record(
+                        &self.patterns[idx].clone(),
+                        RevisionAction::Updated,
+                        Some(previous),
+                    );
+                    self.rebuild_indexes();
+                }
+                ConflictPolicy::Rename => {
+                    let original = pattern.id.clone();
+                    let taken = |id: &String| {
+                        self.sources.contains_key(id) || patterns.iter().any(|p| p.id == *id)
+                    };
+                    let mut id = format!("{}-imported", original);
+                    let mut n = 1;
+                    while taken(&id) {
+                        n += 1;
+                        id = format!("{}-imported-{}", original, n);
+                    }
+                    pattern.id = id;
+                    report.renamed.push((original, pattern.id.clone()));
+                    self.add_pattern(pattern)?;
+                }
+            }
+        }
+        Ok(report)
     }
 
     pub fn get_statistics(&self) -> serde_json::Value {
         let categories = self.get_categories();
         let frameworks = self.get_frameworks();
         let total_usage: usize = self.patterns.iter().map(|p| p.usage_count).sum();
+        let helpful_votes: u32 = self.feedback.values().map(|f| f.helpful).sum();
+        let not_helpful_votes: u32 = self.feedback.values().map(|f| f.not_helpful).sum();
 
         serde_json::json!({
             "total_patterns": self.patterns.len(),
+            "deprecated_patterns": self.patterns.iter().filter(|p| p.deprecated.is_some()).count(),
             "categories": categories,
             "frameworks": frameworks,
             "total_usage": total_usage,
+            "helpful_votes": helpful_votes,
+            "not_helpful_votes": not_helpful_votes,
+            "pending_candidates": self.candidates.len(),
             "avg_relevance": self.avg_relevance_score(),
         })
     }
@@ -495,8 +1248,11 @@ impl TrainingManager {
         self.category_index.keys().cloned().collect()
     }
 
-    fn get_frameworks(&self) -> Vec<String> {
-        self.framework_index.keys().cloned().collect()
+    /// Frameworks with at least one pattern, sorted.
+    pub fn get_frameworks(&self) -> Vec<String> {
+        let mut frameworks: Vec<String> = self.framework_index.keys().cloned().collect();
+        frameworks.sort();
+        frameworks
     }
 
     fn avg_relevance_score(&self) -> f32 {
@@ -513,3 +1269,13 @@ impl TrainingManager {
         &self.patterns
     }
 }
+
+/// Bump `updated_at`, strictly past its previous value.
+fn touch(pattern: &mut CodePattern) {
+    pattern.updated_at = Utc::now().max(pattern.updated_at + TimeDelta::nanoseconds(1));
+}
+
+/// `updated_at` as callers must pass it back: RFC 3339 with full precision.
+pub fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+}
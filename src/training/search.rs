@@ -0,0 +1,404 @@
+//! Full-text pattern search: a tokenized inverted index ranked with BM25F.
+//!
+//! Titles, tags, descriptions and code are indexed as separate fields, each
+//! with its own length normalization and boost. Per-field term frequencies
+//! are combined before BM25 saturation, so a word repeated in every field
+//! does not count as several independent matches. Words are lowercased,
+//! split at camelCase boundaries and reduced by a light suffix stemmer:
+//! "Authentication with JWT" matches "jwt authentication", and "Parameter
+//! Change Handling" matches "parameters changed". Query terms missing from
+//! the index match the closest indexed terms instead, at a reduced weight,
+//! so typos such as "lifecyle" still find results.
+
+use std::collections::HashMap;
+
+use crate::types::CodePattern;
+use crate::utils::edit_distance;
+
+/// Indexed fields, in the order of the per-field arrays below.
+const FIELD_COUNT: usize = 4;
+
+/// Weight of a term occurrence in the title, tags, description and code.
+const FIELD_BOOSTS: [f32; FIELD_COUNT] = [3.0, 2.5, 1.5, 0.5];
+
+/// BM25 term frequency saturation.
+const K1: f32 = 1.2;
+
+/// BM25 length normalization.
+const B: f32 = 0.75;
+
+/// Weight of a term matched through a typo correction.
+const FUZZY_WEIGHT: f32 = 0.5;
+
+/// Words too common to rank anything.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "how", "in", "is", "it", "of",
+    "on", "or", "that", "the", "this", "to", "what", "when", "with",
+];
+
+/// Suffixes the stemmer removes, and what replaces them; first match wins.
+const SUFFIXES: &[(&str, &str)] = &[
+    ("ings", ""),
+    ("ing", ""),
+    ("ions", ""),
+    ("ion", ""),
+    ("ies", "y"),
+    ("ied", "y"),
+    ("ed", ""),
+    ("es", ""),
+    ("s", ""),
+];
+
+/// Documents containing a term, with its frequency per field.
+type Postings = Vec<(usize, [u16; FIELD_COUNT])>;
+
+/// Inverted index over the text of a pattern list; documents are positions.
+#[derive(Debug, Clone, Default)]
+pub(super) struct SearchIndex {
+    postings: HashMap<String, Postings>,
+    /// Token count per field of each document
+    lengths: Vec<[u32; FIELD_COUNT]>,
+    total_lengths: [u64; FIELD_COUNT],
+    /// A lowercased word each term was indexed from, to suggest in corrections
+    words: HashMap<String, String>,
+}
+
+impl SearchIndex {
+    pub(super) fn build(patterns: &[CodePattern]) -> Self {
+        let mut index = Self::default();
+        for pattern in patterns {
+            index.push(pattern);
+        }
+        index
+    }
+
+    /// Index the pattern stored after every pattern indexed so far.
+    pub(super) fn push(&mut self, pattern: &CodePattern) {
+        let doc = self.lengths.len();
+        let fields = [
+            terms(&pattern.title),
+            terms(&pattern.tags.join(" ")),
+            terms(&pattern.description),
+            terms(&pattern.code),
+        ];
+
+        let mut frequencies: HashMap<String, [u16; FIELD_COUNT]> = HashMap::new();
+        let mut lengths = [0u32; FIELD_COUNT];
+        for (field, tokens) in fields.into_iter().enumerate() {
+            lengths[field] = tokens.len() as u32;
+            self.total_lengths[field] += tokens.len() as u64;
+            for (token, word) in tokens {
+                self.words.entry(token.clone()).or_insert(word);
+                let tf = &mut frequencies.entry(token).or_default()[field];
+                *tf = tf.saturating_add(1);
+            }
+        }
+
+        self.lengths.push(lengths);
+        for (term, tf) in frequencies {
+            self.postings.entry(term).or_default().push((doc, tf));
+        }
+    }
+
+    /// BM25F score of every document matching at least one query term.
+    pub(super) fn scores(&self, query: &str) -> HashMap<usize, f32> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+
+        let docs = self.lengths.len() as f32;
+        let averages = self.total_lengths.map(|total| total as f32 / docs.max(1.0));
+
+        // Unknown terms stand for their closest indexed terms, at a reduced weight
+        let mut matched: Vec<(&Postings, f32)> = Vec::new();
+        for term in &terms {
+            if let Some(postings) = self.postings.get(term) {
+                matched.push((postings, 1.0));
+            } else {
+                matched.extend(
+                    self.closest_terms(term)
+                        .into_iter()
+                        .map(|t| (&self.postings[t], FUZZY_WEIGHT)),
+                );
+            }
+        }
+
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+        for (postings, weight) in matched {
+            let df = postings.len() as f32;
+            let idf = weight * (1.0 + (docs - df + 0.5) / (df + 0.5)).ln();
+
+            for (doc, tf) in postings {
+                let weighted: f32 = (0..FIELD_COUNT)
+                    .filter(|&field| tf[field] > 0)
+                    .map(|field| {
+                        let norm = 1.0 - B + B * self.lengths[*doc][field] as f32 / averages[field];
+                        FIELD_BOOSTS[field] * f32::from(tf[field]) / norm
+                    })
+                    .sum();
+                *scores.entry(*doc).or_default() += idf * weighted / (K1 + weighted);
+            }
+        }
+        scores
+    }
+
+    /// Indexed terms nearest to `term` within its typo allowance.
+    fn closest_terms(&self, term: &str) -> Vec<&String> {
+        let allowed = max_typos(term);
+        if allowed == 0 {
+            return vec![];
+        }
+        let mut best = allowed + 1;
+        let mut closest = Vec::new();
+        for candidate in self.postings.keys() {
+            let distance = edit_distance(term, candidate);
+            if distance < best {
+                best = distance;
+                closest.clear();
+            }
+            if distance == best {
+                closest.push(candidate);
+            }
+        }
+        closest
+    }
+
+    /// `query` with each word that matches nothing replaced by the closest
+    /// indexed word; `None` when no word needed a correction.
+    pub(super) fn correct_query(&self, query: &str) -> Option<String> {
+        let mut changed = false;
+        let words: Vec<String> = query
+            .split_whitespace()
+            .map(|word| {
+                let tokens = tokenize(word);
+                if tokens.is_empty() || tokens.iter().any(|t| self.postings.contains_key(t)) {
+                    return word.to_string();
+                }
+                let mut candidates: Vec<&String> =
+                    tokens.iter().flat_map(|t| self.closest_terms(t)).collect();
+                candidates.sort();
+                match candidates.first() {
+                    Some(term) => {
+                        changed = true;
+                        self.words[*term].clone()
+                    }
+                    None => word.to_string(),
+                }
+            })
+            .collect();
+        changed.then(|| words.join(" "))
+    }
+}
+
+/// Typos tolerated in a word: none up to 3 characters, one up to 7, then two.
+fn max_typos(word: &str) -> usize {
+    match word.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// The candidate closest to `given` (ignoring case) within its typo
+/// allowance; ties go to the alphabetically first candidate.
+pub(super) fn closest<'a>(
+    given: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let given = given.to_lowercase();
+    candidates
+        .into_iter()
+        .map(|c| (edit_distance(&given, &c.to_lowercase()), c))
+        .filter(|(distance, _)| *distance <= max_typos(&given))
+        .min()
+        .map(|(_, c)| c)
+}
+
+/// Fuzzy equality of two labels such as tags: equal, or within the typo
+/// allowance of the shorter one.
+pub(super) fn similar(a: &str, b: &str) -> bool {
+    let (a, b) = (a.to_lowercase(), b.to_lowercase());
+    let shorter = if a.len() <= b.len() { &a } else { &b };
+    a == b || edit_distance(&a, &b) <= max_typos(shorter)
+}
+
+/// Search terms of a text: lowercased, stemmed words without stopwords.
+///
+/// camelCase and PascalCase words yield both the whole word and its parts,
+/// so `OnInitializedAsync` matches itself as well as "initialized".
+pub(super) fn tokenize(text: &str) -> Vec<String> {
+    terms(text).into_iter().map(|(term, _)| term).collect()
+}
+
+/// Terms of a text paired with the lowercased word each came from.
+fn terms(text: &str) -> Vec<(String, String)> {
+    let mut terms = Vec::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let parts = camel_case_parts(word);
+        if parts.len() > 1 {
+            let whole = word.to_lowercase();
+            terms.push((stem(&whole), whole));
+        }
+        terms.extend(parts.into_iter().map(|part| {
+            let part = part.to_lowercase();
+            (stem(&part), part)
+        }));
+    }
+    terms.retain(|(t, _)| !STOPWORDS.contains(&t.as_str()));
+    terms
+}
+
+/// `HttpClientFactory` -> `Http`, `Client`, `Factory`; `JSRuntime` -> `JS`, `Runtime`.
+fn camel_case_parts(word: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = word.char_indices().collect();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for i in 1..chars.len() {
+        let (pos, c) = chars[i];
+        let prev = chars[i - 1].1;
+        let next_lower = chars.get(i + 1).is_some_and(|(_, n)| n.is_lowercase());
+        if c.is_uppercase()
+            && (prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower))
+        {
+            parts.push(&word[start..pos]);
+            start = pos;
+        }
+    }
+    parts.push(&word[start..]);
+    parts
+}
+
+/// Reduce inflected forms to a shared stem ("handling", "handled" and
+/// "handle" all become "handl"). Deliberately light: it only has to map a
+/// query and the indexed text to the same term, not produce real words.
+fn stem(word: &str) -> String {
+    if !word.is_ascii() || word.len() <= 3 {
+        return word.to_string();
+    }
+
+    let mut stemmed = word.to_string();
+    for (suffix, replacement) in SUFFIXES {
+        let Some(base) = word.strip_suffix(suffix) else {
+            continue;
+        };
+        // "class", "status" and "analysis" are not plurals
+        if base.len() < 3 || (*suffix == "s" && base.ends_with(['s', 'u', 'i'])) {
+            continue;
+        }
+        stemmed = format!("{}{}", base, replacement);
+        break;
+    }
+    if stemmed.len() > 3 && stemmed.ends_with('e') {
+        stemmed.pop();
+    }
+    stemmed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tags;
+
+    fn pattern(title: &str, description: &str, pattern_tags: &[&str], code: &str) -> CodePattern {
+        CodePattern {
+            title: title.to_string(),
+            description: description.to_string(),
+            code: code.to_string(),
+            tags: tags(pattern_tags),
+            ..crate::test_support::pattern(&title.to_lowercase().replace(' ', "-"))
+        }
+    }
+
+    #[test]
+    fn test_tokenize_and_stem() {
+        assert_eq!(
+            tokenize("Authentication with JWT"),
+            vec!["authenticat", "jwt"]
+        );
+        assert_eq!(
+            tokenize("OnInitializedAsync"),
+            vec!["oninitializedasync", "initializ", "async"]
+        );
+        assert_eq!(tokenize("HTTPClient"), vec!["httpclient", "http", "client"]);
+        for (a, b) in [
+            ("handling", "handle"),
+            ("dependencies", "dependency"),
+            ("services", "service"),
+            ("changed", "change"),
+            ("classes", "class"),
+        ] {
+            assert_eq!(stem(a), stem(b), "{} / {}", a, b);
+        }
+        assert_eq!(stem("status"), "status");
+    }
+
+    #[test]
+    fn test_bm25_ranks_field_matches() {
+        let index = SearchIndex::build(&[
+            pattern(
+                "Authentication with JWT",
+                "Validate bearer tokens",
+                &["auth"],
+                "services.AddAuthentication().AddJwtBearer();",
+            ),
+            pattern(
+                "Logging",
+                "Structured logging; see authentication docs",
+                &["logging"],
+                "logger.LogInformation(\"jwt\");",
+            ),
+            pattern(
+                "Caching",
+                "Memory cache",
+                &["cache"],
+                "cache.Set(key, value);",
+            ),
+        ]);
+
+        let scores = index.scores("jwt authentication");
+        assert_eq!(scores.len(), 2);
+        assert!(scores[&0] > scores[&1]);
+        assert!(!scores.contains_key(&2));
+
+        // Stopwords and unknown terms match nothing
+        assert!(index.scores("the of with").is_empty());
+        assert!(index.scores("kubernetes").is_empty());
+    }
+
+    #[test]
+    fn test_typos_match_closest_terms() {
+        let index = SearchIndex::build(&[
+            pattern(
+                "Component Lifecycle",
+                "Initialization order",
+                &["lifecycle"],
+                "",
+            ),
+            pattern("Caching", "Memory cache", &["cache"], ""),
+        ]);
+
+        let exact = index.scores("lifecycle");
+        let typo = index.scores("lifecyle");
+        assert_eq!(typo.keys().collect::<Vec<_>>(), vec![&0]);
+        assert!(typo[&0] < exact[&0]);
+
+        assert_eq!(
+            index.correct_query("component lifecyle").as_deref(),
+            Some("component lifecycle")
+        );
+        assert_eq!(index.correct_query("component lifecycle"), None);
+        // Short words are never corrected
+        assert!(index.scores("cah").is_empty());
+
+        assert_eq!(
+            closest("blazor-sever", ["blazor-server", "laravel"]),
+            Some("blazor-server")
+        );
+        assert_eq!(closest("django", ["blazor-server", "laravel"]), None);
+        assert!(similar("lifecyle", "Lifecycle"));
+        assert!(!similar("di", "ui"));
+    }
+}
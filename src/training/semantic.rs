@@ -0,0 +1,393 @@
+//! Semantic pattern search with hashed n-gram vectors.
+//!
+//! Needs no model file and makes no network calls. Each pattern becomes a
+//! sparse vector of hashed features: its stemmed words, the character
+//! trigrams of each word (so "auth" lands near "authentication" and
+//! identifiers share pieces), and the concepts from `data/concepts.toml`
+//! its text mentions (so "DI" meets "dependency injection" and "login"
+//! meets "authentication"). Queries are embedded the same way and ranked
+//! by cosine similarity.
+//!
+//! Vectors are cached per pattern ID under `<cache_dir>/semantic/`, keyed by
+//! a hash of the pattern text and of the concept catalog, so unchanged
+//! patterns are not embedded again on the next start.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::search::tokenize;
+use crate::types::CodePattern;
+use crate::utils::hash_string;
+
+/// Catalog compiled into the binary.
+const BUILTIN_CONCEPTS: &str = include_str!("../../data/concepts.toml");
+
+/// Bump when the features change so cached vectors are recomputed.
+const CACHE_VERSION: u32 = 1;
+
+/// Feature hash space; vectors are sparse, so collisions stay rare.
+const DIMENSIONS: u64 = 1 << 20;
+
+/// Weight of the title, tags, description and code.
+const FIELD_WEIGHTS: [f32; 4] = [2.0, 1.5, 1.0, 0.3];
+
+/// Weight of each kind of feature.
+const WORD_WEIGHT: f32 = 1.0;
+const TRIGRAM_WEIGHT: f32 = 0.25;
+const CONCEPT_WEIGHT: f32 = 2.5;
+
+/// Similarities below this are noise from shared trigrams.
+const MIN_SIMILARITY: f32 = 0.1;
+
+/// L2-normalized features, sorted by index.
+type SparseVector = Vec<(u32, f32)>;
+
+#[derive(Debug, Deserialize)]
+struct ConceptFile {
+    concepts: Vec<ConceptEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConceptEntry {
+    name: String,
+    terms: Vec<String>,
+}
+
+/// A concept with each of its terms as a sequence of search terms.
+#[derive(Debug, Clone)]
+struct Concept {
+    name: String,
+    phrases: Vec<Vec<String>>,
+}
+
+/// On-disk cache layout.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    /// Cache version and concept catalog the vectors were computed with
+    model: String,
+    vectors: BTreeMap<String, CachedVector>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedVector {
+    /// Hash of the embedded pattern text
+    hash: String,
+    vector: SparseVector,
+}
+
+/// Pattern vectors, by position in the pattern list.
+#[derive(Debug, Clone)]
+pub(super) struct SemanticIndex {
+    concepts: Vec<Concept>,
+    model: String,
+    location: PathBuf,
+    cached: BTreeMap<String, CachedVector>,
+    vectors: Vec<SparseVector>,
+    dirty: bool,
+}
+
+impl SemanticIndex {
+    /// An index cached in `location`; a missing, unreadable or outdated
+    /// cache yields an empty one.
+    pub(super) fn load(location: PathBuf) -> Self {
+        let mut index = Self {
+            concepts: parse_concepts(BUILTIN_CONCEPTS).expect("built-in concept catalog is valid"),
+            model: format!(
+                "v{}-{}",
+                CACHE_VERSION,
+                &hash_string(BUILTIN_CONCEPTS)[..16]
+            ),
+            location,
+            cached: BTreeMap::new(),
+            vectors: Vec::new(),
+            dirty: false,
+        };
+        if !index.location.exists() {
+            return index;
+        }
+
+        let loaded = fs::read_to_string(&index.location)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(serde_json::from_str::<CacheFile>(&content)?));
+        match loaded {
+            Ok(file) if file.model == index.model => index.cached = file.vectors,
+            Ok(_) => tracing::debug!("Discarding semantic index built with another model"),
+            Err(e) => tracing::warn!(
+                path = %index.location.display(),
+                error = %e,
+                "Failed to load semantic index, rebuilding it"
+            ),
+        }
+        index
+    }
+
+    /// Embed `patterns`, reusing cached vectors of unchanged patterns.
+    pub(super) fn build(&mut self, patterns: &[CodePattern]) {
+        self.vectors.clear();
+        for pattern in patterns {
+            self.push(pattern);
+        }
+        // Drop the vectors of removed patterns
+        let before = self.cached.len();
+        self.cached
+            .retain(|id, _| patterns.iter().any(|p| &p.id == id));
+        self.dirty |= self.cached.len() != before;
+    }
+
+    /// Embed the pattern stored after every pattern embedded so far.
+    pub(super) fn push(&mut self, pattern: &CodePattern) {
+        let hash = hash_string(&format!(
+            "{}\0{}\0{}\0{}",
+            pattern.title,
+            pattern.tags.join(" "),
+            pattern.description,
+            pattern.code
+        ));
+        let vector = match self.cached.get(&pattern.id) {
+            Some(cached) if cached.hash == hash => cached.vector.clone(),
+            _ => {
+                let vector = self.embed(&[
+                    &pattern.title,
+                    &pattern.tags.join(" "),
+                    &pattern.description,
+                    &pattern.code,
+                ]);
+                self.cached.insert(
+                    pattern.id.clone(),
+                    CachedVector {
+                        hash,
+                        vector: vector.clone(),
+                    },
+                );
+                self.dirty = true;
+                vector
+            }
+        };
+        self.vectors.push(vector);
+    }
+
+    /// Cosine similarity of every pattern close enough to `query`.
+    pub(super) fn similarities(&self, query: &str) -> HashMap<usize, f32> {
+        let query = self.embed(&[query]);
+        self.vectors
+            .iter()
+            .enumerate()
+            .map(|(doc, vector)| (doc, cosine(&query, vector)))
+            .filter(|(_, similarity)| *similarity >= MIN_SIMILARITY)
+            .collect()
+    }
+
+    /// Write the cache if vectors were added or removed since the last save.
+    pub(super) fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(dir) = self.location.parent() {
+            fs::create_dir_all(dir).context("Failed to create semantic index directory")?;
+        }
+        let file = CacheFile {
+            model: self.model.clone(),
+            vectors: self.cached.clone(),
+        };
+        let json = serde_json::to_string(&file).context("Failed to serialize semantic index")?;
+        fs::write(&self.location, json).with_context(|| {
+            format!("Failed to write semantic index {}", self.location.display())
+        })?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Vector of a text given as fields weighted by `FIELD_WEIGHTS`.
+    fn embed(&self, fields: &[&str]) -> SparseVector {
+        let mut features: HashMap<u32, f32> = HashMap::new();
+        for (text, field_weight) in fields.iter().zip(FIELD_WEIGHTS) {
+            let terms = tokenize(text);
+            for term in &terms {
+                *features.entry(feature("w", term)).or_default() += field_weight * WORD_WEIGHT;
+                let padded: Vec<char> = format!("^{}$", term).chars().collect();
+                for trigram in padded.windows(3) {
+                    let trigram: String = trigram.iter().collect();
+                    *features.entry(feature("g", &trigram)).or_default() +=
+                        field_weight * TRIGRAM_WEIGHT;
+                }
+            }
+            for concept in &self.concepts {
+                if concept
+                    .phrases
+                    .iter()
+                    .any(|phrase| contains_phrase(&terms, phrase))
+                {
+                    *features.entry(feature("c", &concept.name)).or_default() +=
+                        field_weight * CONCEPT_WEIGHT;
+                }
+            }
+        }
+
+        // Dampen repeated words, then normalize
+        let mut vector: SparseVector = features
+            .into_iter()
+            .map(|(index, weight)| (index, weight.ln_1p()))
+            .collect();
+        vector.sort_unstable_by_key(|(index, _)| *index);
+        let norm = vector.iter().map(|(_, w)| w * w).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            for (_, weight) in &mut vector {
+                *weight /= norm;
+            }
+        }
+        vector
+    }
+}
+
+/// Storage location of the semantic index of the patterns in `storage_path`.
+pub(super) fn cache_location(cache_dir: &Path, storage_path: &Path) -> PathBuf {
+    let key = hash_string(&storage_path.to_string_lossy());
+    cache_dir.join(format!("{}.json", &key[..16]))
+}
+
+fn parse_concepts(content: &str) -> Result<Vec<Concept>> {
+    let file: ConceptFile = toml::from_str(content)?;
+    Ok(file
+        .concepts
+        .into_iter()
+        .map(|entry| Concept {
+            name: entry.name,
+            phrases: entry
+                .terms
+                .iter()
+                .map(|term| tokenize(term))
+                .filter(|phrase| !phrase.is_empty())
+                .collect(),
+        })
+        .collect())
+}
+
+/// Whether `phrase` occurs as consecutive terms of `terms`.
+fn contains_phrase(terms: &[String], phrase: &[String]) -> bool {
+    terms.windows(phrase.len()).any(|window| window == phrase)
+}
+
+/// Hashed index of a feature, with FNV-1a so it is stable across builds.
+fn feature(kind: &str, value: &str) -> u32 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in kind.bytes().chain([b':']).chain(value.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    (hash % DIMENSIONS) as u32
+}
+
+/// Dot product of two normalized sparse vectors.
+fn cosine(a: &SparseVector, b: &SparseVector) -> f32 {
+    let (mut i, mut j, mut dot) = (0, 0, 0.0);
+    while i < a.len() && j < b.len() {
+        match a[i].0.cmp(&b[j].0) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                dot += a[i].1 * b[j].1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tags;
+    use tempfile::tempdir;
+
+    fn pattern(id: &str, title: &str, pattern_tags: &[&str], code: &str) -> CodePattern {
+        CodePattern {
+            title: title.to_string(),
+            code: code.to_string(),
+            tags: tags(pattern_tags),
+            ..crate::test_support::pattern(id)
+        }
+    }
+
+    fn best(index: &SemanticIndex, query: &str) -> Option<usize> {
+        index
+            .similarities(query)
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(doc, _)| doc)
+    }
+
+    #[test]
+    fn test_builtin_concepts_parse() {
+        let concepts = parse_concepts(BUILTIN_CONCEPTS).unwrap();
+        assert!(concepts.len() >= 20);
+        for concept in &concepts {
+            assert!(!concept.phrases.is_empty(), "{}", concept.name);
+        }
+    }
+
+    #[test]
+    fn test_synonyms_meet_through_concepts() {
+        let dir = tempdir().unwrap();
+        let mut index = SemanticIndex::load(dir.path().join("index.json"));
+        index.build(&[
+            pattern(
+                "di",
+                "Register Scoped Service",
+                &["dependency-injection"],
+                "builder.Services.AddScoped<IOrderService, OrderService>();",
+            ),
+            pattern(
+                "auth",
+                "Cascading Authentication State",
+                &["security"],
+                "<CascadingAuthenticationState>",
+            ),
+            pattern(
+                "perf",
+                "Virtualize Large Lists",
+                &["performance"],
+                "<Virtualize>",
+            ),
+        ]);
+
+        assert_eq!(best(&index, "DI"), Some(0));
+        assert_eq!(best(&index, "login"), Some(1));
+        assert_eq!(best(&index, "auth"), Some(1));
+        assert_eq!(best(&index, "speed up slow pages"), Some(2));
+        assert!(index.similarities("kubernetes helm chart").is_empty());
+    }
+
+    #[test]
+    fn test_vectors_are_cached() {
+        let dir = tempdir().unwrap();
+        let location = dir.path().join("semantic").join("index.json");
+        let patterns = vec![pattern("a", "Dependency Injection", &[], "")];
+
+        let mut index = SemanticIndex::load(location.clone());
+        index.build(&patterns);
+        index.save().unwrap();
+        assert!(location.exists());
+
+        let mut reloaded = SemanticIndex::load(location.clone());
+        assert_eq!(reloaded.cached.len(), 1);
+        reloaded.build(&patterns);
+        assert!(!reloaded.dirty, "unchanged patterns reuse cached vectors");
+        assert_eq!(reloaded.vectors, index.vectors);
+
+        // Edited and removed patterns are re-embedded and dropped
+        reloaded.build(&[pattern("a", "Dependency Injection", &["di"], "")]);
+        assert!(reloaded.dirty);
+        reloaded.build(&[]);
+        assert!(reloaded.cached.is_empty());
+
+        // Vectors from another concept catalog are discarded
+        fs::write(&location, r#"{"model":"v0-old","vectors":{}}"#).unwrap();
+        let stale = SemanticIndex::load(location);
+        assert!(stale.cached.is_empty());
+    }
+}
@@ -6,7 +6,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 // ============================================================================
 // Generic Multi-Language Project Types
@@ -77,6 +77,118 @@ pub struct Project {
     pub files: Vec<SourceFile>,
     /// Language-specific metadata
     pub metadata: ProjectMetadata,
+    /// File discovery summary (limits reached, bytes scanned)
+    #[serde(default)]
+    pub scan: ScanSummary,
+    /// Git history, `None` outside a repository or when disabled
+    #[serde(default)]
+    pub git: Option<GitInfo>,
+}
+
+/// Repository state and history for a project.
+///
+/// Paths are relative to the project root.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GitInfo {
+    /// Current branch, `None` when HEAD is detached or unborn
+    pub branch: Option<String>,
+    /// Abbreviated HEAD commit
+    pub head: Option<String>,
+    /// Modified, staged and untracked files
+    pub uncommitted: Vec<UncommittedChange>,
+    /// Length of the churn window in days
+    pub history_days: u32,
+    /// Most frequently changed files within the churn window
+    pub hot_files: Vec<FileChurn>,
+    /// Primary authors per top-level directory within the churn window
+    pub directory_authors: Vec<DirectoryAuthors>,
+    /// Files touched by the most recent commits, newest first
+    pub recent_files: Vec<PathBuf>,
+}
+
+/// A file with uncommitted changes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UncommittedChange {
+    pub path: PathBuf,
+    /// Porcelain status code (`M`, `A`, `D`, `??`, ...)
+    pub status: String,
+}
+
+/// Number of commits that touched a file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileChurn {
+    pub path: PathBuf,
+    pub commits: usize,
+}
+
+/// Authors of a directory, most active first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DirectoryAuthors {
+    /// Top-level directory, `.` for files in the project root
+    pub directory: String,
+    pub authors: Vec<AuthorCommits>,
+}
+
+/// Commit count for one author.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuthorCommits {
+    pub name: String,
+    pub commits: usize,
+}
+
+/// Summary of the file discovery pass over a project.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScanSummary {
+    /// Number of source files collected
+    pub files_scanned: usize,
+    /// Total size of the collected files in bytes
+    pub total_bytes: u64,
+    /// Set when a configured limit cut discovery short
+    pub truncated: Option<ScanLimit>,
+    /// Number of files reparsed (the rest were reused from the analysis cache)
+    #[serde(default)]
+    pub files_reparsed: usize,
+    /// Changes since the previous analysis, `None` on the first run
+    #[serde(default)]
+    pub changes: Option<FileChanges>,
+}
+
+/// Files added, modified or removed since the previous analysis.
+///
+/// Paths are relative to the project root.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct FileChanges {
+    pub added: Vec<PathBuf>,
+    pub modified: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+}
+
+impl FileChanges {
+    /// Returns true if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Discovery limit that stopped a scan early.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ScanLimit {
+    /// `max_files` was reached
+    Files(usize),
+    /// `max_total_size_mb` was reached
+    TotalSize(u64),
+    /// Directories below `max_depth` were not visited
+    Depth(usize),
+}
+
+impl fmt::Display for ScanLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Files(n) => write!(f, "max_files limit ({n} files)"),
+            Self::TotalSize(mb) => write!(f, "max_total_size_mb limit ({mb} MB)"),
+            Self::Depth(d) => write!(f, "max_depth limit ({d} levels)"),
+        }
+    }
 }
 
 /// Generic dependency representation.
@@ -101,6 +213,41 @@ pub struct SourceFile {
     pub size_bytes: u64,
     /// Extracted symbols (classes, functions, etc.)
     pub symbols: Vec<Symbol>,
+    /// Line counts and complexity metrics
+    #[serde(default)]
+    pub metrics: FileMetrics,
+    /// Declared namespace or package (C#, Java, PHP)
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Raw import specifiers (`use` paths, module strings, usings)
+    #[serde(default)]
+    pub imports: Vec<String>,
+}
+
+/// Line counts and complexity metrics for a single source file.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct FileMetrics {
+    /// Lines containing code
+    pub code_lines: usize,
+    /// Lines containing only comments
+    pub comment_lines: usize,
+    /// Empty or whitespace-only lines
+    pub blank_lines: usize,
+    /// Number of functions, methods and closures
+    pub functions: usize,
+    /// Sum of the cyclomatic complexity of all functions plus top-level branches
+    pub cyclomatic_complexity: usize,
+    /// Cyclomatic complexity of the most complex function
+    pub max_function_complexity: usize,
+    /// Deepest nesting of control-flow blocks
+    pub max_nesting_depth: usize,
+}
+
+impl FileMetrics {
+    /// Total number of lines in the file.
+    pub const fn total_lines(&self) -> usize {
+        self.code_lines + self.comment_lines + self.blank_lines
+    }
 }
 
 /// Generic symbol (class, function, interface, etc.)
@@ -114,6 +261,28 @@ pub struct Symbol {
     pub modifiers: Vec<String>,
     /// Child symbols (methods, fields, etc.)
     pub children: Vec<Symbol>,
+    /// Location of the whole declaration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<SourceRange>,
+    /// Declaration header without the body (`pub fn load(path: &Path) -> Result<Config>`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// A symbol found by a project-wide lookup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolMatch {
+    /// File path relative to the project root
+    pub path: PathBuf,
+    /// Name qualified by its enclosing symbols (`Class.Method`)
+    pub qualified_name: String,
+    pub kind: SymbolKind,
+    /// Location of the declaration
+    pub range: Option<SourceRange>,
+    /// Declaration header without the body
+    pub signature: Option<String>,
+    /// Match quality in (0, 1]
+    pub score: f32,
 }
 
 /// Kind of symbol in source code.
@@ -138,6 +307,16 @@ pub enum SymbolKind {
     Other(String),
 }
 
+impl SymbolKind {
+    /// Whether the symbol declares a type (class, struct, interface, trait, enum).
+    pub const fn is_type(&self) -> bool {
+        matches!(
+            self,
+            Self::Class | Self::Interface | Self::Struct | Self::Enum | Self::Trait
+        )
+    }
+}
+
 impl fmt::Display for SymbolKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -175,6 +354,128 @@ pub struct ProjectMetadata {
     pub build_command: Option<String>,
     /// Additional key-value metadata
     pub extra: std::collections::HashMap<String, String>,
+    /// Detected frameworks, most likely first
+    #[serde(default)]
+    pub frameworks: Vec<FrameworkCandidate>,
+    /// Containers, deployment manifests and CI pipelines
+    #[serde(default)]
+    pub infra: InfraSummary,
+}
+
+/// A framework matched by the signature catalog.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameworkCandidate {
+    /// Canonical name, as used by pattern `framework` fields (`nextjs`, `laravel`)
+    pub name: String,
+    /// 0.0 - 1.0, from the kinds of signals that matched
+    pub confidence: f32,
+    /// Signals that matched (`dependency next`, `file artisan`)
+    pub evidence: Vec<String>,
+}
+
+/// Containers, deployment manifests and CI pipelines of a project.
+///
+/// Paths are relative to the project root.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct InfraSummary {
+    pub dockerfiles: Vec<Dockerfile>,
+    /// Services of the Compose files
+    pub compose_services: Vec<ComposeService>,
+    /// Kubernetes objects from plain manifests
+    pub kubernetes: Vec<KubernetesResource>,
+    pub helm_charts: Vec<HelmChart>,
+    /// `None` without `*.tf` files
+    pub terraform: Option<TerraformSummary>,
+    pub ci: Vec<CiPipeline>,
+}
+
+impl InfraSummary {
+    /// Returns true if no infrastructure files were found.
+    pub fn is_empty(&self) -> bool {
+        self.dockerfiles.is_empty()
+            && self.compose_services.is_empty()
+            && self.kubernetes.is_empty()
+            && self.helm_charts.is_empty()
+            && self.terraform.is_none()
+            && self.ci.is_empty()
+    }
+}
+
+/// A Dockerfile and its build stages.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Dockerfile {
+    pub path: PathBuf,
+    /// External images of the `FROM` lines, in order
+    pub base_images: Vec<String>,
+    /// Named stages (`FROM ... AS build`)
+    pub stages: Vec<String>,
+    /// Ports of the `EXPOSE` lines (`80`, `443/tcp`)
+    pub exposed_ports: Vec<String>,
+}
+
+/// A service of a Compose file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ComposeService {
+    pub file: PathBuf,
+    pub name: String,
+    pub image: Option<String>,
+    /// Build context, for services built from a Dockerfile
+    pub build: Option<String>,
+    /// Published ports (`8080:80`)
+    pub ports: Vec<String>,
+    pub depends_on: Vec<String>,
+}
+
+/// A Kubernetes object.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KubernetesResource {
+    pub path: PathBuf,
+    /// `Deployment`, `Service`, `Ingress`...
+    pub kind: String,
+    pub name: Option<String>,
+    /// Container images
+    pub images: Vec<String>,
+}
+
+/// A Helm chart (`Chart.yaml`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HelmChart {
+    pub path: PathBuf,
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/// Terraform configuration across the project's `*.tf` files.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct TerraformSummary {
+    pub files: usize,
+    /// Providers declared or used by resources (`aws`, `azurerm`)
+    pub providers: Vec<String>,
+    /// Resource counts by type, most used first
+    pub resources: Vec<ResourceCount>,
+    /// Module names
+    pub modules: Vec<String>,
+}
+
+/// Number of Terraform resources of one type.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResourceCount {
+    pub resource_type: String,
+    pub count: usize,
+}
+
+/// A CI pipeline definition.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CiPipeline {
+    /// `GitHub Actions`, `GitLab CI` or `Azure Pipelines`
+    pub provider: String,
+    pub path: PathBuf,
+    /// Workflow or pipeline name
+    pub name: Option<String>,
+    /// Events that start the pipeline (`push`, `pull_request`)
+    pub triggers: Vec<String>,
+    pub stages: Vec<String>,
+    pub jobs: Vec<String>,
 }
 
 // ============================================================================
@@ -221,6 +522,9 @@ pub struct ClassInfo {
     pub interfaces: Vec<String>,
     pub methods: Vec<MethodInfo>,
     pub properties: Vec<PropertyInfo>,
+    /// Location of the declaration
+    #[serde(default)]
+    pub range: SourceRange,
 }
 
 /// Interface information from C# analysis.
@@ -228,6 +532,9 @@ pub struct ClassInfo {
 pub struct InterfaceInfo {
     pub name: String,
     pub methods: Vec<MethodInfo>,
+    /// Location of the declaration
+    #[serde(default)]
+    pub range: SourceRange,
 }
 
 /// Method information from C# analysis.
@@ -238,6 +545,9 @@ pub struct MethodInfo {
     pub parameters: Vec<Parameter>,
     pub modifiers: Vec<String>,
     pub is_async: bool,
+    /// Location of the declaration
+    #[serde(default)]
+    pub range: SourceRange,
 }
 
 /// Parameter information.
@@ -254,6 +564,9 @@ pub struct PropertyInfo {
     pub prop_type: String,
     pub has_getter: bool,
     pub has_setter: bool,
+    /// Location of the declaration
+    #[serde(default)]
+    pub range: SourceRange,
 }
 
 // ============================================================================
@@ -288,14 +601,304 @@ pub struct CodePattern {
     pub relevance_score: f32,
     /// When the pattern was created
     pub created_at: DateTime<Utc>,
-    /// When the pattern was last updated
+    /// When the pattern was last updated; also the version checked by edits
     pub updated_at: DateTime<Utc>,
+    /// Set when the pattern should no longer be suggested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<Deprecation>,
+}
+
+/// Why a pattern is deprecated and what replaces it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deprecation {
+    pub reason: String,
+    /// ID of the pattern to use instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
+    pub deprecated_at: DateTime<Utc>,
+}
+
+/// One recorded state of a pattern.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternRevision {
+    /// 1-based, increasing per pattern
+    pub revision: u32,
+    pub action: RevisionAction,
+    /// Revision a rollback restored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rolled_back_to: Option<u32>,
+    /// MCP client (or user) that made the change
+    pub author: String,
+    pub timestamp: DateTime<Utc>,
+    /// The pattern after the change; for deletions, as it was deleted
+    pub pattern: CodePattern,
+}
+
+/// Votes on whether a pattern helped, as recorded by `rate-pattern`, and
+/// how often it was used since feedback tracking started.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PatternFeedback {
+    pub helpful: u32,
+    pub not_helpful: u32,
+    /// Uses recorded by `mark-pattern-used` and `get-observation`, on top of
+    /// the `usage_count` in the pattern file
+    #[serde(default)]
+    pub uses: u32,
+    /// Relevance score before the first vote; votes move the pattern's
+    /// `relevance_score` away from it as they accumulate
+    pub base_relevance: f32,
+}
+
+/// A pattern proposed from an idiom recurring in an analyzed project,
+/// waiting to be accepted or rejected with `review-pattern-candidates`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternCandidate {
+    /// The pattern as it would be added; `code` is an example occurrence
+    pub pattern: CodePattern,
+    /// The occurrence with the parts that vary replaced by `$1`, `$2`, ...
+    pub template: String,
+    /// Times the idiom occurs in the project
+    pub occurrences: usize,
+    /// First occurrences as `path:line`, relative to the project root
+    pub locations: Vec<String>,
+    /// Name of the project it was found in
+    pub project: String,
+    pub extracted_at: DateTime<Utc>,
+}
+
+/// What produced a pattern revision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionAction {
+    /// State before the first tracked change of a pattern loaded from disk
+    Imported,
+    Created,
+    Updated,
+    Deprecated,
+    Restored,
+    Deleted,
+    RolledBack,
+}
+
+impl RevisionAction {
+    /// Returns the lowercase string representation of the action.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Imported => "imported",
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Deprecated => "deprecated",
+            Self::Restored => "restored",
+            Self::Deleted => "deleted",
+            Self::RolledBack => "rolled back",
+        }
+    }
 }
 
 // ============================================================================
 // Analysis Result Types
 // ============================================================================
 
+/// Summary of the intra-project module dependency graph.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ModuleGraphSummary {
+    /// Number of modules (files, or namespaces/packages where imports name those)
+    pub modules: usize,
+    /// Number of import edges between modules
+    pub edges: usize,
+    /// Total number of import cycles
+    pub cycle_count: usize,
+    /// Smallest cycles, each a sorted list of modules
+    pub cycles: Vec<Vec<String>>,
+    /// Total number of layering violations
+    pub violation_count: usize,
+    /// Imports from a lower architecture layer into a higher one
+    pub layer_violations: Vec<LayerViolation>,
+    /// Modules imported by the most other modules
+    pub most_depended: Vec<ModuleDependents>,
+}
+
+/// An import that points up the architecture layers (e.g. domain -> presentation).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LayerViolation {
+    pub from: String,
+    pub to: String,
+    pub from_layer: String,
+    pub to_layer: String,
+}
+
+/// How many modules import a module.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModuleDependents {
+    pub module: String,
+    pub dependents: usize,
+}
+
+/// Test frameworks, test files and per-module test coverage of a project.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TestSummary {
+    /// Detected test frameworks
+    pub frameworks: Vec<TestFramework>,
+    /// Command that runs the whole suite
+    pub command: Option<String>,
+    /// Files classified as tests
+    pub test_files: usize,
+    /// Production source files
+    pub source_files: usize,
+    /// Production files that also hold tests (Rust `mod tests`)
+    pub inline_test_files: usize,
+    /// Directories holding test files, most test files first
+    pub locations: Vec<String>,
+    /// Test coverage per module (directory), most source files first
+    pub modules: Vec<ModuleTests>,
+}
+
+impl TestSummary {
+    /// Test files (inline tests included) per production source file.
+    pub fn ratio(&self) -> f64 {
+        ratio(self.test_files + self.inline_test_files, self.source_files)
+    }
+
+    /// Modules with source files and no tests.
+    pub fn untested(&self) -> impl Iterator<Item = &ModuleTests> {
+        self.modules.iter().filter(|m| m.test_files == 0)
+    }
+}
+
+/// A test framework and the command that runs it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TestFramework {
+    pub name: String,
+    pub command: String,
+}
+
+/// Source and test file counts of a module.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModuleTests {
+    /// Directory relative to the project root, `.` for the root
+    pub module: String,
+    pub source_files: usize,
+    /// Test files covering the module, plus its files with inline tests
+    pub test_files: usize,
+}
+
+impl ModuleTests {
+    /// Test files per source file.
+    pub fn ratio(&self) -> f64 {
+        ratio(self.test_files, self.source_files)
+    }
+}
+
+fn ratio(tests: usize, sources: usize) -> f64 {
+    if sources == 0 {
+        0.0
+    } else {
+        tests as f64 / sources as f64
+    }
+}
+
+/// How a file changed in a diff.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ChangeStatus {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+impl ChangeStatus {
+    /// Single-letter code as used by `git status`.
+    pub const fn as_code(self) -> &'static str {
+        match self {
+            Self::Added => "A",
+            Self::Modified => "M",
+            Self::Deleted => "D",
+            Self::Renamed => "R",
+        }
+    }
+}
+
+/// A position in a source file.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SourcePosition {
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column, in bytes
+    pub column: usize,
+    /// 0-based byte offset from the start of the file
+    pub byte: usize,
+}
+
+/// A span of source code, end exclusive.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SourceRange {
+    pub start: SourcePosition,
+    pub end: SourcePosition,
+}
+
+impl SourceRange {
+    /// Lines covered by the span.
+    pub const fn lines(&self) -> LineRange {
+        LineRange {
+            start: self.start.line,
+            end: self.end.line,
+        }
+    }
+}
+
+impl fmt::Display for SourceRange {
+    /// `line:column`, the usual prefix of a clickable diagnostic.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.start.line, self.start.column)
+    }
+}
+
+/// Inclusive, 1-based line range.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl LineRange {
+    /// Whether the two ranges share at least one line.
+    pub const fn overlaps(&self, other: &Self) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+/// A file changed relative to a diff base.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChangedFile {
+    /// Path relative to the project root
+    pub path: PathBuf,
+    pub status: ChangeStatus,
+    /// Changed line ranges in the new version of the file
+    #[serde(default)]
+    pub lines: Vec<LineRange>,
+}
+
+/// Symbols of one changed file touched by the diff.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TouchedSymbols {
+    /// Path relative to the project root
+    pub path: PathBuf,
+    /// Qualified names such as `Class.Method`
+    pub symbols: Vec<String>,
+}
+
+/// Analysis restricted to the files of a diff.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffAnalysis {
+    /// Git ref or `"diff"` for a supplied unified diff
+    pub base: String,
+    pub changed_files: Vec<ChangedFile>,
+    pub touched_symbols: Vec<TouchedSymbols>,
+    /// Analysis of the changed files only
+    pub analysis: AnalysisResult,
+}
+
 /// Result of analyzing a project.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalysisResult {
@@ -303,10 +906,30 @@ pub struct AnalysisResult {
     pub project: Project,
     /// Relevant patterns found
     pub patterns: Vec<CodePattern>,
+    /// Why each of `patterns` was picked, in the same order; empty for diff
+    /// reviews, whose patterns follow the touched symbols
+    #[serde(default)]
+    pub pattern_matches: Vec<PatternMatch>,
     /// Suggestions for improvement
     pub suggestions: Vec<Suggestion>,
     /// Project statistics
     pub statistics: Statistics,
+    /// Internal module dependencies
+    #[serde(default)]
+    pub module_graph: ModuleGraphSummary,
+    /// Test frameworks and coverage gaps
+    #[serde(default)]
+    pub tests: TestSummary,
+}
+
+/// Why a pattern was picked for a project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternMatch {
+    pub pattern_id: String,
+    /// Project relevance; higher ranks first
+    pub score: f32,
+    /// Project signals behind the score (`uses dependency MediatR`)
+    pub reasons: Vec<String>,
 }
 
 /// Legacy analysis result for .NET (kept for compatibility).
@@ -332,16 +955,43 @@ pub struct Suggestion {
     pub file: Option<PathBuf>,
     /// Line number (if applicable)
     pub line: Option<usize>,
+    /// Exact location in `file` (if applicable)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<SourceRange>,
+    /// Id of the rule that produced the suggestion
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    /// Id of the pattern showing the recommended fix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern_id: Option<String>,
+}
+
+impl Suggestion {
+    /// `path:line:column` of the suggestion, with `file` relative to `root`.
+    ///
+    /// Falls back to `path:line` or `path` when the position is unknown.
+    pub fn location(&self, root: &Path) -> Option<String> {
+        let file = self.file.as_ref()?;
+        let path = file.strip_prefix(root).unwrap_or(file).display();
+        Some(match (self.range, self.line) {
+            (Some(range), _) => format!("{}:{}", path, range),
+            (None, Some(line)) => format!("{}:{}", path, line),
+            (None, None) => path.to_string(),
+        })
+    }
 }
 
 /// Severity level for suggestions.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum SeverityLevel {
     /// Informational suggestion
+    #[serde(alias = "info")]
     Info,
     /// Warning that should be addressed
+    #[serde(alias = "warning")]
     Warning,
     /// Error that must be fixed
+    #[serde(alias = "error")]
     Error,
 }
 
@@ -385,6 +1035,47 @@ pub struct Statistics {
     pub framework_version: String,
     /// Number of dependencies
     pub package_count: usize,
+    /// Lines containing code
+    #[serde(default)]
+    pub code_lines: usize,
+    /// Lines containing only comments
+    #[serde(default)]
+    pub comment_lines: usize,
+    /// Empty or whitespace-only lines
+    #[serde(default)]
+    pub blank_lines: usize,
+    /// Per-language breakdown, keyed by file extension
+    #[serde(default)]
+    pub by_language: std::collections::BTreeMap<String, LanguageStats>,
+    /// Largest files by line count
+    #[serde(default)]
+    pub largest_files: Vec<FileHotspot>,
+    /// Files with the highest cyclomatic complexity
+    #[serde(default)]
+    pub most_complex_files: Vec<FileHotspot>,
+}
+
+/// Aggregated metrics for all files of one language.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct LanguageStats {
+    pub files: usize,
+    pub code_lines: usize,
+    pub comment_lines: usize,
+    pub blank_lines: usize,
+    pub functions: usize,
+    pub cyclomatic_complexity: usize,
+}
+
+/// A file worth pointing the assistant at (large or complex).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileHotspot {
+    /// Path relative to the project root
+    pub path: PathBuf,
+    /// Total lines
+    pub lines: usize,
+    pub cyclomatic_complexity: usize,
+    pub max_function_complexity: usize,
+    pub max_nesting_depth: usize,
 }
 
 #[cfg(test)]
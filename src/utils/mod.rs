@@ -7,13 +7,45 @@ use sha2::{Digest, Sha256};
 ///
 /// # Returns
 /// A lowercase hexadecimal string representation of the hash
-#[allow(dead_code)]
 pub fn hash_string(input: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(input.as_bytes());
     format!("{:x}", hasher.finalize())
 }
 
+/// Counts the single-character edits (insertions, deletions, substitutions
+/// and swaps of adjacent characters) that turn `a` into `b`.
+///
+/// # Arguments
+/// * `a`, `b` - The strings to compare, character by character
+///
+/// # Returns
+/// The optimal string alignment distance: `lifecyle` is 1 away from `lifecycle`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // Rows for the two previous prefixes of `a` and the current one
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
 /// Truncates a string to a maximum length, appending "..." if truncated.
 ///
 /// This function is UTF-8 safe and will not panic on multi-byte characters.
@@ -24,7 +56,6 @@ pub fn hash_string(input: &str) -> String {
 ///
 /// # Returns
 /// The original string if shorter than max_len, otherwise truncated with "..."
-#[allow(dead_code)]
 pub fn truncate_string(s: &str, max_len: usize) -> String {
     if max_len < 4 {
         // Not enough room for "..." plus at least one character
@@ -44,6 +75,16 @@ pub fn truncate_string(s: &str, max_len: usize) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("blazor-sever", "blazor-server"), 1);
+        assert_eq!(edit_distance("lifecyle", "lifecycle"), 1);
+        assert_eq!(edit_distance("sevrer", "server"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
     #[test]
     fn test_truncate_string_short() {
         assert_eq!(truncate_string("hello", 10), "hello");
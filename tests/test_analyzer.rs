@@ -1,5 +1,6 @@
 use anyhow::Result;
-use mcp_context_rust::analyzer::{CSharpAnalyzer, ProjectAnalyzer};
+use mcp_context_rust::analyzer::{CSharpAnalyzer, GenericAnalyzer, ProjectAnalyzer};
+use mcp_context_rust::context::ContextBuilder;
 use std::fs;
 
 #[tokio::test]
@@ -78,6 +79,17 @@ namespace MyApp.Components
     assert_eq!(result.classes[0].properties.len(), 2);
     assert_eq!(result.classes[0].methods.len(), 2);
 
+    // Locations come from the tree-sitter nodes
+    let class = &result.classes[0];
+    assert_eq!((class.range.start.line, class.range.start.column), (7, 5));
+    assert_eq!(class.range.end.line, 24);
+    let method = &class.methods[0];
+    assert_eq!((method.range.start.line, method.range.end.line), (12, 16));
+    assert_eq!(
+        &sample_code[method.range.start.byte..method.range.end.byte][..9],
+        "protected"
+    );
+
     Ok(())
 }
 
@@ -134,3 +146,260 @@ async fn test_project_analyzer() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_generic_analyzer_incremental_reanalysis() -> Result<()> {
+    let project_dir = tempfile::tempdir()?;
+    let cache_dir = tempfile::tempdir()?;
+    let project_path = project_dir.path();
+
+    fs::write(
+        project_path.join("App.csproj"),
+        r#"<Project Sdk="Microsoft.NET.Sdk"><PropertyGroup><TargetFramework>net8.0</TargetFramework></PropertyGroup></Project>"#,
+    )?;
+    fs::write(project_path.join("Program.cs"), "public class Program { }")?;
+    fs::write(project_path.join("Old.cs"), "public class Old { }")?;
+
+    let analyzer = GenericAnalyzer::default().with_cache_dir(cache_dir.path());
+
+    let first = analyzer.analyze_project(project_path).await?;
+    assert!(first.scan.changes.is_none());
+    assert_eq!(first.scan.files_reparsed, 2);
+
+    fs::remove_file(project_path.join("Old.cs"))?;
+    fs::write(project_path.join("New.cs"), "public class New { }")?;
+
+    let second = analyzer.analyze_project(project_path).await?;
+    let changes = second.scan.changes.expect("second run reports changes");
+    assert_eq!(changes.added, vec![std::path::PathBuf::from("New.cs")]);
+    assert_eq!(changes.removed, vec![std::path::PathBuf::from("Old.cs")]);
+    assert!(changes.modified.is_empty());
+    assert_eq!(second.scan.files_reparsed, 1);
+
+    let program = second
+        .files
+        .iter()
+        .find(|f| f.path.ends_with("Program.cs"))
+        .expect("Program.cs analyzed");
+    assert_eq!(program.symbols[0].name, "Program");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_analyze_diff_restricts_to_changed_files() -> Result<()> {
+    let project_dir = tempfile::tempdir()?;
+    let project_path = project_dir.path();
+
+    fs::write(
+        project_path.join("App.csproj"),
+        r#"<Project Sdk="Microsoft.NET.Sdk"><PropertyGroup><TargetFramework>net8.0</TargetFramework></PropertyGroup></Project>"#,
+    )?;
+    fs::write(
+        project_path.join("Changed.cs"),
+        "public class Changed { public void Run() { } }",
+    )?;
+    fs::write(
+        project_path.join("Untouched.cs"),
+        "public class Untouched { }",
+    )?;
+
+    let diff = "--- a/Changed.cs\n+++ b/Changed.cs\n@@ -1 +1 @@\n-public class Changed { }\n+public class Changed { public void Run() { } }\n";
+    let changed = mcp_context_rust::analyzer::diff::parse_unified_diff(diff);
+
+    let project = GenericAnalyzer::default()
+        .analyze_changed_files(project_path, &changed)
+        .await?;
+    assert_eq!(project.files.len(), 1);
+    assert!(project.files[0].path.ends_with("Changed.cs"));
+
+    let review = ContextBuilder::new()
+        .build_diff_analysis(project, "diff", changed)
+        .await?;
+    assert_eq!(review.touched_symbols.len(), 1);
+    assert!(review.touched_symbols[0]
+        .symbols
+        .contains(&"Changed.Run".to_string()));
+
+    let context = ContextBuilder::new().build_diff_context_string(&review);
+    assert!(context.contains("[M] Changed.cs"));
+    assert!(!context.contains("Untouched"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_analyze_diff_touched_symbols_follow_hunks() -> Result<()> {
+    let project_dir = tempfile::tempdir()?;
+    let project_path = project_dir.path();
+
+    fs::write(
+        project_path.join("App.csproj"),
+        r#"<Project Sdk="Microsoft.NET.Sdk"><PropertyGroup><TargetFramework>net8.0</TargetFramework></PropertyGroup></Project>"#,
+    )?;
+    fs::write(
+        project_path.join("Service.cs"),
+        "public class Service\n{\n    public void First()\n    {\n    }\n\n    public void Second()\n    {\n        Work();\n    }\n}\n",
+    )?;
+
+    let diff =
+        "--- a/Service.cs\n+++ b/Service.cs\n@@ -9 +9 @@\n-        Old();\n+        Work();\n";
+    let changed = mcp_context_rust::analyzer::diff::parse_unified_diff(diff);
+    let project = GenericAnalyzer::default()
+        .analyze_changed_files(project_path, &changed)
+        .await?;
+
+    let review = ContextBuilder::new()
+        .build_diff_analysis(project, "diff", changed)
+        .await?;
+    assert_eq!(
+        review.touched_symbols[0].symbols,
+        vec!["Service.Second".to_string()]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dependency_health_suggestions() -> Result<()> {
+    let project_dir = tempfile::tempdir()?;
+    let project_path = project_dir.path();
+
+    fs::write(
+        project_path.join("requirements.txt"),
+        "django>=3.2,<3.3\nrequests==2.31.0\nuvicorn; python_version >= \"3.8\"\n",
+    )?;
+    fs::write(project_path.join("manage.py"), "import django\n")?;
+
+    let project = GenericAnalyzer::default()
+        .analyze_project(project_path)
+        .await?;
+    let versions: Vec<(&str, &str)> = project
+        .dependencies
+        .iter()
+        .map(|d| (d.name.as_str(), d.version.as_str()))
+        .collect();
+    assert_eq!(
+        versions,
+        vec![
+            ("django", ">=3.2,<3.3"),
+            ("requests", "2.31.0"),
+            ("uvicorn", "*")
+        ]
+    );
+
+    let analysis = ContextBuilder::new()
+        .build_generic_analysis(project)
+        .await?;
+    let eol = analysis
+        .suggestions
+        .iter()
+        .find(|s| s.category == "upgrade")
+        .expect("end-of-life suggestion");
+    assert!(eol.message.starts_with("Django 3.2 reached end of support"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_infrastructure_detection() -> Result<()> {
+    let project_dir = tempfile::tempdir()?;
+    let project_path = project_dir.path();
+
+    fs::write(
+        project_path.join("package.json"),
+        r#"{"name": "shop", "version": "1.0.0"}"#,
+    )?;
+    fs::write(project_path.join("index.js"), "console.log('hi');\n")?;
+    fs::write(
+        project_path.join("Dockerfile"),
+        "FROM node:20-alpine\nEXPOSE 3000\n",
+    )?;
+    fs::write(
+        project_path.join("docker-compose.yml"),
+        "services:\n  app:\n    build: .\n    ports:\n      - \"3000:3000\"\n  redis:\n    image: redis:7\n",
+    )?;
+    fs::create_dir_all(project_path.join(".github/workflows"))?;
+    fs::write(
+        project_path.join(".github/workflows/ci.yml"),
+        "name: CI\non: [push]\njobs:\n  test:\n    runs-on: ubuntu-latest\n",
+    )?;
+
+    let project = GenericAnalyzer::default()
+        .analyze_project(project_path)
+        .await?;
+    let infra = &project.metadata.infra;
+    assert_eq!(infra.dockerfiles[0].base_images, vec!["node:20-alpine"]);
+    assert_eq!(infra.compose_services.len(), 2);
+    assert_eq!(infra.ci.len(), 1);
+    assert_eq!(infra.ci[0].jobs, vec!["test"]);
+
+    let analysis = ContextBuilder::new()
+        .build_generic_analysis(project)
+        .await?;
+    let context = ContextBuilder::new().build_generic_context_string(&analysis);
+    assert!(context.contains("## Infrastructure"));
+    assert!(
+        context.contains("GitHub Actions `.github/workflows/ci.yml` \"CI\" on push; jobs: test")
+    );
+    assert!(context.contains("\n\n### Compose Services\n"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_infrastructure_section_without_dockerfile() -> Result<()> {
+    let project_dir = tempfile::tempdir()?;
+    let project_path = project_dir.path();
+
+    fs::write(project_path.join("index.js"), "console.log('hi');\n")?;
+    fs::create_dir_all(project_path.join(".github/workflows"))?;
+    fs::write(
+        project_path.join(".github/workflows/ci.yml"),
+        "name: CI\non: [push]\njobs:\n  test:\n    runs-on: ubuntu-latest\n",
+    )?;
+
+    let project = GenericAnalyzer::default()
+        .analyze_project(project_path)
+        .await?;
+    let analysis = ContextBuilder::new()
+        .build_generic_analysis(project)
+        .await?;
+    let context = ContextBuilder::new().build_generic_context_string(&analysis);
+    assert!(context.contains("## Infrastructure\n\n### CI\n"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_framework_detection_prefers_specific_framework() -> Result<()> {
+    let project_dir = tempfile::tempdir()?;
+    let project_path = project_dir.path();
+
+    fs::write(
+        project_path.join("package.json"),
+        r#"{"name": "web", "dependencies": {"react": "^18.2.0", "react-dom": "^18.2.0", "next": "14.1.0"}}"#,
+    )?;
+    fs::write(
+        project_path.join("next.config.js"),
+        "module.exports = {};\n",
+    )?;
+
+    let project = GenericAnalyzer::default()
+        .analyze_project(project_path)
+        .await?;
+    let names: Vec<&str> = project
+        .metadata
+        .frameworks
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["nextjs", "react"]);
+    assert_eq!(project.metadata.extra["framework"], "nextjs");
+    assert_eq!(
+        ContextBuilder::new().detect_framework_from_project(&project),
+        "nextjs"
+    );
+
+    Ok(())
+}
@@ -7,8 +7,9 @@
 use chrono::Utc;
 use mcp_context_rust::context::ContextBuilder;
 use mcp_context_rust::types::{
-    AnalysisResult, CodePattern, Dependency, Project, ProjectMetadata, ProjectType, SeverityLevel,
-    SourceFile, Statistics, Suggestion, Symbol, SymbolKind,
+    AnalysisResult, CodePattern, Dependency, FileMetrics, ModuleGraphSummary, Project,
+    ProjectMetadata, ProjectType, ScanSummary, SeverityLevel, SourceFile, Statistics, Suggestion,
+    Symbol, SymbolKind, TestSummary,
 };
 use std::path::PathBuf;
 
@@ -41,6 +42,9 @@ fn make_source_file(path: &str, lang: &str, symbols: Vec<Symbol>) -> SourceFile
         language: lang.to_string(),
         size_bytes: 1024,
         symbols,
+        metrics: FileMetrics::default(),
+        namespace: None,
+        imports: vec![],
     }
 }
 
@@ -50,6 +54,8 @@ fn make_symbol(name: &str, kind: SymbolKind) -> Symbol {
         kind,
         modifiers: vec!["public".to_string()],
         children: vec![],
+        range: None,
+        signature: None,
     }
 }
 
@@ -68,6 +74,7 @@ fn make_pattern(id: &str, title: &str, category: &str, framework: &str) -> CodeP
         relevance_score: 0.85,
         created_at: now,
         updated_at: now,
+        deprecated: None,
     }
 }
 
@@ -78,6 +85,9 @@ fn make_suggestion(severity: SeverityLevel, category: &str, message: &str) -> Su
         message: message.to_string(),
         file: Some(PathBuf::from("src/Example.cs")),
         line: Some(42),
+        range: None,
+        rule_id: None,
+        pattern_id: None,
     }
 }
 
@@ -136,8 +146,11 @@ fn test_endless_reduction_minimal_project() {
                 entry_point: Some("src/main.rs".to_string()),
                 ..Default::default()
             },
+            scan: ScanSummary::default(),
+            git: None,
         },
         patterns: vec![],
+        pattern_matches: vec![],
         suggestions: vec![],
         statistics: Statistics {
             total_files: 2,
@@ -146,7 +159,10 @@ fn test_endless_reduction_minimal_project() {
             total_lines: 150,
             framework_version: "2021".to_string(),
             package_count: 2,
+            ..Default::default()
         },
+        module_graph: ModuleGraphSummary::default(),
+        tests: TestSummary::default(),
     };
 
     let builder = ContextBuilder::new();
@@ -243,8 +259,11 @@ fn test_endless_reduction_medium_dotnet_project() {
                 entry_point: Some("Program.cs".to_string()),
                 ..Default::default()
             },
+            scan: ScanSummary::default(),
+            git: None,
         },
         patterns,
+        pattern_matches: vec![],
         suggestions,
         statistics: Statistics {
             total_files: 25,
@@ -253,7 +272,10 @@ fn test_endless_reduction_medium_dotnet_project() {
             total_lines: 5000,
             framework_version: "net10.0".to_string(),
             package_count: 9,
+            ..Default::default()
         },
+        module_graph: ModuleGraphSummary::default(),
+        tests: TestSummary::default(),
     };
 
     let builder = ContextBuilder::new();
@@ -354,8 +376,11 @@ fn test_endless_reduction_large_node_project() {
                 entry_point: Some("src/index.ts".to_string()),
                 ..Default::default()
             },
+            scan: ScanSummary::default(),
+            git: None,
         },
         patterns,
+        pattern_matches: vec![],
         suggestions,
         statistics: Statistics {
             total_files: 80,
@@ -364,7 +389,10 @@ fn test_endless_reduction_large_node_project() {
             total_lines: 15000,
             framework_version: "20.11.0".to_string(),
             package_count: 40,
+            ..Default::default()
         },
+        module_graph: ModuleGraphSummary::default(),
+        tests: TestSummary::default(),
     };
 
     let builder = ContextBuilder::new();
@@ -403,8 +431,11 @@ fn test_endless_reduction_summary() {
                 entry_point: Some("src/main.rs".to_string()),
                 ..Default::default()
             },
+            scan: ScanSummary::default(),
+            git: None,
         },
         patterns: vec![],
+        pattern_matches: vec![],
         suggestions: vec![],
         statistics: Statistics {
             total_files: 1,
@@ -413,7 +444,10 @@ fn test_endless_reduction_summary() {
             total_lines: 50,
             framework_version: "2021".to_string(),
             package_count: 1,
+            ..Default::default()
         },
+        module_graph: ModuleGraphSummary::default(),
+        tests: TestSummary::default(),
     };
 
     let compact = builder.build_compact_context_string(&analysis);
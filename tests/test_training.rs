@@ -1,7 +1,13 @@
 use anyhow::Result;
 use chrono::Utc;
-use mcp_context_rust::training::{SearchCriteria, TrainingManager};
-use mcp_context_rust::types::CodePattern;
+use mcp_context_rust::analyzer::GenericAnalyzer;
+use mcp_context_rust::context::ContextBuilder;
+use mcp_context_rust::training::{
+    BundleFilter, ConflictPolicy, PatternBundle, PatternUpdate, SearchCriteria, SearchMode,
+    TrainingManager,
+};
+use mcp_context_rust::types::{CodePattern, RevisionAction};
+use mcp_context_rust::TrainingError;
 use std::fs;
 
 #[tokio::test]
@@ -171,6 +177,7 @@ async fn test_search_by_framework() -> Result<()> {
         tags: vec![],
         min_score: 0.0,
         max_results: None,
+        mode: SearchMode::Keyword,
     };
 
     let results = manager.search_patterns(&criteria);
@@ -234,6 +241,8 @@ async fn test_search_by_category() -> Result<()> {
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].id, "lc-1");
 
+    assert_eq!(manager.search_by_framework("blazor-server").len(), 2);
+
     Ok(())
 }
 
@@ -291,6 +300,7 @@ async fn test_search_with_scoring() -> Result<()> {
         tags: vec![],
         min_score: 0.0,
         max_results: None,
+        mode: SearchMode::Keyword,
     };
 
     let results = manager.search_patterns(&criteria);
@@ -344,6 +354,7 @@ async fn test_add_and_save_pattern() -> Result<()> {
         relevance_score: 0.75,
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        deprecated: None,
     };
 
     manager
@@ -484,3 +495,688 @@ async fn test_statistics() -> Result<()> {
 
     Ok(())
 }
+
+const EDIT_PATTERNS: &str = r#"{
+  "patterns": [
+    {
+      "id": "edit-a",
+      "category": "lifecycle",
+      "framework": "blazor-server",
+      "version": "10.0",
+      "title": "Edit A",
+      "description": "First",
+      "code": "a",
+      "tags": [],
+      "usage_count": 0,
+      "relevance_score": 0.8,
+      "created_at": "2025-10-25T00:00:00Z",
+      "updated_at": "2025-10-25T00:00:00Z"
+    },
+    {
+      "id": "edit-b",
+      "category": "state",
+      "framework": "blazor-server",
+      "version": "10.0",
+      "title": "Edit B",
+      "description": "Second",
+      "code": "b",
+      "tags": [],
+      "usage_count": 0,
+      "relevance_score": 0.8,
+      "created_at": "2025-10-25T00:00:00Z",
+      "updated_at": "2025-10-25T00:00:00Z"
+    }
+  ]
+}"#;
+
+fn all_in(framework: &str, category: Option<&str>) -> SearchCriteria {
+    SearchCriteria {
+        query: None,
+        category: category.map(str::to_string),
+        framework: Some(framework.to_string()),
+        tags: vec![],
+        min_score: 0.0,
+        max_results: None,
+        mode: SearchMode::Keyword,
+    }
+}
+
+#[tokio::test]
+async fn test_update_pattern_checks_updated_at() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let patterns_path = temp_dir.path().join("patterns");
+    fs::create_dir_all(&patterns_path)?;
+    fs::write(patterns_path.join("custom.json"), EDIT_PATTERNS)?;
+
+    let mut manager = TrainingManager::new(&patterns_path);
+    manager.load_patterns().await?;
+    let read_at = manager.get_pattern_by_id("edit-a").unwrap().updated_at;
+
+    let update = PatternUpdate {
+        title: Some("Edit A v2".to_string()),
+        category: Some("state".to_string()),
+        ..PatternUpdate::default()
+    };
+    let updated_at = manager
+        .update_pattern("edit-a", read_at, update.clone())
+        .expect("first edit applies")
+        .updated_at;
+    assert!(updated_at > read_at);
+
+    // A second writer still holding the old timestamp is rejected
+    let stale = manager.update_pattern("edit-a", read_at, update);
+    assert!(matches!(stale, Err(TrainingError::Conflict { .. })));
+
+    // The category index follows the edit
+    assert_eq!(
+        manager
+            .search_patterns(&all_in("blazor-server", Some("state")))
+            .len(),
+        2
+    );
+    assert!(manager
+        .search_patterns(&all_in("blazor-server", Some("lifecycle")))
+        .is_empty());
+
+    // Saved back to the file it came from
+    manager.save_patterns().await?;
+    assert!(!patterns_path.join("blazor-server-patterns.json").exists());
+    let mut reloaded = TrainingManager::new(&patterns_path);
+    reloaded.load_patterns().await?;
+    assert_eq!(
+        reloaded.get_pattern_by_id("edit-a").unwrap().title,
+        "Edit A v2"
+    );
+    assert_eq!(
+        reloaded.get_pattern_by_id("edit-a").unwrap().updated_at,
+        updated_at
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_pattern_rebuilds_indexes() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let patterns_path = temp_dir.path().join("patterns");
+    fs::create_dir_all(&patterns_path)?;
+    fs::write(patterns_path.join("custom.json"), EDIT_PATTERNS)?;
+
+    let mut manager = TrainingManager::new(&patterns_path);
+    manager.load_patterns().await?;
+    let read_at = manager.get_pattern_by_id("edit-a").unwrap().updated_at;
+
+    let deleted = manager.delete_pattern("edit-a", read_at)?;
+    assert_eq!(deleted.id, "edit-a");
+    assert!(matches!(
+        manager.delete_pattern("edit-a", read_at),
+        Err(TrainingError::PatternNotFound(_))
+    ));
+
+    // Positions shifted: the remaining pattern is still found under its category
+    let results = manager.search_patterns(&all_in("blazor-server", Some("state")));
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0.id, "edit-b");
+
+    // Deleting the last pattern of a file removes the file
+    let read_at = manager.get_pattern_by_id("edit-b").unwrap().updated_at;
+    manager.delete_pattern("edit-b", read_at)?;
+    manager.save_patterns().await?;
+    assert!(!patterns_path.join("custom.json").exists());
+
+    let mut reloaded = TrainingManager::new(&patterns_path);
+    reloaded.load_patterns().await?;
+    assert!(reloaded.get_all_patterns().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deprecate_and_duplicate_patterns() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let patterns_path = temp_dir.path().join("patterns");
+    fs::create_dir_all(&patterns_path)?;
+    fs::write(patterns_path.join("custom.json"), EDIT_PATTERNS)?;
+
+    let mut manager = TrainingManager::new(&patterns_path);
+    manager.load_patterns().await?;
+
+    let duplicate = manager.get_pattern_by_id("edit-a").unwrap().clone();
+    assert!(matches!(
+        manager.add_pattern(duplicate),
+        Err(TrainingError::DuplicatePattern(_))
+    ));
+
+    let read_at = manager.get_pattern_by_id("edit-a").unwrap().updated_at;
+    assert!(matches!(
+        manager.deprecate_pattern("edit-a", read_at, "Outdated", Some("missing")),
+        Err(TrainingError::PatternNotFound(_))
+    ));
+    let pattern = manager.deprecate_pattern("edit-a", read_at, "Outdated", Some("edit-b"))?;
+    let deprecated_at = pattern.updated_at;
+    assert_eq!(
+        pattern.deprecated.as_ref().unwrap().replaced_by.as_deref(),
+        Some("edit-b")
+    );
+
+    let ids: Vec<String> = manager
+        .search_patterns(&all_in("blazor-server", None))
+        .iter()
+        .map(|(p, _)| p.id.clone())
+        .collect();
+    assert_eq!(ids, vec!["edit-b"]);
+    assert_eq!(manager.get_statistics()["deprecated_patterns"], 1);
+
+    manager.save_patterns().await?;
+    let mut reloaded = TrainingManager::new(&patterns_path);
+    reloaded.load_patterns().await?;
+    assert!(reloaded
+        .get_pattern_by_id("edit-a")
+        .unwrap()
+        .deprecated
+        .is_some());
+
+    manager.restore_pattern("edit-a", deprecated_at)?;
+    assert_eq!(
+        manager
+            .search_patterns(&all_in("blazor-server", None))
+            .len(),
+        2
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_revision_history_and_rollback() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let patterns_path = temp_dir.path().join("patterns");
+    fs::create_dir_all(&patterns_path)?;
+    fs::write(patterns_path.join("custom.json"), EDIT_PATTERNS)?;
+
+    let mut manager = TrainingManager::new(&patterns_path);
+    manager.load_patterns().await?;
+    manager.set_author("agent-x");
+    assert!(manager.revisions("edit-a").is_none());
+
+    let read_at = manager.get_pattern_by_id("edit-a").unwrap().updated_at;
+    let update = PatternUpdate {
+        code: Some("bad".to_string()),
+        ..PatternUpdate::default()
+    };
+    let read_at = manager
+        .update_pattern("edit-a", read_at, update)?
+        .updated_at;
+    manager.delete_pattern("edit-a", read_at)?;
+    manager.save_patterns().await?;
+
+    // History survives the deletion and a reload, without being read as patterns
+    let mut manager = TrainingManager::new(&patterns_path);
+    manager.load_patterns().await?;
+    assert_eq!(manager.get_all_patterns().len(), 1);
+    let revisions = manager.revisions("edit-a").unwrap();
+    let actions: Vec<_> = revisions.iter().map(|r| r.action).collect();
+    assert_eq!(
+        actions,
+        vec![
+            RevisionAction::Imported,
+            RevisionAction::Updated,
+            RevisionAction::Deleted
+        ]
+    );
+    assert_eq!(revisions[0].pattern.code, "a");
+    assert_eq!(revisions[1].author, "agent-x");
+    assert_eq!(revisions[1].pattern.code, "bad");
+
+    // A deleted pattern is re-created without an expected timestamp
+    assert!(matches!(
+        manager.rollback_pattern("edit-a", 9, None),
+        Err(TrainingError::RevisionNotFound { revision: 9, .. })
+    ));
+    let restored = manager.rollback_pattern("edit-a", 1, None)?;
+    assert_eq!(restored.code, "a");
+    let last = manager.revisions("edit-a").unwrap().last().unwrap();
+    assert_eq!(last.action, RevisionAction::RolledBack);
+    assert_eq!(last.rolled_back_to, Some(1));
+    assert_eq!(
+        manager
+            .search_patterns(&all_in("blazor-server", Some("lifecycle")))
+            .len(),
+        1
+    );
+
+    // An existing pattern needs one
+    assert!(matches!(
+        manager.rollback_pattern("edit-a", 2, None),
+        Err(TrainingError::Conflict { .. })
+    ));
+    manager.save_patterns().await?;
+    let mut reloaded = TrainingManager::new(&patterns_path);
+    reloaded.load_patterns().await?;
+    assert_eq!(reloaded.get_pattern_by_id("edit-a").unwrap().code, "a");
+    assert_eq!(reloaded.revisions("edit-a").unwrap().len(), 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_usage_and_votes_adjust_ranking() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let patterns_path = temp_dir.path().join("patterns");
+    fs::create_dir_all(&patterns_path)?;
+    fs::write(patterns_path.join("custom.json"), EDIT_PATTERNS)?;
+
+    let mut manager = TrainingManager::new(&patterns_path);
+    manager.load_patterns().await?;
+    let read_at = manager.get_pattern_by_id("edit-b").unwrap().updated_at;
+
+    // Usage and votes are not edits: no revision, no new `updated_at`
+    for _ in 0..10 {
+        manager.increment_usage("edit-b")?;
+    }
+    let rated = manager.rate_pattern("edit-b", true)?;
+    assert_eq!(rated.usage_count, 10);
+    assert_eq!(rated.updated_at, read_at);
+    assert!(rated.relevance_score > 0.8);
+    assert!(manager.revisions("edit-b").is_none());
+
+    manager.rate_pattern("edit-a", false)?;
+    manager.rate_pattern("edit-a", false)?;
+    assert!(manager.get_pattern_by_id("edit-a").unwrap().relevance_score < 0.8);
+    assert!(matches!(
+        manager.rate_pattern("missing", true),
+        Err(TrainingError::PatternNotFound(_))
+    ));
+
+    // Only the feedback store is written
+    manager.save_feedback()?;
+    assert_eq!(
+        fs::read_to_string(patterns_path.join("custom.json"))?,
+        EDIT_PATTERNS
+    );
+
+    // Everything survives a reload, and the helpful, used pattern ranks first
+    let mut reloaded = TrainingManager::new(&patterns_path);
+    reloaded.load_patterns().await?;
+    let feedback = reloaded.feedback("edit-a").unwrap();
+    assert_eq!((feedback.helpful, feedback.not_helpful), (0, 2));
+    assert!((feedback.base_relevance - 0.8).abs() < f32::EPSILON);
+    assert_eq!(
+        reloaded.get_pattern_by_id("edit-b").unwrap().usage_count,
+        10
+    );
+    let ranked: Vec<&str> = reloaded
+        .search_patterns(&all_in("blazor-server", None))
+        .iter()
+        .map(|(p, _)| p.id.as_str())
+        .collect();
+    assert_eq!(ranked, vec!["edit-b", "edit-a"]);
+    assert_eq!(reloaded.get_all_patterns().len(), 2);
+
+    // Pattern files written later keep usage and votes out, so they are not
+    // counted twice on the next load
+    reloaded.increment_usage("edit-b")?;
+    let relevance = reloaded
+        .get_pattern_by_id("edit-a")
+        .unwrap()
+        .relevance_score;
+    reloaded.save_patterns().await?;
+    let mut again = TrainingManager::new(&patterns_path);
+    again.load_patterns().await?;
+    assert_eq!(again.get_pattern_by_id("edit-b").unwrap().usage_count, 11);
+    assert!((again.get_pattern_by_id("edit-a").unwrap().relevance_score - relevance).abs() < 1e-6);
+    let stored = fs::read_to_string(patterns_path.join("custom.json"))?;
+    assert!(!stored.contains("\"usage_count\": 11"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deleted_pattern_loses_its_feedback() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let patterns_path = temp_dir.path().join("patterns");
+    fs::create_dir_all(&patterns_path)?;
+    fs::write(patterns_path.join("custom.json"), EDIT_PATTERNS)?;
+
+    let mut manager = TrainingManager::new(&patterns_path);
+    manager.load_patterns().await?;
+    manager.increment_usage("edit-a")?;
+    manager.rate_pattern("edit-a", false)?;
+    manager.save_feedback()?;
+
+    let read_at = manager.get_pattern_by_id("edit-a").unwrap().updated_at;
+    let mut deleted = manager.delete_pattern("edit-a", read_at)?;
+    assert!(manager.feedback("edit-a").is_none());
+    manager.save_patterns().await?;
+
+    // A new pattern under the same ID does not inherit the old usage and votes
+    deleted.usage_count = 0;
+    deleted.relevance_score = 0.8;
+    manager.add_pattern(deleted)?;
+    manager.save_patterns().await?;
+
+    let mut reloaded = TrainingManager::new(&patterns_path);
+    reloaded.load_patterns().await?;
+    assert!(reloaded.feedback("edit-a").is_none());
+    let recreated = reloaded.get_pattern_by_id("edit-a").unwrap();
+    assert_eq!(recreated.usage_count, 0);
+    assert!((recreated.relevance_score - 0.8).abs() < f32::EPSILON);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mined_candidates_are_reviewed() -> Result<()> {
+    let project_dir = tempfile::tempdir()?;
+    let project_path = project_dir.path();
+    fs::write(
+        project_path.join("Shop.csproj"),
+        r#"<Project Sdk="Microsoft.NET.Sdk.Web"><PropertyGroup><TargetFramework>net8.0</TargetFramework></PropertyGroup></Project>"#,
+    )?;
+    fs::write(
+        project_path.join("Program.cs"),
+        "var builder = WebApplication.CreateBuilder(args);\n\
+         builder.Services.AddScoped<IOrderService, OrderService>();\n\
+         builder.Services.AddScoped<ICartService, CartService>();\n\
+         builder.Services.AddScoped<IUserService, UserService>();\n\
+         var app = builder.Build();\n",
+    )?;
+    for service in ["Order", "Cart", "User"] {
+        fs::write(
+            project_path.join(format!("{}Loader.cs", service)),
+            format!(
+                "public class {service}Loader\n{{\n    public async Task LoadAsync()\n    {{\n        try\n        {{\n            Items = await {service}Service.GetAllAsync();\n        }}\n        catch (HttpRequestException ex)\n        {{\n            Logger.LogError(ex, \"Failed to load\");\n        }}\n    }}\n}}\n"
+            ),
+        )?;
+    }
+
+    let project = GenericAnalyzer::default()
+        .analyze_project(project_path)
+        .await?;
+    let mined = ContextBuilder::new().extract_pattern_candidates(&project, 3);
+    let mut ids: Vec<(&str, &str)> = mined
+        .iter()
+        .map(|c| (c.pattern.category.as_str(), c.pattern.id.as_str()))
+        .collect();
+    ids.sort();
+    assert_eq!(ids.len(), 2, "{:?}", ids);
+    assert_eq!(ids[0].0, "dependency-injection");
+    assert_eq!(ids[1].0, "error-handling");
+    let (registration, wrapper) = (ids[0].1.to_string(), ids[1].1.to_string());
+    assert!(mined.iter().all(|c| c.occurrences == 3));
+
+    let temp_dir = tempfile::tempdir()?;
+    let patterns_path = temp_dir.path().join("patterns");
+    fs::create_dir_all(&patterns_path)?;
+    let mut manager = TrainingManager::new(&patterns_path);
+    manager.load_patterns().await?;
+    assert_eq!(manager.propose_candidates(mined.clone()), 2);
+    // Proposing the same idioms again only refreshes them
+    assert_eq!(manager.propose_candidates(mined.clone()), 0);
+    assert_eq!(manager.candidates().len(), 2);
+
+    let pattern = manager.accept_candidate(
+        &registration,
+        PatternUpdate {
+            title: Some("Scoped service registration".to_string()),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(pattern.title, "Scoped service registration");
+    assert!(pattern
+        .code
+        .contains("AddScoped<IOrderService, OrderService>"));
+    manager.reject_candidate(&wrapper)?;
+    assert!(manager.candidates().is_empty());
+    assert!(matches!(
+        manager.reject_candidate(&wrapper),
+        Err(TrainingError::CandidateNotFound(_))
+    ));
+    manager.save_patterns().await?;
+
+    // Accepted and rejected idioms are not proposed again after a reload
+    let mut reloaded = TrainingManager::new(&patterns_path);
+    reloaded.load_patterns().await?;
+    assert_eq!(reloaded.get_all_patterns().len(), 1);
+    assert!(reloaded.get_pattern_by_id(&registration).is_some());
+    assert_eq!(reloaded.propose_candidates(mined), 0);
+    assert!(reloaded.candidates().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_export_and_import_bundles() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let bundle_path = temp_dir.path().join("bundle.json");
+
+    // Filters pick what goes into a bundle
+    let mut shipped = TrainingManager::new(concat!(env!("CARGO_MANIFEST_DIR"), "/data/patterns"));
+    shipped.load_patterns().await?;
+    let filter = BundleFilter {
+        framework: Some("blazor-server".to_string()),
+        category: Some("lifecycle".to_string()),
+        tags: vec![],
+    };
+    let bundle = shipped.export_bundle(filter.clone());
+    assert!(!bundle.patterns.is_empty());
+    assert!(bundle
+        .verified_patterns()?
+        .iter()
+        .all(|p| p.framework == "blazor-server" && p.category == "lifecycle"));
+    assert_eq!(bundle.manifest.filter, filter);
+
+    // A source with a changed edit-a and an identical edit-b
+    let source_path = temp_dir.path().join("source");
+    fs::create_dir_all(&source_path)?;
+    fs::write(source_path.join("custom.json"), EDIT_PATTERNS)?;
+    let mut source = TrainingManager::new(&source_path);
+    source.load_patterns().await?;
+    let read_at = source.get_pattern_by_id("edit-a").unwrap().updated_at;
+    let update = PatternUpdate {
+        code: Some("a2".to_string()),
+        ..Default::default()
+    };
+    source.update_pattern("edit-a", read_at, update)?;
+    source
+        .export_bundle(BundleFilter::default())
+        .save(&bundle_path)?;
+    let bundle = PatternBundle::load(&bundle_path)?;
+    assert_eq!(bundle.manifest.pattern_count, 2);
+
+    let target_path = temp_dir.path().join("target");
+    fs::create_dir_all(&target_path)?;
+    fs::write(target_path.join("custom.json"), EDIT_PATTERNS)?;
+    let mut target = TrainingManager::new(&target_path);
+    target.load_patterns().await?;
+    let all = BundleFilter::default();
+
+    let report = target.import_bundle(&bundle, &all, ConflictPolicy::Skip)?;
+    assert_eq!(report.skipped, vec!["edit-a", "edit-b"]);
+    assert_eq!(report.imported(), 0);
+
+    // Overwriting only touches the pattern that differs
+    let report = target.import_bundle(&bundle, &all, ConflictPolicy::Overwrite)?;
+    assert_eq!(report.overwritten, vec!["edit-a"]);
+    assert_eq!(report.skipped, vec!["edit-b"]);
+    assert_eq!(target.get_pattern_by_id("edit-a").unwrap().code, "a2");
+    let revisions = target.revisions("edit-a").unwrap();
+    assert_eq!(revisions.last().unwrap().action, RevisionAction::Updated);
+
+    let state_only = BundleFilter {
+        category: Some("state".to_string()),
+        ..Default::default()
+    };
+    let report = target.import_bundle(&bundle, &state_only, ConflictPolicy::Rename)?;
+    assert_eq!(
+        report.renamed,
+        vec![("edit-b".to_string(), "edit-b-imported".to_string())]
+    );
+    let report = target.import_bundle(&bundle, &state_only, ConflictPolicy::Rename)?;
+    assert_eq!(report.renamed[0].1, "edit-b-imported-2");
+    target.save_patterns().await?;
+
+    let mut reloaded = TrainingManager::new(&target_path);
+    reloaded.load_patterns().await?;
+    assert_eq!(reloaded.get_all_patterns().len(), 4);
+    assert_eq!(
+        reloaded.get_pattern_by_id("edit-b-imported").unwrap().title,
+        "Edit B"
+    );
+
+    // A tampered bundle imports nothing
+    let mut tampered = bundle;
+    tampered.patterns[1]["code"] = "evil".into();
+    assert!(matches!(
+        reloaded.import_bundle(&tampered, &all, ConflictPolicy::Overwrite),
+        Err(TrainingError::InvalidBundle(_))
+    ));
+    assert_eq!(reloaded.get_pattern_by_id("edit-b").unwrap().code, "b");
+
+    Ok(())
+}
+
+/// Top search result for `query` among the shipped patterns.
+fn top_shipped(manager: &TrainingManager, query: &str) -> String {
+    let criteria = SearchCriteria {
+        query: Some(query.to_string()),
+        category: None,
+        framework: None,
+        tags: vec![],
+        min_score: 0.0,
+        max_results: Some(5),
+        mode: SearchMode::Keyword,
+    };
+    manager.search_patterns(&criteria)[0].0.id.clone()
+}
+
+#[tokio::test]
+async fn test_bm25_ranking_on_shipped_patterns() -> Result<()> {
+    let mut manager = TrainingManager::new(concat!(env!("CARGO_MANIFEST_DIR"), "/data/patterns"));
+    manager.load_patterns().await?;
+
+    // Word order, inflection and case no longer matter
+    for (query, expected) in [
+        ("authentication state", "blazor-security-cascading-auth"),
+        (
+            "state authentication cascading",
+            "blazor-security-cascading-auth",
+        ),
+        ("parameters changed", "blazor-lifecycle-params"),
+        ("large list virtualization", "blazor-perf-virtualization"),
+        ("csrf token", "blazor-security-antiforgery"),
+        ("call .NET from javascript", "blazor-jsinterop-dotnetref"),
+        ("dispose cancellation token", "blazor-lifecycle-dispose"),
+        ("register scoped services", "blazor-di-scoped-service"),
+        ("OnAfterRenderAsync", "blazor-lifecycle-afterrender"),
+    ] {
+        assert_eq!(top_shipped(&manager, query), expected, "query: {}", query);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_typo_tolerant_search_and_corrections() -> Result<()> {
+    let mut manager = TrainingManager::new(concat!(env!("CARGO_MANIFEST_DIR"), "/data/patterns"));
+    manager.load_patterns().await?;
+
+    // Misspelled query words still rank the intended patterns
+    assert_eq!(
+        top_shipped(&manager, "virtualisation"),
+        "blazor-perf-virtualization"
+    );
+    let criteria = SearchCriteria {
+        query: Some("lifecyle dispose".to_string()),
+        ..all_in("blazor-server", None)
+    };
+    assert_eq!(
+        manager.search_patterns(&criteria)[0].0.id,
+        "blazor-lifecycle-dispose"
+    );
+    let corrections = manager.suggest_corrections(&criteria);
+    assert_eq!(corrections.len(), 1);
+    assert_eq!(corrections[0].field, "query");
+    assert_eq!(corrections[0].suggestion, "lifecycle dispose");
+
+    // Exact filters stay exact, with suggestions when they match nothing
+    let criteria = all_in("blazor-sever", Some("lifecyle"));
+    assert!(manager.search_patterns(&criteria).is_empty());
+    let corrections: Vec<(&str, String)> = manager
+        .suggest_corrections(&criteria)
+        .into_iter()
+        .map(|c| (c.field, c.suggestion))
+        .collect();
+    assert_eq!(
+        corrections,
+        vec![
+            ("framework", "blazor-server".to_string()),
+            ("category", "lifecycle".to_string())
+        ]
+    );
+    assert!(manager
+        .suggest_corrections(&all_in("django", None))
+        .is_empty());
+
+    // Tags tolerate typos
+    let criteria = SearchCriteria {
+        tags: vec!["virtualisation".to_string()],
+        ..all_in("blazor-server", None)
+    };
+    assert_eq!(
+        manager.search_patterns(&criteria)[0].0.id,
+        "blazor-perf-virtualization"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_semantic_and_hybrid_search_find_synonyms() -> Result<()> {
+    let cache_dir = tempfile::tempdir()?;
+    let mut manager = TrainingManager::new(concat!(env!("CARGO_MANIFEST_DIR"), "/data/patterns"))
+        .with_semantic_index(cache_dir.path());
+    manager.load_patterns().await?;
+    assert!(manager.has_semantic_index());
+    // Vectors are persisted for the next start
+    assert_eq!(fs::read_dir(cache_dir.path())?.count(), 1);
+
+    let search = |query: &str, mode| {
+        let criteria = SearchCriteria {
+            query: Some(query.to_string()),
+            category: None,
+            framework: None,
+            tags: vec![],
+            min_score: 0.0,
+            max_results: Some(3),
+            mode,
+        };
+        manager
+            .search_patterns(&criteria)
+            .into_iter()
+            .map(|(p, _)| p.id.clone())
+            .collect::<Vec<_>>()
+    };
+
+    // "DI" is only a tag of one pattern; its concept covers the others
+    let keyword = search("DI", SearchMode::Keyword);
+    assert!(!keyword.iter().any(|id| id.starts_with("blazor-di-")));
+    assert_eq!(
+        search("DI", SearchMode::Semantic)[0],
+        "blazor-di-service-injection"
+    );
+
+    let semantic = search("sign out user", SearchMode::Semantic);
+    assert!(semantic[..2]
+        .iter()
+        .all(|id| id.starts_with("blazor-security-")));
+
+    let hybrid = search("speed up rendering", SearchMode::Hybrid);
+    assert!(
+        hybrid.iter().all(|id| id.starts_with("blazor-perf-")),
+        "{:?}",
+        hybrid
+    );
+
+    Ok(())
+}